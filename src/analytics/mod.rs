@@ -1,6 +1,8 @@
 pub mod performance;
 
-pub use performance::{PerformanceAnalyzer, PerformanceMetrics, DailyPerformanceReport};
+pub use performance::{
+    AggPeriod, PerformanceAnalyzer, PerformanceMetrics, DailyPerformanceReport, VixRegime, VixRegimeStats,
+};
 
 
 
@@ -1,11 +1,12 @@
 /// Performance metrics and reporting module
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::Path;
-use tracing::{info, warn};
+use tracing::info;
 
 use crate::error::Result;
-use crate::types::Position;
+use crate::types::{Position, PositionStatus, Side, Trade};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PerformanceMetrics {
@@ -25,6 +26,10 @@ pub struct PerformanceMetrics {
     pub avg_loss: f64,
     pub largest_win: f64,
     pub largest_loss: f64,
+    #[serde(default)]
+    pub max_consecutive_wins: usize,
+    #[serde(default)]
+    pub max_consecutive_losses: usize,
     
     // P&L Metrics
     pub total_pnl: f64,
@@ -76,6 +81,62 @@ pub struct TradeRecord {
     pub exit_reason: String,
 }
 
+/// Volatility regime at trade entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum VixRegime {
+    Low,
+    Medium,
+    High,
+}
+
+impl VixRegime {
+    /// Regime for a VIX reading: Low below `low_max`, High at or above
+    /// `high_min`; None when the trade carries no VIX (0.0)
+    pub fn from_vix(vix: f64, low_max: f64, high_min: f64) -> Option<Self> {
+        if vix <= 0.0 {
+            None
+        } else if vix < low_max {
+            Some(VixRegime::Low)
+        } else if vix < high_min {
+            Some(VixRegime::Medium)
+        } else {
+            Some(VixRegime::High)
+        }
+    }
+}
+
+/// Trade results within one VIX regime
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VixRegimeStats {
+    pub regime: VixRegime,
+    pub trades: usize,
+    pub winning_trades: usize,
+    pub win_rate: f64,
+    pub net_pnl: f64,
+}
+
+/// Roll-up period for `PerformanceAnalyzer::aggregate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AggPeriod {
+    /// ISO week, labelled with its Monday
+    Week,
+    /// Calendar month, labelled with its 1st
+    Month,
+    /// Everything in one figure, labelled with the first day
+    Cumulative,
+}
+
+impl AggPeriod {
+    /// First day of the period containing `date`
+    fn start_of(&self, date: NaiveDate, first: NaiveDate) -> NaiveDate {
+        match self {
+            AggPeriod::Week => date - Duration::days(date.weekday().num_days_from_monday() as i64),
+            AggPeriod::Month => date.with_day(1).unwrap_or(date),
+            AggPeriod::Cumulative => first,
+        }
+    }
+}
+
 pub struct PerformanceAnalyzer;
 
 impl PerformanceAnalyzer {
@@ -87,6 +148,10 @@ impl PerformanceAnalyzer {
             return Self::empty_metrics();
         }
 
+        // Streaks and drawdown depend on order: walk trades chronologically
+        let sorted = Self::chronological(positions);
+        let positions = sorted.as_slice();
+
         // Separate winning, losing, and breakeven trades
         let winning_trades: Vec<&Position> = positions.iter().filter(|p| p.pnl > 0.0).collect();
         let losing_trades: Vec<&Position> = positions.iter().filter(|p| p.pnl < 0.0).collect();
@@ -124,6 +189,8 @@ impl PerformanceAnalyzer {
         let largest_win = winning_trades.iter().map(|p| p.pnl).fold(0.0, f64::max);
         let largest_loss = losing_trades.iter().map(|p| p.pnl).fold(0.0, f64::min);
 
+        let (max_consecutive_wins, max_consecutive_losses) = Self::calculate_streaks(positions);
+
         // P&L metrics
         let gross_profit: f64 = winning_trades.iter().map(|p| p.pnl).sum();
         let gross_loss: f64 = losing_trades.iter().map(|p| p.pnl.abs()).sum();
@@ -149,15 +216,9 @@ impl PerformanceAnalyzer {
             0.0
         };
 
-        // Hold time metrics
+        // Hold time metrics (closed positions only)
         let hold_times: Vec<f64> = positions.iter()
-            .filter_map(|p| {
-                if let Ok(exit_time) = chrono::DateTime::parse_from_rfc3339(&p.entry_time.to_rfc3339()) {
-                    Some((Utc::now() - exit_time.with_timezone(&Utc)).num_minutes() as f64)
-                } else {
-                    None
-                }
-            })
+            .filter_map(|p| p.exit_time.map(|exit_time| Self::hold_minutes(p.entry_time, exit_time)))
             .collect();
 
         let avg_hold_time = if !hold_times.is_empty() {
@@ -199,6 +260,8 @@ impl PerformanceAnalyzer {
             avg_loss,
             largest_win,
             largest_loss,
+            max_consecutive_wins,
+            max_consecutive_losses,
             total_pnl,
             gross_profit,
             gross_loss,
@@ -218,17 +281,56 @@ impl PerformanceAnalyzer {
         }
     }
 
-    /// Calculate maximum drawdown
+    /// Longest winning and losing streaks (breakeven trades end both)
+    fn calculate_streaks(positions: &[Position]) -> (usize, usize) {
+        let mut wins = 0;
+        let mut losses = 0;
+        let mut max_wins = 0;
+        let mut max_losses = 0;
+
+        for position in positions {
+            if position.pnl > 0.0 {
+                wins += 1;
+                losses = 0;
+            } else if position.pnl < 0.0 {
+                losses += 1;
+                wins = 0;
+            } else {
+                wins = 0;
+                losses = 0;
+            }
+            max_wins = max_wins.max(wins);
+            max_losses = max_losses.max(losses);
+        }
+
+        (max_wins, max_losses)
+    }
+
+    /// Minutes between entry and exit
+    fn hold_minutes(entry_time: DateTime<Utc>, exit_time: DateTime<Utc>) -> f64 {
+        (exit_time - entry_time).num_seconds() as f64 / 60.0
+    }
+
+    /// Positions ordered by exit time (entry time while still open)
+    fn chronological(positions: &[Position]) -> Vec<Position> {
+        let mut sorted = positions.to_vec();
+        sorted.sort_by_key(|p| (p.exit_time.unwrap_or(p.entry_time), p.entry_time));
+        sorted
+    }
+
+    /// Calculate maximum drawdown over the chronological equity curve
     fn calculate_max_drawdown(positions: &[Position]) -> (f64, f64) {
         if positions.is_empty() {
             return (0.0, 0.0);
         }
 
+        let positions = Self::chronological(positions);
+
         let mut cumulative_pnl = 0.0;
         let mut peak = 0.0;
         let mut max_dd = 0.0;
 
-        for position in positions {
+        for position in &positions {
             cumulative_pnl += position.pnl;
             
             if cumulative_pnl > peak {
@@ -265,6 +367,8 @@ impl PerformanceAnalyzer {
             avg_loss: 0.0,
             largest_win: 0.0,
             largest_loss: 0.0,
+            max_consecutive_wins: 0,
+            max_consecutive_losses: 0,
             total_pnl: 0.0,
             gross_profit: 0.0,
             gross_loss: 0.0,
@@ -288,21 +392,25 @@ impl PerformanceAnalyzer {
     pub fn generate_daily_report(positions: &[Position]) -> DailyPerformanceReport {
         let metrics = Self::calculate_metrics(positions);
         
-        let trades: Vec<TradeRecord> = positions.iter().map(|p| TradeRecord {
-            position_id: p.position_id.clone(),
-            symbol: p.symbol.clone(),
-            option_type: p.option_type.as_str().to_string(),
-            strike: p.strike,
-            side: p.side.as_str().to_string(),
-            quantity: p.quantity,
-            entry_price: p.entry_price,
-            exit_price: p.current_price,
-            entry_time: p.entry_time,
-            exit_time: Utc::now(),
-            hold_time_minutes: (Utc::now() - p.entry_time).num_minutes() as f64,
-            pnl: p.pnl,
-            pnl_pct: p.pnl_pct,
-            exit_reason: "EOD".to_string(),
+        let now = Utc::now();
+        let trades: Vec<TradeRecord> = positions.iter().map(|p| {
+            let exit_time = p.exit_time.unwrap_or(now);
+            TradeRecord {
+                position_id: p.position_id.clone(),
+                symbol: p.symbol.clone(),
+                option_type: p.option_type.as_str().to_string(),
+                strike: p.strike,
+                side: p.side.as_str().to_string(),
+                quantity: p.quantity,
+                entry_price: p.entry_price,
+                exit_price: p.current_price,
+                entry_time: p.entry_time,
+                exit_time,
+                hold_time_minutes: Self::hold_minutes(p.entry_time, exit_time),
+                pnl: p.realized_pnl(p.current_price),
+                pnl_pct: p.pnl_pct_at(p.current_price),
+                exit_reason: "EOD".to_string(),
+            }
         }).collect();
 
         let mut notes = Vec::new();
@@ -331,35 +439,82 @@ impl PerformanceAnalyzer {
         }
     }
 
+    /// Generate the report for trading day `date` (IST) from its completed
+    /// trades (net P&L and the recorded exit reasons)
+    pub fn generate_report_from_trades(trades: &[Trade], date: NaiveDate) -> DailyPerformanceReport {
+        let positions: Vec<Position> = trades.iter().map(Self::closed_position_from_trade).collect();
+        let mut report = Self::generate_daily_report(&positions);
+        report.metrics.date = date.format("%Y-%m-%d").to_string();
+
+        for (record, trade) in report.trades.iter_mut().zip(trades) {
+            record.pnl = trade.pnl_net;
+            record.exit_reason = trade.exit_reason.clone();
+        }
+
+        report
+    }
+
+    /// Closed long position equivalent of a completed trade
+    fn closed_position_from_trade(trade: &Trade) -> Position {
+        Position {
+            position_id: trade.position_id.clone(),
+            symbol: trade.symbol.clone(),
+            underlying: trade.underlying.clone(),
+            strike: trade.strike,
+            option_type: trade.option_type,
+            side: Side::Buy,
+            quantity: trade.quantity,
+            entry_price: trade.entry_price,
+            entry_time: trade.entry_time,
+            entry_time_ms: trade.entry_time.timestamp_millis(),
+            underlying_entry: 0.0,
+            stop_loss: 0.0,
+            target: None,
+            trailing_stop: None,
+            trailing_active: false,
+            current_price: trade.exit_price,
+            pnl: trade.pnl_net,
+            pnl_pct: trade.pnl_gross_pct,
+            status: PositionStatus::Closed,
+            entry_reason: trade.entry_reason.clone(),
+            idempotency_key: String::new(),
+            exit_time: Some(trade.exit_time),
+            legs: Vec::new(),
+        }
+    }
+
     /// Save performance report to disk
     pub async fn save_report(report: &DailyPerformanceReport) -> Result<()> {
-        let data_dir = "data/performance";
+        Self::save_report_in(report, "data/performance").await
+    }
+
+    /// Save performance report under `data_dir` and append it to the summary CSV there
+    pub async fn save_report_in<P: AsRef<Path>>(report: &DailyPerformanceReport, data_dir: P) -> Result<()> {
+        let data_dir = data_dir.as_ref();
         tokio::fs::create_dir_all(data_dir).await?;
 
-        let filename = format!("{}/performance_{}.json", 
-                              data_dir, 
-                              report.metrics.date);
+        let filename = data_dir.join(format!("performance_{}.json", report.metrics.date));
         
         let json = serde_json::to_string_pretty(report)?;
         tokio::fs::write(&filename, json).await?;
 
-        info!("💾 Saved performance report to {}", filename);
+        info!("💾 Saved performance report to {}", filename.display());
         
         // Also save a summary CSV for easy analysis
-        Self::append_to_summary_csv(report).await?;
+        Self::append_to_summary_csv(report, data_dir).await?;
 
         Ok(())
     }
 
     /// Append metrics to summary CSV for trend analysis
-    async fn append_to_summary_csv(report: &DailyPerformanceReport) -> Result<()> {
-        let csv_file = "data/performance/summary.csv";
+    async fn append_to_summary_csv(report: &DailyPerformanceReport, data_dir: &Path) -> Result<()> {
+        let csv_file = data_dir.join("summary.csv");
         let m = &report.metrics;
 
         // Create header if file doesn't exist
-        if !Path::new(csv_file).exists() {
+        if !csv_file.exists() {
             let header = "Date,Total Trades,Win Rate,Profit Factor,Total P&L,Max Drawdown %,Avg Win,Avg Loss,CE Trades,PE Trades\n";
-            tokio::fs::write(csv_file, header).await?;
+            tokio::fs::write(&csv_file, header).await?;
         }
 
         // Append data
@@ -369,14 +524,187 @@ impl PerformanceAnalyzer {
             m.max_drawdown_pct, m.avg_win, m.avg_loss, m.ce_trades, m.pe_trades
         );
 
-        let mut content = tokio::fs::read_to_string(csv_file).await.unwrap_or_default();
+        let mut content = tokio::fs::read_to_string(&csv_file).await.unwrap_or_default();
         content.push_str(&row);
-        tokio::fs::write(csv_file, content).await?;
+        tokio::fs::write(&csv_file, content).await?;
 
         info!("📊 Updated performance summary CSV");
         Ok(())
     }
 
+    /// Export trade-level detail to CSV (one row per trade, IST timestamps)
+    pub async fn export_trades_csv<P: AsRef<Path>>(trades: &[Trade], path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let mut content = String::from(
+            "Entry Time,Exit Time,Symbol,Strike,Option Type,Quantity,Entry Price,Exit Price,Gross P&L,Net P&L,Brokerage,Duration (min)\n"
+        );
+
+        for trade in trades {
+            let entry_ist = trade.entry_time.with_timezone(&chrono_tz::Asia::Kolkata);
+            let exit_ist = trade.exit_time.with_timezone(&chrono_tz::Asia::Kolkata);
+
+            content.push_str(&format!(
+                "{},{},{},{},{},{},{:.2},{:.2},{:.2},{:.2},{:.2},{:.1}\n",
+                entry_ist.format("%Y-%m-%d %H:%M:%S"),
+                exit_ist.format("%Y-%m-%d %H:%M:%S"),
+                trade.symbol,
+                trade.strike,
+                trade.option_type.as_str(),
+                trade.quantity,
+                trade.entry_price,
+                trade.exit_price,
+                trade.pnl_gross,
+                trade.pnl_net,
+                trade.brokerage,
+                trade.duration_sec as f64 / 60.0,
+            ));
+        }
+
+        tokio::fs::write(path, content).await?;
+
+        info!("📄 Exported {} trades to {}", trades.len(), path.display());
+        Ok(())
+    }
+
+    /// Net results per VIX regime (by `vix_at_entry`, see `VixRegime::from_vix`);
+    /// untagged trades are skipped
+    pub fn vix_regime_breakdown(trades: &[Trade], low_max: f64, high_min: f64) -> Vec<VixRegimeStats> {
+        [VixRegime::Low, VixRegime::Medium, VixRegime::High]
+            .into_iter()
+            .filter_map(|regime| {
+                let bucket: Vec<&Trade> = trades
+                    .iter()
+                    .filter(|t| VixRegime::from_vix(t.vix_at_entry, low_max, high_min) == Some(regime))
+                    .collect();
+                if bucket.is_empty() {
+                    return None;
+                }
+
+                let winning_trades = bucket.iter().filter(|t| t.pnl_net > 0.0).count();
+                Some(VixRegimeStats {
+                    regime,
+                    trades: bucket.len(),
+                    winning_trades,
+                    win_rate: winning_trades as f64 / bucket.len() as f64 * 100.0,
+                    net_pnl: bucket.iter().map(|t| t.pnl_net).sum(),
+                })
+            })
+            .collect()
+    }
+
+    /// Roll daily metrics up by `period`, oldest period first
+    /// Rates, averages and the profit factor are recomputed from the summed
+    /// counts and P&L. Streaks are the longest single-day streak and the
+    /// drawdown the worse of the daily and day-over-day figures, since the
+    /// trade order across days is not kept. Unparseable dates are skipped.
+    pub fn aggregate(metrics: &[PerformanceMetrics], period: AggPeriod) -> Vec<PerformanceMetrics> {
+        let dated: Vec<(NaiveDate, &PerformanceMetrics)> = metrics
+            .iter()
+            .filter_map(|m| NaiveDate::parse_from_str(&m.date, "%Y-%m-%d").ok().map(|d| (d, m)))
+            .collect();
+        let Some(first) = dated.iter().map(|(d, _)| *d).min() else {
+            return Vec::new();
+        };
+
+        let mut groups: BTreeMap<NaiveDate, Vec<(NaiveDate, &PerformanceMetrics)>> = BTreeMap::new();
+        for (date, m) in dated {
+            groups.entry(period.start_of(date, first)).or_default().push((date, m));
+        }
+
+        groups
+            .into_iter()
+            .map(|(start, mut days)| {
+                days.sort_by_key(|(date, _)| *date);
+                let days: Vec<&PerformanceMetrics> = days.into_iter().map(|(_, m)| m).collect();
+                Self::combine(start, &days)
+            })
+            .collect()
+    }
+
+    /// One period's figures from its days (in date order)
+    fn combine(start: NaiveDate, days: &[&PerformanceMetrics]) -> PerformanceMetrics {
+        let sum_usize = |f: fn(&PerformanceMetrics) -> usize| days.iter().map(|m| f(m)).sum::<usize>();
+        let sum_f64 = |f: fn(&PerformanceMetrics) -> f64| days.iter().map(|m| f(m)).sum::<f64>();
+        let pct = |part: usize, whole: usize| if whole > 0 { part as f64 / whole as f64 * 100.0 } else { 0.0 };
+
+        let total_trades = sum_usize(|m| m.total_trades);
+        let winning_trades = sum_usize(|m| m.winning_trades);
+        let losing_trades = sum_usize(|m| m.losing_trades);
+        let gross_profit = sum_f64(|m| m.gross_profit);
+        let gross_loss = sum_f64(|m| m.gross_loss);
+
+        let avg_win = if winning_trades > 0 { gross_profit / winning_trades as f64 } else { 0.0 };
+        let avg_loss = if losing_trades > 0 { -gross_loss / losing_trades as f64 } else { 0.0 };
+        let profit_factor = if gross_loss > 0.0 {
+            gross_profit / gross_loss
+        } else if gross_profit > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        };
+
+        // Day-over-day equity curve
+        let mut cumulative = 0.0;
+        let mut peak: f64 = 0.0;
+        let mut curve_dd: f64 = 0.0;
+        for m in days {
+            cumulative += m.net_pnl;
+            peak = peak.max(cumulative);
+            curve_dd = curve_dd.max(peak - cumulative);
+        }
+        let max_drawdown = days.iter().map(|m| m.max_drawdown).fold(curve_dd, f64::max);
+
+        // Per-side win counts back out of the daily rates
+        let wins_of = |trades: usize, rate: f64| (trades as f64 * rate / 100.0).round() as usize;
+        let ce_trades = sum_usize(|m| m.ce_trades);
+        let pe_trades = sum_usize(|m| m.pe_trades);
+        let ce_wins: usize = days.iter().map(|m| wins_of(m.ce_trades, m.ce_win_rate)).sum();
+        let pe_wins: usize = days.iter().map(|m| wins_of(m.pe_trades, m.pe_win_rate)).sum();
+
+        let traded: Vec<&&PerformanceMetrics> = days.iter().filter(|m| m.total_trades > 0).collect();
+
+        PerformanceMetrics {
+            date: start.format("%Y-%m-%d").to_string(),
+            timestamp: days.iter().map(|m| m.timestamp).max().unwrap_or_else(Utc::now),
+            total_trades,
+            winning_trades,
+            losing_trades,
+            breakeven_trades: sum_usize(|m| m.breakeven_trades),
+            win_rate: pct(winning_trades, total_trades),
+            loss_rate: pct(losing_trades, total_trades),
+            avg_win,
+            avg_loss,
+            largest_win: days.iter().map(|m| m.largest_win).fold(0.0, f64::max),
+            largest_loss: days.iter().map(|m| m.largest_loss).fold(0.0, f64::min),
+            max_consecutive_wins: days.iter().map(|m| m.max_consecutive_wins).max().unwrap_or(0),
+            max_consecutive_losses: days.iter().map(|m| m.max_consecutive_losses).max().unwrap_or(0),
+            total_pnl: sum_f64(|m| m.total_pnl),
+            gross_profit,
+            gross_loss,
+            net_pnl: sum_f64(|m| m.net_pnl),
+            profit_factor,
+            max_drawdown,
+            max_drawdown_pct: if peak > 0.0 { max_drawdown / peak * 100.0 } else { 0.0 },
+            sharpe_ratio: None,
+            avg_risk_reward: if avg_loss != 0.0 { avg_win / avg_loss.abs() } else { 0.0 },
+            avg_hold_time_minutes: if total_trades > 0 {
+                days.iter().map(|m| m.avg_hold_time_minutes * m.total_trades as f64).sum::<f64>() / total_trades as f64
+            } else {
+                0.0
+            },
+            fastest_trade_minutes: traded.iter().map(|m| m.fastest_trade_minutes).reduce(f64::min).unwrap_or(0.0),
+            longest_trade_minutes: traded.iter().map(|m| m.longest_trade_minutes).fold(0.0, f64::max),
+            ce_trades,
+            pe_trades,
+            ce_win_rate: pct(ce_wins, ce_trades),
+            pe_win_rate: pct(pe_wins, pe_trades),
+        }
+    }
+
     /// Load historical performance metrics
     pub async fn load_historical_metrics(days: usize) -> Result<Vec<PerformanceMetrics>> {
         let mut metrics = Vec::new();
@@ -433,6 +761,7 @@ impl PerformanceAnalyzer {
         info!("   Risk/Reward: {:.2}", metrics.avg_risk_reward);
         info!("   Largest Win: ₹{:.2}", metrics.largest_win);
         info!("   Largest Loss: ₹{:.2}", metrics.largest_loss);
+        info!("   Longest Streaks: {} wins / {} losses", metrics.max_consecutive_wins, metrics.max_consecutive_losses);
         info!("");
         info!("⚠️  RISK METRICS:");
         info!("   Max Drawdown: ₹{:.2} ({:.2}%)", metrics.max_drawdown, metrics.max_drawdown_pct);
@@ -449,5 +778,293 @@ impl PerformanceAnalyzer {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{test_position, OptionType};
+    use chrono::TimeZone;
+
+    fn closed_position(pnl: f64, exit_time: DateTime<Utc>) -> Position {
+        Position {
+            quantity: 50,
+            entry_time: exit_time - chrono::Duration::minutes(30),
+            pnl,
+            status: PositionStatus::Closed,
+            exit_time: Some(exit_time),
+            ..test_position(&uuid::Uuid::new_v4().to_string())
+        }
+    }
+
+    #[test]
+    fn test_streaks_follow_exit_order() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 15, 4, 0, 0).unwrap();
+        let at = |i: i64| start + chrono::Duration::minutes(i * 10);
+
+        // Alternating: W L W L
+        let alternating = vec![
+            closed_position(100.0, at(0)),
+            closed_position(-50.0, at(1)),
+            closed_position(100.0, at(2)),
+            closed_position(-50.0, at(3)),
+        ];
+        let metrics = PerformanceAnalyzer::calculate_metrics(&alternating);
+        assert_eq!(metrics.max_consecutive_wins, 1);
+        assert_eq!(metrics.max_consecutive_losses, 1);
+
+        // Clustered by exit time (W W W L L), supplied out of order
+        let clustered = vec![
+            closed_position(-50.0, at(4)),
+            closed_position(100.0, at(0)),
+            closed_position(-50.0, at(3)),
+            closed_position(100.0, at(2)),
+            closed_position(100.0, at(1)),
+        ];
+        let metrics = PerformanceAnalyzer::calculate_metrics(&clustered);
+        assert_eq!(metrics.max_consecutive_wins, 3);
+        assert_eq!(metrics.max_consecutive_losses, 2);
+    }
+
+    #[test]
+    fn test_hold_time_measured_from_entry_to_exit() {
+        let exit_time = Utc.with_ymd_and_hms(2025, 1, 15, 5, 15, 0).unwrap();
+        let mut quick = closed_position(100.0, exit_time);
+        quick.entry_time = exit_time - chrono::Duration::minutes(45);
+        let mut slow = closed_position(-50.0, exit_time + chrono::Duration::minutes(90));
+        slow.entry_time = exit_time - chrono::Duration::minutes(15);
+
+        let metrics = PerformanceAnalyzer::calculate_metrics(&[quick.clone(), slow]);
+        assert_eq!(metrics.fastest_trade_minutes, 45.0);
+        assert_eq!(metrics.longest_trade_minutes, 105.0);
+        assert_eq!(metrics.avg_hold_time_minutes, 75.0);
+
+        let report = PerformanceAnalyzer::generate_daily_report(&[quick]);
+        assert_eq!(report.trades[0].exit_time, exit_time);
+        assert_eq!(report.trades[0].hold_time_minutes, 45.0);
+    }
+
+    #[test]
+    fn test_drawdown_independent_of_input_order() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 15, 4, 0, 0).unwrap();
+        let at = |i: i64| start + chrono::Duration::minutes(i * 10);
+
+        // Equity curve: 200 -> 50 -> 100 -> 0 -> 300 (drawdown 200 from the first peak)
+        let chronological = vec![
+            closed_position(200.0, at(0)),
+            closed_position(-150.0, at(1)),
+            closed_position(50.0, at(2)),
+            closed_position(-100.0, at(3)),
+            closed_position(300.0, at(4)),
+        ];
+        let mut shuffled = chronological.clone();
+        shuffled.reverse();
+        shuffled.swap(1, 3);
+
+        let expected = PerformanceAnalyzer::calculate_metrics(&chronological);
+        assert_eq!(expected.max_drawdown, 200.0);
+
+        let actual = PerformanceAnalyzer::calculate_metrics(&shuffled);
+        assert_eq!(actual.max_drawdown, expected.max_drawdown);
+        assert_eq!(actual.max_drawdown_pct, expected.max_drawdown_pct);
+    }
+
+    #[test]
+    fn test_vix_regime_breakdown() {
+        let tagged = |pnl_net: f64, vix_at_entry: f64| Trade {
+            trade_id: uuid::Uuid::new_v4().to_string(),
+            position_id: "P".to_string(),
+            symbol: "NIFTY25JAN19500CE".to_string(),
+            underlying: "NIFTY".to_string(),
+            strike: 19500,
+            option_type: OptionType::CE,
+            quantity: 50,
+            entry_time: Utc::now(),
+            entry_price: 100.0,
+            entry_reason: String::new(),
+            exit_time: Utc::now(),
+            exit_price: 100.0,
+            exit_reason: String::new(),
+            secondary_reasons: vec![],
+            pnl_gross: pnl_net,
+            pnl_gross_pct: 0.0,
+            pnl_net,
+            brokerage: 0.0,
+            duration_sec: 0,
+            high_price: 100.0,
+            low_price: 100.0,
+            vix_at_entry,
+            vix_at_exit: vix_at_entry,
+        };
 
+        let trades = vec![
+            tagged(200.0, 12.0),
+            tagged(-100.0, 14.9),
+            tagged(-300.0, 28.0),
+            tagged(500.0, 0.0), // untagged
+        ];
+        let config = crate::config::loader::test_config();
+        let (low_max, high_min) = (config.vix_regime_low_max, config.vix_regime_high_min);
+        let breakdown = PerformanceAnalyzer::vix_regime_breakdown(&trades, low_max, high_min);
+
+        assert_eq!(breakdown.len(), 2);
+        assert_eq!(breakdown[0].regime, VixRegime::Low);
+        assert_eq!(breakdown[0].trades, 2);
+        assert_eq!(breakdown[0].win_rate, 50.0);
+        assert_eq!(breakdown[0].net_pnl, 100.0);
+        assert_eq!(breakdown[1].regime, VixRegime::High);
+        assert_eq!(breakdown[1].net_pnl, -300.0);
+        assert_eq!(VixRegime::from_vix(low_max, low_max, high_min), Some(VixRegime::Medium));
+        
+        // Thresholds come from config: a lower high bound reclassifies
+        let breakdown = PerformanceAnalyzer::vix_regime_breakdown(&trades, 13.0, 14.0);
+        assert_eq!(breakdown.iter().map(|s| (s.regime, s.trades)).collect::<Vec<_>>(), vec![
+            (VixRegime::Low, 1),
+            (VixRegime::High, 2),
+        ]);
+    }
+
+    fn sample_trade(entry_time: DateTime<Utc>) -> Trade {
+        Trade {
+            trade_id: "T1".to_string(),
+            position_id: "P1".to_string(),
+            symbol: "NIFTY25JAN19500CE".to_string(),
+            underlying: "NIFTY".to_string(),
+            strike: 19500,
+            option_type: OptionType::CE,
+            quantity: 50,
+            entry_time,
+            entry_price: 125.0,
+            entry_reason: "test".to_string(),
+            exit_time: entry_time + chrono::Duration::minutes(45),
+            exit_price: 140.5,
+            exit_reason: "TARGET".to_string(),
+            secondary_reasons: vec![],
+            pnl_gross: 775.0,
+            pnl_gross_pct: 12.4,
+            pnl_net: 735.0,
+            brokerage: 40.0,
+            duration_sec: 2700,
+            high_price: 142.0,
+            low_price: 120.0,
+            vix_at_entry: 14.0,
+            vix_at_exit: 14.5,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_trades_csv() {
+        let entry_time = Utc.with_ymd_and_hms(2025, 1, 15, 4, 30, 0).unwrap(); // 10:00 IST
+        let trade = sample_trade(entry_time);
+
+        let path = std::env::temp_dir().join(format!("trades_{}.csv", uuid::Uuid::new_v4()));
+        PerformanceAnalyzer::export_trades_csv(&[trade], &path).await.unwrap();
+
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("Entry Time,Exit Time,Symbol,Strike"));
+        assert_eq!(
+            lines[1],
+            "2025-01-15 10:00:00,2025-01-15 10:45:00,NIFTY25JAN19500CE,19500,CE,50,125.00,140.50,775.00,735.00,40.00,45.0"
+        );
 
+        let _ = tokio::fs::remove_file(&path).await;
+    }
+
+    #[tokio::test]
+    async fn test_daily_report_saved_from_trades() {
+        let entry_time = Utc.with_ymd_and_hms(2025, 1, 15, 4, 30, 0).unwrap();
+        let winner = sample_trade(entry_time);
+        let mut loser = sample_trade(entry_time + chrono::Duration::hours(1));
+        loser.option_type = OptionType::PE;
+        loser.pnl_net = -260.0;
+        loser.exit_reason = "STOP_LOSS".to_string();
+
+        let report = PerformanceAnalyzer::generate_report_from_trades(
+            &[winner, loser],
+            NaiveDate::from_ymd_opt(2025, 1, 15).unwrap(),
+        );
+        assert_eq!(report.metrics.date, "2025-01-15");
+        assert_eq!(report.metrics.total_trades, 2);
+        assert_eq!(report.metrics.winning_trades, 1);
+        assert_eq!(report.metrics.net_pnl, 475.0);
+        assert_eq!((report.metrics.ce_trades, report.metrics.pe_trades), (1, 1));
+        assert_eq!(report.trades[1].exit_reason, "STOP_LOSS");
+        assert_eq!(report.trades[1].pnl, -260.0);
+
+        let dir = std::env::temp_dir().join(format!("performance_{}", uuid::Uuid::new_v4()));
+        PerformanceAnalyzer::save_report_in(&report, &dir).await.unwrap();
+
+        let saved = dir.join(format!("performance_{}.json", report.metrics.date));
+        let content = tokio::fs::read_to_string(&saved).await.unwrap();
+        let loaded: DailyPerformanceReport = serde_json::from_str(&content).unwrap();
+        assert_eq!(loaded.trades.len(), 2);
+
+        let summary = tokio::fs::read_to_string(dir.join("summary.csv")).await.unwrap();
+        assert_eq!(summary.lines().count(), 2);
+        assert!(summary.lines().nth(1).unwrap().starts_with(&format!("{},2,50.00", report.metrics.date)));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[test]
+    fn test_aggregate_week_recomputes_rates() {
+        let day_pnls: [&[f64]; 5] = [
+            &[1000.0, -400.0],
+            &[-300.0],
+            &[500.0, 700.0, -200.0],
+            &[],
+            &[-600.0, 900.0],
+        ];
+
+        let mut all = Vec::new();
+        let mut daily = Vec::new();
+        for (i, pnls) in day_pnls.iter().enumerate() {
+            // Mon 2025-01-13 .. Fri 2025-01-17
+            let day = 13 + i as u32;
+            let positions: Vec<Position> = pnls
+                .iter()
+                .enumerate()
+                .map(|(n, pnl)| closed_position(*pnl, Utc.with_ymd_and_hms(2025, 1, day, 5 + n as u32, 0, 0).unwrap()))
+                .collect();
+            let mut metrics = PerformanceAnalyzer::calculate_metrics(&positions);
+            metrics.date = format!("2025-01-{}", day);
+            daily.push(metrics);
+            all.extend(positions);
+        }
+
+        let weekly = PerformanceAnalyzer::aggregate(&daily, AggPeriod::Week);
+        assert_eq!(weekly.len(), 1);
+        let week = &weekly[0];
+        let expected = PerformanceAnalyzer::calculate_metrics(&all);
+
+        assert_eq!(week.date, "2025-01-13");
+        assert_eq!(week.total_trades, 8);
+        assert_eq!(week.winning_trades, 4);
+        assert_eq!(week.win_rate, expected.win_rate);
+        assert_eq!(week.net_pnl, 1600.0);
+        assert_eq!(week.gross_profit, expected.gross_profit);
+        assert_eq!(week.gross_loss, expected.gross_loss);
+        assert!((week.profit_factor - expected.profit_factor).abs() < 1e-9);
+        assert!((week.avg_win - expected.avg_win).abs() < 1e-9);
+        assert!((week.avg_loss - expected.avg_loss).abs() < 1e-9);
+        assert_eq!(week.ce_win_rate, expected.ce_win_rate);
+        assert_eq!((week.largest_win, week.largest_loss), (1000.0, -600.0));
+
+        // Not the naive mean of the daily win rates (50, 0, 66.7, 0, 50)
+        let naive = daily.iter().map(|m| m.win_rate).sum::<f64>() / daily.len() as f64;
+        assert!((week.win_rate - naive).abs() > 1.0);
+
+        // Monthly and cumulative split/merge on calendar boundaries
+        let mut february = daily[0].clone();
+        february.date = "2025-02-03".to_string();
+        let mut with_feb = daily.clone();
+        with_feb.push(february);
+        let months = PerformanceAnalyzer::aggregate(&with_feb, AggPeriod::Month);
+        assert_eq!(months.iter().map(|m| m.date.as_str()).collect::<Vec<_>>(), vec!["2025-01-01", "2025-02-01"]);
+        assert_eq!(months[1].total_trades, 2);
+        let cumulative = PerformanceAnalyzer::aggregate(&with_feb, AggPeriod::Cumulative);
+        assert_eq!(cumulative.len(), 1);
+        assert_eq!(cumulative[0].total_trades, 10);
+    }
+}
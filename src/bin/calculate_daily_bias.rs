@@ -3,7 +3,7 @@
 
 use rustro::broker::{AngelOneClient, InstrumentCache};
 use rustro::config::load_config;
-use rustro::strategy::{DailyBiasCalculator, DailyBiasToken, BiasDirection};
+use rustro::strategy::{DailyBiasCalculator, DailyBiasToken, BiasDirection, BIAS_SUMMARY_FILE};
 use rustro::types::Bar;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -77,7 +77,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let calculator = DailyBiasCalculator::new(
         config.daily_adx_period,
         config.daily_adx_threshold,
-    );
+    )
+        .with_method(config.daily_bias_method)
+        .with_ema_periods(config.daily_ema_fast, config.daily_ema_slow);
 
     let biases = calculator.calculate_all_bias(&tokens, &bars_map);
     
@@ -85,11 +87,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("\n📊 Step 5: Summary");
     info!("==================");
     
-    let summary = DailyBiasCalculator::get_summary(&biases);
+    let summary = DailyBiasCalculator::summarize(&biases);
     info!("Total underlyings analyzed: {}", summary.total);
     info!("  CE (Bullish): {}", summary.ce_count);
     info!("  PE (Bearish): {}", summary.pe_count);
     info!("  NO_TRADE (Sideways): {}", summary.no_trade_count);
+    info!("  Average ADX: {:.2}", summary.avg_adx);
+    info!("  Strongest trend: {}", summary.strongest_underlying.as_deref().unwrap_or("none"));
+    summary.save(BIAS_SUMMARY_FILE).await?;
 
     // Step 6: Show tradeable underlyings
     info!("\n📈 CE Bias (Bullish - Trade Call Options):");
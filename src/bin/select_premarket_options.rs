@@ -5,6 +5,8 @@ use rustro::broker::{AngelOneClient, InstrumentCache, TokenExtractor};
 use rustro::config::load_config;
 use rustro::strategy::DailyBias;
 use rustro::trading::PremarketSelector;
+use rustro::utils::is_market_open;
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{error, info};
 use tracing_subscriber::EnvFilter;
@@ -61,7 +63,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let extractor = Arc::new(TokenExtractor::new(instruments));
     let selector = PremarketSelector::new(extractor);
 
-    let preselected = selector.select_all_premarket_options(&tradeable);
+    // After 9:15 the opening print replaces the previous close as the ATM reference
+    let mut open_prices = HashMap::new();
+    if is_market_open(chrono::Utc::now()) {
+        for bias in &tradeable {
+            match broker.get_ltp_on("NSE", &bias.spot_token).await {
+                Ok(ltp) => {
+                    open_prices.insert(bias.spot_token.clone(), ltp);
+                }
+                Err(e) => error!("   {} open price unavailable, using prev close: {}", bias.underlying, e),
+            }
+        }
+    }
+    let preselected = selector.select_all_options_at_open(&tradeable, &open_prices);
 
     // Step 5: Display results
     info!("\n📊 Step 5: Pre-Selected Options");
@@ -120,6 +120,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 info!("   Underlying bars: {}", asset_report.underlying_bars);
                 info!("   Futures synced: {}", asset_report.futures_synced);
                 info!("   Options synced: {}", asset_report.options_synced);
+                info!("   Expired contracts skipped: {}", asset_report.skipped_expired);
+                info!("   Empty responses: {}", asset_report.empty_responses);
+                info!("   Network errors: {}", asset_report.network_errors);
                 info!("   Daily bars: {}", asset_report.total_daily_bars);
                 info!("   Hourly bars: {}", asset_report.total_hourly_bars);
                 info!("   Strikes covered: {:?}", asset_report.strikes_covered);
@@ -7,9 +7,10 @@ use tracing::{debug, error, info, warn};
 
 use crate::broker::tokens::{TokenManager, Tokens};
 use crate::error::{Result, TradingError};
-use crate::types::{Bar, Instrument, OrderType, Side};
+use crate::types::{Bar, BrokerOrder, Instrument, OrderType, ProductType, Quote, Side};
 
 const BASE_URL: &str = "https://apiconnect.angelbroking.com";
+const INSTRUMENT_MASTER_URL: &str = "https://margincalculator.angelbroking.com/OpenAPI_File/files/OpenAPIScripMaster.json";
 
 #[derive(Debug, Serialize)]
 struct LoginRequest {
@@ -59,6 +60,84 @@ struct OrderRequest {
     #[serde(rename = "stoploss")]
     stop_loss: String,
     quantity: String,
+    #[serde(rename = "ordertag", skip_serializing_if = "Option::is_none")]
+    order_tag: Option<String>,
+}
+
+/// Parse an HTTP `Date` header (RFC 7231 IMF-fixdate, e.g. "Tue, 15 Nov 1994 08:12:31 GMT")
+fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc2822(value.trim())
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Local clock offset from the server (positive = local clock is ahead)
+fn clock_drift(local: DateTime<Utc>, server: DateTime<Utc>) -> chrono::Duration {
+    local - server
+}
+
+/// Build a regular (NORMAL variety) order request
+fn normal_order_request(
+    symbol: &str,
+    token: &str,
+    side: Side,
+    quantity: i32,
+    order_type: OrderType,
+    price: Option<f64>,
+    product_type: ProductType,
+) -> OrderRequest {
+    OrderRequest {
+        variety: "NORMAL".to_string(),
+        trading_symbol: symbol.to_string(),
+        symbol_token: token.to_string(),
+        transaction_type: side.as_str().to_string(),
+        exchange: "NFO".to_string(),
+        order_type: order_type.as_str().to_string(),
+        product_type: product_type.as_str().to_string(),
+        duration: "DAY".to_string(),
+        price: price.map(|p| p.to_string()).unwrap_or_else(|| "0".to_string()),
+        square_off: "0".to_string(),
+        stop_loss: "0".to_string(),
+        quantity: quantity.to_string(),
+        order_tag: None,
+    }
+}
+
+/// Build a cover order request (ROBO variety, BO product)
+/// SmartAPI expects `stoploss` as an absolute point distance from the entry price;
+/// `squareoff` is left at zero since targets are managed by the bot
+fn cover_order_request(
+    symbol: &str,
+    token: &str,
+    side: Side,
+    quantity: i32,
+    price: f64,
+    stop_price: f64,
+) -> OrderRequest {
+    let stop_distance = (price - stop_price).abs();
+    
+    OrderRequest {
+        variety: "ROBO".to_string(),
+        trading_symbol: symbol.to_string(),
+        symbol_token: token.to_string(),
+        transaction_type: side.as_str().to_string(),
+        exchange: "NFO".to_string(),
+        order_type: OrderType::Limit.as_str().to_string(),
+        product_type: "BO".to_string(),
+        duration: "DAY".to_string(),
+        price: format!("{:.2}", price),
+        square_off: "0".to_string(),
+        stop_loss: format!("{:.2}", stop_distance),
+        quantity: quantity.to_string(),
+        order_tag: None,
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct CancelOrderRequest {
+    variety: String,
+    #[serde(rename = "orderid")]
+    order_id: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -76,6 +155,53 @@ struct OrderResponseData {
     order_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+struct OrderBookResponse {
+    status: bool,
+    message: String,
+    #[serde(rename = "errorcode")]
+    error_code: Option<String>,
+    data: Option<Vec<OrderBookEntry>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OrderBookEntry {
+    #[serde(rename = "orderid")]
+    order_id: String,
+    #[serde(rename = "tradingsymbol")]
+    trading_symbol: String,
+    #[serde(rename = "symboltoken")]
+    symbol_token: String,
+    #[serde(rename = "transactiontype")]
+    transaction_type: String,
+    #[serde(rename = "filledshares", default)]
+    filled_shares: String,
+    #[serde(rename = "averageprice", default)]
+    average_price: f64,
+    #[serde(rename = "orderstatus", default)]
+    order_status: String,
+    #[serde(rename = "ordertag", default)]
+    order_tag: String,
+    #[serde(rename = "parentorderid", default)]
+    parent_order_id: String,
+}
+
+impl OrderBookEntry {
+    fn into_broker_order(self) -> BrokerOrder {
+        BrokerOrder {
+            broker_order_id: self.order_id,
+            symbol: self.trading_symbol,
+            token: self.symbol_token,
+            side: if self.transaction_type == "SELL" { Side::Sell } else { Side::Buy },
+            filled_quantity: self.filled_shares.parse().unwrap_or(0),
+            average_price: self.average_price,
+            status: self.order_status,
+            order_tag: (!self.order_tag.is_empty()).then_some(self.order_tag),
+            parent_order_id: (!self.parent_order_id.is_empty()).then_some(self.parent_order_id),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 struct CandleRequest {
     exchange: String,
@@ -93,7 +219,7 @@ struct CandleResponse {
     status: bool,
     message: String,
     #[serde(rename = "errorcode")]
-    _error_code: Option<String>,
+    error_code: Option<String>,
     data: Option<Vec<Vec<String>>>,
 }
 
@@ -109,6 +235,84 @@ struct LtpData {
     ltp: f64,
 }
 
+#[derive(Debug, Deserialize)]
+struct QuoteResponse {
+    status: bool,
+    message: String,
+    data: Option<QuoteData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteData {
+    #[serde(default)]
+    fetched: Vec<QuoteEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteEntry {
+    ltp: f64,
+    #[serde(rename = "tradeVolume", default)]
+    trade_volume: i64,
+    #[serde(rename = "opnInterest", default)]
+    open_interest: i64,
+    #[serde(default)]
+    depth: Option<QuoteDepth>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuoteDepth {
+    #[serde(default)]
+    buy: Vec<DepthLevel>,
+    #[serde(default)]
+    sell: Vec<DepthLevel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DepthLevel {
+    price: f64,
+}
+
+impl QuoteEntry {
+    fn into_quote(self) -> Quote {
+        let best = |levels: Option<&Vec<DepthLevel>>| levels
+            .and_then(|l| l.first())
+            .map_or(0.0, |level| level.price);
+        Quote {
+            ltp: self.ltp,
+            volume: self.trade_volume,
+            open_interest: self.open_interest,
+            bid: best(self.depth.as_ref().map(|d| &d.buy)),
+            ask: best(self.depth.as_ref().map(|d| &d.sell)),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct RmsResponse {
+    status: bool,
+    message: String,
+    data: Option<RmsData>,
+}
+
+/// RMS limits; amounts arrive as strings
+#[derive(Debug, Deserialize)]
+struct RmsData {
+    #[serde(default)]
+    net: Option<String>,
+    #[serde(rename = "availablecash", default)]
+    available_cash: Option<String>,
+}
+
+impl RmsData {
+    /// Net available margin, falling back to available cash
+    fn funds(&self) -> Option<f64> {
+        [&self.net, &self.available_cash]
+            .into_iter()
+            .flatten()
+            .find_map(|amount| amount.trim().parse::<f64>().ok())
+    }
+}
+
 /// Angel One SmartAPI client
 pub struct AngelOneClient {
     client: Client,
@@ -118,6 +322,8 @@ pub struct AngelOneClient {
     mpin: Option<String>,
     totp_secret: String,
     api_key: String,
+    product_type: ProductType,
+    instrument_master_url: String,
 }
 
 impl AngelOneClient {
@@ -142,9 +348,23 @@ impl AngelOneClient {
             mpin,
             totp_secret,
             api_key,
+            product_type: ProductType::Carryforward,
+            instrument_master_url: INSTRUMENT_MASTER_URL.to_string(),
         }
     }
     
+    /// Set the product type used for regular orders (defaults to CARRYFORWARD)
+    pub fn with_product_type(mut self, product_type: ProductType) -> Self {
+        self.product_type = product_type;
+        self
+    }
+    
+    /// Download the instrument master from `url` instead of Angel One's
+    pub fn with_instrument_master_url(mut self, url: impl Into<String>) -> Self {
+        self.instrument_master_url = url.into();
+        self
+    }
+    
     /// Authenticate and get tokens
     pub async fn login(&self) -> Result<Tokens> {
         info!("Attempting login to Angel One");
@@ -276,6 +496,30 @@ impl AngelOneClient {
         }
     }
     
+    /// Measure local clock drift against the broker's `Date` response header
+    /// Local time is taken at the midpoint of the request to cancel out latency;
+    /// the header only has 1-second resolution so sub-second drift is noise
+    pub async fn measure_clock_drift(&self) -> Result<chrono::Duration> {
+        let sent_at = Utc::now();
+        let response = self.client
+            .head(BASE_URL)
+            .send()
+            .await?;
+        let received_at = Utc::now();
+        
+        let server_time = response.headers()
+            .get(reqwest::header::DATE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(parse_http_date)
+            .ok_or_else(|| TradingError::MissingData("No Date header in broker response".to_string()))?;
+        
+        let local_time = sent_at + (received_at - sent_at) / 2;
+        let drift = clock_drift(local_time, server_time);
+        debug!("Clock drift vs broker: {} ms", drift.num_milliseconds());
+        
+        Ok(drift)
+    }
+    
     /// Calculate token expiry (3:30 AM next day IST)
     fn calculate_token_expiry(&self, now: DateTime<Utc>) -> DateTime<Utc> {
         use chrono::TimeZone;
@@ -348,25 +592,175 @@ impl AngelOneClient {
         order_type: OrderType,
         price: Option<f64>,
     ) -> Result<String> {
+        let order_req = normal_order_request(
+            symbol,
+            token,
+            side,
+            quantity,
+            order_type,
+            price,
+            self.product_type,
+        );
+        
+        debug!("Placing order: {:?}", order_req);
+        
+        self.send_order(&order_req).await
+    }
+    
+    /// Place an order carrying an `ordertag` that the order book echoes back
+    /// LIMIT at `price`, or MARKET when no price is given
+    pub async fn place_tagged_order(
+        &self,
+        symbol: &str,
+        token: &str,
+        side: Side,
+        quantity: i32,
+        price: Option<f64>,
+        order_tag: &str,
+    ) -> Result<String> {
+        let order_type = if price.is_some() { OrderType::Limit } else { OrderType::Market };
+        let mut order_req = normal_order_request(
+            symbol,
+            token,
+            side,
+            quantity,
+            order_type,
+            price,
+            self.product_type,
+        );
+        order_req.order_tag = Some(order_tag.to_string());
+        
+        debug!("Placing order: {:?}", order_req);
+        
+        self.send_order(&order_req).await
+    }
+    
+    /// Place a cover order with a broker-side stop loss attached
+    /// The stop is held by the exchange, so it survives a bot crash or disconnect
+    pub async fn place_cover_order(
+        &self,
+        symbol: &str,
+        token: &str,
+        side: Side,
+        quantity: i32,
+        price: f64,
+        stop_price: f64,
+    ) -> Result<String> {
+        if (price - stop_price).abs() < f64::EPSILON {
+            return Err(TradingError::InvalidParameter(
+                "Cover order stop price must differ from entry price".to_string()
+            ));
+        }
+        
+        let order_req = cover_order_request(symbol, token, side, quantity, price, stop_price);
+        
+        debug!("Placing cover order: {:?}", order_req);
+        
+        self.send_order(&order_req).await
+    }
+    
+    /// Cancel an open order
+    pub async fn cancel_order(&self, broker_order_id: &str) -> Result<()> {
+        self.cancel_variety(broker_order_id, "NORMAL").await
+    }
+    
+    /// Cancel a cover order (ROBO variety): before the entry fills this drops
+    /// it; once filled, it exits the position and removes the stop leg
+    pub async fn cancel_cover_order(&self, broker_order_id: &str) -> Result<()> {
+        self.cancel_variety(broker_order_id, "ROBO").await
+    }
+    
+    async fn cancel_variety(&self, broker_order_id: &str, variety: &str) -> Result<()> {
         let tokens = self.token_manager.get_tokens().await
             .ok_or_else(|| TradingError::TokenExpired("No tokens available".to_string()))?;
         
-        let order_req = OrderRequest {
-            variety: "NORMAL".to_string(),
-            trading_symbol: symbol.to_string(),
-            symbol_token: token.to_string(),
-            transaction_type: side.as_str().to_string(),
-            exchange: "NFO".to_string(),
-            order_type: order_type.as_str().to_string(),
-            product_type: "CARRYFORWARD".to_string(),
-            duration: "DAY".to_string(),
-            price: price.map(|p| p.to_string()).unwrap_or_else(|| "0".to_string()),
-            square_off: "0".to_string(),
-            stop_loss: "0".to_string(),
-            quantity: quantity.to_string(),
+        let cancel_req = CancelOrderRequest {
+            variety: variety.to_string(),
+            order_id: broker_order_id.to_string(),
         };
         
-        debug!("Placing order: {:?}", order_req);
+        debug!("Cancelling order: {:?}", cancel_req);
+        
+        let response = self.client
+            .post(format!("{}/rest/secure/angelbroking/order/v1/cancelOrder", BASE_URL))
+            .header("Authorization", format!("Bearer {}", tokens.jwt_token))
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .header("X-UserType", "USER")
+            .header("X-SourceID", "WEB")
+            .header("X-ClientLocalIP", "127.0.0.1")
+            .header("X-ClientPublicIP", "127.0.0.1")
+            .header("X-MACAddress", "00:00:00:00:00:00")
+            .header("X-PrivateKey", &self.api_key)
+            .json(&cancel_req)
+            .send()
+            .await?;
+        
+        let body = response.text().await?;
+        
+        let cancel_response: OrderResponse = serde_json::from_str(&body)
+            .map_err(|e| TradingError::BrokerApiError {
+                code: "PARSE".to_string(),
+                message: format!("Cancel response parse error: {}", e),
+            })?;
+        
+        if !cancel_response.status {
+            return Err(TradingError::BrokerApiError {
+                code: cancel_response.error_code.unwrap_or_default(),
+                message: format!("Cancel failed: {}", cancel_response.message),
+            });
+        }
+        
+        info!("Order cancelled: {}", broker_order_id);
+        Ok(())
+    }
+    
+    /// Fetch today's order book
+    pub async fn get_order_book(&self) -> Result<Vec<BrokerOrder>> {
+        let tokens = self.token_manager.get_tokens().await
+            .ok_or_else(|| TradingError::TokenExpired("No tokens available".to_string()))?;
+        
+        let response = self.client
+            .get(format!("{}/rest/secure/angelbroking/order/v1/getOrderBook", BASE_URL))
+            .header("Authorization", format!("Bearer {}", tokens.jwt_token))
+            .header("Content-Type", "application/json")
+            .header("Accept", "application/json")
+            .header("X-UserType", "USER")
+            .header("X-SourceID", "WEB")
+            .header("X-ClientLocalIP", "127.0.0.1")
+            .header("X-ClientPublicIP", "127.0.0.1")
+            .header("X-MACAddress", "00:00:00:00:00:00")
+            .header("X-PrivateKey", &self.api_key)
+            .send()
+            .await?;
+        
+        let body = response.text().await?;
+        
+        let book: OrderBookResponse = serde_json::from_str(&body)
+            .map_err(|e| TradingError::BrokerApiError {
+                code: "PARSE".to_string(),
+                message: format!("Order book parse error: {}", e),
+            })?;
+        
+        if !book.status {
+            return Err(TradingError::BrokerApiError {
+                code: book.error_code.unwrap_or_default(),
+                message: format!("Order book fetch failed: {}", book.message),
+            });
+        }
+        
+        // An empty order book comes back as `data: null`
+        Ok(book.data
+            .unwrap_or_default()
+            .into_iter()
+            .map(OrderBookEntry::into_broker_order)
+            .collect())
+    }
+    
+    /// Submit an order request and return the broker order ID
+    async fn send_order(&self, order_req: &OrderRequest) -> Result<String> {
+        let tokens = self.token_manager.get_tokens().await
+            .ok_or_else(|| TradingError::TokenExpired("No tokens available".to_string()))?;
         
         let response = self.client
             .post(&format!("{}/rest/secure/angelbroking/order/v1/placeOrder", BASE_URL))
@@ -379,7 +773,7 @@ impl AngelOneClient {
             .header("X-ClientPublicIP", "127.0.0.1")
             .header("X-MACAddress", "00:00:00:00:00:00")
             .header("X-PrivateKey", &self.api_key)
-            .json(&order_req)
+            .json(order_req)
             .send()
             .await?;
         
@@ -388,14 +782,20 @@ impl AngelOneClient {
         
         debug!("Order response status: {}, body: {}", status, body);
         
-        let order_response: OrderResponse = serde_json::from_str(&body)
-            .map_err(|e| TradingError::OrderPlacementFailed(format!("Parse error: {}", e)))?;
+        let order_response: OrderResponse = serde_json::from_str(&body).map_err(|e| {
+            TradingError::classify_broker(status.as_u16(), "", &body)
+                .unwrap_or_else(|| TradingError::OrderPlacementFailed(format!("Parse error: {}", e)))
+        })?;
         
         if !order_response.status {
+            let code = order_response.error_code.unwrap_or_default();
+            if let Some(e) = TradingError::classify_broker(status.as_u16(), &code, &order_response.message) {
+                return Err(e);
+            }
             return Err(TradingError::OrderPlacementFailed(format!(
                 "Order failed: {} (code: {})",
                 order_response.message,
-                order_response.error_code.unwrap_or_default()
+                code
             )));
         }
         
@@ -438,11 +838,17 @@ impl AngelOneClient {
             .send()
             .await?;
         
+        let status = response.status();
         let body = response.text().await?;
-        let candle_response: CandleResponse = serde_json::from_str(&body)
-            .map_err(|e| TradingError::DeserializationError(e))?;
+        let candle_response: CandleResponse = serde_json::from_str(&body).map_err(|e| {
+            TradingError::classify_broker(status.as_u16(), "", &body).unwrap_or(TradingError::DeserializationError(e))
+        })?;
         
         if !candle_response.status {
+            let code = candle_response.error_code.unwrap_or_default();
+            if let Some(e) = TradingError::classify_broker(status.as_u16(), &code, &candle_response.message) {
+                return Err(e);
+            }
             return Err(TradingError::MissingData(format!(
                 "Candle fetch failed: {}",
                 candle_response.message
@@ -478,17 +884,29 @@ impl AngelOneClient {
             })
             .collect();
         
+        // Broker occasionally returns zero rows or high < low
+        let parsed = bars.len();
+        let bars: Vec<Bar> = bars.into_iter().filter(Bar::is_valid).collect();
+        if bars.len() < parsed {
+            warn!("Dropped {} invalid candles for {} {}", parsed - bars.len(), symbol_token, interval);
+        }
+        
         debug!("Fetched {} candles", bars.len());
         Ok(bars)
     }
     
-    /// Get LTP for a symbol
+    /// Get LTP for an NFO symbol
     pub async fn get_ltp(&self, symbol_token: &str) -> Result<f64> {
+        self.get_ltp_on("NFO", symbol_token).await
+    }
+    
+    /// Get LTP for a symbol on `exchange` (e.g. "NSE" for index spot tokens)
+    pub async fn get_ltp_on(&self, exchange: &str, symbol_token: &str) -> Result<f64> {
         let tokens = self.token_manager.get_tokens().await
             .ok_or_else(|| TradingError::TokenExpired("No tokens available".to_string()))?;
         
         let payload = serde_json::json!({
-            "exchange": "NFO",
+            "exchange": exchange,
             "symboltoken": symbol_token,
             "tradingsymbol": ""
         });
@@ -519,14 +937,77 @@ impl AngelOneClient {
         Ok(ltp)
     }
     
+    /// Full-mode quote for an NFO token (LTP, traded volume, open interest, best bid/ask)
+    pub async fn get_quote(&self, symbol_token: &str) -> Result<Quote> {
+        let tokens = self.token_manager.get_tokens().await
+            .ok_or_else(|| TradingError::TokenExpired("No tokens available".to_string()))?;
+        
+        let payload = serde_json::json!({
+            "mode": "FULL",
+            "exchangeTokens": { "NFO": [symbol_token] }
+        });
+        
+        let response = self.client
+            .post(format!("{}/rest/secure/angelbroking/market/v1/quote/", BASE_URL))
+            .header("Authorization", format!("Bearer {}", tokens.jwt_token))
+            .header("Content-Type", "application/json")
+            .header("X-PrivateKey", &self.api_key)
+            .json(&payload)
+            .send()
+            .await?;
+        
+        let body = response.text().await?;
+        let quote_response: QuoteResponse = serde_json::from_str(&body)?;
+        
+        if !quote_response.status {
+            return Err(TradingError::MissingData(format!(
+                "Quote fetch failed: {}",
+                quote_response.message
+            )));
+        }
+        
+        let entry = quote_response.data
+            .and_then(|d| d.fetched.into_iter().next())
+            .ok_or_else(|| TradingError::MissingData(format!("No quote for {}", symbol_token)))?;
+        
+        Ok(entry.into_quote())
+    }
+    
+    /// Funds available for trading, from the RMS limits endpoint
+    pub async fn get_available_funds(&self) -> Result<f64> {
+        let tokens = self.token_manager.get_tokens().await
+            .ok_or_else(|| TradingError::TokenExpired("No tokens available".to_string()))?;
+        
+        let response = self.client
+            .get(format!("{}/rest/secure/angelbroking/user/v1/getRMS", BASE_URL))
+            .header("Authorization", format!("Bearer {}", tokens.jwt_token))
+            .header("Content-Type", "application/json")
+            .header("X-PrivateKey", &self.api_key)
+            .send()
+            .await?;
+        
+        let body = response.text().await?;
+        let rms_response: RmsResponse = serde_json::from_str(&body)?;
+        
+        if !rms_response.status {
+            return Err(TradingError::MissingData(format!(
+                "RMS fetch failed: {}",
+                rms_response.message
+            )));
+        }
+        
+        rms_response.data
+            .as_ref()
+            .and_then(RmsData::funds)
+            .ok_or_else(|| TradingError::MissingData("No funds in RMS response".to_string()))
+    }
+    
     /// Download instrument master CSV
     pub async fn download_instrument_master(&self) -> Result<Vec<Instrument>> {
         info!("Downloading instrument master");
         
-        let url = "https://margincalculator.angelbroking.com/OpenAPI_File/files/OpenAPIScripMaster.json";
-        
         let response = self.client
-            .get(url)
+            .get(&self.instrument_master_url)
             .send()
             .await?;
         
@@ -575,3 +1056,93 @@ impl AngelOneClient {
         self.login().await
     }
 }
+
+/// Client with no session tokens (token file in a fresh temp path), so every
+/// API call fails without reaching the network (tests only)
+#[cfg(test)]
+pub(crate) fn test_client() -> AngelOneClient {
+    let token_file = std::env::temp_dir().join(format!("angel_tokens_{}.json", uuid::Uuid::new_v4()));
+    AngelOneClient::new(
+        Arc::new(TokenManager::new(token_file.display().to_string())),
+        "TEST".to_string(),
+        "password".to_string(),
+        None,
+        "JBSWY3DPEHPK3PXP".to_string(),
+        "api_key".to_string(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_clock_drift_from_date_header() {
+        let server = parse_http_date("Tue, 14 Jan 2025 04:00:00 GMT").unwrap();
+        assert_eq!(server.to_rfc3339(), "2025-01-14T04:00:00+00:00");
+        
+        let local = server + chrono::Duration::milliseconds(7500);
+        assert_eq!(clock_drift(local, server).num_milliseconds(), 7500);
+        assert_eq!(clock_drift(server, local).num_milliseconds(), -7500);
+        
+        assert!(parse_http_date("not a date").is_none());
+    }
+    
+    #[test]
+    fn test_normal_order_product_type_serialization() {
+        for (product_type, expected) in [
+            (ProductType::Intraday, "INTRADAY"),
+            (ProductType::Carryforward, "CARRYFORWARD"),
+        ] {
+            let req = normal_order_request(
+                "NIFTY25JAN19500CE", "12345", Side::Buy, 50, OrderType::Limit, Some(125.0), product_type,
+            );
+            let json = serde_json::to_value(&req).unwrap();
+            
+            assert_eq!(json["variety"], "NORMAL");
+            assert_eq!(json["producttype"], expected);
+        }
+        
+        // Config values deserialize from the broker spelling
+        let parsed: ProductType = serde_json::from_str("\"INTRADAY\"").unwrap();
+        assert_eq!(parsed, ProductType::Intraday);
+        assert!(serde_json::from_str::<ProductType>("\"MIS\"").is_err());
+    }
+    
+    #[test]
+    fn test_full_quote_depth_feeds_spread_check() {
+        let body = r#"{"status":true,"message":"SUCCESS","data":{"fetched":[{
+            "ltp":120.0,"tradeVolume":5000,"opnInterest":90000,
+            "depth":{"buy":[{"price":110.0,"quantity":75,"orders":2}],
+                     "sell":[{"price":130.0,"quantity":75,"orders":1}]}}]}}"#;
+        let response: QuoteResponse = serde_json::from_str(body).unwrap();
+        let quote = response.data.unwrap().fetched.into_iter().next().unwrap().into_quote();
+        assert_eq!((quote.bid, quote.ask), (110.0, 130.0));
+        
+        // 20 wide on a 120 mid (16.7%) blocks the entry at a 5% cap
+        let mut config = crate::config::loader::test_config();
+        config.max_spread_pct = Some(0.05);
+        let validator = crate::orders::OrderValidator::new(std::sync::Arc::new(config));
+        assert!(validator.check_spread(quote.bid, quote.ask).is_err());
+        assert!(validator.check_spread(119.5, 120.5).is_ok());
+        
+        // No depth: no two-sided quote, nothing to block on
+        let bare = serde_json::from_str::<QuoteEntry>(r#"{"ltp":120.0}"#).unwrap().into_quote();
+        assert_eq!((bare.bid, bare.ask), (0.0, 0.0));
+    }
+    
+    #[test]
+    fn test_cover_order_request_serialization() {
+        let req = cover_order_request("NIFTY25JAN19500CE", "12345", Side::Buy, 50, 125.0, 100.0);
+        let json = serde_json::to_value(&req).unwrap();
+        
+        assert_eq!(json["variety"], "ROBO");
+        assert_eq!(json["producttype"], "BO");
+        assert_eq!(json["ordertype"], "LIMIT");
+        assert_eq!(json["transactiontype"], "BUY");
+        assert_eq!(json["price"], "125.00");
+        assert_eq!(json["stoploss"], "25.00");
+        assert_eq!(json["squareoff"], "0");
+        assert_eq!(json["quantity"], "50");
+    }
+}
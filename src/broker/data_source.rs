@@ -0,0 +1,406 @@
+/// Market data sources with vendor fallback
+/// `FallbackBroker` serves candles/LTP from a primary source and retries on
+/// a secondary (e.g. CSV files) when the primary errors.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use futures_util::future::BoxFuture;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+
+use crate::broker::AngelOneClient;
+use crate::data::ConcurrentTickBuffer;
+use crate::error::{Result, TradingError};
+use crate::events::{Event, EventBus, EventPayload, EventType};
+use crate::types::Bar;
+
+/// Candle and LTP provider
+pub trait MarketDataSource: Send + Sync {
+    /// Source name used in logs
+    fn name(&self) -> &str;
+
+    /// Candles for `token` at `interval` (SmartAPI names, e.g. "ONE_HOUR")
+    fn get_candles<'a>(
+        &'a self,
+        token: &'a str,
+        interval: &'a str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>>;
+
+    /// Last traded price for `token`
+    fn get_ltp<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<f64>>;
+}
+
+impl MarketDataSource for AngelOneClient {
+    fn name(&self) -> &str {
+        "angel_one"
+    }
+
+    fn get_candles<'a>(
+        &'a self,
+        token: &'a str,
+        interval: &'a str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(AngelOneClient::get_candles(self, token, interval, from, to))
+    }
+
+    fn get_ltp<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<f64>> {
+        Box::pin(AngelOneClient::get_ltp(self, token))
+    }
+}
+
+/// One CSV row: `timestamp,open,high,low,close,volume` (RFC 3339 timestamps)
+#[derive(Debug, Deserialize)]
+struct CsvCandle {
+    timestamp: DateTime<Utc>,
+    open: f64,
+    high: f64,
+    low: f64,
+    close: f64,
+    volume: i64,
+}
+
+/// Candles from `<dir>/<token>_<interval>.csv`; LTP is the last ONE_MINUTE close
+pub struct CsvCandleSource {
+    dir: PathBuf,
+}
+
+impl CsvCandleSource {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        CsvCandleSource { dir: dir.into() }
+    }
+
+    fn read_bars(&self, token: &str, interval: &str) -> Result<Vec<Bar>> {
+        let path = self.dir.join(format!("{}_{}.csv", token, interval));
+        let mut reader = csv::Reader::from_path(&path).map_err(|e| {
+            TradingError::MissingData(format!("{}: {}", path.display(), e))
+        })?;
+
+        reader
+            .deserialize::<CsvCandle>()
+            .map(|row| {
+                let row = row.map_err(|e| {
+                    TradingError::InvalidBarData(format!("{}: {}", path.display(), e))
+                })?;
+                Ok(Bar {
+                    timestamp: row.timestamp,
+                    timestamp_ms: row.timestamp.timestamp_millis(),
+                    open: row.open,
+                    high: row.high,
+                    low: row.low,
+                    close: row.close,
+                    volume: row.volume,
+                    bar_complete: true,
+                })
+            })
+            .collect()
+    }
+}
+
+impl MarketDataSource for CsvCandleSource {
+    fn name(&self) -> &str {
+        "csv"
+    }
+
+    fn get_candles<'a>(
+        &'a self,
+        token: &'a str,
+        interval: &'a str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(async move {
+            let mut bars = self.read_bars(token, interval)?;
+            bars.retain(|b| b.timestamp >= from && b.timestamp <= to);
+            Ok(bars)
+        })
+    }
+
+    fn get_ltp<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<f64>> {
+        Box::pin(async move {
+            self.read_bars(token, "ONE_MINUTE")?
+                .last()
+                .map(|b| b.close)
+                .ok_or_else(|| TradingError::MissingData(format!("No CSV candles for {}", token)))
+        })
+    }
+}
+
+/// Tries `primary`, then `secondary` on error
+pub struct FallbackBroker {
+    primary: Arc<dyn MarketDataSource>,
+    secondary: Arc<dyn MarketDataSource>,
+    /// Requests served per source name
+    served: RwLock<HashMap<String, u64>>,
+}
+
+impl FallbackBroker {
+    pub fn new(primary: Arc<dyn MarketDataSource>, secondary: Arc<dyn MarketDataSource>) -> Self {
+        FallbackBroker {
+            primary,
+            secondary,
+            served: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Requests served per source name
+    pub async fn served_counts(&self) -> HashMap<String, u64> {
+        self.served.read().await.clone()
+    }
+
+    async fn record(&self, source: &str, request: &str) {
+        debug!("{} served by {}", request, source);
+        *self.served.write().await.entry(source.to_string()).or_default() += 1;
+    }
+}
+
+impl MarketDataSource for FallbackBroker {
+    fn name(&self) -> &str {
+        "fallback"
+    }
+
+    fn get_candles<'a>(
+        &'a self,
+        token: &'a str,
+        interval: &'a str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+        Box::pin(async move {
+            let request = format!("Candles {} {}", token, interval);
+            match self.primary.get_candles(token, interval, from, to).await {
+                Ok(bars) => {
+                    self.record(self.primary.name(), &request).await;
+                    Ok(bars)
+                }
+                Err(e) => {
+                    warn!(
+                        "{} failed on {} ({}), trying {}",
+                        request,
+                        self.primary.name(),
+                        e,
+                        self.secondary.name()
+                    );
+                    let bars = self.secondary.get_candles(token, interval, from, to).await?;
+                    self.record(self.secondary.name(), &request).await;
+                    Ok(bars)
+                }
+            }
+        })
+    }
+
+    fn get_ltp<'a>(&'a self, token: &'a str) -> BoxFuture<'a, Result<f64>> {
+        Box::pin(async move {
+            let request = format!("LTP {}", token);
+            match self.primary.get_ltp(token).await {
+                Ok(ltp) => {
+                    self.record(self.primary.name(), &request).await;
+                    Ok(ltp)
+                }
+                Err(e) => {
+                    warn!(
+                        "{} failed on {} ({}), trying {}",
+                        request,
+                        self.primary.name(),
+                        e,
+                        self.secondary.name()
+                    );
+                    let ltp = self.secondary.get_ltp(token).await?;
+                    self.record(self.secondary.name(), &request).await;
+                    Ok(ltp)
+                }
+            }
+        })
+    }
+}
+
+/// `primary`, falling back to CSV candles in `fallback_dir` when set
+pub fn with_csv_fallback(
+    primary: Arc<dyn MarketDataSource>,
+    fallback_dir: Option<&str>,
+) -> Arc<dyn MarketDataSource> {
+    match fallback_dir {
+        Some(dir) => Arc::new(FallbackBroker::new(primary, Arc::new(CsvCandleSource::new(dir)))),
+        None => primary,
+    }
+}
+
+/// LTP for `token`: the last tick if younger than `max_age`, else `source`
+/// over REST. Ticks that stopped arriving (while the socket may still look
+/// connected) are reported as a tick `DataGapDetected`.
+pub async fn live_ltp(
+    tick_buffer: &ConcurrentTickBuffer,
+    source: &dyn MarketDataSource,
+    event_bus: &EventBus,
+    token: &str,
+    symbol: &str,
+    max_age: chrono::Duration,
+) -> Result<f64> {
+    if let Some(tick) = tick_buffer.fresh(token, max_age).await {
+        return Ok(tick.ltp);
+    }
+
+    if let Some(last_tick) = tick_buffer.latest(token).await {
+        warn!("⚠️  Stale ticks for {} (last {}), using REST LTP", symbol, last_tick.timestamp);
+        let _ = event_bus.publish(Event::new(
+            EventType::DataGapDetected,
+            EventPayload::DataGapDetected {
+                symbol: symbol.to_string(),
+                timeframe: "tick".to_string(),
+                gap_start: last_tick.timestamp,
+                gap_end: Utc::now(),
+                missing_bars: 0,
+            },
+        )).await;
+    }
+
+    source.get_ltp(token).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::broker::angel_one::test_client;
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn test_secondary_serves_when_primary_fails() {
+        let dir = std::env::temp_dir().join(format!("csv_source_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("99926000_ONE_HOUR.csv"),
+            "timestamp,open,high,low,close,volume\n\
+             2025-01-15T03:45:00Z,23500,23550,23480,23520,1000\n\
+             2025-01-15T04:45:00Z,23520,23600,23510,23590,1200\n\
+             2025-01-16T03:45:00Z,23590,23620,23500,23510,900\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("99926000_ONE_MINUTE.csv"),
+            "timestamp,open,high,low,close,volume\n\
+             2025-01-15T09:59:00Z,23580,23590,23575,23588.5,100\n",
+        )
+        .unwrap();
+
+        // No session tokens: every Angel One call fails
+        let primary = Arc::new(test_client());
+        let broker = FallbackBroker::new(primary, Arc::new(CsvCandleSource::new(&dir)));
+
+        let from = Utc.with_ymd_and_hms(2025, 1, 15, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2025, 1, 15, 23, 59, 0).unwrap();
+        let bars = broker.get_candles("99926000", "ONE_HOUR", from, to).await.unwrap();
+        assert_eq!(bars.len(), 2);
+        assert_eq!(bars[1].close, 23590.0);
+
+        assert_eq!(broker.get_ltp("99926000").await.unwrap(), 23588.5);
+
+        let served = broker.served_counts().await;
+        assert_eq!(served.get("csv"), Some(&2));
+        assert_eq!(served.get("angel_one"), None);
+
+        // Both sources failing surfaces the secondary's error
+        assert!(broker.get_ltp("unknown").await.is_err());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_csv_fallback_only_when_configured() {
+        let dir = std::env::temp_dir().join(format!("csv_fallback_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("99926000_ONE_MINUTE.csv"),
+            "timestamp,open,high,low,close,volume\n\
+             2025-01-15T09:59:00Z,23580,23590,23575,23588.5,100\n",
+        )
+        .unwrap();
+        let primary: Arc<dyn MarketDataSource> = Arc::new(test_client());
+
+        let source = with_csv_fallback(Arc::clone(&primary), None);
+        assert_eq!(source.name(), "angel_one");
+        assert!(source.get_ltp("99926000").await.is_err());
+
+        let source = with_csv_fallback(primary, Some(&dir.display().to_string()));
+        assert_eq!(source.name(), "fallback");
+        assert_eq!(source.get_ltp("99926000").await.unwrap(), 23588.5);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /// REST stand-in returning a fixed LTP
+    struct FixedLtp(f64);
+
+    impl MarketDataSource for FixedLtp {
+        fn name(&self) -> &str {
+            "fixed"
+        }
+
+        fn get_candles<'a>(
+            &'a self,
+            _token: &'a str,
+            _interval: &'a str,
+            _from: DateTime<Utc>,
+            _to: DateTime<Utc>,
+        ) -> BoxFuture<'a, Result<Vec<Bar>>> {
+            Box::pin(async { Ok(Vec::new()) })
+        }
+
+        fn get_ltp<'a>(&'a self, _token: &'a str) -> BoxFuture<'a, Result<f64>> {
+            Box::pin(async move { Ok(self.0) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stale_tick_falls_back_to_rest_ltp() {
+        let dir = std::env::temp_dir().join(format!("live_ltp_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = EventBus::new(dir.join("events.jsonl").display().to_string());
+        let tick_buffer = ConcurrentTickBuffer::new(16);
+        let rest = FixedLtp(131.5);
+        let max_age = chrono::Duration::seconds(5);
+
+        let tick = |ltp: f64, age_sec: i64| {
+            let timestamp = Utc::now() - chrono::Duration::seconds(age_sec);
+            crate::types::Tick {
+                symbol: "NIFTY25JAN23500CE".to_string(),
+                token: "43210".to_string(),
+                ltp,
+                bid: 0.0,
+                ask: 0.0,
+                volume: 0,
+                timestamp,
+                timestamp_ms: timestamp.timestamp_millis(),
+            }
+        };
+
+        // Fresh tick: no REST call, no gap
+        tick_buffer.push(tick(120.0, 0)).await;
+        let ltp = live_ltp(&tick_buffer, &rest, &event_bus, "43210", "NIFTY25JAN23500CE", max_age).await.unwrap();
+        assert_eq!(ltp, 120.0);
+
+        // Feed went quiet: REST price and a tick data gap
+        tick_buffer.push(tick(118.0, 60)).await;
+        let ltp = live_ltp(&tick_buffer, &rest, &event_bus, "43210", "NIFTY25JAN23500CE", max_age).await.unwrap();
+        assert_eq!(ltp, 131.5);
+
+        let gaps: Vec<_> = event_bus.replay_events(DateTime::<Utc>::MIN_UTC).await.unwrap()
+            .into_iter()
+            .filter(|e| e.event_type == EventType::DataGapDetected)
+            .collect();
+        assert_eq!(gaps.len(), 1);
+        match &gaps[0].payload {
+            EventPayload::DataGapDetected { symbol, timeframe, .. } => {
+                assert_eq!(symbol, "NIFTY25JAN23500CE");
+                assert_eq!(timeframe, "tick");
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
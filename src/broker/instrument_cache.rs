@@ -1,20 +1,55 @@
 /// Instrument cache for fast token lookups
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use chrono::{DateTime, Datelike, NaiveDate, Utc};
-use tracing::info;
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Utc};
+use chrono_tz::Asia::Kolkata;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
 
 use crate::broker::AngelOneClient;
 use crate::error::{Result, TradingError};
+use crate::time::{Clock, SystemClock};
 use crate::types::{Instrument, OptionType};
 
+/// On-disk copy of the instrument master
+const INSTRUMENT_CACHE_FILE: &str = "data/instrument_master.json";
+
+/// Hour (IST) by which Angel One publishes the daily instrument master
+const MASTER_UPDATE_HOUR_IST: u32 = 8;
+
+/// Persisted instrument master with its refresh time
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedMaster {
+    updated_at: DateTime<Utc>,
+    instruments: Vec<Instrument>,
+}
+
+/// Changes between two instrument masters
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentDiff {
+    pub added: Vec<Instrument>,
+    pub removed: Vec<Instrument>,
+    /// Expiries not present in the previous master, sorted
+    pub new_expiries: Vec<String>,
+}
+
+impl InstrumentDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
 /// Instrument cache for fast lookups
 pub struct InstrumentCache {
     broker: Arc<AngelOneClient>,
     instruments: Arc<RwLock<Vec<Instrument>>>,
     token_map: Arc<RwLock<HashMap<String, Instrument>>>,
     last_updated: Arc<RwLock<Option<DateTime<Utc>>>>,
+    /// On-disk copy of the master
+    cache_file: PathBuf,
+    clock: Arc<dyn Clock>,
 }
 
 impl InstrumentCache {
@@ -24,15 +59,94 @@ impl InstrumentCache {
             instruments: Arc::new(RwLock::new(Vec::new())),
             token_map: Arc::new(RwLock::new(HashMap::new())),
             last_updated: Arc::new(RwLock::new(None)),
+            cache_file: PathBuf::from(INSTRUMENT_CACHE_FILE),
+            clock: Arc::new(SystemClock),
         }
     }
     
-    /// Download and cache instrument master
-    pub async fn refresh(&self) -> Result<()> {
+    /// Use a custom clock (e.g. `MockClock` in tests)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Persist the master somewhere other than `data/instrument_master.json`
+    pub fn with_cache_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.cache_file = path.into();
+        self
+    }
+    
+    /// Download and cache instrument master; returns what changed since the
+    /// previously cached master (everything is "added" on a cold cache)
+    /// If the download fails, today's on-disk copy is used instead
+    pub async fn refresh(&self) -> Result<InstrumentDiff> {
         info!("📥 Downloading instrument master...");
         
-        let instruments = self.broker.download_instrument_master().await?;
+        let instruments = match self.broker.download_instrument_master().await {
+            Ok(instruments) => instruments,
+            Err(e) => return self.fall_back_to_disk(e).await,
+        };
+        let diff = diff_instruments(&self.instruments.read().await, &instruments);
+        
+        let updated_at = self.clock.now();
+        let count = instruments.len();
+        
+        // Persist so a restart later in the day can skip the download
+        let cached = CachedMaster { updated_at, instruments };
+        if let Some(parent) = self.cache_file.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        tokio::fs::write(&self.cache_file, serde_json::to_string(&cached)?).await?;
+        
+        self.set_instruments(cached.instruments, updated_at).await;
+        
+        info!(
+            "✅ Cached {} instruments (+{} / -{}, {} new expiries)",
+            count,
+            diff.added.len(),
+            diff.removed.len(),
+            diff.new_expiries.len()
+        );
+        Ok(diff)
+    }
+    
+    /// Use the on-disk master after a failed download if it was refreshed
+    /// earlier today (IST); otherwise surface the download error
+    async fn fall_back_to_disk(&self, error: TradingError) -> Result<InstrumentDiff> {
+        let cached = match tokio::fs::read_to_string(&self.cache_file).await {
+            Ok(content) => serde_json::from_str::<CachedMaster>(&content).ok(),
+            Err(_) => None,
+        };
+        let today = self.clock.now().with_timezone(&Kolkata).date_naive();
+        
+        match cached {
+            Some(cached) if cached.updated_at.with_timezone(&Kolkata).date_naive() == today => {
+                warn!(
+                    "⚠️  Instrument master download failed ({}) - using cached copy from {}",
+                    error, cached.updated_at
+                );
+                let diff = diff_instruments(&self.instruments.read().await, &cached.instruments);
+                self.set_instruments(cached.instruments, cached.updated_at).await;
+                Ok(diff)
+            }
+            _ => Err(error),
+        }
+    }
+    
+    /// Load the instrument master persisted by the last refresh
+    pub async fn load_from_file(&self) -> Result<()> {
+        let content = tokio::fs::read_to_string(&self.cache_file).await?;
+        let cached: CachedMaster = serde_json::from_str(&content)?;
+        let count = cached.instruments.len();
+        
+        self.set_instruments(cached.instruments, cached.updated_at).await;
         
+        info!("📂 Loaded {} cached instruments (refreshed {})", count, cached.updated_at);
+        Ok(())
+    }
+    
+    /// Replace cached instruments and rebuild the lookup map
+    async fn set_instruments(&self, instruments: Vec<Instrument>, updated_at: DateTime<Utc>) {
         // Build token map for fast lookups
         let mut token_map = HashMap::new();
         for inst in &instruments {
@@ -43,7 +157,7 @@ impl InstrumentCache {
         // Update cache
         {
             let mut cache = self.instruments.write().await;
-            *cache = instruments.clone();
+            *cache = instruments;
         }
         {
             let mut map = self.token_map.write().await;
@@ -51,11 +165,8 @@ impl InstrumentCache {
         }
         {
             let mut updated = self.last_updated.write().await;
-            *updated = Some(Utc::now());
+            *updated = Some(updated_at);
         }
-        
-        info!("✅ Cached {} instruments", instruments.len());
-        Ok(())
     }
     
     /// Find NIFTY underlying token
@@ -128,6 +239,13 @@ impl InstrumentCache {
         Ok((instrument.token.clone(), instrument.symbol.clone()))
     }
     
+    /// Find the index/stock future for an underlying
+    /// Returns the nearest expiry unless `expiry` (e.g. "28NOV2024") is given
+    pub async fn find_future(&self, underlying: &str, expiry: Option<&str>) -> Option<Instrument> {
+        let instruments = self.instruments.read().await;
+        select_future(&instruments, underlying, expiry).cloned()
+    }
+    
     /// Get instrument by token
     pub async fn get_by_token(&self, token: &str) -> Option<Instrument> {
         let map = self.token_map.read().await;
@@ -167,41 +285,15 @@ impl InstrumentCache {
     }
     
     /// Check if cache needs refresh
-    /// Returns true if:
-    /// 1. Cache is empty (never downloaded)
-    /// 2. Cache is very old (more than 30 days)
-    /// 3. Cache is from a different month (new month started)
+    /// Returns true if the cache is empty or was last refreshed before the
+    /// most recent 8 AM IST master update
     pub async fn needs_refresh(&self) -> bool {
-        let last_updated = self.last_updated.read().await;
-        
-        match *last_updated {
-            None => {
-                // No cache available - must download
-                true
-            }
-            Some(last) => {
-                let now = Utc::now();
-                let diff = now - last;
-                
-                // Check if cache is very old (more than 30 days)
-                if diff.num_days() >= 30 {
-                    return true;
-                }
-                
-                // Check if cache is from a different month (new month started)
-                let now_ist = now.with_timezone(&chrono_tz::Asia::Kolkata);
-                let last_ist = last.with_timezone(&chrono_tz::Asia::Kolkata);
-                
-                // If month or year changed, need to refresh
-                if now_ist.date_naive().month() != last_ist.date_naive().month() 
-                    || now_ist.date_naive().year() != last_ist.date_naive().year() {
-                    return true;
-                }
-                
-                // Otherwise, don't refresh (once downloaded in a month, no need to download again)
-                false
-            }
+        if self.size().await == 0 {
+            return true;
         }
+        
+        let last_updated = self.last_updated.read().await;
+        needs_refresh_at(*last_updated, self.clock.now())
     }
     
     /// Get cache size
@@ -216,3 +308,218 @@ impl InstrumentCache {
         instruments.clone()
     }
 }
+
+/// Diff two instrument masters by token
+pub fn diff_instruments(previous: &[Instrument], current: &[Instrument]) -> InstrumentDiff {
+    let previous_tokens: HashSet<&str> = previous.iter().map(|i| i.token.as_str()).collect();
+    let current_tokens: HashSet<&str> = current.iter().map(|i| i.token.as_str()).collect();
+    let previous_expiries: HashSet<&str> = previous.iter().map(|i| i.expiry.as_str()).collect();
+    
+    let new_expiries: BTreeSet<String> = current
+        .iter()
+        .filter(|i| !i.expiry.is_empty() && !previous_expiries.contains(i.expiry.as_str()))
+        .map(|i| i.expiry.clone())
+        .collect();
+    
+    InstrumentDiff {
+        added: current
+            .iter()
+            .filter(|i| !previous_tokens.contains(i.token.as_str()))
+            .cloned()
+            .collect(),
+        removed: previous
+            .iter()
+            .filter(|i| !current_tokens.contains(i.token.as_str()))
+            .cloned()
+            .collect(),
+        new_expiries: new_expiries.into_iter().collect(),
+    }
+}
+
+/// Pick the nearest (or the specified) expiry FUTIDX/FUTSTK contract
+pub fn select_future<'a>(
+    instruments: &'a [Instrument],
+    underlying: &str,
+    expiry: Option<&str>,
+) -> Option<&'a Instrument> {
+    let parse_expiry = |s: &str| NaiveDate::parse_from_str(s, "%d%b%Y").ok();
+    let target_expiry = match expiry {
+        Some(s) => Some(parse_expiry(s)?),
+        None => None,
+    };
+    
+    instruments.iter()
+        .filter(|i| {
+            i.name == underlying
+                && i.exch_seg == "NFO"
+                && (i.instrument_type == "FUTIDX" || i.instrument_type == "FUTSTK")
+        })
+        .filter_map(|i| parse_expiry(&i.expiry).map(|d| (d, i)))
+        .filter(|(d, _)| target_expiry.is_none_or(|target| *d == target))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, i)| i)
+}
+
+/// Most recent 8 AM IST master update at or before `now`
+pub fn master_refresh_cutoff(now: DateTime<Utc>) -> DateTime<Utc> {
+    let now_ist = now.with_timezone(&Kolkata);
+    
+    let today_cutoff = Kolkata
+        .with_ymd_and_hms(
+            now_ist.year(),
+            now_ist.month(),
+            now_ist.day(),
+            MASTER_UPDATE_HOUR_IST,
+            0,
+            0,
+        )
+        .unwrap()
+        .with_timezone(&Utc);
+    
+    if now < today_cutoff {
+        today_cutoff - chrono::Duration::days(1)
+    } else {
+        today_cutoff
+    }
+}
+
+/// Whether a cache refreshed at `last_updated` is stale at `now`
+pub fn needs_refresh_at(last_updated: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+    match last_updated {
+        None => true,
+        Some(last) => {
+            let cutoff = master_refresh_cutoff(now);
+            let stale = last < cutoff;
+            debug!("Instrument master refreshed {} (cutoff {}) → stale: {}", last, cutoff, stale);
+            stale
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn ist(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<Utc> {
+        Kolkata.with_ymd_and_hms(y, m, d, h, min, 0).unwrap().with_timezone(&Utc)
+    }
+    
+    #[test]
+    fn test_yesterday_evening_cache_refreshes_after_8am() {
+        let last = ist(2024, 11, 19, 20, 0);
+        
+        // Started at 9 AM next day: master has been republished
+        assert!(needs_refresh_at(Some(last), ist(2024, 11, 20, 9, 0)));
+        // Started at 7:59 AM: today's master isn't out yet
+        assert!(!needs_refresh_at(Some(last), ist(2024, 11, 20, 7, 59)));
+        // Exactly at the cutoff
+        assert!(needs_refresh_at(Some(last), ist(2024, 11, 20, 8, 0)));
+    }
+    
+    #[test]
+    fn test_cache_after_cutoff_is_fresh() {
+        let last = ist(2024, 11, 20, 8, 5);
+        
+        assert!(!needs_refresh_at(Some(last), ist(2024, 11, 20, 15, 30)));
+        assert!(!needs_refresh_at(Some(last), ist(2024, 11, 21, 7, 0)));
+        assert!(needs_refresh_at(Some(last), ist(2024, 11, 21, 8, 1)));
+        assert!(needs_refresh_at(None, ist(2024, 11, 21, 8, 1)));
+    }
+    
+    fn future(symbol: &str, expiry: &str) -> Instrument {
+        Instrument {
+            token: symbol.to_string(),
+            symbol: symbol.to_string(),
+            name: "NIFTY".to_string(),
+            expiry: expiry.to_string(),
+            strike: -1.0,
+            lotsize: 50,
+            instrument_type: "FUTIDX".to_string(),
+            exch_seg: "NFO".to_string(),
+            tick_size: 0.05,
+        }
+    }
+    
+    #[test]
+    fn test_select_nearest_future() {
+        // Listed out of order; string order would wrongly pick 26DEC
+        let instruments = vec![
+            future("NIFTY30JAN25FUT", "30JAN2025"),
+            future("NIFTY26DEC24FUT", "26DEC2024"),
+            future("NIFTY28NOV24FUT", "28NOV2024"),
+        ];
+        
+        let nearest = select_future(&instruments, "NIFTY", None).unwrap();
+        assert_eq!(nearest.symbol, "NIFTY28NOV24FUT");
+        
+        let specified = select_future(&instruments, "NIFTY", Some("30JAN2025")).unwrap();
+        assert_eq!(specified.symbol, "NIFTY30JAN25FUT");
+        
+        assert!(select_future(&instruments, "NIFTY", Some("27FEB2025")).is_none());
+        assert!(select_future(&instruments, "BANKNIFTY", None).is_none());
+    }
+    
+    #[test]
+    fn test_diff_instruments_reports_listings_and_delistings() {
+        let previous = vec![
+            future("NIFTY28NOV24FUT", "28NOV2024"),
+            future("NIFTY26DEC24FUT", "26DEC2024"),
+        ];
+        let current = vec![
+            future("NIFTY26DEC24FUT", "26DEC2024"),
+            future("NIFTY30JAN25FUT", "30JAN2025"),
+            future("NIFTY27FEB25FUT", "27FEB2025"),
+        ];
+        
+        let diff = diff_instruments(&previous, &current);
+        let tokens = |list: &[Instrument]| list.iter().map(|i| i.token.clone()).collect::<Vec<_>>();
+        
+        assert_eq!(tokens(&diff.added), vec!["NIFTY30JAN25FUT", "NIFTY27FEB25FUT"]);
+        assert_eq!(tokens(&diff.removed), vec!["NIFTY28NOV24FUT"]);
+        assert_eq!(diff.new_expiries, vec!["27FEB2025", "30JAN2025"]);
+        
+        assert!(diff_instruments(&current, &current).is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_failed_download_falls_back_to_todays_cache() {
+        use crate::broker::angel_one::test_client;
+        use crate::time::MockClock;
+        
+        let dir = std::env::temp_dir().join(format!("instruments_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("instrument_master.json");
+        
+        // Nothing listens on the discard port: every download fails
+        let broker = Arc::new(
+            test_client().with_instrument_master_url("http://127.0.0.1:9/OpenAPIScripMaster.json")
+        );
+        let cache = InstrumentCache::new(broker)
+            .with_cache_file(&path)
+            .with_clock(Arc::new(MockClock::new(ist(2024, 11, 20, 9, 5))));
+        
+        // No cache on disk: the download error is fatal
+        assert!(cache.refresh().await.is_err());
+        
+        // Yesterday's copy is not usable
+        let write = |updated_at| {
+            let cached = CachedMaster {
+                updated_at,
+                instruments: vec![future("NIFTY28NOV24FUT", "28NOV2024")],
+            };
+            std::fs::write(&path, serde_json::to_string(&cached).unwrap()).unwrap();
+        };
+        write(ist(2024, 11, 19, 20, 0));
+        assert!(cache.refresh().await.is_err());
+        assert_eq!(cache.size().await, 0);
+        
+        // This morning's copy lets startup continue
+        write(ist(2024, 11, 20, 8, 10));
+        let diff = cache.refresh().await.unwrap();
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(cache.size().await, 1);
+        assert!(cache.get_by_symbol("NIFTY28NOV24FUT").await.is_some());
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
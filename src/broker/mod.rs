@@ -1,14 +1,22 @@
 pub mod angel_one;
 pub mod tokens;
+pub mod token_monitor;
 pub mod instrument_cache;
 pub mod paper_trading;
 pub mod websocket;
+pub mod order_updates;
 pub mod token_extractor;
+pub mod data_source;
+pub mod order_broker;
 
 pub use angel_one::AngelOneClient;
 pub use tokens::TokenManager;
-pub use instrument_cache::InstrumentCache;
-pub use paper_trading::PaperTradingBroker;
-pub use websocket::AngelWebSocket;
+pub use token_monitor::{TokenMonitor, TokenStatus};
+pub use instrument_cache::{diff_instruments, InstrumentCache, InstrumentDiff};
+pub use paper_trading::{bar_ticks, bars_to_ticks, PaperTradingBroker, ReplayPace};
+pub use websocket::{AngelWebSocket, OutageWindow};
+pub use order_updates::{parse_order_update, OrderUpdate, OrderUpdateSocket};
 pub use token_extractor::{TokenExtractor, AssetTokens, FutureToken, OptionToken};
+pub use data_source::{live_ltp, with_csv_fallback, CsvCandleSource, FallbackBroker, MarketDataSource};
+pub use order_broker::OrderBroker;
 
@@ -0,0 +1,100 @@
+/// Order routing used by `OrderManager`
+/// `AngelOneClient` is the live implementation; tests substitute a mock.
+use futures_util::future::BoxFuture;
+
+use crate::broker::AngelOneClient;
+use crate::error::Result;
+use crate::types::{BrokerOrder, OrderType, Side};
+
+/// Places, cancels and reports orders at the broker
+pub trait OrderBroker: Send + Sync {
+    /// Untagged order of `order_type` (LIMIT at `price`, or MARKET)
+    fn place_order<'a>(
+        &'a self,
+        symbol: &'a str,
+        token: &'a str,
+        side: Side,
+        quantity: i32,
+        order_type: OrderType,
+        price: Option<f64>,
+    ) -> BoxFuture<'a, Result<String>>;
+
+    /// LIMIT at `price` (MARKET when `None`) carrying `order_tag`
+    fn place_tagged_order<'a>(
+        &'a self,
+        symbol: &'a str,
+        token: &'a str,
+        side: Side,
+        quantity: i32,
+        price: Option<f64>,
+        order_tag: &'a str,
+    ) -> BoxFuture<'a, Result<String>>;
+
+    /// LIMIT entry at `price` with a broker-side stop at `stop_price`
+    fn place_cover_order<'a>(
+        &'a self,
+        symbol: &'a str,
+        token: &'a str,
+        side: Side,
+        quantity: i32,
+        price: f64,
+        stop_price: f64,
+    ) -> BoxFuture<'a, Result<String>>;
+
+    fn cancel_order<'a>(&'a self, broker_order_id: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    /// Cancel a cover order, or exit it (stop leg included) once filled
+    fn cancel_cover_order<'a>(&'a self, broker_order_id: &'a str) -> BoxFuture<'a, Result<()>>;
+
+    fn get_order_book(&self) -> BoxFuture<'_, Result<Vec<BrokerOrder>>>;
+}
+
+impl OrderBroker for AngelOneClient {
+    fn place_order<'a>(
+        &'a self,
+        symbol: &'a str,
+        token: &'a str,
+        side: Side,
+        quantity: i32,
+        order_type: OrderType,
+        price: Option<f64>,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(AngelOneClient::place_order(self, symbol, token, side, quantity, order_type, price))
+    }
+
+    fn place_tagged_order<'a>(
+        &'a self,
+        symbol: &'a str,
+        token: &'a str,
+        side: Side,
+        quantity: i32,
+        price: Option<f64>,
+        order_tag: &'a str,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(AngelOneClient::place_tagged_order(self, symbol, token, side, quantity, price, order_tag))
+    }
+
+    fn place_cover_order<'a>(
+        &'a self,
+        symbol: &'a str,
+        token: &'a str,
+        side: Side,
+        quantity: i32,
+        price: f64,
+        stop_price: f64,
+    ) -> BoxFuture<'a, Result<String>> {
+        Box::pin(AngelOneClient::place_cover_order(self, symbol, token, side, quantity, price, stop_price))
+    }
+
+    fn cancel_order<'a>(&'a self, broker_order_id: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(AngelOneClient::cancel_order(self, broker_order_id))
+    }
+
+    fn cancel_cover_order<'a>(&'a self, broker_order_id: &'a str) -> BoxFuture<'a, Result<()>> {
+        Box::pin(AngelOneClient::cancel_cover_order(self, broker_order_id))
+    }
+
+    fn get_order_book(&self) -> BoxFuture<'_, Result<Vec<BrokerOrder>>> {
+        Box::pin(AngelOneClient::get_order_book(self))
+    }
+}
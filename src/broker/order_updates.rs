@@ -0,0 +1,231 @@
+/// Angel One order-status WebSocket feed
+/// Pushes fills, rejects and cancels as they happen so orders don't have to
+/// wait for the next order-book poll.
+use std::sync::Arc;
+use futures_util::StreamExt;
+use serde_json::Value;
+use tokio::sync::{mpsc, RwLock};
+use tokio_tungstenite::{
+    connect_async,
+    tungstenite::{client::IntoClientRequest, http::HeaderValue, protocol::Message},
+};
+use tracing::{debug, error, info, warn};
+
+use crate::broker::TokenManager;
+use crate::error::{Result, TradingError};
+
+const ORDER_WS_URL: &str = "wss://tns.angelone.in/smart-order-update";
+
+/// Broker-side change to one order
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderUpdate {
+    /// Cumulative fill so far (may be partial)
+    Fill {
+        broker_order_id: String,
+        fill_price: f64,
+        filled_quantity: i32,
+    },
+    Rejected {
+        broker_order_id: String,
+        reason: String,
+    },
+    Cancelled {
+        broker_order_id: String,
+    },
+}
+
+impl OrderUpdate {
+    pub fn broker_order_id(&self) -> &str {
+        match self {
+            OrderUpdate::Fill { broker_order_id, .. }
+            | OrderUpdate::Rejected { broker_order_id, .. }
+            | OrderUpdate::Cancelled { broker_order_id } => broker_order_id,
+        }
+    }
+}
+
+/// Numeric field that the feed sends either as a number or a string
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Decode one order-update message; anything other than a fill, reject or
+/// cancel (acks, heartbeats, open/modified states) yields `None`
+pub fn parse_order_update(text: &str) -> Option<OrderUpdate> {
+    let message: Value = serde_json::from_str(text).ok()?;
+    let data = message.get("orderData")?;
+
+    let broker_order_id = data.get("orderid")?.as_str()?.to_string();
+    if broker_order_id.is_empty() {
+        return None;
+    }
+
+    let status = data
+        .get("orderstatus")
+        .or_else(|| data.get("status"))?
+        .as_str()?
+        .to_lowercase();
+    let filled_quantity = data.get("filledshares").and_then(number).unwrap_or(0.0) as i32;
+
+    match status.as_str() {
+        "rejected" => Some(OrderUpdate::Rejected {
+            broker_order_id,
+            reason: data
+                .get("text")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string(),
+        }),
+        "cancelled" if filled_quantity == 0 => Some(OrderUpdate::Cancelled { broker_order_id }),
+        _ if filled_quantity > 0 => Some(OrderUpdate::Fill {
+            broker_order_id,
+            fill_price: data.get("averageprice").and_then(number)?,
+            filled_quantity,
+        }),
+        _ => None,
+    }
+}
+
+pub struct OrderUpdateSocket {
+    token_manager: Arc<TokenManager>,
+    tx: mpsc::UnboundedSender<OrderUpdate>,
+    rx: Arc<RwLock<mpsc::UnboundedReceiver<OrderUpdate>>>,
+    is_connected: Arc<RwLock<bool>>,
+}
+
+impl OrderUpdateSocket {
+    pub fn new(token_manager: Arc<TokenManager>) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        OrderUpdateSocket {
+            token_manager,
+            tx,
+            rx: Arc::new(RwLock::new(rx)),
+            is_connected: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    /// Connect to the order-status stream and start decoding updates
+    pub async fn connect(&self) -> Result<()> {
+        let tokens = self.token_manager.get_tokens().await
+            .ok_or_else(|| TradingError::TokenExpired("No tokens available".to_string()))?;
+
+        info!("🔌 Connecting to Angel One order-update feed...");
+
+        let mut request = ORDER_WS_URL.into_client_request()
+            .map_err(|e| TradingError::WebSocketError(format!("Bad request: {}", e)))?;
+        let auth = HeaderValue::from_str(&format!("Bearer {}", tokens.jwt_token))
+            .map_err(|e| TradingError::WebSocketError(format!("Bad auth header: {}", e)))?;
+        request.headers_mut().insert("Authorization", auth);
+
+        let (ws_stream, _) = connect_async(request).await
+            .map_err(|e| TradingError::WebSocketError(format!("Connection failed: {}", e)))?;
+
+        let (_write, mut read) = ws_stream.split();
+        *self.is_connected.write().await = true;
+
+        info!("✅ Order-update feed connected");
+
+        let tx = self.tx.clone();
+        let is_connected = Arc::clone(&self.is_connected);
+
+        tokio::spawn(async move {
+            while let Some(msg) = read.next().await {
+                match msg {
+                    Ok(Message::Text(text)) => {
+                        let Some(update) = parse_order_update(&text) else {
+                            debug!("Ignoring order-feed message: {}", text);
+                            continue;
+                        };
+                        if let Err(e) = tx.send(update) {
+                            error!("Failed to send order update: {}", e);
+                            break;
+                        }
+                    }
+                    Ok(Message::Close(_)) => {
+                        warn!("Order-update feed closed by server");
+                        break;
+                    }
+                    Err(e) => {
+                        error!("Order-update feed error: {}", e);
+                        break;
+                    }
+                    _ => {}
+                }
+            }
+
+            *is_connected.write().await = false;
+            warn!("Order-update reader task ended");
+        });
+
+        Ok(())
+    }
+
+    /// Get update receiver
+    pub fn get_update_receiver(&self) -> Arc<RwLock<mpsc::UnboundedReceiver<OrderUpdate>>> {
+        Arc::clone(&self.rx)
+    }
+
+    /// Check if connected
+    pub async fn is_connected(&self) -> bool {
+        *self.is_connected.read().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_order_update_messages() {
+        let fill = r#"{
+            "user-id": "A123456",
+            "status-code": "200",
+            "order-status": "AB05",
+            "error-message": "",
+            "orderData": {
+                "variety": "NORMAL",
+                "ordertype": "LIMIT",
+                "tradingsymbol": "NIFTY25JAN19500CE",
+                "transactiontype": "BUY",
+                "orderid": "250115000123456",
+                "status": "complete",
+                "orderstatus": "complete",
+                "averageprice": 126.5,
+                "filledshares": "50",
+                "unfilledshares": "0",
+                "text": ""
+            }
+        }"#;
+        assert_eq!(
+            parse_order_update(fill),
+            Some(OrderUpdate::Fill {
+                broker_order_id: "250115000123456".to_string(),
+                fill_price: 126.5,
+                filled_quantity: 50,
+            })
+        );
+
+        let reject = r#"{"orderData": {"orderid": "250115000123457", "orderstatus": "rejected",
+            "averageprice": 0, "filledshares": "0", "text": "Insufficient funds"}}"#;
+        let update = parse_order_update(reject).unwrap();
+        assert_eq!(update.broker_order_id(), "250115000123457");
+        assert_eq!(
+            update,
+            OrderUpdate::Rejected {
+                broker_order_id: "250115000123457".to_string(),
+                reason: "Insufficient funds".to_string(),
+            }
+        );
+
+        // Open orders and non-order messages carry nothing to apply
+        let open = r#"{"orderData": {"orderid": "1", "orderstatus": "open", "filledshares": "0"}}"#;
+        assert_eq!(parse_order_update(open), None);
+        assert_eq!(parse_order_update(r#"{"status-code": "200"}"#), None);
+        assert_eq!(parse_order_update("pong"), None);
+    }
+}
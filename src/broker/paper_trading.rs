@@ -1,11 +1,97 @@
 /// Paper trading (simulation mode) wrapper
+/// Can also replay stored bars (as synthetic ticks) and recorded ticks so
+/// paper mode runs without a live feed.
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use chrono::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::sync::{mpsc, RwLock};
 use tracing::{info, warn};
 
-use crate::error::Result;
-use crate::types::{OrderType, Side};
+use crate::error::{Result, TradingError};
+use crate::types::{Bar, OrderType, Side, Tick};
+
+/// Default paper capital if not configured
+const DEFAULT_STARTING_CASH: f64 = 1_000_000.0;
+
+/// Estimated brokerage + statutory charges per fill (same model as trade PNL)
+fn estimate_charges(turnover: f64) -> f64 {
+    (turnover * 0.0003).max(20.0)
+}
+
+/// How fast stored bars are replayed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayPace {
+    /// No waiting between ticks
+    AsFastAsPossible,
+    /// Multiple of real time (1.0 = real time, 60.0 = an hour per minute)
+    Speed(f64),
+}
+
+impl ReplayPace {
+    /// `speed` from config; zero or less replays without waiting
+    pub fn from_speed(speed: f64) -> Self {
+        if speed > 0.0 {
+            ReplayPace::Speed(speed)
+        } else {
+            ReplayPace::AsFastAsPossible
+        }
+    }
+
+    /// Wall-clock wait for `gap` of market time
+    fn delay(&self, gap: Duration) -> Option<std::time::Duration> {
+        match self {
+            ReplayPace::AsFastAsPossible => None,
+            ReplayPace::Speed(speed) => gap.to_std().ok().map(|d| d.div_f64(*speed)),
+        }
+    }
+}
+
+/// Synthetic ticks for a run of bars; bar length is taken from the spacing
+/// between bars (1 minute for a single bar)
+pub fn bars_to_ticks(bars: &[Bar], token: &str, symbol: &str) -> Vec<Tick> {
+    let mut bar_duration = Duration::minutes(1);
+    let mut ticks = Vec::new();
+    
+    for (i, bar) in bars.iter().enumerate() {
+        if let Some(next) = bars.get(i + 1) {
+            bar_duration = next.timestamp - bar.timestamp;
+        }
+        ticks.extend(bar_ticks(bar, token, symbol, bar_duration));
+    }
+    
+    ticks
+}
+
+/// Four synthetic ticks spanning `bar`: open, then the extreme against the
+/// bar's direction, the extreme with it, and the close just before the bar ends
+pub fn bar_ticks(bar: &Bar, token: &str, symbol: &str, bar_duration: Duration) -> Vec<Tick> {
+    let (first, second) = if bar.close >= bar.open {
+        (bar.low, bar.high)
+    } else {
+        (bar.high, bar.low)
+    };
+    let step = bar_duration / 3;
+    let close_at = bar_duration - Duration::seconds(1).min(bar_duration / 4);
+
+    [(bar.open, Duration::zero()), (first, step), (second, step * 2), (bar.close, close_at)]
+        .into_iter()
+        .map(|(ltp, offset)| {
+            let timestamp = bar.timestamp + offset;
+            Tick {
+                symbol: symbol.to_string(),
+                token: token.to_string(),
+                ltp,
+                bid: ltp,
+                ask: ltp,
+                volume: bar.volume / 4,
+                timestamp,
+                timestamp_ms: timestamp.timestamp_millis(),
+            }
+        })
+        .collect()
+}
 
 /// Paper trading broker that simulates orders
 pub struct PaperTradingBroker {
@@ -17,6 +103,22 @@ pub struct PaperTradingBroker {
     
     /// Simulated slippage (basis points)
     slippage_bps: f64,
+    
+    /// Cash balance after premiums and charges
+    cash: Arc<RwLock<f64>>,
+    
+    /// Net quantity and mark price per symbol (positive = long)
+    holdings: Arc<RwLock<HashMap<String, Holding>>>,
+    
+    /// Synthetic ticks from bar replay
+    tick_tx: mpsc::UnboundedSender<Tick>,
+    tick_rx: Arc<RwLock<mpsc::UnboundedReceiver<Tick>>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Holding {
+    quantity: i32,
+    mark_price: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -33,10 +135,24 @@ struct SimulatedOrder {
 
 impl PaperTradingBroker {
     pub fn new(auto_fill: bool, slippage_bps: f64) -> Self {
+        let (tick_tx, tick_rx) = mpsc::unbounded_channel();
+        
         PaperTradingBroker {
             orders: Arc::new(RwLock::new(HashMap::new())),
             auto_fill,
             slippage_bps,
+            cash: Arc::new(RwLock::new(DEFAULT_STARTING_CASH)),
+            holdings: Arc::new(RwLock::new(HashMap::new())),
+            tick_tx,
+            tick_rx: Arc::new(RwLock::new(tick_rx)),
+        }
+    }
+    
+    /// Set the starting cash balance
+    pub fn with_starting_cash(self, cash: f64) -> Self {
+        PaperTradingBroker {
+            cash: Arc::new(RwLock::new(cash)),
+            ..self
         }
     }
     
@@ -65,6 +181,7 @@ impl PaperTradingBroker {
         // Auto-fill if enabled
         if self.auto_fill {
             let fill_price = self.calculate_fill_price(&order);
+            self.apply_fill(&symbol, side, quantity, fill_price).await?;
             order.fill_price = Some(fill_price);
             order.filled = true;
             
@@ -85,6 +202,75 @@ impl PaperTradingBroker {
         Ok(order_id)
     }
     
+    /// Debit/credit cash for a fill and update holdings
+    /// Buys that cost more than the available cash are rejected
+    async fn apply_fill(&self, symbol: &str, side: Side, quantity: i32, price: f64) -> Result<()> {
+        let turnover = price * quantity as f64;
+        let charges = estimate_charges(turnover);
+        
+        let mut cash = self.cash.write().await;
+        let cash_change = match side {
+            Side::Buy => -(turnover + charges),
+            Side::Sell => turnover - charges,
+        };
+        
+        if *cash + cash_change < 0.0 {
+            return Err(TradingError::InsufficientMargin(format!(
+                "Paper cash {:.2} < required {:.2}",
+                *cash,
+                -cash_change
+            )));
+        }
+        *cash += cash_change;
+        
+        let signed_qty = match side {
+            Side::Buy => quantity,
+            Side::Sell => -quantity,
+        };
+        let mut holdings = self.holdings.write().await;
+        let holding = holdings
+            .entry(symbol.to_string())
+            .or_insert(Holding { quantity: 0, mark_price: price });
+        holding.quantity += signed_qty;
+        holding.mark_price = price;
+        if holding.quantity == 0 {
+            holdings.remove(symbol);
+        }
+        
+        Ok(())
+    }
+    
+    /// Update the mark price used for equity
+    pub async fn mark_price(&self, symbol: &str, price: f64) {
+        let mut holdings = self.holdings.write().await;
+        if let Some(holding) = holdings.get_mut(symbol) {
+            holding.mark_price = price;
+        }
+    }
+    
+    /// Cash a buy of `quantity` at `price` needs: premium plus estimated charges
+    pub fn buy_cost(&self, price: f64, quantity: i32) -> f64 {
+        let turnover = price * quantity as f64;
+        turnover + estimate_charges(turnover)
+    }
+    
+    /// Cash available for new positions
+    pub async fn available_cash(&self) -> f64 {
+        *self.cash.read().await
+    }
+    
+    /// Cash plus mark-to-market value of open holdings
+    pub async fn equity(&self) -> f64 {
+        let cash = *self.cash.read().await;
+        let holdings = self.holdings.read().await;
+        let open_value: f64 = holdings
+            .values()
+            .map(|h| h.quantity as f64 * h.mark_price)
+            .sum();
+        
+        cash + open_value
+    }
+    
     /// Calculate simulated fill price with slippage
     fn calculate_fill_price(&self, order: &SimulatedOrder) -> f64 {
         let base_price = order.limit_price.unwrap_or(100.0);
@@ -113,6 +299,61 @@ impl PaperTradingBroker {
         let orders = self.orders.read().await;
         orders.len()
     }
+    
+    /// Load bars (one JSON `Bar` per line) for replay; malformed lines are skipped
+    pub async fn load_replay_bars(path: impl AsRef<Path>) -> Result<Vec<Bar>> {
+        let file = tokio::fs::File::open(path.as_ref()).await?;
+        let mut lines = BufReader::new(file).lines();
+        let mut bars = Vec::new();
+        
+        while let Some(line) = lines.next_line().await? {
+            match serde_json::from_str::<Bar>(&line) {
+                Ok(bar) => bars.push(bar),
+                Err(e) => warn!("Skipping malformed bar in {}: {}", path.as_ref().display(), e),
+            }
+        }
+        
+        Ok(bars)
+    }
+    
+    /// Replay `bars` as synthetic ticks on the tick receiver, marking `symbol`
+    /// at each tick (see `bars_to_ticks`). Returns ticks sent.
+    pub async fn replay_bars(&self, bars: &[Bar], token: &str, symbol: &str, pace: ReplayPace) -> Result<usize> {
+        let sent = self.replay_ticks(bars_to_ticks(bars, token, symbol), pace).await?;
+        info!("⏪ [PAPER] Replayed {} bars of {} as {} ticks", bars.len(), symbol, sent);
+        Ok(sent)
+    }
+    
+    /// Replay ticks (e.g. recorded option ticks merged with bar ticks) on the
+    /// tick receiver in timestamp order, marking each tick's symbol.
+    /// Returns ticks sent.
+    pub async fn replay_ticks(&self, mut ticks: Vec<Tick>, pace: ReplayPace) -> Result<usize> {
+        ticks.sort_by_key(|tick| tick.timestamp);
+        let mut last_time = None;
+        
+        for tick in &ticks {
+            if let Some(delay) = last_time.and_then(|last| pace.delay(tick.timestamp - last)) {
+                tokio::time::sleep(delay).await;
+            }
+            last_time = Some(tick.timestamp);
+            
+            self.mark_price(&tick.symbol, tick.ltp).await;
+            self.tick_tx.send(tick.clone())
+                .map_err(|e| TradingError::WebSocketError(format!("Replay receiver closed: {}", e)))?;
+        }
+        
+        Ok(ticks.len())
+    }
+    
+    /// Last mark for an open holding (set by fills and replayed ticks)
+    pub async fn mark(&self, symbol: &str) -> Option<f64> {
+        self.holdings.read().await.get(symbol).map(|holding| holding.mark_price)
+    }
+    
+    /// Receiver for replayed ticks
+    pub fn get_tick_receiver(&self) -> Arc<RwLock<mpsc::UnboundedReceiver<Tick>>> {
+        Arc::clone(&self.tick_rx)
+    }
 }
 
 #[cfg(test)]
@@ -136,4 +377,154 @@ mod tests {
         let fill_price = broker.get_fill_price(&order_id).await.unwrap();
         assert!(fill_price > 125.0); // Should have slippage
     }
+    
+    #[tokio::test]
+    async fn test_cash_ledger_round_trip() {
+        let broker = PaperTradingBroker::new(true, 0.0).with_starting_cash(100_000.0);
+        
+        // Buy 50 @ 100: premium 5000 + 20 minimum charges
+        broker.place_order("NIFTY19500CE".to_string(), Side::Buy, 50, OrderType::Limit, Some(100.0))
+            .await.unwrap();
+        assert_eq!(broker.available_cash().await, 94_980.0);
+        assert_eq!(broker.equity().await, 99_980.0);
+        
+        broker.mark_price("NIFTY19500CE", 110.0).await;
+        assert_eq!(broker.equity().await, 100_480.0);
+        
+        // Sell 50 @ 110: cash restored with 500 profit less charges on both legs
+        broker.place_order("NIFTY19500CE".to_string(), Side::Sell, 50, OrderType::Limit, Some(110.0))
+            .await.unwrap();
+        assert_eq!(broker.available_cash().await, 100_460.0);
+        assert_eq!(broker.equity().await, 100_460.0);
+        
+        // A buy larger than available cash is rejected
+        let result = broker.place_order(
+            "NIFTY19500PE".to_string(), Side::Buy, 1000, OrderType::Limit, Some(150.0),
+        ).await;
+        assert!(result.is_err());
+        assert_eq!(broker.available_cash().await, 100_460.0);
+    }
+    
+    #[tokio::test]
+    async fn test_bar_replay_drives_stop_and_target() {
+        use crate::events::EventBus;
+        use crate::positions::PositionManager;
+        use crate::time::MockClock;
+        use crate::types::{test_position, Position};
+        use chrono::{TimeZone, Utc};
+        
+        let dir = std::env::temp_dir().join(format!("replay_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let start = Utc.with_ymd_and_hms(2025, 1, 15, 4, 15, 0).unwrap(); // 09:45 IST
+        
+        // Hourly option bars: (open, high, low, close)
+        let day = |ohlc: &[(f64, f64, f64, f64)]| -> Vec<Bar> {
+            ohlc.iter().enumerate().map(|(i, &(open, high, low, close))| {
+                let timestamp = start + Duration::hours(i as i64);
+                Bar { timestamp, timestamp_ms: timestamp.timestamp_millis(), open, high, low, close, volume: 400, bar_complete: true }
+            }).collect()
+        };
+        let rally = day(&[(100.0, 108.0, 97.0, 106.0), (106.0, 118.0, 104.0, 116.0), (116.0, 134.0, 115.0, 131.0)]);
+        let selloff = day(&[(100.0, 103.0, 92.0, 94.0), (94.0, 96.0, 78.0, 82.0), (82.0, 85.0, 70.0, 72.0)]);
+        
+        let mut config = crate::config::loader::test_config();
+        config.use_trailing_stop = false;
+        let config = Arc::new(config);
+        
+        for (bars, expected) in [(rally, "TARGET"), (selloff, "STOP_LOSS")] {
+            let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+            let clock = Arc::new(MockClock::new(start));
+            let manager = PositionManager::new(event_bus, Arc::clone(&config)).with_clock(clock.clone());
+            manager.open_position(Position {
+                entry_time: start,
+                entry_time_ms: start.timestamp_millis(),
+                ..test_position("P1")
+            }).await.unwrap();
+            
+            let broker = PaperTradingBroker::new(true, 0.0);
+            let sent = broker.replay_bars(&bars, "43250", "NIFTY25JAN23500CE", ReplayPace::AsFastAsPossible)
+                .await.unwrap();
+            assert_eq!(sent, 12);
+            
+            let rx = broker.get_tick_receiver();
+            let mut rx = rx.write().await;
+            let mut exit = None;
+            while let Ok(tick) = rx.try_recv() {
+                clock.set(tick.timestamp);
+                if let Some(reason) = manager.update_position("P1", tick.ltp).await.unwrap() {
+                    let trade = manager.close_position("P1", tick.ltp, reason.clone()).await.unwrap();
+                    exit = Some((reason, trade.exit_time));
+                    break;
+                }
+            }
+            
+            // Target on the last bar's high; stop on the second bar's low
+            let (reason, exit_time) = exit.expect("replay should exit the position");
+            assert_eq!(reason, expected);
+            let exit_bar = if expected == "TARGET" { 2 } else { 1 };
+            assert_eq!(exit_time, start + Duration::hours(exit_bar) + Duration::minutes(40));
+        }
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_recorded_option_ticks_replay_with_underlying_bars() {
+        use crate::data::{tick_file, TickRecorder, TickReplay};
+        use chrono::{TimeZone, Utc};
+        
+        let dir = std::env::temp_dir().join(format!("tick_replay_{}", uuid::Uuid::new_v4()));
+        let start = Utc.with_ymd_and_hms(2025, 1, 15, 4, 15, 0).unwrap();
+        let option_tick = |ltp: f64, secs: i64| {
+            let timestamp = start + Duration::seconds(secs);
+            Tick {
+                symbol: "NIFTY25JAN23500CE".to_string(),
+                token: "43250".to_string(),
+                ltp,
+                bid: ltp - 0.5,
+                ask: ltp + 0.5,
+                volume: 10,
+                timestamp,
+                timestamp_ms: timestamp.timestamp_millis(),
+            }
+        };
+        
+        // Option ticks as recorded by the live tick handler
+        let recorder = TickRecorder::new(&dir);
+        for tick in [option_tick(101.0, 10), option_tick(96.5, 50)] {
+            recorder.record(&tick).await.unwrap();
+        }
+        recorder.flush().await.unwrap();
+        let recorded = TickReplay::load(tick_file(&dir, "43250", start)).await.unwrap();
+        
+        let bar = Bar {
+            timestamp: start,
+            timestamp_ms: start.timestamp_millis(),
+            open: 23500.0,
+            high: 23520.0,
+            low: 23490.0,
+            close: 23510.0,
+            volume: 400,
+            bar_complete: true,
+        };
+        let mut ticks = bars_to_ticks(&[bar], "99926000", "NIFTY");
+        ticks.extend(recorded);
+        
+        let broker = PaperTradingBroker::new(true, 0.0);
+        broker.place_order("NIFTY25JAN23500CE".to_string(), Side::Buy, 75, OrderType::Limit, Some(100.0)).await.unwrap();
+        assert_eq!(broker.replay_ticks(ticks, ReplayPace::AsFastAsPossible).await.unwrap(), 6);
+        
+        // One stream in market-time order; option ticks mark the paper holding
+        let rx = broker.get_tick_receiver();
+        let mut rx = rx.write().await;
+        let mut received = Vec::new();
+        while let Ok(tick) = rx.try_recv() {
+            received.push((tick.token, tick.timestamp));
+        }
+        assert!(received.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert_eq!(received.iter().filter(|(token, _)| token == "43250").count(), 2);
+        assert_eq!(broker.mark("NIFTY25JAN23500CE").await, Some(96.5));
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -1,10 +1,14 @@
 /// Automatic token extraction from instrument master
 /// Intelligently identifies underlying, futures, and options tokens
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use chrono::NaiveDate;
+use chrono_tz::Asia::Kolkata;
 use tracing::{info, warn};
 
-use crate::types::Instrument;
+use crate::time::{Clock, SystemClock};
+use crate::types::{Config, Instrument};
 
 /// Token information for an underlying asset
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -39,11 +43,41 @@ pub struct OptionToken {
 /// Automatic token extractor
 pub struct TokenExtractor {
     instruments: Vec<Instrument>,
+    /// Keep options in only the nearest N expiries (None = all)
+    max_expiries: Option<usize>,
+    /// Drop options expiring more than this many calendar days out (None = all)
+    max_dte: Option<i64>,
+    clock: Arc<dyn Clock>,
 }
 
 impl TokenExtractor {
     pub fn new(instruments: Vec<Instrument>) -> Self {
-        Self { instruments }
+        Self {
+            instruments,
+            max_expiries: None,
+            max_dte: None,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Extractor limited to the configured expiries (`token_max_expiries`,
+    /// `token_max_dte`); use this wherever option tokens feed the bot
+    pub fn from_config(instruments: Vec<Instrument>, config: &Config) -> Self {
+        Self::new(instruments).with_expiry_filter(config.token_max_expiries, config.token_max_dte)
+    }
+
+    /// Limit extracted options to near expiries: the nearest `max_expiries`
+    /// and/or those within `max_dte` days
+    pub fn with_expiry_filter(mut self, max_expiries: Option<usize>, max_dte: Option<i64>) -> Self {
+        self.max_expiries = max_expiries;
+        self.max_dte = max_dte;
+        self
+    }
+
+    /// Use a custom clock (e.g. `MockClock` in tests)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
     }
 
     /// Extract all tokens for a given underlying (NIFTY, BANKNIFTY, FINNIFTY)
@@ -58,8 +92,8 @@ impl TokenExtractor {
         // Find futures
         let futures = self.find_futures(&underlying_upper);
 
-        // Find options
-        let options = self.find_options(&underlying_upper);
+        // Find options, near expiries only
+        let options = self.filter_expiries(self.find_options(&underlying_upper));
 
         info!("✅ Extracted tokens for {}:", underlying);
         info!("   Spot: {} (token: {})", 
@@ -326,6 +360,34 @@ impl TokenExtractor {
         options
     }
 
+    /// Apply the max-expiries / max-DTE filter. Already-expired contracts and
+    /// unparseable expiries are dropped whenever a filter is set.
+    fn filter_expiries(&self, options: Vec<OptionToken>) -> Vec<OptionToken> {
+        if self.max_expiries.is_none() && self.max_dte.is_none() {
+            return options;
+        }
+
+        let today = self.clock.now().with_timezone(&Kolkata).date_naive();
+        let parse = |expiry: &str| NaiveDate::parse_from_str(expiry, "%d%b%Y").ok();
+
+        let kept: BTreeSet<NaiveDate> = options.iter()
+            .filter_map(|o| parse(&o.expiry))
+            .filter(|date| *date >= today)
+            .filter(|date| self.max_dte.is_none_or(|max| (*date - today).num_days() <= max))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .take(self.max_expiries.unwrap_or(usize::MAX))
+            .collect();
+
+        let total = options.len();
+        let options: Vec<OptionToken> = options.into_iter()
+            .filter(|o| parse(&o.expiry).is_some_and(|date| kept.contains(&date)))
+            .collect();
+        info!("   Kept {} of {} option contracts in {} near expiries", options.len(), total, kept.len());
+
+        options
+    }
+
     /// Get options for a specific strike range
     pub fn get_options_in_range(
         &self,
@@ -514,5 +576,58 @@ mod tests {
         assert_eq!(options[0].strike, 23500.0);
         assert_eq!(options[0].option_type, "CE");
     }
-}
 
+    #[test]
+    fn test_expiry_filter_keeps_nearest_two() {
+        use crate::time::MockClock;
+        use chrono::{TimeZone, Utc};
+
+        let option = |token: &str, expiry: &str| Instrument {
+            token: token.to_string(),
+            symbol: format!("NIFTY{}23500CE", expiry),
+            name: "NIFTY".to_string(),
+            expiry: expiry.to_string(),
+            strike: 23500.0,
+            lotsize: 75,
+            instrument_type: "OPTIDX".to_string(),
+            exch_seg: "NFO".to_string(),
+            tick_size: 0.05,
+        };
+        let instruments = vec![
+            option("1", "30JAN2025"),
+            option("2", "06FEB2025"),
+            option("3", "27FEB2025"),
+            option("4", "26JUN2025"),
+            option("5", "23JAN2025"), // already expired
+            option("6", "06FEB2025"),
+        ];
+        let now = Utc.with_ymd_and_hms(2025, 1, 27, 4, 0, 0).unwrap();
+        let clock: Arc<dyn Clock> = Arc::new(MockClock::new(now));
+
+        let extractor = TokenExtractor::new(instruments.clone())
+            .with_expiry_filter(Some(2), None)
+            .with_clock(Arc::clone(&clock));
+        let tokens: Vec<String> = extractor.extract_asset_tokens("NIFTY").options
+            .into_iter()
+            .map(|o| o.token)
+            .collect();
+        assert_eq!(tokens, vec!["1", "2", "6"]);
+
+        // Same filter from config
+        let mut config = crate::config::loader::test_config();
+        config.token_max_expiries = Some(2);
+        config.token_max_dte = None;
+        let extractor = TokenExtractor::from_config(instruments.clone(), &config)
+            .with_clock(Arc::clone(&clock));
+        assert_eq!(extractor.extract_asset_tokens("NIFTY").options.len(), 3);
+
+        // DTE cap alone: everything within 45 days
+        let extractor = TokenExtractor::new(instruments.clone())
+            .with_expiry_filter(None, Some(45))
+            .with_clock(clock);
+        assert_eq!(extractor.extract_asset_tokens("NIFTY").options.len(), 4);
+
+        // No filter: every listed option
+        assert_eq!(TokenExtractor::new(instruments).extract_asset_tokens("NIFTY").options.len(), 6);
+    }
+}
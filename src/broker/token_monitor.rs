@@ -0,0 +1,261 @@
+/// Session token health: refresh ahead of expiry, and square off while exit
+/// orders can still be placed if the refresh fails
+use std::future::Future;
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use tracing::{error, info, warn};
+
+use crate::broker::tokens::{TokenManager, Tokens};
+use crate::error::Result;
+use crate::events::{Event, EventBus, EventPayload, EventType};
+use crate::time::{Clock, SystemClock};
+use crate::types::Config;
+
+/// Outcome of one `TokenMonitor::check`
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenStatus {
+    /// Not yet within the warning window
+    Healthy,
+    /// Refreshed ahead of expiry
+    Refreshed,
+    /// Refresh failed but expiry is still beyond the flatten grace period
+    RefreshFailed,
+    /// Refresh failed within the grace period; positions were flattened
+    /// (`still_open` lists the ones whose exits failed every retry)
+    Flattened { position_ids: Vec<String>, still_open: Vec<String> },
+}
+
+impl TokenStatus {
+    /// New entries need a session that will outlive them
+    pub fn allows_entries(&self) -> bool {
+        matches!(self, TokenStatus::Healthy | TokenStatus::Refreshed)
+    }
+}
+
+pub struct TokenMonitor {
+    token_manager: Arc<TokenManager>,
+    event_bus: Arc<EventBus>,
+    warning_minutes: i64,
+    /// Flatten when a refresh fails this close to expiry (0 = never)
+    grace_sec: i64,
+    clock: Arc<dyn Clock>,
+}
+
+impl TokenMonitor {
+    pub fn new(token_manager: Arc<TokenManager>, event_bus: Arc<EventBus>, config: &Config) -> Self {
+        TokenMonitor {
+            token_manager,
+            event_bus,
+            warning_minutes: config.token_expiry_warning_min,
+            grace_sec: config.token_grace_to_flatten_sec as i64,
+            clock: Arc::new(SystemClock),
+        }
+    }
+
+    /// Use a custom clock (e.g. `MockClock` in tests)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Earlier of the JWT and feed token expiries
+    async fn expires_at(&self) -> Option<DateTime<Utc>> {
+        self.token_manager.get_tokens().await.map(|t| t.jwt_expiry.min(t.feed_expiry))
+    }
+
+    /// One monitor pass. Within `token_expiry_warning_min` of expiry, try
+    /// `refresh`; if that fails within `token_grace_to_flatten_sec` of expiry
+    /// (or after it), publish `SessionRevalidationRequired`, run `flatten`
+    /// (returning the closed and still-open position IDs) and publish
+    /// `PositionsClosed`.
+    pub async fn check<R, RFut, F, FFut>(&self, refresh: R, flatten: F) -> Result<TokenStatus>
+    where
+        R: FnOnce() -> RFut,
+        RFut: Future<Output = Result<Tokens>>,
+        F: FnOnce() -> FFut,
+        FFut: Future<Output = Result<(Vec<String>, Vec<String>)>>,
+    {
+        let now = self.clock.now();
+        let Some(expires_at) = self.expires_at().await else {
+            return Ok(TokenStatus::Healthy);
+        };
+        let remaining = expires_at - now;
+        if remaining.num_minutes() >= self.warning_minutes {
+            return Ok(TokenStatus::Healthy);
+        }
+
+        warn!("🔑 Tokens expire at {} ({} min) - refreshing", expires_at, remaining.num_minutes());
+        self.event_bus.publish(Event::new(
+            EventType::TokenExpiryWarning,
+            EventPayload::TokenExpiryWarning {
+                expires_at,
+                minutes_remaining: remaining.num_minutes(),
+            },
+        )).await?;
+        self.event_bus.publish(Event::new(
+            EventType::TokenRefreshStarted,
+            EventPayload::TokenRefreshStarted { attempt: 1 },
+        )).await?;
+
+        let reason = match refresh().await {
+            Ok(tokens) => {
+                let new_expiry = tokens.jwt_expiry.min(tokens.feed_expiry);
+                info!("✅ Tokens refreshed - new expiry {}", new_expiry);
+                self.event_bus.publish(Event::new(
+                    EventType::TokenRefreshSuccess,
+                    EventPayload::TokenRefreshSuccess { new_expiry },
+                )).await?;
+                return Ok(TokenStatus::Refreshed);
+            }
+            Err(e) => e.to_string(),
+        };
+
+        error!("❌ Token refresh failed: {}", reason);
+        self.event_bus.publish(Event::new(
+            EventType::TokenRefreshFailed,
+            EventPayload::TokenRefreshFailed {
+                reason: reason.clone(),
+                attempts: 1,
+            },
+        )).await?;
+
+        if self.grace_sec == 0 || remaining.num_seconds() > self.grace_sec {
+            return Ok(TokenStatus::RefreshFailed);
+        }
+
+        // Exit orders need a live session: square off before it dies
+        error!("🚨 Tokens expire in {}s with no refresh - flattening all positions", remaining.num_seconds());
+        let close_reason = format!("TOKEN_EXPIRY: {}", reason);
+        self.event_bus.publish(Event::new(
+            EventType::SessionRevalidationRequired,
+            EventPayload::SessionRevalidationRequired { reason: close_reason.clone() },
+        )).await?;
+
+        let (position_ids, still_open) = flatten().await?;
+        if !still_open.is_empty() {
+            error!("🚨 {} position(s) still open with the session expiring - close them manually: {:?}",
+                   still_open.len(), still_open);
+        }
+        self.event_bus.publish(Event::new(
+            EventType::PositionsClosed,
+            EventPayload::PositionsClosed {
+                position_ids: position_ids.clone(),
+                reason: close_reason,
+                still_open: still_open.clone(),
+            },
+        )).await?;
+
+        Ok(TokenStatus::Flattened { position_ids, still_open })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::loader::test_config;
+    use crate::error::TradingError;
+    use crate::time::MockClock;
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn test_failed_refresh_near_expiry_flattens() {
+        let dir = std::env::temp_dir().join(format!("token_monitor_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut config = test_config();
+        config.token_expiry_warning_min = 30;
+        config.token_grace_to_flatten_sec = 180;
+
+        let expiry = Utc.with_ymd_and_hms(2025, 1, 15, 9, 0, 0).unwrap();
+        let token_manager = Arc::new(TokenManager::new(dir.join("tokens.json").display().to_string()));
+        token_manager.set_tokens(Tokens {
+            jwt_token: "jwt".to_string(),
+            feed_token: "feed".to_string(),
+            jwt_expiry: expiry,
+            feed_expiry: expiry,
+            refresh_token: None,
+        }).await.unwrap();
+
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let clock = Arc::new(MockClock::new(expiry - chrono::Duration::minutes(10)));
+        let monitor = TokenMonitor::new(token_manager, Arc::clone(&event_bus), &config)
+            .with_clock(clock.clone());
+
+        let failed_refresh = || async { Err(TradingError::TokenRefreshFailed("login rejected".to_string())) };
+        let flatten = || async { Ok((vec!["P1".to_string(), "P2".to_string()], Vec::new())) };
+
+        // 10 minutes out: refresh fails, but there is still time to retry;
+        // no new entries meanwhile
+        let status = monitor.check(failed_refresh, flatten).await.unwrap();
+        assert_eq!(status, TokenStatus::RefreshFailed);
+        assert!(!status.allows_entries());
+
+        // 2 minutes out: square off now
+        clock.set(expiry - chrono::Duration::minutes(2));
+        let status = monitor.check(failed_refresh, flatten).await.unwrap();
+        assert_eq!(status, TokenStatus::Flattened {
+            position_ids: vec!["P1".to_string(), "P2".to_string()],
+            still_open: Vec::new(),
+        });
+        assert!(!status.allows_entries());
+
+        let events: Vec<EventType> = event_bus.replay_events(DateTime::<Utc>::MIN_UTC).await.unwrap()
+            .into_iter()
+            .map(|e| e.event_type)
+            .collect();
+        let tail = &events[events.len() - 2..];
+        assert_eq!(tail, &[EventType::SessionRevalidationRequired, EventType::PositionsClosed]);
+        assert_eq!(events.iter().filter(|e| **e == EventType::PositionsClosed).count(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_partial_flatten_reports_positions_still_open() {
+        let dir = std::env::temp_dir().join(format!("token_monitor_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut config = test_config();
+        config.token_expiry_warning_min = 30;
+        config.token_grace_to_flatten_sec = 180;
+
+        let expiry = Utc.with_ymd_and_hms(2025, 1, 15, 9, 0, 0).unwrap();
+        let token_manager = Arc::new(TokenManager::new(dir.join("tokens.json").display().to_string()));
+        token_manager.set_tokens(Tokens {
+            jwt_token: "jwt".to_string(),
+            feed_token: "feed".to_string(),
+            jwt_expiry: expiry,
+            feed_expiry: expiry,
+            refresh_token: None,
+        }).await.unwrap();
+
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let clock = Arc::new(MockClock::new(expiry - chrono::Duration::minutes(1)));
+        let monitor = TokenMonitor::new(token_manager, Arc::clone(&event_bus), &config)
+            .with_clock(clock);
+
+        let status = monitor.check(
+            || async { Err(TradingError::TokenRefreshFailed("login rejected".to_string())) },
+            || async { Ok((vec!["P1".to_string()], vec!["P2".to_string()])) },
+        ).await.unwrap();
+        assert_eq!(status, TokenStatus::Flattened {
+            position_ids: vec!["P1".to_string()],
+            still_open: vec!["P2".to_string()],
+        });
+
+        let closed = event_bus.replay_events(DateTime::<Utc>::MIN_UTC).await.unwrap()
+            .into_iter()
+            .rev()
+            .find(|e| e.event_type == EventType::PositionsClosed)
+            .unwrap();
+        match closed.payload {
+            EventPayload::PositionsClosed { position_ids, still_open, .. } => {
+                assert_eq!(position_ids, vec!["P1".to_string()]);
+                assert_eq!(still_open, vec!["P2".to_string()]);
+            }
+            other => panic!("unexpected payload {:?}", other),
+        }
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
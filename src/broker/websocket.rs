@@ -1,5 +1,7 @@
 /// Angel One SmartAPI WebSocket client for real-time data
+use std::collections::BTreeMap;
 use std::sync::Arc;
+use chrono::{DateTime, Utc};
 use tokio::sync::{mpsc, RwLock};
 use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
 use futures_util::StreamExt;
@@ -32,6 +34,38 @@ struct WsToken {
     tokens: Vec<String>,
 }
 
+/// SmartAPI exchange type code
+fn exchange_type(exchange: &str) -> u8 {
+    match exchange {
+        "NSE" => 1,
+        "NFO" => 2,
+        "BSE" => 3,
+        "BFO" => 4,
+        "MCX" => 5,
+        _ => 1,
+    }
+}
+
+/// Build an LTP-mode subscribe request for `(token, exchange)` pairs,
+/// with one token list per exchange type
+fn subscribe_request(subscriptions: &[(String, String)]) -> WsSubscribeRequest {
+    let mut by_exchange: BTreeMap<u8, Vec<String>> = BTreeMap::new();
+    for (token, exchange) in subscriptions {
+        by_exchange.entry(exchange_type(exchange)).or_default().push(token.clone());
+    }
+    
+    WsSubscribeRequest {
+        action: 1, // Subscribe
+        params: WsSubscribeParams {
+            mode: 1, // LTP mode (mode 2 = Quote, mode 3 = Snap Quote)
+            token_list: by_exchange
+                .into_iter()
+                .map(|(exchange_type, tokens)| WsToken { exchange_type, tokens })
+                .collect(),
+        },
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct WsTickData {
     #[serde(rename = "tk")]
@@ -50,12 +84,22 @@ struct WsTickData {
     _exchange: Option<String>,
 }
 
+/// Period during which no ticks were received
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OutageWindow {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+}
+
 pub struct AngelWebSocket {
     token_manager: Arc<TokenManager>,
     tx: mpsc::UnboundedSender<Tick>,
     rx: Arc<RwLock<mpsc::UnboundedReceiver<Tick>>>,
-    subscribed_tokens: Arc<RwLock<Vec<String>>>,
+    /// Subscribed `(token, exchange)` pairs
+    subscribed_tokens: Arc<RwLock<Vec<(String, String)>>>,
     is_connected: Arc<RwLock<bool>>,
+    /// When the reader task last ended (start of the current outage)
+    disconnected_at: Arc<RwLock<Option<DateTime<Utc>>>>,
 }
 
 impl AngelWebSocket {
@@ -68,6 +112,7 @@ impl AngelWebSocket {
             rx: Arc::new(RwLock::new(rx)),
             subscribed_tokens: Arc::new(RwLock::new(Vec::new())),
             is_connected: Arc::new(RwLock::new(false)),
+            disconnected_at: Arc::new(RwLock::new(None)),
         }
     }
     
@@ -103,6 +148,7 @@ impl AngelWebSocket {
         // Spawn reader task
         let tx = self.tx.clone();
         let is_connected = Arc::clone(&self.is_connected);
+        let disconnected_at = Arc::clone(&self.disconnected_at);
         
         tokio::spawn(async move {
             while let Some(msg) = read.next().await {
@@ -155,6 +201,7 @@ impl AngelWebSocket {
             
             let mut connected = is_connected.write().await;
             *connected = false;
+            *disconnected_at.write().await = Some(Utc::now());
             warn!("WebSocket reader task ended");
         });
         
@@ -163,32 +210,23 @@ impl AngelWebSocket {
     
     /// Subscribe to tokens
     pub async fn subscribe(&self, tokens: Vec<String>, exchange: &str) -> Result<()> {
-        let exchange_type = match exchange {
-            "NSE" => 1,
-            "NFO" => 2,
-            "BSE" => 3,
-            "BFO" => 4,
-            "MCX" => 5,
-            _ => 1,
-        };
-        
-        let _subscribe_req = WsSubscribeRequest {
-            action: 1, // Subscribe
-            params: WsSubscribeParams {
-                mode: 1, // LTP mode (mode 2 = Quote, mode 3 = Snap Quote)
-                token_list: vec![WsToken {
-                    exchange_type,
-                    tokens: tokens.clone(),
-                }],
-            },
-        };
+        let pairs: Vec<(String, String)> = tokens
+            .iter()
+            .map(|token| (token.clone(), exchange.to_string()))
+            .collect();
+        
+        let _subscribe_req = subscribe_request(&pairs);
         
         // Would send to WebSocket here
         // ws_write.send(Message::Text(serde_json::to_string(&subscribe_req)?)).await?;
         
         {
             let mut subscribed = self.subscribed_tokens.write().await;
-            subscribed.extend(tokens.clone());
+            for pair in pairs {
+                if !subscribed.contains(&pair) {
+                    subscribed.push(pair);
+                }
+            }
         }
         
         info!("📡 Subscribed to {} tokens on {}", tokens.len(), exchange);
@@ -196,13 +234,35 @@ impl AngelWebSocket {
         Ok(())
     }
     
+    /// Current subscriptions as `(token, exchange)` pairs
+    pub async fn subscribed(&self) -> Vec<(String, String)> {
+        let subscribed = self.subscribed_tokens.read().await;
+        subscribed.clone()
+    }
+    
+    /// Re-send all subscriptions after a reconnect, each on its own exchange
+    async fn resubscribe(&self) -> Option<WsSubscribeRequest> {
+        let subscriptions = self.subscribed().await;
+        if subscriptions.is_empty() {
+            return None;
+        }
+        
+        let subscribe_req = subscribe_request(&subscriptions);
+        
+        // Would send to WebSocket here
+        // ws_write.send(Message::Text(serde_json::to_string(&subscribe_req)?)).await?;
+        
+        info!("📡 Resubscribed to {} tokens", subscriptions.len());
+        Some(subscribe_req)
+    }
+    
     /// Unsubscribe from tokens
     pub async fn unsubscribe(&self, tokens: Vec<String>) -> Result<()> {
         // Would send unsubscribe message
         
         {
             let mut subscribed = self.subscribed_tokens.write().await;
-            subscribed.retain(|t| !tokens.contains(t));
+            subscribed.retain(|(token, _)| !tokens.contains(token));
         }
         
         info!("📡 Unsubscribed from {} tokens", tokens.len());
@@ -251,7 +311,8 @@ impl AngelWebSocket {
     }
     
     /// Reconnect with exponential backoff
-    pub async fn reconnect(&self, max_attempts: u32) -> Result<()> {
+    /// Returns the outage window so missed bars can be backfilled
+    pub async fn reconnect(&self, max_attempts: u32) -> Result<OutageWindow> {
         let mut attempt = 0;
         let backoffs = vec![1, 2, 4, 8, 16]; // seconds
         
@@ -267,17 +328,12 @@ impl AngelWebSocket {
                 Ok(_) => {
                     info!("✅ Reconnected successfully");
                     
-                    // Re-subscribe to previous tokens
-                    let tokens = {
-                        let subscribed = self.subscribed_tokens.read().await;
-                        subscribed.clone()
-                    };
+                    // Re-subscribe to previous tokens on their original exchanges
+                    self.resubscribe().await;
                     
-                    if !tokens.is_empty() {
-                        self.subscribe(tokens, "NFO").await?;
-                    }
-                    
-                    return Ok(());
+                    let end = Utc::now();
+                    let start = self.disconnected_at.write().await.take().unwrap_or(end);
+                    return Ok(OutageWindow { start, end });
                 }
                 Err(e) => {
                     error!("Reconnection attempt {} failed: {}", attempt, e);
@@ -311,4 +367,31 @@ mod tests {
         assert_eq!(tick.token, "10");
         assert!(tick.ltp > 0.0);
     }
+    
+    #[tokio::test]
+    async fn test_resubscribe_preserves_exchange() {
+        let token_manager = Arc::new(TokenManager::new("test_ws_tokens.json".to_string()));
+        let ws = AngelWebSocket::new(token_manager);
+        
+        ws.subscribe(vec!["26000".to_string(), "26017".to_string()], "NSE").await.unwrap();
+        ws.subscribe(vec!["43650".to_string()], "NFO").await.unwrap();
+        // Re-subscribing the same token doesn't duplicate it
+        ws.subscribe(vec!["43650".to_string()], "NFO").await.unwrap();
+        
+        assert_eq!(ws.subscribed().await, vec![
+            ("26000".to_string(), "NSE".to_string()),
+            ("26017".to_string(), "NSE".to_string()),
+            ("43650".to_string(), "NFO".to_string()),
+        ]);
+        
+        let request = ws.resubscribe().await.unwrap();
+        let token_list = &request.params.token_list;
+        assert_eq!(token_list.len(), 2);
+        assert_eq!(token_list[0].exchange_type, 1);
+        assert_eq!(token_list[0].tokens, vec!["26000", "26017"]);
+        assert_eq!(token_list[1].exchange_type, 2);
+        assert_eq!(token_list[1].tokens, vec!["43650"]);
+        
+        assert_eq!(ws.subscribed().await.len(), 3);
+    }
 }
@@ -0,0 +1,277 @@
+/// Command-line subcommands for the trading binary
+use std::future::Future;
+use tracing::{error, warn};
+
+use crate::data::UnderlyingAsset;
+use crate::error::{Result, TradingError};
+use crate::orders::OrderManager;
+use crate::positions::PositionManager;
+use crate::types::Position;
+
+/// Default history depth for `backfill`
+pub const DEFAULT_BACKFILL_DAYS: u32 = 365;
+
+/// Default output file for `export-tokens`
+pub const DEFAULT_TOKENS_FILE: &str = "data/index_tokens.json";
+
+/// File a running bot polls for operator commands (see `ControlCommand`)
+pub const CONTROL_FILE: &str = "data/control";
+
+/// Usage text printed for `help` and on parse errors
+pub const USAGE: &str = "Usage: rustro [run | backfill [--asset NIFTY|BANKNIFTY|FINNIFTY] [--days N] | export-tokens [--output FILE] | flatten | help]";
+
+/// Subcommand selected on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Live trading loop (default)
+    Run,
+    /// Download history without trading; `None` syncs every asset
+    Backfill {
+        asset: Option<UnderlyingAsset>,
+        days: u32,
+    },
+    /// Write the index token mapping to a JSON file
+    ExportTokens { output: String },
+    /// Ask the running bot to close all positions; unlike Ctrl-C it keeps running
+    Flatten,
+    /// Print `USAGE` and exit
+    Help,
+}
+
+/// Operator command passed to a running bot through `CONTROL_FILE`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    /// Close all positions at market and carry on
+    Flatten,
+}
+
+impl ControlCommand {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ControlCommand::Flatten => "flatten",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim() {
+            "flatten" => Some(ControlCommand::Flatten),
+            _ => None,
+        }
+    }
+}
+
+/// Leave `command` in the control file for the running bot
+pub async fn send_control_command(path: &str, command: ControlCommand) -> Result<()> {
+    tokio::fs::write(path, command.as_str()).await?;
+    Ok(())
+}
+
+/// Take the pending command, if any; the file is removed so it runs once
+/// (an unrecognised command is dropped with an error)
+pub async fn take_control_command(path: &str) -> Result<Option<ControlCommand>> {
+    let content = match tokio::fs::read_to_string(path).await {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    tokio::fs::remove_file(path).await?;
+
+    ControlCommand::parse(&content).map(Some).ok_or_else(|| {
+        TradingError::InvalidParameter(format!("Unknown control command: {}", content.trim()))
+    })
+}
+
+/// Run the command left in `path`, if any, and return it. A flatten cancels
+/// working orders and closes every position through `exit`; unlike Ctrl-C
+/// the bot keeps running.
+pub async fn handle_control_command<F, Fut>(
+    path: &str,
+    order_manager: &OrderManager,
+    position_manager: &PositionManager,
+    exit: F,
+) -> Option<ControlCommand>
+where
+    F: Fn(Position) -> Fut,
+    Fut: Future<Output = Result<f64>>,
+{
+    let command = match take_control_command(path).await {
+        Ok(command) => command?,
+        Err(e) => {
+            warn!("⚠️  Ignoring control command: {}", e);
+            return None;
+        }
+    };
+
+    match command {
+        ControlCommand::Flatten => {
+            if let Err(e) = order_manager.cancel_all_active("MANUAL_FLATTEN").await {
+                error!("Failed to cancel working orders: {}", e);
+            }
+            match position_manager.manual_flatten(exit).await {
+                Ok(report) => {
+                    for position_id in &report.still_open {
+                        error!("🚨 Position {} still open after exit retries - close it manually", position_id);
+                    }
+                }
+                Err(e) => error!("❌ Manual flatten failed: {}", e),
+            }
+        }
+    }
+
+    Some(command)
+}
+
+/// Parse arguments (excluding the program name) into a command
+pub fn parse_args<I, S>(args: I) -> Result<Command>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let args: Vec<String> = args.into_iter().map(|a| a.as_ref().to_string()).collect();
+    let Some((subcommand, rest)) = args.split_first() else {
+        return Ok(Command::Run);
+    };
+
+    match subcommand.as_str() {
+        "run" => {
+            reject_extra(subcommand, rest)?;
+            Ok(Command::Run)
+        }
+        "backfill" => {
+            let mut asset = None;
+            let mut days = DEFAULT_BACKFILL_DAYS;
+            let mut iter = rest.iter();
+            while let Some(flag) = iter.next() {
+                let value = flag_value(flag, iter.next())?;
+                match flag.as_str() {
+                    "--asset" => {
+                        asset = Some(UnderlyingAsset::from_symbol(value).ok_or_else(|| {
+                            TradingError::InvalidParameter(format!("Unknown asset: {}", value))
+                        })?);
+                    }
+                    "--days" => {
+                        days = value
+                            .parse()
+                            .ok()
+                            .filter(|d| *d > 0)
+                            .ok_or_else(|| {
+                                TradingError::InvalidParameter(format!("Invalid --days: {}", value))
+                            })?;
+                    }
+                    _ => return Err(unknown_flag(subcommand, flag)),
+                }
+            }
+            Ok(Command::Backfill { asset, days })
+        }
+        "export-tokens" => {
+            let mut output = DEFAULT_TOKENS_FILE.to_string();
+            let mut iter = rest.iter();
+            while let Some(flag) = iter.next() {
+                let value = flag_value(flag, iter.next())?;
+                match flag.as_str() {
+                    "--output" => output = value.to_string(),
+                    _ => return Err(unknown_flag(subcommand, flag)),
+                }
+            }
+            Ok(Command::ExportTokens { output })
+        }
+        "flatten" => {
+            reject_extra(subcommand, rest)?;
+            Ok(Command::Flatten)
+        }
+        "help" | "--help" | "-h" => Ok(Command::Help),
+        other => Err(TradingError::InvalidParameter(format!("Unknown subcommand: {}", other))),
+    }
+}
+
+fn flag_value<'a>(flag: &str, value: Option<&'a String>) -> Result<&'a str> {
+    value
+        .map(|v| v.as_str())
+        .ok_or_else(|| TradingError::InvalidParameter(format!("Missing value for {}", flag)))
+}
+
+fn unknown_flag(subcommand: &str, flag: &str) -> TradingError {
+    TradingError::InvalidParameter(format!("Unknown option for {}: {}", subcommand, flag))
+}
+
+fn reject_extra(subcommand: &str, rest: &[String]) -> Result<()> {
+    match rest.first() {
+        Some(flag) => Err(unknown_flag(subcommand, flag)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use crate::broker::angel_one::test_client;
+    use crate::config::loader::test_config;
+    use crate::events::EventBus;
+    use crate::types::test_position;
+
+    #[test]
+    fn test_parse_args_routes_subcommands() {
+        let none: [&str; 0] = [];
+        assert_eq!(parse_args(none).unwrap(), Command::Run);
+        assert_eq!(parse_args(["run"]).unwrap(), Command::Run);
+
+        assert_eq!(
+            parse_args(["backfill", "--asset", "NIFTY", "--days", "365"]).unwrap(),
+            Command::Backfill { asset: Some(UnderlyingAsset::Nifty), days: 365 }
+        );
+        assert_eq!(
+            parse_args(["backfill"]).unwrap(),
+            Command::Backfill { asset: None, days: DEFAULT_BACKFILL_DAYS }
+        );
+
+        assert_eq!(
+            parse_args(["export-tokens", "--output", "tokens.json"]).unwrap(),
+            Command::ExportTokens { output: "tokens.json".to_string() }
+        );
+
+        assert_eq!(parse_args(["flatten"]).unwrap(), Command::Flatten);
+        assert_eq!(parse_args(["help"]).unwrap(), Command::Help);
+        assert_eq!(parse_args(["--help"]).unwrap(), Command::Help);
+        assert!(parse_args(["flatten", "--now"]).is_err());
+
+        assert!(parse_args(["trade"]).is_err());
+        assert!(parse_args(["backfill", "--asset", "SENSEX"]).is_err());
+        assert!(parse_args(["backfill", "--days"]).is_err());
+        assert!(parse_args(["backfill", "--days", "0"]).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_flatten_command_closes_positions_once() {
+        let dir = std::env::temp_dir().join(format!("control_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let control_file = dir.join("control.json").display().to_string();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let config = Arc::new(test_config());
+        
+        let broker = Arc::new(test_client());
+        let order_manager = OrderManager::new(broker, Arc::clone(&event_bus), Arc::clone(&config));
+        let position_manager = PositionManager::new(event_bus, config);
+        position_manager.open_position(test_position("P1")).await.unwrap();
+        position_manager.open_position(test_position("P2")).await.unwrap();
+        
+        let exit = |position: Position| async move { Ok(position.current_price) };
+        
+        // Nothing requested yet
+        assert_eq!(handle_control_command(&control_file, &order_manager, &position_manager, exit).await, None);
+        assert_eq!(position_manager.get_open_positions().await.len(), 2);
+        
+        send_control_command(&control_file, ControlCommand::Flatten).await.unwrap();
+        assert_eq!(
+            handle_control_command(&control_file, &order_manager, &position_manager, exit).await,
+            Some(ControlCommand::Flatten)
+        );
+        assert!(position_manager.get_open_positions().await.is_empty());
+        assert!(!std::path::Path::new(&control_file).exists());
+        
+        // The request is consumed, not replayed
+        assert_eq!(handle_control_command(&control_file, &order_manager, &position_manager, exit).await, None);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,21 +1,196 @@
-/// Configuration loading from TOML file
+/// Configuration loading from TOML file, with `RUSTRO_<FIELD>` env overrides
 use std::path::Path;
+use tracing::{info, warn};
 use crate::error::{Result, TradingError};
-use crate::types::Config;
+use crate::types::{Config, ProductType, TradingMode};
+
+/// Prefix for environment overrides (e.g. `RUSTRO_MAX_POSITIONS=2`)
+const ENV_PREFIX: &str = "RUSTRO_";
+
+/// Separator for nested tables (e.g. `RUSTRO_LOT_SIZE__NIFTY=75`)
+const ENV_NESTED_SEPARATOR: &str = "__";
+
+/// Fields that should come from the environment rather than the TOML file
+const CREDENTIAL_FIELDS: &[&str] = &[
+    "angel_one_password",
+    "angel_one_mpin",
+    "angel_one_totp_secret",
+    "angel_one_api_key",
+    "angel_one_secret_key",
+];
+
+/// Shipped defaults, and the allow-list of fields a config file may leave out
+const DEFAULT_CONFIG: &str = include_str!("../../config.defaults.toml");
+
+/// Fields never taken from the defaults: the account and its credentials
+/// must be configured, and `mode` must not override `enable_paper_trading`
+const NEVER_DEFAULTED: &[&str] = &["angel_one_client_code", "mode"];
+
+/// Risk and sizing fields (and sections) a config file must set itself
+const REQUIRED_FIELDS: &[&str] = &[
+    "option_stop_loss_pct",
+    "max_positions",
+    "max_positions_per_underlying",
+    "daily_loss_limit_pct",
+    "default_start_capital",
+    "consecutive_loss_limit",
+    "max_notional_per_trade",
+    "min_lots",
+    "max_lots",
+    "base_position_size_pct",
+    "vix_mult_anchors",
+    "dte_mult",
+    "freeze_quantity",
+    "lot_size",
+];
 
 pub fn load_config<P: AsRef<Path>>(path: P) -> Result<Config> {
     let content = std::fs::read_to_string(path)
         .map_err(|e| TradingError::ConfigError(format!("Failed to read config file: {}", e)))?;
     
-    let config: Config = toml::from_str(&content)
+    let mut table: toml::Table = toml::from_str(&content)
         .map_err(|e| TradingError::ConfigError(format!("Failed to parse config: {}", e)))?;
     
+    // Environment takes precedence over TOML
+    let overridden = apply_env_overrides(&mut table, std::env::vars())?;
+    if !overridden.is_empty() {
+        info!("Config fields overridden from environment: {}", overridden.join(", "));
+    }
+    
+    for field in CREDENTIAL_FIELDS {
+        if table.contains_key(*field) && !overridden.iter().any(|f| f == field) {
+            warn!(
+                "{} is read from the config file - prefer {}{}",
+                field,
+                ENV_PREFIX,
+                field.to_uppercase()
+            );
+        }
+    }
+    
+    let config = Config::merged_with_defaults(table)?;
+    
     // Validate config
     validate_config(&config)?;
     
     Ok(config)
 }
 
+impl Config {
+    /// Deserialize `table`, filling missing fields that config.defaults.toml
+    /// lists; a missing credential or risk/sizing field is an error
+    pub fn merged_with_defaults(mut table: toml::Table) -> Result<Config> {
+        let missing: Vec<&str> = REQUIRED_FIELDS.iter()
+            .copied()
+            .filter(|field| !table.contains_key(*field))
+            .collect();
+        if !missing.is_empty() {
+            return Err(TradingError::ConfigError(format!(
+                "Missing risk/sizing fields (never defaulted): {}",
+                missing.join(", ")
+            )));
+        }
+        
+        let mut defaults: toml::Table = toml::from_str(DEFAULT_CONFIG)
+            .map_err(|e| TradingError::ConfigError(format!("Failed to parse default config: {}", e)))?;
+        for field in CREDENTIAL_FIELDS.iter().chain(NEVER_DEFAULTED).chain(REQUIRED_FIELDS) {
+            defaults.remove(*field);
+        }
+        
+        let mut defaulted = Vec::new();
+        fill_missing(&mut table, defaults, "", &mut defaulted);
+        if !defaulted.is_empty() {
+            info!("Config fields defaulted: {}", defaulted.join(", "));
+        }
+        
+        table.try_into()
+            .map_err(|e| TradingError::ConfigError(format!("Failed to parse config: {}", e)))
+    }
+}
+
+/// Copy keys absent from `table` out of `defaults`, recursing into nested
+/// tables; records the dotted names of the keys it filled
+fn fill_missing(table: &mut toml::Table, defaults: toml::Table, prefix: &str, filled: &mut Vec<String>) {
+    for (key, default) in defaults {
+        let name = format!("{}{}", prefix, key);
+        match (table.get_mut(&key), default) {
+            (Some(toml::Value::Table(section)), toml::Value::Table(default_section)) => {
+                fill_missing(section, default_section, &format!("{}.", name), filled);
+            }
+            (Some(_), _) => {}
+            (None, default) => {
+                table.insert(key, default);
+                filled.push(name);
+            }
+        }
+    }
+}
+
+/// Apply `RUSTRO_<FIELD>` variables on top of the parsed TOML table
+/// Values are parsed as the type already present in the file; new keys are
+/// parsed as TOML literals, falling back to a plain string.
+/// Returns the overridden field names (never their values).
+pub fn apply_env_overrides<I>(table: &mut toml::Table, vars: I) -> Result<Vec<String>>
+where
+    I: IntoIterator<Item = (String, String)>,
+{
+    let mut overridden = Vec::new();
+    
+    for (name, raw) in vars {
+        let Some(field) = name.strip_prefix(ENV_PREFIX) else {
+            continue;
+        };
+        let field = field.to_lowercase();
+        let path: Vec<&str> = field.split(ENV_NESTED_SEPARATOR).collect();
+        let (key, parents) = path.split_last().expect("split yields at least one item");
+        
+        // Walk into nested tables; unknown sections are skipped
+        let section = parents.iter()
+            .try_fold(&mut *table, |t, parent| t.get_mut(*parent)?.as_table_mut());
+        let Some(target) = section else {
+            warn!("Ignoring {}: no config section for it", name);
+            continue;
+        };
+        
+        let value = parse_env_value(&name, &raw, target.get(*key))?;
+        target.insert(key.to_string(), value);
+        overridden.push(field.clone());
+    }
+    
+    overridden.sort();
+    Ok(overridden)
+}
+
+/// Parse an env value as the type of the existing TOML value
+fn parse_env_value(name: &str, raw: &str, existing: Option<&toml::Value>) -> Result<toml::Value> {
+    let invalid = |kind: &str| {
+        TradingError::ConfigError(format!("{} must be {}, got {:?}", name, kind, raw))
+    };
+    
+    let value = match existing {
+        Some(toml::Value::String(_)) => toml::Value::String(raw.to_string()),
+        Some(toml::Value::Integer(_)) => {
+            toml::Value::Integer(raw.trim().parse().map_err(|_| invalid("an integer"))?)
+        }
+        Some(toml::Value::Float(_)) => {
+            toml::Value::Float(raw.trim().parse().map_err(|_| invalid("a number"))?)
+        }
+        Some(toml::Value::Boolean(_)) => {
+            toml::Value::Boolean(raw.trim().parse().map_err(|_| invalid("true or false"))?)
+        }
+        Some(_) => parse_toml_literal(raw).ok_or_else(|| invalid("a TOML value"))?,
+        None => parse_toml_literal(raw).unwrap_or_else(|| toml::Value::String(raw.to_string())),
+    };
+    
+    Ok(value)
+}
+
+/// Parse a bare TOML literal such as `[1, 2]` or `{ nifty = 75 }`
+fn parse_toml_literal(raw: &str) -> Option<toml::Value> {
+    let mut doc: toml::Table = toml::from_str(&format!("v = {}", raw)).ok()?;
+    doc.remove("v")
+}
+
 fn validate_config(config: &Config) -> Result<()> {
     // Validate time windows
     if config.entry_window_start.is_empty() {
@@ -35,6 +210,34 @@ fn validate_config(config: &Config) -> Result<()> {
         ));
     }
     
+    if config.max_notional_per_trade <= 0.0 {
+        return Err(TradingError::ConfigError(
+            format!("Invalid max_notional_per_trade: {}", config.max_notional_per_trade)
+        ));
+    }
+    
+    if config.min_lots == 0 || config.max_lots < config.min_lots {
+        return Err(TradingError::ConfigError(
+            format!("Invalid lot bounds: min_lots={} max_lots={}", config.min_lots, config.max_lots)
+        ));
+    }
+    
+    // The rest of the system assumes flat by EOD; overnight risk is not modelled
+    if config.product_type == ProductType::Carryforward {
+        warn!("product_type is CARRYFORWARD - positions left open after EOD carry unmodelled overnight risk");
+    }
+    
+    // `mode` wins over the legacy flag; a contradiction is most likely a mistake
+    match (config.mode, config.enable_paper_trading) {
+        (Some(TradingMode::Paper), false) | (Some(TradingMode::Live), true) => {
+            return Err(TradingError::ConfigError(format!(
+                "mode = {:?} conflicts with enable_paper_trading = {}",
+                config.mode, config.enable_paper_trading
+            )));
+        }
+        _ => {}
+    }
+    
     // Validate VIX thresholds
     if config.vix_spike_threshold <= config.vix_resume_threshold {
         return Err(TradingError::ConfigError(
@@ -42,11 +245,177 @@ fn validate_config(config: &Config) -> Result<()> {
         ));
     }
     
+    if config.entry_fill_wait_sec <= config.entry_limit_timeout_sec {
+        return Err(TradingError::ConfigError(
+            "entry_fill_wait_sec must be > entry_limit_timeout_sec".to_string()
+        ));
+    }
+    
+    if config.vix_regime_low_max >= config.vix_regime_high_min {
+        return Err(TradingError::ConfigError(
+            "vix_regime_low_max must be < vix_regime_high_min".to_string()
+        ));
+    }
+    
+    if config.adx_exit_threshold >= config.adx_enter_threshold {
+        return Err(TradingError::ConfigError(
+            "adx_exit_threshold must be < adx_enter_threshold".to_string()
+        ));
+    }
+    
     // Validate periods
     if config.daily_adx_period < 2 || config.hourly_adx_period < 2 {
         return Err(TradingError::ConfigError("ADX periods must be >= 2".to_string()));
     }
     
+    if config.daily_ema_fast == 0 || config.daily_ema_fast >= config.daily_ema_slow {
+        return Err(TradingError::ConfigError(
+            "daily_ema_fast must be > 0 and < daily_ema_slow".to_string()
+        ));
+    }
+    
     Ok(())
 }
 
+/// Config parsed from the bundled config.toml (tests only)
+#[cfg(test)]
+pub(crate) fn test_config() -> Config {
+    toml::from_str(include_str!("../../config.toml")).expect("config.toml should parse")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+    
+    fn config_table() -> toml::Table {
+        toml::from_str(include_str!("../../config.toml")).unwrap()
+    }
+    
+    #[test]
+    fn test_env_overrides_numeric_and_string_fields() {
+        let mut table = config_table();
+        
+        let overridden = apply_env_overrides(&mut table, vars(&[
+            ("RUSTRO_MAX_POSITIONS", "2"),
+            ("RUSTRO_OPTION_STOP_LOSS_PCT", "0.25"),
+            ("RUSTRO_ANGEL_ONE_PASSWORD", "from-env"),
+            ("RUSTRO_ANGEL_ONE_MPIN", "4321"),
+            ("HOME", "/root"),
+        ])).unwrap();
+        
+        assert_eq!(overridden, vec![
+            "angel_one_mpin", "angel_one_password", "max_positions", "option_stop_loss_pct",
+        ]);
+        
+        let config: Config = table.try_into().unwrap();
+        assert_eq!(config.max_positions, 2);
+        assert_eq!(config.option_stop_loss_pct, 0.25);
+        assert_eq!(config.angel_one_password, "from-env");
+        assert_eq!(config.angel_one_mpin.as_deref(), Some("4321"));
+    }
+    
+    #[test]
+    fn test_env_override_type_mismatch_is_rejected() {
+        let mut table = config_table();
+        
+        let result = apply_env_overrides(&mut table, vars(&[("RUSTRO_MAX_POSITIONS", "two")]));
+        assert!(result.is_err());
+    }
+    
+    /// Credentials plus the required risk/sizing fields, nothing else
+    fn minimal_table() -> toml::Table {
+        let shipped = config_table();
+        let mut minimal: toml::Table = toml::from_str(r#"
+            angel_one_client_code = "A123"
+            angel_one_password = "pw"
+            angel_one_totp_secret = "totp"
+            angel_one_api_key = "key"
+            angel_one_secret_key = "secret"
+        "#).unwrap();
+        for field in REQUIRED_FIELDS {
+            minimal.insert(field.to_string(), shipped[*field].clone());
+        }
+        minimal.insert("max_positions".to_string(), toml::Value::Integer(1));
+        minimal
+    }
+    
+    #[test]
+    fn test_minimal_config_filled_from_defaults() {
+        let config = Config::merged_with_defaults(minimal_table()).unwrap();
+        let shipped = test_config();
+        assert_eq!(config.max_positions, 1);
+        assert_eq!(config.lot_size.banknifty, shipped.lot_size.banknifty);
+        assert_eq!(config.option_stop_loss_pct, shipped.option_stop_loss_pct);
+        assert_eq!(config.entry_window_start, shipped.entry_window_start);
+        assert_eq!(config.angel_one_client_code, "A123");
+        assert_eq!(config.angel_one_mpin, None);
+        assert_eq!(config.mode, None);
+        assert!(validate_config(&config).is_ok());
+        
+        // Credentials stay required
+        let mut no_password = config_table();
+        no_password.remove("angel_one_password");
+        assert!(Config::merged_with_defaults(no_password).is_err());
+        
+        // So do risk and sizing fields
+        for field in ["option_stop_loss_pct", "max_notional_per_trade", "lot_size"] {
+            let mut table = minimal_table();
+            table.remove(field);
+            let err = Config::merged_with_defaults(table).unwrap_err().to_string();
+            assert!(err.contains(field), "{}", err);
+        }
+        
+        // Feature flags default at the serde level too
+        let mut old_file = config_table();
+        for flag in ["use_trailing_stop", "use_cover_orders", "record_ticks", "oi_change_window"] {
+            old_file.remove(flag);
+        }
+        let config: Config = old_file.try_into().unwrap();
+        assert!(config.use_trailing_stop);
+        assert!(!config.use_cover_orders);
+        assert_eq!(config.oi_change_window, 15);
+    }
+    
+    #[test]
+    fn test_mode_conflicting_with_paper_flag_is_rejected() {
+        let mut config = test_config();
+        config.mode = Some(TradingMode::Live);
+        config.enable_paper_trading = true;
+        assert!(validate_config(&config).is_err());
+        
+        config.enable_paper_trading = false;
+        assert!(validate_config(&config).is_ok());
+        
+        // Analysis places no orders either way
+        config.mode = Some(TradingMode::Analysis);
+        assert!(validate_config(&config).is_ok());
+    }
+    
+    #[test]
+    fn test_adx_exit_threshold_must_be_below_enter() {
+        let mut config = test_config();
+        config.adx_exit_threshold = config.adx_enter_threshold;
+        assert!(validate_config(&config).is_err());
+        
+        config.adx_exit_threshold = config.adx_enter_threshold - 5.0;
+        assert!(validate_config(&config).is_ok());
+    }
+    
+    #[test]
+    fn test_defaults_file_tracks_config_toml() {
+        let defaults: toml::Table = toml::from_str(DEFAULT_CONFIG).unwrap();
+        
+        // Every shipped field is either defaultable (with the same value,
+        // nested sections included) or must be set: a credential, `mode`,
+        // or a risk/sizing field
+        let mut defaultable = config_table();
+        for field in CREDENTIAL_FIELDS.iter().chain(NEVER_DEFAULTED).chain(REQUIRED_FIELDS) {
+            defaultable.remove(*field);
+        }
+        assert_eq!(defaultable, defaults);
+    }
+}
@@ -41,6 +41,28 @@ impl Timeframe {
         }
     }
     
+    /// SmartAPI candle interval name
+    pub fn api_interval(&self) -> &str {
+        match self {
+            Timeframe::OneMinute => "ONE_MINUTE",
+            Timeframe::FiveMinute => "FIVE_MINUTE",
+            Timeframe::FifteenMinute => "FIFTEEN_MINUTE",
+            Timeframe::OneHour => "ONE_HOUR",
+            Timeframe::OneDay => "ONE_DAY",
+        }
+    }
+    
+    /// Longest date range SmartAPI returns in one candle request
+    pub fn max_fetch_days(&self) -> i64 {
+        match self {
+            Timeframe::OneMinute => 30,
+            Timeframe::FiveMinute => 100,
+            Timeframe::FifteenMinute => 200,
+            Timeframe::OneHour => 400,
+            Timeframe::OneDay => 2000,
+        }
+    }
+    
     /// Get bar boundary timestamp
     pub fn get_bar_boundary(&self, timestamp: DateTime<Utc>) -> DateTime<Utc> {
         let ist = timestamp.with_timezone(&chrono_tz::Asia::Kolkata);
@@ -169,6 +191,10 @@ pub struct BarAggregator {
     symbol: String,
     timeframe: Timeframe,
     current_bar: Arc<RwLock<Option<PartialBar>>>,
+    /// Previous bar held open for late ticks until its grace window ends
+    closing_bar: Arc<RwLock<Option<PartialBar>>>,
+    /// How long after its period ends a bar still accepts ticks
+    grace: chrono::Duration,
     bar_store: Arc<ConcurrentBarStore>,
     event_bus: Arc<EventBus>,
     last_tick_time: Arc<RwLock<Option<DateTime<Utc>>>>,
@@ -185,67 +211,74 @@ impl BarAggregator {
             symbol,
             timeframe,
             current_bar: Arc::new(RwLock::new(None)),
+            closing_bar: Arc::new(RwLock::new(None)),
+            grace: chrono::Duration::zero(),
             bar_store,
             event_bus,
             last_tick_time: Arc::new(RwLock::new(None)),
         }
     }
     
+    /// Emit `BarReady` only `grace_sec` after a bar's period ends, so late
+    /// ticks still update it (default 0: emit on the first tick past the boundary)
+    pub fn with_grace_period(mut self, grace_sec: u64) -> Self {
+        self.grace = chrono::Duration::seconds(grace_sec as i64);
+        self
+    }
+    
+    /// Time from which `bar` no longer accepts late ticks
+    fn grace_deadline(&self, bar: &PartialBar) -> DateTime<Utc> {
+        bar.timestamp + chrono::Duration::minutes(self.timeframe.duration_minutes()) + self.grace
+    }
+    
     /// Process incoming tick
     pub async fn process_tick(&self, tick: &Tick) -> Result<()> {
         let bar_boundary = self.timeframe.get_bar_boundary(tick.timestamp);
         
         let mut current = self.current_bar.write().await;
+        let mut closing = self.closing_bar.write().await;
         
-        match current.as_mut() {
-            Some(bar) => {
-                // Check if we've crossed into a new bar period
-                if bar.timestamp != bar_boundary {
-                    // Finalize current bar
-                    let completed_bar = bar.to_bar(true);
-                    
-                    // Save to store
-                    self.bar_store.append(completed_bar.clone()).await?;
-                    
-                    // Emit BAR_READY event
-                    self.event_bus.publish(Event::new(
-                        EventType::BarReady,
-                        EventPayload::BarReady {
-                            symbol: self.symbol.clone(),
-                            timeframe: self.timeframe.as_str().to_string(),
-                            bar_time: completed_bar.timestamp,
-                            bar_complete: true,
-                        },
-                    )).await?;
-                    
+        match closing.as_mut() {
+            // Late tick for the bar still in its grace window
+            Some(bar) if bar.timestamp == bar_boundary => bar.update(tick.ltp, tick.volume),
+            _ => match current.as_mut() {
+                Some(bar) if bar_boundary < bar.timestamp => {
                     debug!(
-                        "📊 Bar completed: {} {} @ {} - O:{:.2} H:{:.2} L:{:.2} C:{:.2} V:{}",
+                        "Dropping tick @ {} for finished {} {} bar",
+                        tick.timestamp,
                         self.symbol,
-                        self.timeframe.as_str(),
-                        completed_bar.timestamp,
-                        completed_bar.open,
-                        completed_bar.high,
-                        completed_bar.low,
-                        completed_bar.close,
-                        completed_bar.volume
+                        self.timeframe.as_str()
                     );
-                    
-                    // Start new bar
+                }
+                Some(bar) if bar.timestamp != bar_boundary => {
+                    // Crossed into a new bar period: hold the finished bar for late ticks
+                    if let Some(superseded) = closing.take() {
+                        self.complete_bar(superseded).await?;
+                    }
+                    *closing = current.take();
                     *current = Some(PartialBar::new(bar_boundary, tick.ltp, tick.volume));
-                } else {
+                }
+                Some(bar) => {
                     // Update current bar
                     bar.update(tick.ltp, tick.volume);
                 }
-            }
-            None => {
-                // Start first bar
-                *current = Some(PartialBar::new(bar_boundary, tick.ltp, tick.volume));
-                info!(
-                    "🆕 Started new bar: {} {} @ {}",
-                    self.symbol,
-                    self.timeframe.as_str(),
-                    bar_boundary
-                );
+                None => {
+                    // Start first bar
+                    *current = Some(PartialBar::new(bar_boundary, tick.ltp, tick.volume));
+                    info!(
+                        "🆕 Started new bar: {} {} @ {}",
+                        self.symbol,
+                        self.timeframe.as_str(),
+                        bar_boundary
+                    );
+                }
+            },
+        }
+        
+        // Grace window over: the closing bar is final
+        if closing.as_ref().is_some_and(|bar| tick.timestamp >= self.grace_deadline(bar)) {
+            if let Some(bar) = closing.take() {
+                self.complete_bar(bar).await?;
             }
         }
         
@@ -258,36 +291,62 @@ impl BarAggregator {
         Ok(())
     }
     
+    /// Store a finished bar and emit `BarReady`
+    async fn complete_bar(&self, bar: PartialBar) -> Result<()> {
+        let completed_bar = bar.to_bar(true);
+        
+        // Save to store
+        self.bar_store.append(completed_bar.clone()).await?;
+        
+        // Emit BAR_READY event
+        self.event_bus.publish(Event::new(
+            EventType::BarReady,
+            EventPayload::BarReady {
+                symbol: self.symbol.clone(),
+                timeframe: self.timeframe.as_str().to_string(),
+                bar_time: completed_bar.timestamp,
+                bar_complete: true,
+            },
+        )).await?;
+        
+        debug!(
+            "📊 Bar completed: {} {} @ {} - O:{:.2} H:{:.2} L:{:.2} C:{:.2} V:{}",
+            self.symbol,
+            self.timeframe.as_str(),
+            completed_bar.timestamp,
+            completed_bar.open,
+            completed_bar.high,
+            completed_bar.low,
+            completed_bar.close,
+            completed_bar.volume
+        );
+        
+        Ok(())
+    }
+    
     /// Get current partial bar (for monitoring)
     pub async fn get_current_bar(&self) -> Option<Bar> {
         let current = self.current_bar.read().await;
         current.as_ref().map(|b| b.to_bar(false))
     }
     
-    /// Force finalize current bar (e.g., at EOD)
+    /// Force finalize the closing and current bars (e.g., at EOD)
     pub async fn finalize_current_bar(&self) -> Result<()> {
+        if let Some(bar) = self.closing_bar.write().await.take() {
+            self.complete_bar(bar).await?;
+        }
+        
         let mut current = self.current_bar.write().await;
         
         if let Some(bar) = current.take() {
-            let completed_bar = bar.to_bar(true);
-            
-            self.bar_store.append(completed_bar.clone()).await?;
-            
-            self.event_bus.publish(Event::new(
-                EventType::BarReady,
-                EventPayload::BarReady {
-                    symbol: self.symbol.clone(),
-                    timeframe: self.timeframe.as_str().to_string(),
-                    bar_time: completed_bar.timestamp,
-                    bar_complete: true,
-                },
-            )).await?;
+            let bar_time = bar.timestamp;
+            self.complete_bar(bar).await?;
             
             info!(
                 "✅ Finalized current bar: {} {} @ {}",
                 self.symbol,
                 self.timeframe.as_str(),
-                completed_bar.timestamp
+                bar_time
             );
         }
         
@@ -309,9 +368,14 @@ impl BarAggregator {
 }
 
 /// Multi-symbol, multi-timeframe bar aggregator
+/// Aggregators indexed by token/symbol key, then timeframe
+type AggregatorIndex = HashMap<String, HashMap<Timeframe, Arc<BarAggregator>>>;
+
 pub struct MultiBarAggregator {
-    aggregators: Arc<RwLock<HashMap<(String, Timeframe), Arc<BarAggregator>>>>,
+    aggregators: Arc<RwLock<AggregatorIndex>>,
     event_bus: Arc<EventBus>,
+    /// Grace period given to every aggregator added (`bar_ready_grace_sec`)
+    grace_sec: u64,
 }
 
 impl MultiBarAggregator {
@@ -319,9 +383,16 @@ impl MultiBarAggregator {
         MultiBarAggregator {
             aggregators: Arc::new(RwLock::new(HashMap::new())),
             event_bus,
+            grace_sec: 0,
         }
     }
     
+    /// Hold finished bars open for late ticks for `grace_sec` (see `BarAggregator::with_grace_period`)
+    pub fn with_bar_ready_grace(mut self, grace_sec: u64) -> Self {
+        self.grace_sec = grace_sec;
+        self
+    }
+    
     /// Add aggregator for symbol and timeframe
     pub async fn add_aggregator(
         &self,
@@ -334,20 +405,28 @@ impl MultiBarAggregator {
             timeframe,
             bar_store,
             Arc::clone(&self.event_bus),
-        ));
+        ).with_grace_period(self.grace_sec));
         
         let mut aggregators = self.aggregators.write().await;
-        aggregators.insert((symbol.clone(), timeframe), aggregator);
+        aggregators
+            .entry(symbol.clone())
+            .or_default()
+            .insert(timeframe, aggregator);
         
         info!("➕ Added aggregator: {} {}", symbol, timeframe.as_str());
     }
     
-    /// Process tick for all relevant aggregators
+    /// Route a tick to the aggregators registered for its token
+    /// (falls back to the tick symbol for aggregators registered by name)
     pub async fn process_tick(&self, tick: Tick) -> Result<()> {
         let aggregators = self.aggregators.read().await;
         
-        for ((symbol, _timeframe), aggregator) in aggregators.iter() {
-            if symbol == &tick.symbol || symbol == &tick.token {
+        let by_timeframe = aggregators
+            .get(&tick.token)
+            .or_else(|| aggregators.get(&tick.symbol));
+        
+        if let Some(by_timeframe) = by_timeframe {
+            for aggregator in by_timeframe.values() {
                 aggregator.process_tick(&tick).await?;
             }
         }
@@ -359,7 +438,7 @@ impl MultiBarAggregator {
     pub async fn finalize_all(&self) -> Result<()> {
         let aggregators = self.aggregators.read().await;
         
-        for aggregator in aggregators.values() {
+        for aggregator in aggregators.values().flat_map(|by_timeframe| by_timeframe.values()) {
             aggregator.finalize_current_bar().await?;
         }
         
@@ -372,9 +451,11 @@ impl MultiBarAggregator {
         let aggregators = self.aggregators.read().await;
         let mut gaps = Vec::new();
         
-        for ((symbol, timeframe), aggregator) in aggregators.iter() {
-            if aggregator.check_data_gap(threshold_seconds).await {
-                gaps.push((symbol.clone(), *timeframe));
+        for (symbol, by_timeframe) in aggregators.iter() {
+            for (timeframe, aggregator) in by_timeframe {
+                if aggregator.check_data_gap(threshold_seconds).await {
+                    gaps.push((symbol.clone(), *timeframe));
+                }
             }
         }
         
@@ -409,4 +490,99 @@ mod tests {
         assert_eq!(bar.close, 98.0);
         assert_eq!(bar.volume, 1800);
     }
+    
+    #[tokio::test]
+    async fn test_tick_routed_only_to_matching_token() {
+        let dir = std::env::temp_dir().join(format!("tick_routing_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let store = Arc::new(ConcurrentBarStore::new(
+            "TEST".to_string(),
+            "1h".to_string(),
+            dir.join("bars.jsonl"),
+            10,
+        ));
+        let multi = MultiBarAggregator::new(event_bus);
+        
+        for token in 0..500 {
+            for timeframe in [Timeframe::OneHour, Timeframe::OneDay] {
+                multi.add_aggregator(token.to_string(), timeframe, Arc::clone(&store)).await;
+            }
+        }
+        
+        let now = Utc::now();
+        multi.process_tick(Tick {
+            symbol: "NIFTY".to_string(),
+            token: "42".to_string(),
+            ltp: 19500.0,
+            bid: 19499.95,
+            ask: 19500.05,
+            volume: 10,
+            timestamp: now,
+            timestamp_ms: now.timestamp_millis(),
+        }).await.unwrap();
+        
+        let aggregators = multi.aggregators.read().await;
+        for (token, by_timeframe) in aggregators.iter() {
+            assert_eq!(by_timeframe.len(), 2);
+            for aggregator in by_timeframe.values() {
+                let started = aggregator.get_current_bar().await.is_some();
+                assert_eq!(started, token == "42", "token {} routing", token);
+            }
+        }
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_late_tick_within_grace_updates_closing_bar() {
+        use chrono_tz::Asia::Kolkata;
+        
+        let dir = std::env::temp_dir().join(format!("bar_grace_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let store = Arc::new(ConcurrentBarStore::new(
+            "NIFTY".to_string(),
+            "1h".to_string(),
+            dir.join("bars.jsonl"),
+            10,
+        ));
+        let aggregator = BarAggregator::new("NIFTY".to_string(), Timeframe::OneHour, Arc::clone(&store), event_bus)
+            .with_grace_period(120);
+        
+        let tick = |h: u32, m: u32, sec: u32, ltp: f64| {
+            let timestamp = Kolkata.with_ymd_and_hms(2025, 1, 15, h, m, sec).unwrap().with_timezone(&Utc);
+            Tick {
+                symbol: "NIFTY".to_string(),
+                token: "26000".to_string(),
+                ltp,
+                bid: ltp,
+                ask: ltp,
+                volume: 1,
+                timestamp,
+                timestamp_ms: timestamp.timestamp_millis(),
+            }
+        };
+        
+        aggregator.process_tick(&tick(10, 30, 0, 23500.0)).await.unwrap();
+        aggregator.process_tick(&tick(11, 0, 5, 23510.0)).await.unwrap();
+        
+        // Late 10:59:59 tick arrives after the boundary but within grace
+        aggregator.process_tick(&tick(10, 59, 59, 23560.0)).await.unwrap();
+        assert!(store.get_last().await.is_none(), "bar emitted before grace ended");
+        
+        // Grace over: the 10:00 bar is final and includes the late tick
+        aggregator.process_tick(&tick(11, 2, 0, 23520.0)).await.unwrap();
+        let bar = store.get_last().await.unwrap();
+        assert_eq!(bar.timestamp, Kolkata.with_ymd_and_hms(2025, 1, 15, 10, 0, 0).unwrap().with_timezone(&Utc));
+        assert_eq!((bar.high, bar.close, bar.volume), (23560.0, 23560.0, 2));
+        
+        // The new bar never saw the late tick; later stragglers are dropped
+        aggregator.process_tick(&tick(10, 59, 59, 23600.0)).await.unwrap();
+        let current = aggregator.get_current_bar().await.unwrap();
+        assert_eq!((current.open, current.high, current.volume), (23510.0, 23520.0, 2));
+        assert_eq!(store.memory_count().await, 1);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 }
@@ -0,0 +1,116 @@
+/// Cleanup for broker candles
+/// Corrupt rows (zero OHLC, `high < low`) are dropped; a single corrupt bar
+/// between two good ones can be replaced by an interpolated bar instead.
+use chrono::{DateTime, Utc};
+
+use crate::types::Bar;
+
+/// Result of `repair_bars`
+#[derive(Debug, Clone)]
+pub struct RepairedBars {
+    pub bars: Vec<Bar>,
+    /// Invalid bars removed (not counting interpolated replacements)
+    pub dropped: usize,
+    /// Timestamps of bars synthesized by interpolation
+    pub interpolated: Vec<DateTime<Utc>>,
+}
+
+impl RepairedBars {
+    /// Whether `bar` was synthesized rather than reported by the broker
+    pub fn is_repaired(&self, bar: &Bar) -> bool {
+        self.interpolated.contains(&bar.timestamp)
+    }
+}
+
+/// Drop invalid bars; with `interpolate`, an invalid bar whose neighbours are
+/// both valid is replaced by one bridging the previous close to the next open
+pub fn repair_bars(bars: Vec<Bar>, interpolate: bool) -> RepairedBars {
+    let mut repaired = Vec::with_capacity(bars.len());
+    let mut dropped = 0;
+    let mut interpolated = Vec::new();
+    
+    for (idx, bar) in bars.iter().enumerate() {
+        if bar.is_valid() {
+            repaired.push(bar.clone());
+            continue;
+        }
+        
+        let neighbours = idx
+            .checked_sub(1)
+            .and_then(|prev| bars.get(prev))
+            .zip(bars.get(idx + 1))
+            .filter(|(prev, next)| prev.is_valid() && next.is_valid());
+        
+        match neighbours {
+            Some((prev, next)) if interpolate => {
+                let open = prev.close;
+                let close = next.open;
+                repaired.push(Bar {
+                    timestamp: bar.timestamp,
+                    timestamp_ms: bar.timestamp_ms,
+                    open,
+                    high: open.max(close),
+                    low: open.min(close),
+                    close,
+                    volume: 0,
+                    bar_complete: bar.bar_complete,
+                });
+                interpolated.push(bar.timestamp);
+            }
+            _ => dropped += 1,
+        }
+    }
+    
+    RepairedBars { bars: repaired, dropped, interpolated }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    
+    fn bar(minute: u32, open: f64, high: f64, low: f64, close: f64) -> Bar {
+        let timestamp = Utc.with_ymd_and_hms(2025, 1, 15, 4, minute, 0).unwrap();
+        Bar {
+            timestamp,
+            timestamp_ms: timestamp.timestamp_millis(),
+            open,
+            high,
+            low,
+            close,
+            volume: 100,
+            bar_complete: true,
+        }
+    }
+    
+    #[test]
+    fn test_corrupt_bars_dropped_or_interpolated() {
+        let bars = vec![
+            bar(0, 100.0, 105.0, 99.0, 104.0),
+            bar(15, 104.0, 101.0, 106.0, 103.0), // high < low
+            bar(30, 102.0, 108.0, 101.0, 107.0),
+            bar(45, 0.0, 0.0, 0.0, 0.0),         // zero row, last bar
+        ];
+        assert!(bars[0].is_valid());
+        assert!(!bars[1].is_valid());
+        assert!(!bars[3].is_valid());
+        
+        // Drop only
+        let cleaned = repair_bars(bars.clone(), false);
+        assert_eq!(cleaned.bars.len(), 2);
+        assert_eq!(cleaned.dropped, 2);
+        assert!(cleaned.interpolated.is_empty());
+        
+        // Single gap between good bars is bridged; the trailing zero row has no next bar
+        let repaired = repair_bars(bars, true);
+        assert_eq!(repaired.bars.len(), 3);
+        assert_eq!(repaired.dropped, 1);
+        
+        let filled = &repaired.bars[1];
+        assert!(repaired.is_repaired(filled));
+        assert!(!repaired.is_repaired(&repaired.bars[0]));
+        assert_eq!((filled.open, filled.high, filled.low, filled.close), (104.0, 104.0, 102.0, 102.0));
+        assert_eq!(filled.volume, 0);
+        assert!(filled.is_valid());
+    }
+}
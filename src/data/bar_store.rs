@@ -1,17 +1,41 @@
 /// Hybrid Bar Storage - Ring Buffer (memory) + JSONL (disk)
-/// Optimized for O(1) append and fast recent reads
-use std::collections::VecDeque;
+/// Optimized for O(1) append and fast recent reads; older bars stay on disk
+/// and are paged in by segment for `range` queries (e.g. months of backtest data)
+use std::collections::{HashMap, VecDeque};
+use std::io::SeekFrom;
 use std::path::PathBuf;
 use std::sync::Arc;
+use chrono::{DateTime, Utc};
 use tokio::sync::RwLock;
 use tokio::fs::OpenOptions;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, AsyncWriteExt, BufReader};
 use tracing::debug;
 
 use crate::types::Bar;
 use crate::error::Result;
 
-/// Hybrid bar storage combining in-memory ring buffer and disk persistence
+/// Bars per cold segment
+const SEGMENT_BARS: usize = 500;
+
+/// Cold segments kept decoded after a `range` query
+const CACHED_SEGMENTS: usize = 8;
+
+/// A run of consecutive bars in the disk file: its byte span plus time
+/// bounds, so `range` can skip segments without reading them
+#[derive(Debug, Clone)]
+struct ColdSegment {
+    offset: u64,
+    len: u64,
+    /// Position of the segment's first bar in the whole series
+    first_index: usize,
+    count: usize,
+    min_ms: i64,
+    max_ms: i64,
+}
+
+/// Bar storage: a hot in-memory window of the latest bars plus the full
+/// series on disk with a segment index for on-demand paging.
+/// Single-owner; share it through `ConcurrentBarStore`.
 pub struct HybridBarStore {
     /// Hot path: in-memory ring buffer (last N bars)
     memory_buffer: VecDeque<Bar>,
@@ -19,6 +43,12 @@ pub struct HybridBarStore {
     
     /// Cold path: disk storage (JSONL format)
     disk_file: PathBuf,
+    disk_len: u64,
+    segments: Vec<ColdSegment>,
+    segment_bars: usize,
+    /// Decoded cold segments by index, evicted oldest-first
+    segment_cache: HashMap<usize, Vec<Bar>>,
+    cache_order: VecDeque<usize>,
     
     /// Metadata
     total_bars: usize,
@@ -28,20 +58,35 @@ pub struct HybridBarStore {
 
 impl HybridBarStore {
     pub fn new(symbol: String, timeframe: String, disk_file: PathBuf, memory_capacity: usize) -> Self {
+        // Appends go after whatever is already on disk
+        let disk_len = std::fs::metadata(&disk_file).map(|m| m.len()).unwrap_or(0);
         HybridBarStore {
             memory_buffer: VecDeque::with_capacity(memory_capacity),
             memory_capacity,
             disk_file,
+            disk_len,
+            segments: Vec::new(),
+            segment_bars: SEGMENT_BARS,
+            segment_cache: HashMap::new(),
+            cache_order: VecDeque::new(),
             total_bars: 0,
             symbol,
             timeframe,
         }
     }
     
+    /// Bars per cold segment (smaller pages in less per `range` miss)
+    pub fn with_segment_bars(mut self, segment_bars: usize) -> Self {
+        self.segment_bars = segment_bars.max(1);
+        self
+    }
+    
     /// Append a new bar (O(1) operation)
     pub async fn append(&mut self, bar: Bar) -> Result<()> {
         // Write to disk immediately for durability
-        self.append_to_disk(&bar).await?;
+        let written = self.append_to_disk(&bar).await?;
+        self.index_bar(self.disk_len, written, &bar);
+        self.disk_len += written;
         
         // Add to memory buffer
         if self.memory_buffer.len() >= self.memory_capacity {
@@ -79,6 +124,17 @@ impl HybridBarStore {
         self.load_from_disk_and_memory(n).await
     }
     
+    /// Get recent N closed bars, skipping an in-progress (`bar_complete=false`) bar
+    pub async fn get_recent_complete(&self, n: usize) -> Result<Vec<Bar>> {
+        let mut bars: Vec<Bar> = self.get_recent(n + 1).await?
+            .into_iter()
+            .filter(|b| b.bar_complete)
+            .collect();
+        let excess = bars.len().saturating_sub(n);
+        bars.drain(..excess);
+        Ok(bars)
+    }
+    
     /// Get the last bar (most recent)
     pub fn get_last(&self) -> Option<&Bar> {
         self.memory_buffer.back()
@@ -99,19 +155,113 @@ impl HybridBarStore {
         self.memory_buffer.len()
     }
     
-    /// Append bar to disk (JSONL format)
-    async fn append_to_disk(&self, bar: &Bar) -> Result<()> {
+    /// Number of cold segments currently decoded in the cache
+    pub fn cached_segment_count(&self) -> usize {
+        self.segment_cache.len()
+    }
+    
+    /// Bars with `from <= timestamp <= to`, oldest first. The hot window is
+    /// served from memory; older bars come from the cold segments overlapping
+    /// the range, paged in from disk on first use and cached.
+    pub async fn range(&mut self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<Bar>> {
+        let (from_ms, to_ms) = (from.timestamp_millis(), to.timestamp_millis());
+        let in_range = |bar: &Bar| bar.timestamp_ms >= from_ms && bar.timestamp_ms <= to_ms;
+        
+        // Series positions from here on are in memory (and also on disk)
+        let hot_start = self.total_bars.saturating_sub(self.memory_buffer.len());
+        
+        let wanted: Vec<usize> = self.segments.iter()
+            .enumerate()
+            .filter(|(_, seg)| seg.first_index < hot_start && seg.min_ms <= to_ms && seg.max_ms >= from_ms)
+            .map(|(i, _)| i)
+            .collect();
+        
+        let mut bars = Vec::new();
+        for i in wanted {
+            let first_index = self.segments[i].first_index;
+            let segment = self.page_in(i).await?;
+            bars.extend(segment.iter()
+                .enumerate()
+                .filter(|(j, bar)| first_index + j < hot_start && in_range(bar))
+                .map(|(_, bar)| bar.clone()));
+        }
+        
+        bars.extend(self.memory_buffer.iter().filter(|bar| in_range(bar)).cloned());
+        Ok(bars)
+    }
+    
+    /// Decoded bars of cold segment `i`, reading it from disk on a cache miss
+    async fn page_in(&mut self, i: usize) -> Result<&Vec<Bar>> {
+        if !self.segment_cache.contains_key(&i) {
+            let segment = &self.segments[i];
+            let mut file = tokio::fs::File::open(&self.disk_file).await?;
+            file.seek(SeekFrom::Start(segment.offset)).await?;
+            let mut buf = vec![0u8; segment.len as usize];
+            file.read_exact(&mut buf).await?;
+            
+            let bars: Vec<Bar> = String::from_utf8_lossy(&buf)
+                .lines()
+                .filter_map(|line| serde_json::from_str::<Bar>(line).ok())
+                .collect();
+            
+            debug!("Paged in segment {} of {} {} ({} bars)", i, self.symbol, self.timeframe, bars.len());
+            
+            if self.cache_order.len() >= CACHED_SEGMENTS {
+                if let Some(evicted) = self.cache_order.pop_front() {
+                    self.segment_cache.remove(&evicted);
+                }
+            }
+            self.cache_order.push_back(i);
+            self.segment_cache.insert(i, bars);
+        }
+        Ok(&self.segment_cache[&i])
+    }
+    
+    /// Add a bar written at `offset` (`len` bytes) to the segment index
+    fn index_bar(&mut self, offset: u64, len: u64, bar: &Bar) {
+        let next_index = self.segments.last().map_or(0, |seg| seg.first_index + seg.count);
+        match self.segments.last_mut() {
+            Some(seg) if seg.count < self.segment_bars => {
+                seg.len = offset + len - seg.offset;
+                seg.count += 1;
+                seg.min_ms = seg.min_ms.min(bar.timestamp_ms);
+                seg.max_ms = seg.max_ms.max(bar.timestamp_ms);
+                // A cached copy of a growing segment is stale
+                let last = self.segments.len() - 1;
+                self.segment_cache.remove(&last);
+                self.cache_order.retain(|&cached| cached != last);
+            }
+            _ => self.segments.push(ColdSegment {
+                offset,
+                len,
+                first_index: next_index,
+                count: 1,
+                min_ms: bar.timestamp_ms,
+                max_ms: bar.timestamp_ms,
+            }),
+        }
+    }
+    
+    /// Forget the segment index and cache (file replaced or re-read)
+    fn reset_index(&mut self) {
+        self.segments.clear();
+        self.segment_cache.clear();
+        self.cache_order.clear();
+    }
+    
+    /// Append bar to disk (JSONL format); returns the bytes written
+    async fn append_to_disk(&self, bar: &Bar) -> Result<u64> {
         let mut file = OpenOptions::new()
             .create(true)
             .append(true)
             .open(&self.disk_file)
             .await?;
         
-        let json_line = serde_json::to_string(bar)?;
-        file.write_all(format!("{}\n", json_line).as_bytes()).await?;
+        let json_line = format!("{}\n", serde_json::to_string(bar)?);
+        file.write_all(json_line.as_bytes()).await?;
         file.sync_all().await?;
         
-        Ok(())
+        Ok(json_line.len() as u64)
     }
     
     /// Load bars from disk and combine with memory
@@ -161,15 +311,26 @@ impl HybridBarStore {
         }
         
         let file = tokio::fs::File::open(&self.disk_file).await?;
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
+        let mut reader = BufReader::new(file);
         
+        // Rebuild the segment index while reading
+        self.reset_index();
         let mut all_bars = Vec::new();
-        while let Some(line) = lines.next_line().await? {
+        let mut offset = 0u64;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            let read = reader.read_line(&mut line).await? as u64;
+            if read == 0 {
+                break;
+            }
             if let Ok(bar) = serde_json::from_str::<Bar>(&line) {
+                self.index_bar(offset, read, &bar);
                 all_bars.push(bar);
             }
+            offset += read;
         }
+        self.disk_len = offset;
         
         self.total_bars = all_bars.len();
         
@@ -214,10 +375,15 @@ impl HybridBarStore {
         // Set new file
         self.disk_file = new_file;
         self.total_bars = self.memory_buffer.len();
+        self.disk_len = 0;
+        self.reset_index();
         
         // Rewrite memory buffer to new file
-        for bar in &self.memory_buffer {
-            self.append_to_disk(bar).await?;
+        let bars: Vec<Bar> = self.memory_buffer.iter().cloned().collect();
+        for bar in &bars {
+            let written = self.append_to_disk(bar).await?;
+            self.index_bar(self.disk_len, written, bar);
+            self.disk_len += written;
         }
         
         debug!("Rotated disk file for {} {}", self.symbol, self.timeframe);
@@ -252,6 +418,11 @@ impl ConcurrentBarStore {
         store.get_recent(n).await
     }
     
+    pub async fn get_recent_complete(&self, n: usize) -> Result<Vec<Bar>> {
+        let store = self.store.read().await;
+        store.get_recent_complete(n).await
+    }
+    
     pub async fn get_last(&self) -> Option<Bar> {
         let store = self.store.read().await;
         store.get_last().cloned()
@@ -262,6 +433,12 @@ impl ConcurrentBarStore {
         store.get_all_in_memory()
     }
     
+    /// Bars in `[from, to]`; takes the write lock since cold segments are cached
+    pub async fn range(&self, from: DateTime<Utc>, to: DateTime<Utc>) -> Result<Vec<Bar>> {
+        let mut store = self.store.write().await;
+        store.range(from, to).await
+    }
+    
     pub async fn load_from_disk(&self, load_last_n: usize) -> Result<()> {
         let mut store = self.store.write().await;
         store.load_from_disk(load_last_n).await
@@ -286,7 +463,6 @@ impl ConcurrentBarStore {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use chrono::Utc;
     
     #[tokio::test]
     async fn test_hybrid_bar_store() {
@@ -328,4 +504,75 @@ mod tests {
         // Cleanup
         let _ = std::fs::remove_file(temp_file);
     }
+    
+    #[tokio::test]
+    async fn test_recent_complete_skips_forming_bar() {
+        let temp_file = std::env::temp_dir().join(format!("bars_{}.jsonl", uuid::Uuid::new_v4()));
+        let store = ConcurrentBarStore::new("NIFTY".to_string(), "1h".to_string(), temp_file.clone(), 10);
+        
+        for i in 0..6 {
+            store.append(Bar {
+                timestamp: Utc::now(),
+                timestamp_ms: i,
+                open: 19000.0,
+                high: 19100.0,
+                low: 18900.0,
+                close: 19000.0 + i as f64,
+                volume: 1000,
+                bar_complete: i < 5,
+            }).await.unwrap();
+        }
+        
+        let recent = store.get_recent(3).await.unwrap();
+        assert!(!recent.last().unwrap().bar_complete);
+        
+        let closed = store.get_recent_complete(3).await.unwrap();
+        let closes: Vec<f64> = closed.iter().map(|b| b.close).collect();
+        assert_eq!(closes, vec![19002.0, 19003.0, 19004.0]);
+        
+        let _ = std::fs::remove_file(&temp_file);
+    }
+    
+    #[tokio::test]
+    async fn test_range_spans_cold_and_hot_segments() {
+        use chrono::TimeZone;
+        
+        let temp_file = std::env::temp_dir().join(format!("bars_{}.jsonl", uuid::Uuid::new_v4()));
+        let mut store = HybridBarStore::new("NIFTY".to_string(), "1h".to_string(), temp_file.clone(), 10)
+            .with_segment_bars(10);
+        
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 4, 0, 0).unwrap();
+        let at = |i: i64| start + chrono::Duration::hours(i);
+        for i in 0..50 {
+            store.append(Bar {
+                timestamp: at(i),
+                timestamp_ms: at(i).timestamp_millis(),
+                open: 19000.0,
+                high: 19100.0,
+                low: 18900.0,
+                close: 19000.0 + i as f64,
+                volume: 1000,
+                bar_complete: true,
+            }).await.unwrap();
+        }
+        
+        // Bars 0-39 are cold, 40-49 hot; 25..=44 pages in two cold segments only
+        let bars = store.range(at(25), at(44)).await.unwrap();
+        let closes: Vec<f64> = bars.iter().map(|b| b.close - 19000.0).collect();
+        assert_eq!(closes, (25..=44).map(|i| i as f64).collect::<Vec<_>>());
+        assert_eq!(store.cached_segment_count(), 2);
+        
+        // Cached segments serve the repeat; a hot-only query reads no disk
+        assert_eq!(store.range(at(25), at(44)).await.unwrap().len(), 20);
+        assert_eq!(store.range(at(45), at(60)).await.unwrap().len(), 5);
+        assert_eq!(store.cached_segment_count(), 2);
+        
+        // The index survives a reload from disk
+        let mut reloaded = HybridBarStore::new("NIFTY".to_string(), "1h".to_string(), temp_file.clone(), 10)
+            .with_segment_bars(10);
+        reloaded.load_from_disk(10).await.unwrap();
+        assert_eq!(reloaded.range(at(0), at(49)).await.unwrap().len(), 50);
+        
+        let _ = std::fs::remove_file(&temp_file);
+    }
 }
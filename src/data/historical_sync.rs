@@ -1,25 +1,136 @@
 /// Complete historical data synchronization module
 /// Downloads data for underlying + relevant option strikes
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use chrono_tz::Asia::Kolkata;
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
+use tokio::sync::RwLock;
 use tracing::{error, info, warn};
 
 use crate::broker::{AngelOneClient, InstrumentCache};
-use crate::data::ConcurrentBarStore;
-use crate::error::Result;
-use crate::types::Instrument;
+use crate::data::{ConcurrentBarStore, Timeframe};
+use crate::error::{Result, TradingError};
+use crate::time::holidays::is_trading_day;
+use crate::types::{Bar, Instrument};
 use crate::Config;
 
+/// Calendar days of daily bars checked for gaps after a sync
+const COVERAGE_WINDOW_DAYS: i64 = 90;
+
+/// Option bars kept in memory per store
+const OPTION_STORE_CAPACITY: usize = 1000;
+
+/// Store key for an option's bars, matching `MultiAssetHistoricalSync`:
+/// the bare symbol for daily bars, `<symbol>_hourly` for hourly
+pub fn option_store_key(symbol: &str, timeframe: Timeframe) -> String {
+    match timeframe {
+        Timeframe::OneDay => symbol.to_string(),
+        Timeframe::OneHour => format!("{}_hourly", symbol),
+        other => format!("{}_{}", symbol, other.as_str()),
+    }
+}
+
+/// Incremental fetch window for `timeframe` bars: from the bar after
+/// `last_stored` (or `initial` back when nothing is stored) up to `now`.
+/// None while that next bar cannot have closed yet.
+pub fn incremental_range(
+    last_stored: Option<DateTime<Utc>>,
+    timeframe: Timeframe,
+    now: DateTime<Utc>,
+    initial: Duration,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let bar_len = Duration::minutes(timeframe.duration_minutes());
+    let from = match last_stored {
+        Some(last) => last + bar_len,
+        None => now - initial,
+    };
+    (from + bar_len <= now).then_some((from, now))
+}
+
+/// Fetched bars worth appending: newer than `last_stored` and already closed
+pub fn new_closed_bars(
+    bars: Vec<Bar>,
+    last_stored: Option<DateTime<Utc>>,
+    timeframe: Timeframe,
+    now: DateTime<Utc>,
+) -> Vec<Bar> {
+    let bar_len = Duration::minutes(timeframe.duration_minutes());
+    bars.into_iter()
+        .filter(|bar| last_stored.is_none_or(|last| bar.timestamp > last) && bar.timestamp + bar_len <= now)
+        .collect()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SyncReport {
     pub timestamp: DateTime<Utc>,
     pub symbol: String,
     pub underlying_bars_downloaded: usize,
     pub option_strikes_synced: usize,
+    /// Option-strike step skipped (`sync_option_strikes = false`)
+    #[serde(default)]
+    pub option_sync_skipped: bool,
+    /// Daily + hourly option bars (not counted in the underlying's bar counts)
+    #[serde(default)]
+    pub option_bars_downloaded: usize,
     pub daily_bars_downloaded: usize,
     pub hourly_bars_downloaded: usize,
     pub errors: Vec<String>,
+    /// Daily-bar coverage of recent trading days
+    #[serde(default)]
+    pub coverage: Option<CoverageReport>,
+}
+
+/// Trading days expected vs daily bars present over a date range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoverageReport {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub expected_days: usize,
+    pub present_days: usize,
+    pub missing_dates: Vec<NaiveDate>,
+}
+
+impl CoverageReport {
+    /// Present trading days as a percentage of expected (100 when none expected)
+    pub fn coverage_pct(&self) -> f64 {
+        if self.expected_days == 0 {
+            return 100.0;
+        }
+        self.present_days as f64 / self.expected_days as f64 * 100.0
+    }
+}
+
+/// Compare daily bars in `store` (by IST date) with the trading days in `from..=to`
+pub async fn verify_coverage(store: &ConcurrentBarStore, from: NaiveDate, to: NaiveDate) -> CoverageReport {
+    let bar_dates: HashSet<NaiveDate> = store
+        .get_all_in_memory()
+        .await
+        .iter()
+        .map(|b| b.timestamp.with_timezone(&Kolkata).date_naive())
+        .collect();
+
+    let mut expected_days = 0;
+    let mut missing_dates = Vec::new();
+    let mut date = from;
+    while date <= to {
+        if is_trading_day(date) {
+            expected_days += 1;
+            if !bar_dates.contains(&date) {
+                missing_dates.push(date);
+            }
+        }
+        date += Duration::days(1);
+    }
+
+    CoverageReport {
+        from,
+        to,
+        expected_days,
+        present_days: expected_days - missing_dates.len(),
+        missing_dates,
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,11 +141,61 @@ pub struct DataQualityMetrics {
     pub last_sync: Option<DateTime<Utc>>,
 }
 
+/// One candle request needed to patch an outage
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BackfillRequest {
+    pub timeframe: Timeframe,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+}
+
+/// Candle requests covering the completed bars of an outage, starting at
+/// the bar the outage began in and split to the broker's range limit
+pub fn backfill_requests(
+    outage_start: DateTime<Utc>,
+    outage_end: DateTime<Utc>,
+    timeframe: Timeframe,
+) -> Vec<BackfillRequest> {
+    let mut requests = Vec::new();
+    let max_span = Duration::days(timeframe.max_fetch_days());
+    
+    // The bar in progress at reconnect is still built from live ticks
+    let end = timeframe.get_bar_boundary(outage_end);
+    let mut from = timeframe.get_bar_boundary(outage_start);
+    while from < end {
+        let to = (from + max_span).min(end);
+        requests.push(BackfillRequest { timeframe, from, to });
+        from = to;
+    }
+    
+    requests
+}
+
+/// Candle requests covering the last `lookback_days` up to `now`, split to
+/// the broker's range limit for `timeframe`
+pub fn sync_requests(timeframe: Timeframe, lookback_days: i64, now: DateTime<Utc>) -> Vec<BackfillRequest> {
+    let mut requests = Vec::new();
+    let max_span = Duration::days(timeframe.max_fetch_days());
+    
+    let mut from = now - Duration::days(lookback_days);
+    while from < now {
+        let to = (from + max_span).min(now);
+        requests.push(BackfillRequest { timeframe, from, to });
+        from = to;
+    }
+    
+    requests
+}
+
 pub struct HistoricalDataSync {
     broker: Arc<AngelOneClient>,
     instrument_cache: Arc<InstrumentCache>,
     daily_store: Arc<ConcurrentBarStore>,
     hourly_store: Arc<ConcurrentBarStore>,
+    /// Stores for timeframes other than daily/hourly
+    extra_stores: HashMap<Timeframe, Arc<ConcurrentBarStore>>,
+    /// Per-option stores keyed by `option_store_key`, created on first sync
+    option_stores: RwLock<HashMap<String, Arc<ConcurrentBarStore>>>,
     config: Arc<Config>,
     data_dir: String,
 }
@@ -52,33 +213,107 @@ impl HistoricalDataSync {
             instrument_cache,
             daily_store,
             hourly_store,
+            extra_stores: HashMap::new(),
+            option_stores: RwLock::new(HashMap::new()),
             config,
             data_dir: "data/bars".to_string(),
         }
     }
 
-    /// Complete sync: underlying only (options selected based on daily bias later)
-    /// We only need underlying hourly bars for analysis
-    /// Options are selected dynamically based on daily bias (CE/PE) and ATM strike
+    /// Directory for sync reports and option bar files (default `data/bars`)
+    pub fn with_data_dir(mut self, data_dir: impl Into<String>) -> Self {
+        self.data_dir = data_dir.into();
+        self
+    }
+
+    /// Dedicated store for another timeframe (e.g. 15m)
+    pub fn with_store(mut self, timeframe: Timeframe, store: Arc<ConcurrentBarStore>) -> Self {
+        self.extra_stores.insert(timeframe, store);
+        self
+    }
+
+    /// Store that bars of `timeframe` are synced into
+    fn store_for(&self, timeframe: Timeframe) -> Option<&Arc<ConcurrentBarStore>> {
+        match timeframe {
+            Timeframe::OneDay => Some(&self.daily_store),
+            Timeframe::OneHour => Some(&self.hourly_store),
+            _ => self.extra_stores.get(&timeframe),
+        }
+    }
+
+    /// Option store registered under `key` (see `option_store_key`)
+    pub async fn get_option_store(&self, key: &str) -> Option<Arc<ConcurrentBarStore>> {
+        self.option_stores.read().await.get(key).cloned()
+    }
+
+    /// Store for `symbol` bars of `timeframe`, persisted to
+    /// `<data_dir>/<key>.jsonl`; registered (and its file reloaded) on first use
+    pub async fn option_store(&self, symbol: &str, timeframe: Timeframe) -> Arc<ConcurrentBarStore> {
+        let key = option_store_key(symbol, timeframe);
+        let mut stores = self.option_stores.write().await;
+        if let Some(store) = stores.get(&key) {
+            return Arc::clone(store);
+        }
+
+        let store = Arc::new(ConcurrentBarStore::new(
+            symbol.to_string(),
+            timeframe.as_str().to_string(),
+            PathBuf::from(&self.data_dir).join(format!("{}.jsonl", key)),
+            OPTION_STORE_CAPACITY,
+        ));
+        if let Err(e) = store.load_from_disk(OPTION_STORE_CAPACITY).await {
+            warn!("Failed to reload {} bars: {}", key, e);
+        }
+        stores.insert(key, Arc::clone(&store));
+        store
+    }
+
+    /// Append downloaded option bars to that option's store
+    /// Only bars newer than the store's last bar are appended
+    /// Returns the number of bars stored
+    pub async fn store_option_bars(&self, symbol: &str, timeframe: Timeframe, bars: Vec<Bar>) -> Result<usize> {
+        if bars.is_empty() {
+            return Ok(0);
+        }
+        tokio::fs::create_dir_all(&self.data_dir).await?;
+
+        let store = self.option_store(symbol, timeframe).await;
+        let mut last_time = store.get_last().await.map(|bar| bar.timestamp);
+        let mut stored = 0;
+        for bar in bars {
+            if last_time.is_some_and(|last| bar.timestamp <= last) {
+                continue;
+            }
+            last_time = Some(bar.timestamp);
+            store.append(bar).await?;
+            stored += 1;
+        }
+        Ok(stored)
+    }
+
+    /// Complete sync: underlying daily + hourly bars, which is all the hourly
+    /// analysis needs (options are selected later from the daily bias and ATM).
+    /// With `sync_option_strikes` on, strikes around ATM are downloaded too.
     pub async fn sync_historical_data(&self, underlying_token: &str, underlying: &str) -> Result<SyncReport> {
         info!("📊 Starting historical data sync for {}", underlying);
-        info!("   Downloading: underlying only (options selected based on daily bias)");
 
         let mut report = SyncReport {
             timestamp: Utc::now(),
             symbol: underlying.to_string(),
             underlying_bars_downloaded: 0,
             option_strikes_synced: 0,
+            option_sync_skipped: false,
+            option_bars_downloaded: 0,
             daily_bars_downloaded: 0,
             hourly_bars_downloaded: 0,
             errors: Vec::new(),
+            coverage: None,
         };
 
         // Create data directory
         tokio::fs::create_dir_all(&self.data_dir).await.ok();
 
-        // Sync underlying only - this is what we need for hourly analysis
-        // Options will be selected dynamically based on daily bias (CE/PE) and ATM strike
+        // Underlying first - this is what hourly analysis needs
         info!("📥 Downloading underlying {} data...", underlying);
         match self.sync_underlying_data(underlying_token, underlying).await {
             Ok((daily, hourly)) => {
@@ -94,8 +329,24 @@ impl HistoricalDataSync {
             }
         }
         
-        info!("✅ Historical sync complete - only underlying data needed for analysis");
-        info!("   Options will be selected dynamically based on daily bias (CE/PE)");
+        self.sync_option_strikes(underlying, &mut report).await;
+
+        // Check recent daily bars for holes (today's bar may not exist yet)
+        let yesterday = Utc::now().with_timezone(&Kolkata).date_naive() - Duration::days(1);
+        let coverage = verify_coverage(
+            &self.daily_store,
+            yesterday - Duration::days(COVERAGE_WINDOW_DAYS),
+            yesterday,
+        ).await;
+        if !coverage.missing_dates.is_empty() {
+            warn!(
+                "⚠️  Daily coverage {:.1}% - missing {} trading day(s): {:?}",
+                coverage.coverage_pct(),
+                coverage.missing_dates.len(),
+                coverage.missing_dates
+            );
+        }
+        report.coverage = Some(coverage);
 
         // Save report
         self.save_sync_report(&report).await.ok();
@@ -104,6 +355,11 @@ impl HistoricalDataSync {
         info!("   Underlying bars: {} (daily + hourly)", report.underlying_bars_downloaded);
         info!("   Daily bars: {}", report.daily_bars_downloaded);
         info!("   Hourly bars: {}", report.hourly_bars_downloaded);
+        if report.option_sync_skipped {
+            info!("   Option strikes: skipped (sync_option_strikes = false)");
+        } else {
+            info!("   Option strikes: {} ({} bars)", report.option_strikes_synced, report.option_bars_downloaded);
+        }
         if !report.errors.is_empty() {
             warn!("   Errors encountered: {}", report.errors.len());
         }
@@ -111,29 +367,108 @@ impl HistoricalDataSync {
         Ok(report)
     }
 
-    /// Sync underlying index data (NIFTY)
+    /// Sync underlying index data (NIFTY): 365 days daily, 30 days hourly
     async fn sync_underlying_data(&self, token: &str, symbol: &str) -> Result<(usize, usize)> {
-        let to_date = Utc::now();
-        
-        // Download daily bars (last 365 days)
-        let from_daily = to_date - Duration::days(365);
-        let daily_bars = self.broker.get_candles(token, "ONE_DAY", from_daily, to_date).await?;
-        let daily_count = daily_bars.len();
+        let daily_count = self.sync_timeframe(token, symbol, Timeframe::OneDay, 365).await?;
+        let hourly_count = self.sync_timeframe(token, symbol, Timeframe::OneHour, 30).await?;
+
+        Ok((daily_count, hourly_count))
+    }
+
+    /// Download the last `lookback_days` of `timeframe` bars into its store
+    /// Returns the number of bars downloaded
+    pub async fn sync_timeframe(
+        &self,
+        token: &str,
+        symbol: &str,
+        timeframe: Timeframe,
+        lookback_days: i64,
+    ) -> Result<usize> {
+        let store = self.store_for(timeframe).ok_or_else(|| {
+            TradingError::ConfigError(format!("No bar store for {} - add one with with_store", timeframe.as_str()))
+        })?;
         
-        for bar in daily_bars {
-            self.daily_store.append(bar).await.ok();
+        let mut downloaded = 0;
+        for request in sync_requests(timeframe, lookback_days, Utc::now()) {
+            let bars = self.broker
+                .get_candles(token, timeframe.api_interval(), request.from, request.to)
+                .await?;
+            downloaded += bars.len();
+            
+            for bar in bars {
+                store.append(bar).await.ok();
+            }
         }
+        
+        info!("📥 Synced {} {} bars for {} ({} days)", downloaded, timeframe.as_str(), symbol, lookback_days);
+        Ok(downloaded)
+    }
 
-        // Download hourly bars (last 30 days)
-        let from_hourly = to_date - Duration::days(30);
-        let hourly_bars = self.broker.get_candles(token, "ONE_HOUR", from_hourly, to_date).await?;
-        let hourly_count = hourly_bars.len();
+    /// Backfill bars missed during a feed outage
+    /// Only bars newer than the store's last bar are appended
+    pub async fn backfill_outage(
+        &self,
+        token: &str,
+        timeframe: Timeframe,
+        outage_start: DateTime<Utc>,
+        outage_end: DateTime<Utc>,
+    ) -> Result<usize> {
+        let Some(store) = self.store_for(timeframe) else {
+            warn!("No bar store for {} - skipping backfill", timeframe.as_str());
+            return Ok(0);
+        };
+        
+        let mut last_time = store.get_last().await.map(|bar| bar.timestamp);
+        let mut recovered = 0;
+        
+        for request in backfill_requests(outage_start, outage_end, timeframe) {
+            let bars = self.broker
+                .get_candles(token, timeframe.api_interval(), request.from, request.to)
+                .await?;
+            
+            for bar in bars {
+                if last_time.is_some_and(|last| bar.timestamp <= last) {
+                    continue;
+                }
+                last_time = Some(bar.timestamp);
+                store.append(bar).await?;
+                recovered += 1;
+            }
+        }
         
-        for bar in hourly_bars {
-            self.hourly_store.append(bar).await.ok();
+        info!("🩹 Backfilled {} {} bars for {}", recovered, timeframe.as_str(), token);
+        Ok(recovered)
+    }
+
+    /// Download bars for the strikes around ATM into their option stores,
+    /// unless `sync_option_strikes` is off (index-only strategies)
+    async fn sync_option_strikes(&self, underlying: &str, report: &mut SyncReport) {
+        if !self.config.sync_option_strikes {
+            info!("⏭️  Skipping option strike sync for {} (sync_option_strikes = false)", underlying);
+            report.option_sync_skipped = true;
+            return;
         }
 
-        Ok((daily_count, hourly_count))
+        let strikes = match self.identify_relevant_strikes(underlying).await {
+            Ok(strikes) => strikes,
+            Err(e) => {
+                let err_msg = format!("Failed to select option strikes: {}", e);
+                error!("❌ {}", err_msg);
+                report.errors.push(err_msg);
+                return;
+            }
+        };
+
+        for instrument in &strikes {
+            match self.sync_option_data(instrument).await {
+                Ok((daily, hourly)) => {
+                    report.option_strikes_synced += 1;
+                    report.option_bars_downloaded += daily + hourly;
+                }
+                Err(e) => report.errors.push(format!("Failed to sync {}: {}", instrument.symbol, e)),
+            }
+        }
+        info!("✅ Synced {} option strikes for {}", report.option_strikes_synced, underlying);
     }
 
     /// Identify relevant option strikes to download data for
@@ -249,32 +584,28 @@ impl HistoricalDataSync {
         }
     }
 
-    /// Sync option strike data
+    /// Sync option strike data: daily and hourly bars after the last stored
+    /// one (30 / 7 days back for a new option). Returns the bars stored.
     async fn sync_option_data(&self, instrument: &Instrument) -> Result<(usize, usize)> {
-        let to_date = Utc::now();
-        
         // For options, we typically need less history (they expire weekly/monthly)
-        // Download last 30 days of daily data
-        let from_daily = to_date - Duration::days(30);
-        let daily_bars = match self.broker.get_candles(&instrument.token, "ONE_DAY", from_daily, to_date).await {
-            Ok(bars) => bars,
-            Err(_) => Vec::new(), // Option might not have existed 30 days ago
-        };
-        let daily_count = daily_bars.len();
+        let daily_count = self.sync_option_timeframe(instrument, Timeframe::OneDay, Duration::days(30)).await?;
+        let hourly_count = self.sync_option_timeframe(instrument, Timeframe::OneHour, Duration::days(7)).await?;
 
-        // Download last 7 days of hourly data (options are short-term)
-        let from_hourly = to_date - Duration::days(7);
-        let hourly_bars = match self.broker.get_candles(&instrument.token, "ONE_HOUR", from_hourly, to_date).await {
-            Ok(bars) => bars,
-            Err(_) => Vec::new(),
-        };
-        let hourly_count = hourly_bars.len();
+        Ok((daily_count, hourly_count))
+    }
 
-        // Note: We're not storing option bars in the main stores
-        // In a complete implementation, you'd want separate stores per option
-        // or a more sophisticated storage system
+    async fn sync_option_timeframe(&self, instrument: &Instrument, timeframe: Timeframe, initial: Duration) -> Result<usize> {
+        let store = self.option_store(&instrument.symbol, timeframe).await;
+        let last_stored = store.get_last().await.map(|bar| bar.timestamp);
+        let Some((from, to)) = incremental_range(last_stored, timeframe, Utc::now(), initial) else {
+            return Ok(0);
+        };
 
-        Ok((daily_count, hourly_count))
+        let bars = self.broker
+            .get_candles(&instrument.token, timeframe.api_interval(), from, to)
+            .await
+            .unwrap_or_default(); // Option might not have existed that far back
+        self.store_option_bars(&instrument.symbol, timeframe, bars).await
     }
 
     /// Save sync report to disk
@@ -303,3 +634,260 @@ impl HistoricalDataSync {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::broker::angel_one::test_client;
+    use chrono::TimeZone;
+    use chrono_tz::Asia::Kolkata;
+
+    fn ist(h: u32, min: u32) -> DateTime<Utc> {
+        Kolkata.with_ymd_and_hms(2024, 11, 20, h, min, 0).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_backfill_covers_disconnect_window() {
+        // Feed dropped at 10:42 and came back at 12:07
+        let requests = backfill_requests(ist(10, 42), ist(12, 7), Timeframe::OneHour);
+
+        assert_eq!(requests, vec![BackfillRequest {
+            timeframe: Timeframe::OneHour,
+            from: ist(10, 0),
+            to: ist(12, 0),
+        }]);
+
+        // Long outages are chunked to the broker's range limit
+        let start = ist(10, 0);
+        // Minute bars: window ends on a bar boundary
+        let end = start + Duration::days(45);
+        let requests = backfill_requests(start, end, Timeframe::OneMinute);
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].to, start + Duration::days(30));
+        assert_eq!(requests[1].from, requests[0].to);
+        assert_eq!(requests[1].to, end);
+
+        // A drop within the current bar leaves nothing to fetch
+        assert!(backfill_requests(ist(11, 5), ist(11, 40), Timeframe::OneHour).is_empty());
+    }
+
+    #[test]
+    fn test_sync_requests_per_timeframe() {
+        let now = ist(15, 30);
+
+        // Daily and hourly lookbacks fit in one request each
+        let daily = sync_requests(Timeframe::OneDay, 365, now);
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].timeframe.api_interval(), "ONE_DAY");
+        assert_eq!((daily[0].from, daily[0].to), (now - Duration::days(365), now));
+
+        let hourly = sync_requests(Timeframe::OneHour, 30, now);
+        assert_eq!(hourly[0].timeframe.api_interval(), "ONE_HOUR");
+        assert_eq!((hourly[0].from, hourly[0].to), (now - Duration::days(30), now));
+
+        // 15m for 60 days
+        let m15 = sync_requests(Timeframe::FifteenMinute, 60, now);
+        assert_eq!(m15.len(), 1);
+        assert_eq!(m15[0].timeframe.api_interval(), "FIFTEEN_MINUTE");
+        assert_eq!(m15[0].from, now - Duration::days(60));
+
+        // Minute bars beyond the 30-day limit are chunked back to back
+        let m1 = sync_requests(Timeframe::OneMinute, 75, now);
+        assert_eq!(m1.len(), 3);
+        assert_eq!(m1[0].to, m1[1].from);
+        assert_eq!(m1[0].to - m1[0].from, Duration::days(30));
+        assert_eq!(m1[2].to, now);
+    }
+
+    #[tokio::test]
+    async fn test_option_bars_stored_per_option() {
+        let dir = std::env::temp_dir().join(format!("option_bars_{}", uuid::Uuid::new_v4()));
+        let broker = Arc::new(test_client());
+        let store = |name: &str| Arc::new(ConcurrentBarStore::new(
+            "NIFTY".to_string(),
+            "1d".to_string(),
+            dir.join(name),
+            10,
+        ));
+        let sync = HistoricalDataSync::new(
+            Arc::clone(&broker),
+            Arc::new(InstrumentCache::new(broker)),
+            store("nifty_daily.jsonl"),
+            store("nifty_hourly.jsonl"),
+            Arc::new(crate::config::loader::test_config()),
+        ).with_data_dir(dir.to_string_lossy());
+
+        let bar = |h: u32, close: f64| Bar {
+            timestamp: ist(h, 15),
+            timestamp_ms: ist(h, 15).timestamp_millis(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            volume: 100,
+            bar_complete: true,
+        };
+
+        let ce = "NIFTY24NOV23500CE";
+        let pe = "NIFTY24NOV23500PE";
+        assert_eq!(sync.store_option_bars(ce, Timeframe::OneHour, vec![bar(9, 120.0), bar(10, 125.0)]).await.unwrap(), 2);
+        assert_eq!(sync.store_option_bars(pe, Timeframe::OneHour, vec![bar(9, 90.0)]).await.unwrap(), 1);
+        assert_eq!(sync.store_option_bars(ce, Timeframe::OneDay, vec![bar(0, 118.0)]).await.unwrap(), 1);
+
+        // A re-download overlapping what is stored appends only the new bar
+        assert_eq!(sync.store_option_bars(ce, Timeframe::OneHour, vec![bar(9, 120.0), bar(10, 125.0), bar(11, 127.0)]).await.unwrap(), 1);
+
+        let ce_hourly = sync.get_option_store(&option_store_key(ce, Timeframe::OneHour)).await.unwrap();
+        let closes: Vec<f64> = ce_hourly.get_all_in_memory().await.iter().map(|b| b.close).collect();
+        assert_eq!(closes, vec![120.0, 125.0, 127.0]);
+        assert_eq!(sync.get_option_store(ce).await.unwrap().get_last().await.unwrap().close, 118.0);
+        assert_eq!(sync.get_option_store(&format!("{}_hourly", pe)).await.unwrap().memory_count().await, 1);
+        assert!(sync.get_option_store(pe).await.is_none());
+
+        // Persisted per option
+        assert!(dir.join(format!("{}_hourly.jsonl", ce)).exists());
+        assert!(dir.join(format!("{}.jsonl", ce)).exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_coverage_reports_missing_trading_days() {
+        let dir = std::env::temp_dir().join(format!("coverage_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = ConcurrentBarStore::new(
+            "NIFTY".to_string(),
+            "1D".to_string(),
+            dir.join("daily.jsonl"),
+            100,
+        );
+
+        // 2025-02-24..2025-03-07: 26 Feb (Mahashivratri) is a holiday
+        let from = NaiveDate::from_ymd_opt(2025, 2, 24).unwrap();
+        let to = NaiveDate::from_ymd_opt(2025, 3, 7).unwrap();
+        let dropped = [
+            NaiveDate::from_ymd_opt(2025, 2, 27).unwrap(),
+            NaiveDate::from_ymd_opt(2025, 3, 4).unwrap(),
+        ];
+
+        let mut date = from;
+        while date <= to {
+            if is_trading_day(date) && !dropped.contains(&date) {
+                let timestamp = Kolkata
+                    .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+                    .unwrap()
+                    .with_timezone(&Utc);
+                store.append(crate::types::Bar {
+                    timestamp,
+                    timestamp_ms: timestamp.timestamp_millis(),
+                    open: 22500.0,
+                    high: 22600.0,
+                    low: 22400.0,
+                    close: 22550.0,
+                    volume: 0,
+                    bar_complete: true,
+                }).await.unwrap();
+            }
+            date += Duration::days(1);
+        }
+
+        let report = verify_coverage(&store, from, to).await;
+        assert_eq!(report.expected_days, 9);
+        assert_eq!(report.present_days, 7);
+        assert_eq!(report.missing_dates, dropped.to_vec());
+        assert!(report.coverage_pct() < 80.0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_incremental_hourly_range() {
+        let hour = Duration::hours(1);
+        let bar = |timestamp: DateTime<Utc>| Bar {
+            timestamp,
+            timestamp_ms: timestamp.timestamp_millis(),
+            open: 23500.0,
+            high: 23550.0,
+            low: 23450.0,
+            close: 23520.0,
+            volume: 0,
+            bar_complete: true,
+        };
+
+        // Last stored 10:15 bar; at 12:20 the 11:15 bar is the first new one
+        let last = Some(ist(10, 15));
+        assert_eq!(
+            incremental_range(last, Timeframe::OneHour, ist(12, 20), hour * 2),
+            Some((ist(11, 15), ist(12, 20)))
+        );
+
+        // 11:15 bar still forming at 12:10: nothing to fetch yet
+        assert_eq!(incremental_range(last, Timeframe::OneHour, ist(12, 10), hour * 2), None);
+
+        // Empty store: fall back to the initial window
+        assert_eq!(
+            incremental_range(None, Timeframe::OneHour, ist(12, 20), hour * 2),
+            Some((ist(10, 20), ist(12, 20)))
+        );
+
+        // Keep only bars after the stored one that have closed
+        let fetched = vec![bar(ist(10, 15)), bar(ist(11, 15)), bar(ist(12, 15))];
+        let kept: Vec<DateTime<Utc>> = new_closed_bars(fetched, last, Timeframe::OneHour, ist(12, 20))
+            .into_iter()
+            .map(|b| b.timestamp)
+            .collect();
+        assert_eq!(kept, vec![ist(11, 15)]);
+    }
+
+    #[tokio::test]
+    async fn test_option_strike_sync_can_be_disabled() {
+        let dir = std::env::temp_dir().join(format!("option_sync_flag_{}", uuid::Uuid::new_v4()));
+        let broker = Arc::new(test_client());
+        let store = |name: &str| Arc::new(ConcurrentBarStore::new(
+            "NIFTY".to_string(),
+            "1d".to_string(),
+            dir.join(name),
+            10,
+        ));
+        let sync_with = |enabled: bool| {
+            let mut config = crate::config::loader::test_config();
+            config.sync_option_strikes = enabled;
+            HistoricalDataSync::new(
+                Arc::clone(&broker),
+                Arc::new(InstrumentCache::new(Arc::clone(&broker))),
+                store("nifty_daily.jsonl"),
+                store("nifty_hourly.jsonl"),
+                Arc::new(config),
+            ).with_data_dir(dir.to_string_lossy())
+        };
+        let empty_report = || SyncReport {
+            timestamp: Utc::now(),
+            symbol: "NIFTY".to_string(),
+            underlying_bars_downloaded: 0,
+            option_strikes_synced: 0,
+            option_sync_skipped: false,
+            option_bars_downloaded: 0,
+            daily_bars_downloaded: 0,
+            hourly_bars_downloaded: 0,
+            errors: Vec::new(),
+            coverage: None,
+        };
+
+        // On by default
+        assert!(crate::config::loader::test_config().sync_option_strikes);
+
+        // Disabled: skipped before any strike selection or fetch
+        let mut report = empty_report();
+        sync_with(false).sync_option_strikes("NIFTY", &mut report).await;
+        assert!(report.option_sync_skipped);
+        assert_eq!(report.option_strikes_synced, 0);
+
+        // Enabled: the step runs, finding no strikes in an empty cache
+        let mut report = empty_report();
+        sync_with(true).sync_option_strikes("NIFTY", &mut report).await;
+        assert!(!report.option_sync_skipped);
+        assert!(report.errors.is_empty());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -3,18 +3,25 @@
 /// Supports futures and individual stock options as well
 
 use chrono::{DateTime, Duration, NaiveDate, Utc};
+use futures_util::future::join_all;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::time::sleep;
+use tokio::sync::Semaphore;
 use tracing::{error, info, warn};
 
 use crate::broker::{AngelOneClient, InstrumentCache, TokenExtractor};
 use crate::data::ConcurrentBarStore;
 use crate::error::Result;
-use crate::types::Instrument;
+use crate::types::{Bar, Instrument};
+use crate::utils::RateLimiter;
 use crate::Config;
 
+/// Bars kept in memory per registered asset store
+const ASSET_STORE_CAPACITY: usize = 10000;
+
 /// Supported underlying assets
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum UnderlyingAsset {
@@ -56,6 +63,13 @@ impl UnderlyingAsset {
         }
     }
 
+    /// Parse an index symbol such as "NIFTY" (case-insensitive)
+    pub fn from_symbol(symbol: &str) -> Option<UnderlyingAsset> {
+        Self::all()
+            .into_iter()
+            .find(|asset| asset.as_str().eq_ignore_ascii_case(symbol))
+    }
+
     pub fn all() -> Vec<UnderlyingAsset> {
         vec![
             UnderlyingAsset::Nifty,
@@ -63,6 +77,25 @@ impl UnderlyingAsset {
             UnderlyingAsset::FinNifty,
         ]
     }
+
+    /// Assets in priority order: listed symbols first, then the rest of `all()`
+    /// Unknown or repeated symbols are skipped
+    pub fn prioritized(order: &[String]) -> Vec<UnderlyingAsset> {
+        let mut assets = Vec::new();
+        for symbol in order {
+            match Self::from_symbol(symbol) {
+                Some(asset) if !assets.contains(&asset) => assets.push(asset),
+                Some(_) => {}
+                None => warn!("Unknown asset '{}' in asset_sync_order - skipped", symbol),
+            }
+        }
+        for asset in Self::all() {
+            if !assets.contains(&asset) {
+                assets.push(asset);
+            }
+        }
+        assets
+    }
 }
 
 /// Instrument filter configuration
@@ -107,6 +140,8 @@ pub struct MultiAssetSyncReport {
     pub total_instruments: usize,
     pub total_bars_downloaded: usize,
     pub total_errors: usize,
+    pub total_empty_responses: usize,
+    pub total_network_errors: usize,
     pub success_rate: f64,
 }
 
@@ -118,12 +153,41 @@ pub struct AssetSyncReport {
     pub underlying_bars: usize,
     pub futures_synced: usize,
     pub options_synced: usize,
+    pub skipped_expired: usize,
+    /// Candle requests that succeeded but returned no bars (valid, e.g. illiquid strike)
+    pub empty_responses: usize,
+    /// Candle requests that failed (network/API errors)
+    pub network_errors: usize,
     pub total_daily_bars: usize,
     pub total_hourly_bars: usize,
     pub strikes_covered: Vec<i32>,
     pub errors: Vec<String>,
 }
 
+/// Outcome counters for derivative candle fetches
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct FetchStats {
+    empty_responses: usize,
+    network_errors: usize,
+}
+
+impl std::ops::AddAssign for FetchStats {
+    fn add_assign(&mut self, other: FetchStats) {
+        self.empty_responses += other.empty_responses;
+        self.network_errors += other.network_errors;
+    }
+}
+
+/// Result of syncing one asset's option chain
+struct OptionsSync {
+    synced: usize,
+    strikes: Vec<i32>,
+    daily_bars: usize,
+    hourly_bars: usize,
+    skipped_expired: usize,
+    fetch_stats: FetchStats,
+}
+
 /// Multi-asset historical data synchronizer
 pub struct MultiAssetHistoricalSync {
     broker: Arc<AngelOneClient>,
@@ -132,6 +196,14 @@ pub struct MultiAssetHistoricalSync {
     config: Arc<Config>,
     data_dir: String,
     filter_config: FilterConfig,
+    /// Daily lookback for underlying spot data
+    history_days: i64,
+    /// Paces historical candle requests (config.rate_limit_historical)
+    rate_limiter: Arc<RateLimiter>,
+    /// Caps concurrent instrument fetches (config.sync_concurrency)
+    fetch_semaphore: Arc<Semaphore>,
+    /// Caps underlyings synced at once (config.asset_sync_concurrency)
+    asset_semaphore: Arc<Semaphore>,
 }
 
 impl MultiAssetHistoricalSync {
@@ -140,32 +212,86 @@ impl MultiAssetHistoricalSync {
         instrument_cache: Arc<InstrumentCache>,
         config: Arc<Config>,
     ) -> Self {
+        let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit_historical.max(1)));
+        let fetch_semaphore = Arc::new(Semaphore::new(config.sync_concurrency.max(1)));
+        let asset_semaphore = Arc::new(Semaphore::new(config.asset_sync_concurrency.max(1)));
+        
         Self {
             broker,
             instrument_cache,
             bar_stores: HashMap::new(),
+            filter_config: FilterConfig {
+                include_options: config.sync_option_strikes,
+                ..FilterConfig::default()
+            },
             config,
             data_dir: "data/bars".to_string(),
-            filter_config: FilterConfig::default(),
+            history_days: 365,
+            rate_limiter,
+            fetch_semaphore,
+            asset_semaphore,
         }
     }
 
+    /// Share a historical-data rate limiter with other components
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
     /// Set custom filter configuration
     pub fn with_filter_config(mut self, filter_config: FilterConfig) -> Self {
         self.filter_config = filter_config;
         self
     }
 
+    /// Set the underlying daily lookback (hourly history is capped at 30 days)
+    pub fn with_history_days(mut self, days: u32) -> Self {
+        self.history_days = days.max(1) as i64;
+        self
+    }
+
+    /// Persist registered asset stores under `data_dir` (default "data/bars")
+    pub fn with_data_dir(mut self, data_dir: impl Into<String>) -> Self {
+        self.data_dir = data_dir.into();
+        self
+    }
+
     /// Register a bar store for a specific symbol
     pub fn register_bar_store(&mut self, symbol: String, store: Arc<ConcurrentBarStore>) {
         self.bar_stores.insert(symbol, store);
     }
 
-    /// Sync all configured assets (NIFTY, BANKNIFTY, FINNIFTY)
+    /// Register `asset`'s daily (`<ASSET>`) and hourly (`<ASSET>_hourly`)
+    /// stores, persisted to `<data_dir>/<asset>_daily.jsonl` / `_hourly.jsonl`
+    pub fn register_asset_stores(&mut self, asset: UnderlyingAsset) {
+        let name = asset.as_str().to_string();
+        for (key, timeframe, suffix) in [
+            (name.clone(), "1D", "daily"),
+            (format!("{}_hourly", name), "1H", "hourly"),
+        ] {
+            let store = Arc::new(ConcurrentBarStore::new(
+                name.clone(),
+                timeframe.to_string(),
+                PathBuf::from(&self.data_dir).join(format!("{}_{}.jsonl", name.to_lowercase(), suffix)),
+                ASSET_STORE_CAPACITY,
+            ));
+            self.bar_stores.insert(key, store);
+        }
+    }
+
+    /// Hourly store registered for `symbol`, if any
+    pub fn hourly_store(&self, symbol: &str) -> Option<Arc<ConcurrentBarStore>> {
+        self.bar_stores.get(&format!("{}_hourly", symbol)).cloned()
+    }
+
+    /// Sync all configured assets (NIFTY, BANKNIFTY, FINNIFTY) in
+    /// `asset_sync_order`, up to `asset_sync_concurrency` at a time
     pub async fn sync_all_assets(&self) -> Result<MultiAssetSyncReport> {
         let start_time = Utc::now();
+        let assets = UnderlyingAsset::prioritized(&self.config.asset_sync_order);
         info!("🚀 Starting MULTI-ASSET historical data synchronization");
-        info!("   Assets: NIFTY, BANKNIFTY, FINNIFTY");
+        info!("   Assets: {}", assets.iter().map(|a| a.as_str()).collect::<Vec<_>>().join(", "));
         info!("   Filter: Spot={}, Futures={}, Options={}", 
               self.filter_config.include_spot,
               self.filter_config.include_futures,
@@ -174,85 +300,56 @@ impl MultiAssetHistoricalSync {
         // Create data directory
         tokio::fs::create_dir_all(&self.data_dir).await.ok();
 
-        let mut asset_reports = Vec::new();
-        let assets = UnderlyingAsset::all();
+        let asset_reports = self.sync_assets(&assets).await;
+        let report = summarize_assets(asset_reports, start_time, Utc::now());
+
+        // Save comprehensive report
+        self.save_multi_asset_report(&report).await.ok();
+
+        info!("✅ MULTI-ASSET sync complete!");
+        info!("   Duration: {}s", report.duration_sec);
+        info!("   Total instruments: {}", report.total_instruments);
+        info!("   Total bars: {}", report.total_bars_downloaded);
+        info!("   Empty responses: {}, network errors: {}", report.total_empty_responses, report.total_network_errors);
+        info!("   Success rate: {:.1}%", report.success_rate);
+
+        Ok(report)
+    }
+
+    /// Sync `assets` concurrently (bounded); reports come back in `assets` order
+    async fn sync_assets(&self, assets: &[UnderlyingAsset]) -> Vec<AssetSyncReport> {
+        let total = assets.len();
+        for_each_bounded(assets, &self.asset_semaphore, |idx, asset| async move {
+            info!("📊 [{}/{}] Processing {}...", idx + 1, total, asset.as_str());
 
-        for (idx, asset) in assets.iter().enumerate() {
-            info!("📊 [{}/{}] Processing {}...", idx + 1, assets.len(), asset.as_str());
-            
             match self.sync_single_asset(*asset).await {
                 Ok(report) => {
                     info!("✅ {} sync complete: {} instruments, {} bars", 
                           asset.as_str(), 
-                          report.options_synced + report.futures_synced + if report.underlying_bars > 0 { 1 } else { 0 },
+                          instrument_count(&report),
                           report.total_daily_bars + report.total_hourly_bars);
-                    asset_reports.push(report);
+                    report
                 }
                 Err(e) => {
                     error!("❌ Failed to sync {}: {}", asset.as_str(), e);
                     // Create error report
-                    asset_reports.push(AssetSyncReport {
+                    AssetSyncReport {
                         asset: asset.as_str().to_string(),
                         underlying_token: String::new(),
                         underlying_bars: 0,
                         futures_synced: 0,
                         options_synced: 0,
+                        skipped_expired: 0,
+                        empty_responses: 0,
+                        network_errors: 0,
                         total_daily_bars: 0,
                         total_hourly_bars: 0,
                         strikes_covered: Vec::new(),
                         errors: vec![format!("Sync failed: {}", e)],
-                    });
+                    }
                 }
             }
-
-            // Rate limiting between assets
-            if idx < assets.len() - 1 {
-                sleep(tokio::time::Duration::from_secs(2)).await;
-            }
-        }
-
-        let end_time = Utc::now();
-        let duration = (end_time - start_time).num_seconds();
-
-        // Calculate summary statistics
-        let total_instruments: usize = asset_reports.iter()
-            .map(|r| r.options_synced + r.futures_synced + if r.underlying_bars > 0 { 1 } else { 0 })
-            .sum();
-        
-        let total_bars: usize = asset_reports.iter()
-            .map(|r| r.total_daily_bars + r.total_hourly_bars)
-            .sum();
-        
-        let total_errors: usize = asset_reports.iter()
-            .map(|r| r.errors.len())
-            .sum();
-
-        let success_rate = if total_instruments > 0 {
-            ((total_instruments - total_errors) as f64 / total_instruments as f64) * 100.0
-        } else {
-            0.0
-        };
-
-        let report = MultiAssetSyncReport {
-            timestamp: end_time,
-            duration_sec: duration,
-            assets_synced: asset_reports,
-            total_instruments,
-            total_bars_downloaded: total_bars,
-            total_errors,
-            success_rate,
-        };
-
-        // Save comprehensive report
-        self.save_multi_asset_report(&report).await.ok();
-
-        info!("✅ MULTI-ASSET sync complete!");
-        info!("   Duration: {}s", duration);
-        info!("   Total instruments: {}", total_instruments);
-        info!("   Total bars: {}", total_bars);
-        info!("   Success rate: {:.1}%", success_rate);
-
-        Ok(report)
+        }).await
     }
 
     /// Sync a single asset (underlying + futures + options)
@@ -266,6 +363,9 @@ impl MultiAssetHistoricalSync {
             underlying_bars: 0,
             futures_synced: 0,
             options_synced: 0,
+            skipped_expired: 0,
+            empty_responses: 0,
+            network_errors: 0,
             total_daily_bars: 0,
             total_hourly_bars: 0,
             strikes_covered: Vec::new(),
@@ -309,8 +409,11 @@ impl MultiAssetHistoricalSync {
         if self.filter_config.include_futures {
             info!("📈 Syncing {} futures...", asset_name);
             match self.sync_futures(asset).await {
-                Ok(count) => {
+                Ok((count, expired, fetch_stats)) => {
                     report.futures_synced = count;
+                    report.skipped_expired += expired;
+                    report.empty_responses += fetch_stats.empty_responses;
+                    report.network_errors += fetch_stats.network_errors;
                     info!("✅ Synced {} futures contracts", count);
                 }
                 Err(e) => {
@@ -325,11 +428,15 @@ impl MultiAssetHistoricalSync {
         if self.filter_config.include_options {
             info!("🎯 Syncing {} options...", asset_name);
             match self.sync_options(asset).await {
-                Ok((count, strikes, daily, hourly)) => {
-                    report.options_synced = count;
-                    report.strikes_covered = strikes;
-                    report.total_daily_bars += daily;
-                    report.total_hourly_bars += hourly;
+                Ok(options) => {
+                    report.options_synced = options.synced;
+                    report.skipped_expired += options.skipped_expired;
+                    report.empty_responses += options.fetch_stats.empty_responses;
+                    report.network_errors += options.fetch_stats.network_errors;
+                    report.strikes_covered = options.strikes;
+                    report.total_daily_bars += options.daily_bars;
+                    report.total_hourly_bars += options.hourly_bars;
+                    let count = options.synced;
                     info!("✅ Synced {} option contracts across {} strikes", count, report.strikes_covered.len());
                 }
                 Err(e) => {
@@ -349,7 +456,7 @@ impl MultiAssetHistoricalSync {
         let asset_name = asset.as_str();
 
         // Use TokenExtractor for intelligent token discovery
-        let extractor = TokenExtractor::new(instruments);
+        let extractor = TokenExtractor::from_config(instruments, &self.config);
         let asset_tokens = extractor.extract_asset_tokens(asset_name);
 
         asset_tokens.spot_token
@@ -362,8 +469,9 @@ impl MultiAssetHistoricalSync {
     async fn sync_underlying_data(&self, token: &str, symbol: &str) -> Result<(usize, usize)> {
         let to_date = Utc::now();
         
-        // Download daily bars (last 365 days)
-        let from_daily = to_date - Duration::days(365);
+        // Download daily bars (last `history_days` days)
+        let from_daily = to_date - Duration::days(self.history_days);
+        self.rate_limiter.acquire().await;
         let daily_bars = self.broker.get_candles(token, "ONE_DAY", from_daily, to_date).await?;
         let daily_count = daily_bars.len();
         
@@ -374,8 +482,9 @@ impl MultiAssetHistoricalSync {
             }
         }
 
-        // Download hourly bars (last 30 days)
-        let from_hourly = to_date - Duration::days(30);
+        // Download hourly bars (last 30 days at most)
+        let from_hourly = to_date - Duration::days(self.history_days.min(30));
+        self.rate_limiter.acquire().await;
         let hourly_bars = self.broker.get_candles(token, "ONE_HOUR", from_hourly, to_date).await?;
         let hourly_count = hourly_bars.len();
         
@@ -389,44 +498,52 @@ impl MultiAssetHistoricalSync {
     }
 
     /// Sync futures contracts
-    async fn sync_futures(&self, asset: UnderlyingAsset) -> Result<usize> {
+    /// Returns (synced, skipped_expired, fetch_stats)
+    async fn sync_futures(&self, asset: UnderlyingAsset) -> Result<(usize, usize, FetchStats)> {
         let instruments = self.instrument_cache.get_all_instruments().await;
         let asset_name = asset.as_str();
 
         // Filter futures contracts
-        let futures: Vec<&Instrument> = instruments.iter()
+        let futures: Vec<Instrument> = instruments.into_iter()
             .filter(|i| {
                 i.name == asset_name
                 && i.exch_seg == "NFO"
                 && i.instrument_type == "FUTIDX"
             })
             .collect();
+        
+        // Drop contracts that have already expired
+        let (futures, expired) = drop_expired(futures, today_ist());
+        if expired > 0 {
+            info!("   Skipped {} expired futures contracts", expired);
+        }
 
         info!("   Found {} futures contracts for {}", futures.len(), asset_name);
 
-        let mut synced = 0;
-        for (idx, future) in futures.iter().enumerate() {
+        let total = futures.len();
+        let results = for_each_bounded(&futures, &self.fetch_semaphore, |idx, future| async move {
             info!("   [{}/{}] Syncing {} (expiry: {})...", 
-                  idx + 1, futures.len(), future.symbol, future.expiry);
+                  idx + 1, total, future.symbol, future.expiry);
             
-            match self.sync_derivative_data(&future.token, &future.symbol).await {
-                Ok(_) => synced += 1,
-                Err(e) => {
-                    warn!("⚠️  Failed to sync {}: {}", future.symbol, e);
-                }
+            let result = self.sync_derivative_data(&future.token, &future.symbol).await;
+            if let Err(e) = &result {
+                warn!("⚠️  Failed to sync {}: {}", future.symbol, e);
             }
+            result
+        }).await;
 
-            // Rate limiting
-            if idx < futures.len() - 1 {
-                sleep(tokio::time::Duration::from_millis(500)).await;
-            }
+        let mut synced = 0;
+        let mut fetch_stats = FetchStats::default();
+        for (_, _, stats) in results.into_iter().flatten() {
+            synced += 1;
+            fetch_stats += stats;
         }
 
-        Ok(synced)
+        Ok((synced, expired, fetch_stats))
     }
 
     /// Sync options contracts
-    async fn sync_options(&self, asset: UnderlyingAsset) -> Result<(usize, Vec<i32>, usize, usize)> {
+    async fn sync_options(&self, asset: UnderlyingAsset) -> Result<OptionsSync> {
         let asset_name = asset.as_str();
         
         // Get current price estimate
@@ -448,7 +565,7 @@ impl MultiAssetHistoricalSync {
         let instruments = self.instrument_cache.get_all_instruments().await;
         
         // Filter relevant options
-        let mut options: Vec<Instrument> = instruments
+        let options: Vec<Instrument> = instruments
             .into_iter()
             .filter(|inst| {
                 // Must be the right underlying
@@ -465,6 +582,12 @@ impl MultiAssetHistoricalSync {
             })
             .collect();
 
+        // Drop contracts that have already expired
+        let (mut options, expired) = drop_expired(options, today_ist());
+        if expired > 0 {
+            info!("   Skipped {} expired option contracts", expired);
+        }
+
         // Apply expiry filter
         options = self.apply_expiry_filter(options).await;
 
@@ -504,32 +627,37 @@ impl MultiAssetHistoricalSync {
         strikes.dedup();
 
         // Sync option data
-        let mut synced = 0;
-        let mut total_daily = 0;
-        let mut total_hourly = 0;
-
-        for (idx, option) in options.iter().enumerate() {
+        let total = options.len();
+        let results = for_each_bounded(&options, &self.fetch_semaphore, |idx, option| async move {
             info!("   [{}/{}] Syncing {} (strike: {}, expiry: {})...", 
-                  idx + 1, options.len(), option.symbol, option.strike, option.expiry);
+                  idx + 1, total, option.symbol, option.strike, option.expiry);
             
-            match self.sync_option_data(option).await {
-                Ok((daily, hourly)) => {
-                    synced += 1;
-                    total_daily += daily;
-                    total_hourly += hourly;
-                }
-                Err(e) => {
-                    warn!("⚠️  Failed to sync {}: {}", option.symbol, e);
-                }
+            let result = self.sync_option_data(option).await;
+            if let Err(e) = &result {
+                warn!("⚠️  Failed to sync {}: {}", option.symbol, e);
             }
+            result
+        }).await;
 
-            // Rate limiting
-            if idx < options.len() - 1 {
-                sleep(tokio::time::Duration::from_millis(300)).await;
-            }
+        let mut synced = 0;
+        let mut total_daily = 0;
+        let mut total_hourly = 0;
+        let mut fetch_stats = FetchStats::default();
+        for (daily, hourly, stats) in results.into_iter().flatten() {
+            synced += 1;
+            total_daily += daily;
+            total_hourly += hourly;
+            fetch_stats += stats;
         }
 
-        Ok((synced, strikes, total_daily, total_hourly))
+        Ok(OptionsSync {
+            synced,
+            strikes,
+            daily_bars: total_daily,
+            hourly_bars: total_hourly,
+            skipped_expired: expired,
+            fetch_stats,
+        })
     }
 
     /// Apply expiry filter to options
@@ -611,23 +739,29 @@ impl MultiAssetHistoricalSync {
     }
 
     /// Sync derivative (futures/options) data
-    async fn sync_derivative_data(&self, token: &str, symbol: &str) -> Result<(usize, usize)> {
+    /// Returns (daily_bars, hourly_bars, fetch_stats)
+    async fn sync_derivative_data(&self, token: &str, symbol: &str) -> Result<(usize, usize, FetchStats)> {
         let to_date = Utc::now();
+        let mut fetch_stats = FetchStats::default();
         
         // For derivatives, download last 60 days of daily data
         let from_daily = to_date - Duration::days(60);
-        let daily_bars = match self.broker.get_candles(token, "ONE_DAY", from_daily, to_date).await {
-            Ok(bars) => bars,
-            Err(_) => Vec::new(),
-        };
+        self.rate_limiter.acquire().await;
+        let daily_bars = classify_fetch(
+            symbol,
+            self.broker.get_candles(token, "ONE_DAY", from_daily, to_date).await,
+            &mut fetch_stats,
+        );
         let daily_count = daily_bars.len();
 
         // Download last 14 days of hourly data
         let from_hourly = to_date - Duration::days(14);
-        let hourly_bars = match self.broker.get_candles(token, "ONE_HOUR", from_hourly, to_date).await {
-            Ok(bars) => bars,
-            Err(_) => Vec::new(),
-        };
+        self.rate_limiter.acquire().await;
+        let hourly_bars = classify_fetch(
+            symbol,
+            self.broker.get_candles(token, "ONE_HOUR", from_hourly, to_date).await,
+            &mut fetch_stats,
+        );
         let hourly_count = hourly_bars.len();
 
         // Store if we have a registered store for this symbol
@@ -643,27 +777,33 @@ impl MultiAssetHistoricalSync {
             }
         }
 
-        Ok((daily_count, hourly_count))
+        Ok((daily_count, hourly_count, fetch_stats))
     }
 
     /// Sync option data (shorter history)
-    async fn sync_option_data(&self, instrument: &Instrument) -> Result<(usize, usize)> {
+    /// Returns (daily_bars, hourly_bars, fetch_stats)
+    async fn sync_option_data(&self, instrument: &Instrument) -> Result<(usize, usize, FetchStats)> {
         let to_date = Utc::now();
+        let mut fetch_stats = FetchStats::default();
         
         // For options, download last 30 days of daily data
         let from_daily = to_date - Duration::days(30);
-        let daily_bars = match self.broker.get_candles(&instrument.token, "ONE_DAY", from_daily, to_date).await {
-            Ok(bars) => bars,
-            Err(_) => Vec::new(),
-        };
+        self.rate_limiter.acquire().await;
+        let daily_bars = classify_fetch(
+            &instrument.symbol,
+            self.broker.get_candles(&instrument.token, "ONE_DAY", from_daily, to_date).await,
+            &mut fetch_stats,
+        );
         let daily_count = daily_bars.len();
 
         // Download last 7 days of hourly data
         let from_hourly = to_date - Duration::days(7);
-        let hourly_bars = match self.broker.get_candles(&instrument.token, "ONE_HOUR", from_hourly, to_date).await {
-            Ok(bars) => bars,
-            Err(_) => Vec::new(),
-        };
+        self.rate_limiter.acquire().await;
+        let hourly_bars = classify_fetch(
+            &instrument.symbol,
+            self.broker.get_candles(&instrument.token, "ONE_HOUR", from_hourly, to_date).await,
+            &mut fetch_stats,
+        );
         let hourly_count = hourly_bars.len();
 
         // Store if we have a registered store for this option
@@ -679,7 +819,7 @@ impl MultiAssetHistoricalSync {
             }
         }
 
-        Ok((daily_count, hourly_count))
+        Ok((daily_count, hourly_count, fetch_stats))
     }
 
     /// Save multi-asset sync report
@@ -713,3 +853,254 @@ impl MultiAssetHistoricalSync {
     }
 }
 
+/// Current date in IST (expiry dates are exchange-local)
+fn today_ist() -> NaiveDate {
+    Utc::now().with_timezone(&chrono_tz::Asia::Kolkata).date_naive()
+}
+
+/// Remove instruments whose expiry is before `today`
+/// Instruments without a parseable expiry are kept. Returns (kept, expired_count).
+fn drop_expired(instruments: Vec<Instrument>, today: NaiveDate) -> (Vec<Instrument>, usize) {
+    let before = instruments.len();
+    let kept: Vec<Instrument> = instruments
+        .into_iter()
+        .filter(|i| {
+            NaiveDate::parse_from_str(&i.expiry, "%d%b%Y")
+                .map(|expiry| expiry >= today)
+                .unwrap_or(true)
+        })
+        .collect();
+    let expired = before - kept.len();
+
+    (kept, expired)
+}
+
+/// Count a candle fetch as an empty response or a network error
+/// Failures still yield an empty vec so one bad contract doesn't abort the sync
+fn classify_fetch(symbol: &str, result: Result<Vec<Bar>>, stats: &mut FetchStats) -> Vec<Bar> {
+    match result {
+        Ok(bars) => {
+            if bars.is_empty() {
+                stats.empty_responses += 1;
+            }
+            bars
+        }
+        Err(e) => {
+            warn!("⚠️  Candle fetch failed for {}: {}", symbol, e);
+            stats.network_errors += 1;
+            Vec::new()
+        }
+    }
+}
+
+/// Instruments synced for one asset (spot counts once if it returned bars)
+fn instrument_count(report: &AssetSyncReport) -> usize {
+    report.options_synced + report.futures_synced + if report.underlying_bars > 0 { 1 } else { 0 }
+}
+
+/// Aggregate per-asset reports into the run summary
+fn summarize_assets(
+    asset_reports: Vec<AssetSyncReport>,
+    start_time: DateTime<Utc>,
+    end_time: DateTime<Utc>,
+) -> MultiAssetSyncReport {
+    let total_instruments: usize = asset_reports.iter().map(instrument_count).sum();
+    let total_bars: usize = asset_reports.iter()
+        .map(|r| r.total_daily_bars + r.total_hourly_bars)
+        .sum();
+    let total_errors: usize = asset_reports.iter().map(|r| r.errors.len()).sum();
+    let total_empty_responses: usize = asset_reports.iter().map(|r| r.empty_responses).sum();
+    let total_network_errors: usize = asset_reports.iter().map(|r| r.network_errors).sum();
+
+    let success_rate = if total_instruments > 0 {
+        (total_instruments.saturating_sub(total_errors) as f64 / total_instruments as f64) * 100.0
+    } else {
+        0.0
+    };
+
+    MultiAssetSyncReport {
+        timestamp: end_time,
+        duration_sec: (end_time - start_time).num_seconds(),
+        assets_synced: asset_reports,
+        total_instruments,
+        total_bars_downloaded: total_bars,
+        total_errors,
+        total_empty_responses,
+        total_network_errors,
+        success_rate,
+    }
+}
+
+/// Run `fetch` for every item with at most `semaphore`-permits in flight
+/// Results are returned in input order
+async fn for_each_bounded<'a, T, R, F, Fut>(items: &'a [T], semaphore: &Semaphore, fetch: F) -> Vec<R>
+where
+    F: Fn(usize, &'a T) -> Fut,
+    Fut: Future<Output = R>,
+{
+    let tasks = items.iter().enumerate().map(|(idx, item)| {
+        let fut = fetch(idx, item);
+        async move {
+            let _permit = semaphore.acquire().await.expect("sync semaphore closed");
+            fut.await
+        }
+    });
+
+    join_all(tasks).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::broker::angel_one::test_client;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn instrument(symbol: &str, expiry: &str) -> Instrument {
+        Instrument {
+            token: symbol.to_string(),
+            symbol: symbol.to_string(),
+            name: "NIFTY".to_string(),
+            expiry: expiry.to_string(),
+            strike: 23500.0,
+            lotsize: 50,
+            instrument_type: "OPTIDX".to_string(),
+            exch_seg: "NFO".to_string(),
+            tick_size: 0.05,
+        }
+    }
+
+    #[test]
+    fn test_drop_expired_instruments() {
+        let today = NaiveDate::from_ymd_opt(2024, 11, 20).unwrap();
+        let instruments = vec![
+            instrument("PAST_MONTH", "31OCT2024"),
+            instrument("YESTERDAY", "19NOV2024"),
+            instrument("TODAY", "20NOV2024"),
+            instrument("NEXT_WEEK", "28NOV2024"),
+            instrument("NO_EXPIRY", ""),
+        ];
+
+        let (kept, expired) = drop_expired(instruments, today);
+
+        assert_eq!(expired, 2);
+        let symbols: Vec<&str> = kept.iter().map(|i| i.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["TODAY", "NEXT_WEEK", "NO_EXPIRY"]);
+    }
+
+    #[tokio::test]
+    async fn test_semaphore_caps_in_flight_fetches() {
+        let semaphore = Semaphore::new(3);
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+        let items: Vec<usize> = (0..12).collect();
+
+        let results = for_each_bounded(&items, &semaphore, |_, item| {
+            let in_flight = &in_flight;
+            let max_in_flight = &max_in_flight;
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                *item * 2
+            }
+        }).await;
+
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 3);
+        assert_eq!(results, (0..12).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    fn bar() -> Bar {
+        Bar {
+            timestamp: Utc::now(),
+            timestamp_ms: 0,
+            open: 100.0,
+            high: 101.0,
+            low: 99.0,
+            close: 100.5,
+            volume: 10,
+            bar_complete: true,
+        }
+    }
+
+    #[test]
+    fn test_classify_empty_response() {
+        let mut stats = FetchStats::default();
+
+        let bars = classify_fetch("NIFTY_OPT", Ok(Vec::new()), &mut stats);
+        assert!(bars.is_empty());
+        assert_eq!(stats, FetchStats { empty_responses: 1, network_errors: 0 });
+
+        // Bars returned: neither counter moves
+        let bars = classify_fetch("NIFTY_OPT", Ok(vec![bar()]), &mut stats);
+        assert_eq!(bars.len(), 1);
+        assert_eq!(stats, FetchStats { empty_responses: 1, network_errors: 0 });
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_asset_sync_reports_every_asset_in_priority_order() {
+        let mut config = crate::config::loader::test_config();
+        config.asset_sync_concurrency = 3;
+        config.asset_sync_order = vec!["FINNIFTY".to_string(), "nifty".to_string(), "SENSEX".to_string()];
+
+        // Empty instrument cache: every asset fails token discovery
+        let broker = Arc::new(test_client());
+        let cache = Arc::new(InstrumentCache::new(Arc::clone(&broker)));
+        let sync = MultiAssetHistoricalSync::new(broker, cache, Arc::new(config.clone()));
+        assert!(sync.filter_config.include_options, "option strikes sync by default");
+
+        let assets = UnderlyingAsset::prioritized(&config.asset_sync_order);
+        assert_eq!(assets, vec![UnderlyingAsset::FinNifty, UnderlyingAsset::Nifty, UnderlyingAsset::BankNifty]);
+
+        let start = Utc::now();
+        let reports = sync.sync_assets(&assets).await;
+        let names: Vec<&str> = reports.iter().map(|r| r.asset.as_str()).collect();
+        assert_eq!(names, vec!["FINNIFTY", "NIFTY", "BANKNIFTY"]);
+
+        let summary = summarize_assets(reports, start, Utc::now());
+        assert_eq!(summary.assets_synced.len(), 3);
+        assert_eq!(summary.total_errors, 3);
+        assert_eq!(summary.total_instruments, 0);
+        assert_eq!(summary.success_rate, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_asset_stores_persist_per_asset() {
+        let dir = std::env::temp_dir().join(format!("asset_stores_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let broker = Arc::new(test_client());
+        let cache = Arc::new(InstrumentCache::new(Arc::clone(&broker)));
+        let mut sync = MultiAssetHistoricalSync::new(broker, cache, Arc::new(crate::config::loader::test_config()))
+            .with_data_dir(dir.display().to_string());
+        sync.register_asset_stores(UnderlyingAsset::BankNifty);
+
+        assert!(sync.hourly_store("NIFTY").is_none());
+        let store = sync.hourly_store("BANKNIFTY").unwrap();
+        let timestamp = Utc::now();
+        store.append(Bar {
+            timestamp,
+            timestamp_ms: timestamp.timestamp_millis(),
+            open: 49000.0,
+            high: 49100.0,
+            low: 48900.0,
+            close: 49050.0,
+            volume: 0,
+            bar_complete: true,
+        }).await.unwrap();
+
+        assert!(dir.join("banknifty_hourly.jsonl").exists());
+        assert!(!dir.join("banknifty_daily.jsonl").exists());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_classify_network_error() {
+        let mut stats = FetchStats::default();
+        let err = crate::error::TradingError::NetworkTimeout("candle request timed out".to_string());
+
+        let bars = classify_fetch("NIFTY_OPT", Err(err), &mut stats);
+        assert!(bars.is_empty());
+        assert_eq!(stats, FetchStats { empty_responses: 0, network_errors: 1 });
+    }
+}
@@ -4,11 +4,18 @@ pub mod bar_aggregator;
 pub mod historical_sync;
 pub mod historical_sync_multi;
 pub mod hourly_tokens;
+pub mod tick_recorder;
+pub mod bar_repair;
 
 pub use bar_store::{ConcurrentBarStore, HybridBarStore};
-pub use tick_buffer::TickBuffer;
+pub use tick_buffer::{ConcurrentTickBuffer, TickBuffer};
+pub use tick_recorder::{tick_file, TickRecorder, TickReplay};
 pub use bar_aggregator::{BarAggregator, MultiBarAggregator, Timeframe};
-pub use historical_sync::{HistoricalDataSync, SyncReport, DataQualityMetrics};
+pub use bar_repair::{repair_bars, RepairedBars};
+pub use historical_sync::{
+    backfill_requests, incremental_range, new_closed_bars, option_store_key, verify_coverage, BackfillRequest, CoverageReport, HistoricalDataSync,
+    SyncReport, DataQualityMetrics,
+};
 pub use historical_sync_multi::{
     MultiAssetHistoricalSync, MultiAssetSyncReport, AssetSyncReport,
     UnderlyingAsset, FilterConfig, ExpiryFilter,
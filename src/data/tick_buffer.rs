@@ -1,11 +1,13 @@
 /// Tick buffer for real-time market data
+/// Bounded ring buffer per instrument token; doubles as a latest-price cache
+use chrono::Utc;
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::types::Tick;
 
-/// Buffer for storing recent ticks per symbol
+/// Buffer for storing recent ticks per token
 pub struct TickBuffer {
     buffers: HashMap<String, VecDeque<Tick>>,
     capacity: usize,
@@ -15,14 +17,14 @@ impl TickBuffer {
     pub fn new(capacity: usize) -> Self {
         TickBuffer {
             buffers: HashMap::new(),
-            capacity,
+            capacity: capacity.max(1),
         }
     }
     
     /// Add a tick to the buffer
     pub fn push(&mut self, tick: Tick) {
         let buffer = self.buffers
-            .entry(tick.symbol.clone())
+            .entry(tick.token.clone())
             .or_insert_with(|| VecDeque::with_capacity(self.capacity));
         
         if buffer.len() >= self.capacity {
@@ -31,14 +33,34 @@ impl TickBuffer {
         buffer.push_back(tick);
     }
     
-    /// Get the last tick for a symbol
-    pub fn get_last(&self, symbol: &str) -> Option<&Tick> {
-        self.buffers.get(symbol)?.back()
+    /// Get the last tick for a token
+    pub fn get_last(&self, token: &str) -> Option<&Tick> {
+        self.buffers.get(token)?.back()
     }
     
-    /// Get recent N ticks for a symbol
-    pub fn get_recent(&self, symbol: &str, n: usize) -> Vec<Tick> {
-        if let Some(buffer) = self.buffers.get(symbol) {
+    /// Latest tick for a token (owned copy)
+    pub fn latest(&self, token: &str) -> Option<Tick> {
+        self.get_last(token).cloned()
+    }
+    
+    /// Time since the latest tick for a token (by tick timestamp)
+    pub fn last_update_age(&self, token: &str) -> Option<chrono::Duration> {
+        self.get_last(token).map(|tick| Utc::now() - tick.timestamp)
+    }
+    
+    /// Latest tick for a token unless it is older than `max_age`
+    /// None means the caller should fall back to REST pricing
+    pub fn fresh(&self, token: &str, max_age: chrono::Duration) -> Option<Tick> {
+        let tick = self.get_last(token)?;
+        if Utc::now() - tick.timestamp > max_age {
+            return None;
+        }
+        Some(tick.clone())
+    }
+    
+    /// Get recent N ticks for a token
+    pub fn get_recent(&self, token: &str, n: usize) -> Vec<Tick> {
+        if let Some(buffer) = self.buffers.get(token) {
             buffer.iter()
                 .rev()
                 .take(n)
@@ -50,18 +72,18 @@ impl TickBuffer {
         }
     }
     
-    /// Get all ticks for a symbol
-    pub fn get_all(&self, symbol: &str) -> Vec<Tick> {
-        if let Some(buffer) = self.buffers.get(symbol) {
+    /// Get all ticks for a token
+    pub fn get_all(&self, token: &str) -> Vec<Tick> {
+        if let Some(buffer) = self.buffers.get(token) {
             buffer.iter().cloned().collect()
         } else {
             Vec::new()
         }
     }
     
-    /// Clear buffer for a symbol
-    pub fn clear(&mut self, symbol: &str) {
-        if let Some(buffer) = self.buffers.get_mut(symbol) {
+    /// Clear buffer for a token
+    pub fn clear(&mut self, token: &str) {
+        if let Some(buffer) = self.buffers.get_mut(token) {
             buffer.clear();
         }
     }
@@ -89,24 +111,39 @@ impl ConcurrentTickBuffer {
         buffer.push(tick);
     }
     
-    pub async fn get_last(&self, symbol: &str) -> Option<Tick> {
+    pub async fn get_last(&self, token: &str) -> Option<Tick> {
+        let buffer = self.buffer.read().await;
+        buffer.latest(token)
+    }
+    
+    pub async fn latest(&self, token: &str) -> Option<Tick> {
+        let buffer = self.buffer.read().await;
+        buffer.latest(token)
+    }
+    
+    pub async fn last_update_age(&self, token: &str) -> Option<chrono::Duration> {
+        let buffer = self.buffer.read().await;
+        buffer.last_update_age(token)
+    }
+    
+    pub async fn fresh(&self, token: &str, max_age: chrono::Duration) -> Option<Tick> {
         let buffer = self.buffer.read().await;
-        buffer.get_last(symbol).cloned()
+        buffer.fresh(token, max_age)
     }
     
-    pub async fn get_recent(&self, symbol: &str, n: usize) -> Vec<Tick> {
+    pub async fn get_recent(&self, token: &str, n: usize) -> Vec<Tick> {
         let buffer = self.buffer.read().await;
-        buffer.get_recent(symbol, n)
+        buffer.get_recent(token, n)
     }
     
-    pub async fn get_all(&self, symbol: &str) -> Vec<Tick> {
+    pub async fn get_all(&self, token: &str) -> Vec<Tick> {
         let buffer = self.buffer.read().await;
-        buffer.get_all(symbol)
+        buffer.get_all(token)
     }
     
-    pub async fn clear(&self, symbol: &str) {
+    pub async fn clear(&self, token: &str) {
         let mut buffer = self.buffer.write().await;
-        buffer.clear(symbol);
+        buffer.clear(token);
     }
     
     pub async fn clear_all(&self) {
@@ -115,3 +152,61 @@ impl ConcurrentTickBuffer {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn tick(token: &str, ltp: f64) -> Tick {
+        let now = Utc::now();
+        Tick {
+            symbol: format!("SYM{}", token),
+            token: token.to_string(),
+            ltp,
+            bid: ltp - 0.05,
+            ask: ltp + 0.05,
+            volume: 100,
+            timestamp: now,
+            timestamp_ms: now.timestamp_millis(),
+        }
+    }
+    
+    #[test]
+    fn test_capacity_eviction_and_latest() {
+        let mut buffer = TickBuffer::new(3);
+        
+        for ltp in [100.0, 101.0, 102.0, 103.0] {
+            buffer.push(tick("1", ltp));
+        }
+        buffer.push(tick("2", 50.0));
+        
+        // Oldest tick evicted once capacity is reached
+        let ltps: Vec<f64> = buffer.get_all("1").iter().map(|t| t.ltp).collect();
+        assert_eq!(ltps, vec![101.0, 102.0, 103.0]);
+        
+        assert_eq!(buffer.latest("1").unwrap().ltp, 103.0);
+        assert_eq!(buffer.latest("2").unwrap().ltp, 50.0);
+        assert!(buffer.latest("3").is_none());
+        
+        let age = buffer.last_update_age("1").unwrap();
+        assert!(age >= chrono::Duration::zero() && age < chrono::Duration::seconds(5));
+        assert!(buffer.last_update_age("3").is_none());
+    }
+    
+    #[test]
+    fn test_stale_tick_forces_rest_fallback() {
+        let mut buffer = TickBuffer::new(3);
+        let max_age = chrono::Duration::seconds(10);
+        
+        buffer.push(tick("1", 100.0));
+        assert_eq!(buffer.fresh("1", max_age).unwrap().ltp, 100.0);
+        
+        // Feed went silent 30s ago: no tick price, caller polls REST
+        let mut stale = tick("2", 50.0);
+        stale.timestamp = Utc::now() - chrono::Duration::seconds(30);
+        buffer.push(stale);
+        assert!(buffer.fresh("2", max_age).is_none());
+        assert!(buffer.latest("2").is_some());
+        
+        assert!(buffer.fresh("3", max_age).is_none());
+    }
+}
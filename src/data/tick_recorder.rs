@@ -0,0 +1,215 @@
+/// Raw tick recording and replay
+/// Ticks are appended as JSON lines to `<dir>/ticks_<token>_YYYYMMDD.jsonl`
+/// and can be fed back through the bar aggregators for tick-level backtests.
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::data::MultiBarAggregator;
+use crate::error::Result;
+use crate::types::Tick;
+
+/// Recording file for a token on the tick's (UTC) date
+pub fn tick_file(dir: &Path, token: &str, date: DateTime<Utc>) -> PathBuf {
+    dir.join(format!("ticks_{}_{}.jsonl", token, date.format("%Y%m%d")))
+}
+
+/// Buffered writes reach disk at least this often
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Open day file per token
+struct DayWriter {
+    path: PathBuf,
+    writer: BufWriter<tokio::fs::File>,
+}
+
+/// Appends live ticks to per-token daily files through one buffered writer
+/// per token, reopened only when the day rolls over
+pub struct TickRecorder {
+    dir: PathBuf,
+    writers: Mutex<HashMap<String, DayWriter>>,
+    last_flush: Mutex<Instant>,
+}
+
+impl TickRecorder {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        TickRecorder {
+            dir: dir.into(),
+            writers: Mutex::new(HashMap::new()),
+            last_flush: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Append one tick; buffered output is flushed every `FLUSH_INTERVAL`
+    pub async fn record(&self, tick: &Tick) -> Result<()> {
+        let path = tick_file(&self.dir, &tick.token, tick.timestamp);
+        let mut writers = self.writers.lock().await;
+
+        if writers.get(&tick.token).is_none_or(|day| day.path != path) {
+            if let Some(mut previous) = writers.remove(&tick.token) {
+                previous.writer.flush().await?;
+            }
+            tokio::fs::create_dir_all(&self.dir).await?;
+            let file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await?;
+            writers.insert(tick.token.clone(), DayWriter { path, writer: BufWriter::new(file) });
+        }
+
+        if let Some(day) = writers.get_mut(&tick.token) {
+            day.writer.write_all(format!("{}\n", serde_json::to_string(tick)?).as_bytes()).await?;
+        }
+
+        let mut last_flush = self.last_flush.lock().await;
+        if last_flush.elapsed() >= FLUSH_INTERVAL {
+            for day in writers.values_mut() {
+                day.writer.flush().await?;
+            }
+            *last_flush = Instant::now();
+        }
+        Ok(())
+    }
+
+    /// Write out everything buffered so far
+    pub async fn flush(&self) -> Result<()> {
+        for day in self.writers.lock().await.values_mut() {
+            day.writer.flush().await?;
+        }
+        *self.last_flush.lock().await = Instant::now();
+        Ok(())
+    }
+}
+
+/// Reads recorded ticks back
+pub struct TickReplay;
+
+impl TickReplay {
+    /// Load ticks from a recording; malformed lines are skipped
+    pub async fn load(path: impl AsRef<Path>) -> Result<Vec<Tick>> {
+        let file = tokio::fs::File::open(path.as_ref()).await?;
+        let mut lines = BufReader::new(file).lines();
+        let mut ticks = Vec::new();
+
+        while let Some(line) = lines.next_line().await? {
+            match serde_json::from_str::<Tick>(&line) {
+                Ok(tick) => ticks.push(tick),
+                Err(e) => warn!("Skipping malformed tick in {}: {}", path.as_ref().display(), e),
+            }
+        }
+
+        Ok(ticks)
+    }
+
+    /// Feed a recording through the aggregators; returns ticks replayed
+    pub async fn replay_into(path: impl AsRef<Path>, aggregator: &MultiBarAggregator) -> Result<usize> {
+        let ticks = Self::load(path.as_ref()).await?;
+        let count = ticks.len();
+
+        for tick in ticks {
+            aggregator.process_tick(tick).await?;
+        }
+
+        info!("⏪ Replayed {} ticks from {}", count, path.as_ref().display());
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::{ConcurrentBarStore, Timeframe};
+    use crate::events::EventBus;
+    use chrono::TimeZone;
+    use std::sync::Arc;
+
+    fn tick(ltp: f64, timestamp: DateTime<Utc>) -> Tick {
+        Tick {
+            symbol: "NIFTY".to_string(),
+            token: "99926000".to_string(),
+            ltp,
+            bid: ltp - 0.5,
+            ask: ltp + 0.5,
+            volume: 10,
+            timestamp,
+            timestamp_ms: timestamp.timestamp_millis(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_recorded_ticks_round_trip_into_bars() {
+        let dir = std::env::temp_dir().join(format!("ticks_{}", uuid::Uuid::new_v4()));
+        let start = Utc.with_ymd_and_hms(2025, 1, 15, 4, 0, 0).unwrap();
+        let ticks = vec![
+            tick(23500.0, start),
+            tick(23510.0, start + chrono::Duration::seconds(20)),
+            tick(23495.0, start + chrono::Duration::seconds(40)),
+            tick(23505.0, start + chrono::Duration::seconds(65)),
+        ];
+
+        let recorder = TickRecorder::new(&dir);
+        for t in &ticks {
+            recorder.record(t).await.unwrap();
+        }
+        recorder.flush().await.unwrap();
+
+        let path = tick_file(&dir, "99926000", start);
+        assert!(path.ends_with("ticks_99926000_20250115.jsonl"));
+
+        let loaded = TickReplay::load(&path).await.unwrap();
+        assert_eq!(loaded.len(), ticks.len());
+        for (a, b) in loaded.iter().zip(&ticks) {
+            assert_eq!(a.token, b.token);
+            assert_eq!(a.ltp, b.ltp);
+            assert_eq!(a.bid, b.bid);
+            assert_eq!(a.timestamp, b.timestamp);
+        }
+
+        // Replay rebuilds the first 1m bar
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let store = Arc::new(ConcurrentBarStore::new(
+            "NIFTY".to_string(),
+            "1m".to_string(),
+            dir.join("bars_1m.jsonl"),
+            10,
+        ));
+        let aggregator = MultiBarAggregator::new(event_bus);
+        aggregator.add_aggregator("99926000".to_string(), Timeframe::OneMinute, Arc::clone(&store)).await;
+
+        assert_eq!(TickReplay::replay_into(&path, &aggregator).await.unwrap(), 4);
+
+        assert_eq!(store.total_count().await, 1);
+        let bar = store.get_last().await.unwrap();
+        assert_eq!(bar.timestamp, start);
+        assert_eq!((bar.open, bar.high, bar.low, bar.close), (23500.0, 23510.0, 23495.0, 23495.0));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_recorder_keeps_one_writer_per_token_across_days() {
+        let dir = std::env::temp_dir().join(format!("ticks_{}", uuid::Uuid::new_v4()));
+        let day1 = Utc.with_ymd_and_hms(2025, 1, 15, 4, 0, 0).unwrap();
+        let day2 = Utc.with_ymd_and_hms(2025, 1, 16, 4, 0, 0).unwrap();
+
+        let recorder = TickRecorder::new(&dir);
+        recorder.record(&tick(23500.0, day1)).await.unwrap();
+        recorder.record(&tick(23510.0, day1 + chrono::Duration::seconds(1))).await.unwrap();
+        recorder.record(&tick(23600.0, day2)).await.unwrap();
+        recorder.flush().await.unwrap();
+
+        // The rollover flushed and replaced the day-one writer
+        assert_eq!(recorder.writers.lock().await.len(), 1);
+        assert_eq!(TickReplay::load(tick_file(&dir, "99926000", day1)).await.unwrap().len(), 2);
+        let day2_ticks = TickReplay::load(tick_file(&dir, "99926000", day2)).await.unwrap();
+        assert_eq!(day2_ticks.len(), 1);
+        assert_eq!(day2_ticks[0].ltp, 23600.0);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -122,6 +122,9 @@ pub enum TradingError {
     #[error("Rate limit exceeded: {0}")]
     RateLimitExceeded(String),
     
+    #[error("Broker maintenance: {0}")]
+    BrokerMaintenance(String),
+    
     #[error("Instrument not found: {0}")]
     InstrumentNotFound(String),
     
@@ -166,6 +169,15 @@ pub enum TradingError {
 
 pub type Result<T> = std::result::Result<T, TradingError>;
 
+/// HTTP 429 Too Many Requests
+const HTTP_TOO_MANY_REQUESTS: u16 = 429;
+
+/// Wait before retrying after a broker rate limit
+const RATE_LIMIT_BACKOFF_SECS: u64 = 2;
+
+/// Pause before retrying while the broker is in a maintenance window
+const MAINTENANCE_PAUSE_SECS: u64 = 300;
+
 impl TradingError {
     /// Check if error is recoverable
     pub fn is_recoverable(&self) -> bool {
@@ -176,9 +188,45 @@ impl TradingError {
                 | TradingError::DataGap(_)
                 | TradingError::OrderPlacementFailed(_)
                 | TradingError::RateLimitExceeded(_)
+                | TradingError::BrokerMaintenance(_)
         )
     }
     
+    /// Rate-limit or maintenance error for a broker response: HTTP 429 or a
+    /// rate-limit message (Angel One has no dedicated error code for it; its
+    /// AB1004 is a generic failure). `message` may be a raw non-JSON body.
+    /// `None` for anything else
+    pub fn classify_broker(http_status: u16, code: &str, message: &str) -> Option<TradingError> {
+        let text = message.to_lowercase();
+        let detail = if code.is_empty() {
+            message.to_string()
+        } else {
+            format!("{} ({})", message, code)
+        };
+        
+        if http_status == HTTP_TOO_MANY_REQUESTS
+            || text.contains("exceeding access rate")
+            || text.contains("rate limit")
+            || text.contains("too many requests")
+        {
+            Some(TradingError::RateLimitExceeded(detail))
+        } else if text.contains("maintenance") || text.contains("downtime") {
+            Some(TradingError::BrokerMaintenance(detail))
+        } else {
+            None
+        }
+    }
+    
+    /// How long to wait before retrying: a short backoff for rate limits,
+    /// a pause for maintenance; `None` when no special wait applies
+    pub fn retry_after(&self) -> Option<std::time::Duration> {
+        match self {
+            TradingError::RateLimitExceeded(_) => Some(std::time::Duration::from_secs(RATE_LIMIT_BACKOFF_SECS)),
+            TradingError::BrokerMaintenance(_) => Some(std::time::Duration::from_secs(MAINTENANCE_PAUSE_SECS)),
+            _ => None,
+        }
+    }
+    
     /// Check if error requires immediate system shutdown
     pub fn is_fatal(&self) -> bool {
         matches!(
@@ -240,6 +288,7 @@ impl TradingError {
             TradingError::BrokerApiError { .. } => "BROKER_001",
             TradingError::RateLimitExceeded(_) => "BROKER_002",
             TradingError::InstrumentNotFound(_) => "BROKER_003",
+            TradingError::BrokerMaintenance(_) => "BROKER_004",
             TradingError::SystemShutdown(_) => "SYS_001",
             TradingError::FatalError(_) => "SYS_002",
             TradingError::GracefulExit(_) => "SYS_003",
@@ -255,3 +304,39 @@ impl TradingError {
     }
 }
 
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_broker_error_classification() {
+        let rate = TradingError::classify_broker(429, "", "").unwrap();
+        assert!(matches!(rate, TradingError::RateLimitExceeded(_)));
+        assert_eq!(rate.retry_after(), Some(std::time::Duration::from_secs(RATE_LIMIT_BACKOFF_SECS)));
+        
+        // Plain-text 403 body, no code
+        let body = TradingError::classify_broker(403, "", "Access denied because of exceeding access rate").unwrap();
+        assert!(matches!(body, TradingError::RateLimitExceeded(_)));
+        
+        // AB1004 is Angel One's generic "something went wrong", not a rate limit
+        assert!(TradingError::classify_broker(200, "AB1004", "Something Went Wrong, Please Try After Sometime").is_none());
+        
+        let maintenance = TradingError::classify_broker(200, "", "System under Maintenance, please try later").unwrap();
+        assert!(matches!(maintenance, TradingError::BrokerMaintenance(_)));
+        assert_eq!(maintenance.error_code(), "BROKER_004");
+        
+        // Both are retried, neither aborts nor forces an exit
+        for e in [&rate, &maintenance] {
+            assert!(e.is_recoverable());
+            assert!(!e.is_fatal());
+            assert!(!e.requires_exit());
+        }
+        assert!(maintenance.retry_after() > rate.retry_after());
+        
+        // Ordinary rejections are left to the caller
+        assert!(TradingError::classify_broker(200, "AB4008", "Insufficient funds").is_none());
+        assert!(TradingError::InsufficientMargin(String::new()).retry_after().is_none());
+    }
+}
@@ -1,6 +1,8 @@
 /// Event Bus - Pub/Sub system for event-driven architecture
 use std::collections::{HashMap, HashSet};
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
+use futures_util::FutureExt;
 use tokio::sync::{mpsc, RwLock};
 use tracing::{debug, error, warn};
 
@@ -9,11 +11,27 @@ use crate::error::{Result, TradingError};
 
 pub type EventHandler = Arc<dyn Fn(Event) -> futures_util::future::BoxFuture<'static, Result<()>> + Send + Sync>;
 
+/// Selects events for a filtered subscription
+pub type EventPredicate = Arc<dyn Fn(&Event) -> bool + Send + Sync>;
+
+/// Handler outcomes for one event type
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SubscriberStats {
+    pub succeeded: u64,
+    /// Handlers that returned an error
+    pub failed: u64,
+    /// Handlers that panicked (caught; delivery continued)
+    pub panicked: u64,
+}
+
 /// Event bus for publish-subscribe pattern
 pub struct EventBus {
     /// Subscribers for each event type
     subscribers: Arc<RwLock<HashMap<EventType, Vec<EventHandler>>>>,
     
+    /// Subscribers selected by predicate, across all event types
+    filtered_subscribers: Arc<RwLock<Vec<(EventPredicate, EventHandler)>>>,
+    
     /// Channel for publishing events
     tx: mpsc::UnboundedSender<Event>,
     rx: Arc<RwLock<mpsc::UnboundedReceiver<Event>>>,
@@ -23,6 +41,9 @@ pub struct EventBus {
     
     /// Event log file path
     event_log_path: String,
+    
+    /// Per-event-type handler outcomes
+    subscriber_stats: Arc<RwLock<HashMap<EventType, SubscriberStats>>>,
 }
 
 impl EventBus {
@@ -31,10 +52,12 @@ impl EventBus {
         
         EventBus {
             subscribers: Arc::new(RwLock::new(HashMap::new())),
+            filtered_subscribers: Arc::new(RwLock::new(Vec::new())),
             tx,
             rx: Arc::new(RwLock::new(rx)),
             processed_events: Arc::new(RwLock::new(HashSet::new())),
             event_log_path,
+            subscriber_stats: Arc::new(RwLock::new(HashMap::new())),
         }
     }
     
@@ -52,6 +75,18 @@ impl EventBus {
         debug!("Subscribed handler to event: {:?}", event_type);
     }
     
+    /// Subscribe to every event for which `predicate` returns true
+    /// Runs after the type subscribers of a matching event.
+    pub async fn subscribe_filtered(
+        &self,
+        predicate: EventPredicate,
+        handler: EventHandler,
+    ) {
+        let mut filtered = self.filtered_subscribers.write().await;
+        filtered.push((predicate, handler));
+        debug!("Subscribed filtered handler ({} total)", filtered.len());
+    }
+    
     /// Publish an event to all subscribers
     pub async fn publish(&self, event: Event) -> Result<()> {
         // Check idempotency
@@ -81,9 +116,35 @@ impl EventBus {
         Ok(())
     }
     
+    /// Publish an event under a caller-provided stable idempotency key
+    /// Re-emitting the same logical event (e.g. replaying a known fill) is a no-op.
+    /// Returns `true` if the event was delivered, `false` if it was a duplicate.
+    pub async fn publish_idempotent(&self, mut event: Event, key: &str) -> Result<bool> {
+        event.idempotency_key = key.to_string();
+        
+        match self.publish(event).await {
+            Ok(()) => Ok(true),
+            Err(TradingError::DuplicateEvent(key)) => {
+                debug!("Skipping already published event: {}", key);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+    
+    /// Handler success/failure counters per event type
+    pub async fn subscriber_stats(&self) -> HashMap<EventType, SubscriberStats> {
+        let stats = self.subscriber_stats.read().await;
+        stats.clone()
+    }
+    
     /// Start processing events from the queue
+    /// Each handler is isolated: an error or panic is logged and counted,
+    /// and delivery continues to the remaining handlers
     pub async fn start_processing(&self) {
         let subscribers = Arc::clone(&self.subscribers);
+        let filtered_subscribers = Arc::clone(&self.filtered_subscribers);
+        let subscriber_stats = Arc::clone(&self.subscriber_stats);
         let rx = Arc::clone(&self.rx);
         
         tokio::spawn(async move {
@@ -98,21 +159,44 @@ impl EventBus {
                     event.timestamp
                 );
                 
-                // Get all handlers for this event type
-                let handlers = {
+                // Get all handlers for this event type, then matching filters
+                let mut handlers = {
                     let subs = subscribers.read().await;
-                    subs.get(&event_type).cloned()
+                    subs.get(&event_type).cloned().unwrap_or_default()
                 };
+                {
+                    let filtered = filtered_subscribers.read().await;
+                    handlers.extend(
+                        filtered
+                            .iter()
+                            .filter(|(predicate, _)| {
+                                // A panicking predicate counts as no match
+                                std::panic::catch_unwind(AssertUnwindSafe(|| predicate(&event)))
+                                    .unwrap_or(false)
+                            })
+                            .map(|(_, handler)| Arc::clone(handler)),
+                    );
+                }
                 
-                if let Some(handlers) = handlers {
+                if !handlers.is_empty() {
                     // Execute all handlers
                     for handler in handlers {
                         let event_clone = event.clone();
-                        match handler(event_clone).await {
-                            Ok(_) => {
+                        // Calling the handler happens inside the future so a
+                        // panic in either the call or the await is caught
+                        let outcome = AssertUnwindSafe(async move { handler(event_clone).await })
+                            .catch_unwind()
+                            .await;
+                        
+                        let mut stats = subscriber_stats.write().await;
+                        let stats = stats.entry(event_type.clone()).or_default();
+                        match outcome {
+                            Ok(Ok(_)) => {
+                                stats.succeeded += 1;
                                 debug!("Handler executed successfully for: {:?}", event_type);
                             }
-                            Err(e) => {
+                            Ok(Err(e)) => {
+                                stats.failed += 1;
                                 error!(
                                     "Handler failed for event {:?}: {} ({})",
                                     event_type,
@@ -120,6 +204,10 @@ impl EventBus {
                                     e.error_code()
                                 );
                             }
+                            Err(_) => {
+                                stats.panicked += 1;
+                                error!("Handler panicked for event {:?}", event_type);
+                            }
                         }
                     }
                 } else {
@@ -149,6 +237,12 @@ impl EventBus {
         Ok(())
     }
     
+    /// Rotate the event log to `<name>_YYYYMMDD.jsonl`
+    /// The log is reopened on every append, so the next event starts a fresh file.
+    pub async fn rotate_log(&self, date: chrono::NaiveDate) -> Result<Option<std::path::PathBuf>> {
+        crate::utils::rotate_file(std::path::Path::new(&self.event_log_path), date).await
+    }
+    
     /// Replay events from log (for recovery)
     pub async fn replay_events(&self, from_timestamp: chrono::DateTime<chrono::Utc>) -> Result<Vec<Event>> {
         use tokio::fs::File;
@@ -253,4 +347,149 @@ mod tests {
         // Cleanup
         let _ = std::fs::remove_file("test_idempotency.jsonl");
     }
+    
+    #[tokio::test]
+    async fn test_publish_idempotent_delivers_once() {
+        let bus = EventBus::new("test_publish_idempotent.jsonl".to_string());
+        
+        let calls = Arc::new(RwLock::new(0));
+        let calls_clone = Arc::clone(&calls);
+        
+        let handler: EventHandler = Arc::new(move |_event| {
+            let calls = Arc::clone(&calls_clone);
+            Box::pin(async move {
+                *calls.write().await += 1;
+                Ok(())
+            })
+        });
+        
+        bus.subscribe(EventType::ConfigLoaded, handler).await;
+        bus.start_processing().await;
+        
+        let make_event = || Event::new(
+            EventType::ConfigLoaded,
+            EventPayload::ConfigLoaded {
+                config_hash: "test".to_string(),
+                data_paths: vec![],
+            },
+        );
+        
+        // Distinct Event instances, same logical key
+        assert!(bus.publish_idempotent(make_event(), "fill-ORD123").await.unwrap());
+        assert!(!bus.publish_idempotent(make_event(), "fill-ORD123").await.unwrap());
+        
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        
+        assert_eq!(*calls.read().await, 1);
+        
+        let logged = std::fs::read_to_string("test_publish_idempotent.jsonl").unwrap();
+        assert_eq!(logged.lines().count(), 1);
+        
+        // Cleanup
+        let _ = std::fs::remove_file("test_publish_idempotent.jsonl");
+    }
+    
+    #[tokio::test]
+    async fn test_panicking_subscriber_is_isolated() {
+        let bus = EventBus::new("test_subscriber_isolation.jsonl".to_string());
+        
+        let panicking: EventHandler = Arc::new(|_event| {
+            Box::pin(async move {
+                panic!("subscriber bug");
+            })
+        });
+        
+        let called = Arc::new(RwLock::new(0));
+        let called_clone = Arc::clone(&called);
+        let healthy: EventHandler = Arc::new(move |_event| {
+            let called = Arc::clone(&called_clone);
+            Box::pin(async move {
+                *called.write().await += 1;
+                Ok(())
+            })
+        });
+        
+        bus.subscribe(EventType::ConfigLoaded, panicking).await;
+        bus.subscribe(EventType::ConfigLoaded, healthy).await;
+        bus.start_processing().await;
+        
+        for hash in ["a", "b"] {
+            bus.publish(Event::new(
+                EventType::ConfigLoaded,
+                EventPayload::ConfigLoaded {
+                    config_hash: hash.to_string(),
+                    data_paths: vec![],
+                },
+            )).await.unwrap();
+        }
+        
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        
+        // Second subscriber got both events; processing survived the panics
+        assert_eq!(*called.read().await, 2);
+        
+        let stats = bus.subscriber_stats().await;
+        assert_eq!(stats[&EventType::ConfigLoaded], SubscriberStats {
+            succeeded: 2,
+            failed: 0,
+            panicked: 2,
+        });
+        
+        // Cleanup
+        let _ = std::fs::remove_file("test_subscriber_isolation.jsonl");
+    }
+    
+    #[tokio::test]
+    async fn test_filtered_subscription_spans_event_types() {
+        let bus = EventBus::new("test_filtered_subscription.jsonl".to_string());
+        
+        let seen = Arc::new(RwLock::new(Vec::new()));
+        let seen_clone = Arc::clone(&seen);
+        let handler: EventHandler = Arc::new(move |event| {
+            let seen = Arc::clone(&seen_clone);
+            Box::pin(async move {
+                seen.write().await.push(event.event_type);
+                Ok(())
+            })
+        });
+        let order_events: EventPredicate = Arc::new(|event| {
+            event.event_type.as_str().starts_with("ORDER_")
+        });
+        
+        bus.subscribe_filtered(order_events, handler).await;
+        bus.start_processing().await;
+        
+        bus.publish(Event::new(
+            EventType::OrderPlaced,
+            EventPayload::OrderPlaced {
+                order_id: "ORD1".to_string(),
+                broker_order_id: "B1".to_string(),
+                symbol: "NIFTY25JAN19500CE".to_string(),
+                quantity: 50,
+                price: 125.0,
+            },
+        )).await.unwrap();
+        bus.publish(Event::new(
+            EventType::ConfigLoaded,
+            EventPayload::ConfigLoaded {
+                config_hash: "test".to_string(),
+                data_paths: vec![],
+            },
+        )).await.unwrap();
+        bus.publish(Event::new(
+            EventType::OrderFailed,
+            EventPayload::OrderFailed {
+                order_id: "ORD2".to_string(),
+                reason: "rejected".to_string(),
+                retry_count: 0,
+            },
+        )).await.unwrap();
+        
+        tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
+        
+        assert_eq!(*seen.read().await, vec![EventType::OrderPlaced, EventType::OrderFailed]);
+        
+        // Cleanup
+        let _ = std::fs::remove_file("test_filtered_subscription.jsonl");
+    }
 }
@@ -1,6 +1,6 @@
 pub mod event_bus;
 pub mod types;
 
-pub use event_bus::EventBus;
+pub use event_bus::{EventBus, EventHandler, EventPredicate, SubscriberStats};
 pub use types::*;
 
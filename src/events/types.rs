@@ -39,6 +39,7 @@ pub enum EventType {
     TokenRefreshStarted,
     TokenRefreshSuccess,
     TokenRefreshFailed,
+    ClockDriftWarning,
     
     // Data Collection
     InstrumentMasterDownloaded,
@@ -52,12 +53,14 @@ pub enum EventType {
     TickReceived,
     BarReady,
     DataGapDetected,
+    RecoveryStarted,
     RecoveryCompleted,
     RecoveryFailed,
     
     // Analysis & Strategy
     DailyDirectionDetermined,
     SignalGenerated,
+    NoTradeSignal,
     
     // Risk Management
     VixDataReceived,
@@ -75,6 +78,8 @@ pub enum EventType {
     OrderRejected,
     OrderFailed,
     OrderRetrying,
+    OrderCancelled,
+    DuplicateFill,
     PositionOpened,
     PositionUpdated,
     
@@ -84,6 +89,7 @@ pub enum EventType {
     TrailingStopActivated,
     TrailingStopUpdated,
     TargetReached,
+    ExitLevelAdjusted,
     EodMandatoryExit,
     PositionClosed,
     PositionsClosed,
@@ -171,11 +177,23 @@ pub enum EventPayload {
         reason: String,
         attempts: u32,
     },
+    ClockDriftWarning {
+        drift_ms: i64,
+        threshold_sec: u64,
+    },
     
     // Data
     InstrumentMasterDownloaded {
         instrument_count: usize,
         file_path: String,
+        /// Tokens listed since the previous master
+        #[serde(default)]
+        added: usize,
+        /// Tokens no longer in the master
+        #[serde(default)]
+        removed: usize,
+        #[serde(default)]
+        new_expiries: Vec<String>,
     },
     HistoricalDataSyncStarted {
         symbol: String,
@@ -226,6 +244,12 @@ pub enum EventPayload {
         gap_end: DateTime<Utc>,
         missing_bars: usize,
     },
+    RecoveryStarted {
+        symbol: String,
+        timeframe: String,
+        gap_start: DateTime<Utc>,
+        gap_end: DateTime<Utc>,
+    },
     RecoveryCompleted {
         symbol: String,
         timeframe: String,
@@ -257,6 +281,11 @@ pub enum EventPayload {
         option_ltp: f64,
         vix: f64,
     },
+    /// An entry signal was dropped before ordering
+    NoTradeSignal {
+        symbol: String,
+        reason: String,
+    },
     
     // Risk
     VixDataReceived {
@@ -328,6 +357,18 @@ pub enum EventPayload {
         max_retries: u32,
         backoff_sec: u64,
     },
+    OrderCancelled {
+        order_id: String,
+        filled_quantity: i32,
+        cancelled_quantity: i32,
+        reason: String,
+    },
+    DuplicateFill {
+        order_id: String,
+        idempotency_key: String,
+        duplicate_broker_order_ids: Vec<String>,
+        extra_quantity: i32,
+    },
     PositionOpened {
         position_id: String,
         symbol: String,
@@ -369,6 +410,14 @@ pub enum EventPayload {
         target: f64,
         current_price: f64,
     },
+    /// Manual stop/target override ("STOP_LOSS" or "TARGET")
+    ExitLevelAdjusted {
+        position_id: String,
+        level: String,
+        previous: Option<f64>,
+        new_level: f64,
+        current_price: f64,
+    },
     EodMandatoryExit {
         time: DateTime<Utc>,
         positions_to_close: Vec<String>,
@@ -383,6 +432,9 @@ pub enum EventPayload {
     PositionsClosed {
         position_ids: Vec<String>,
         reason: String,
+        /// Positions whose exits failed every retry; these need a human
+        #[serde(default)]
+        still_open: Vec<String>,
     },
     
     // System
@@ -448,6 +500,7 @@ impl EventType {
             EventType::TokenRefreshStarted => "TOKEN_REFRESH_STARTED",
             EventType::TokenRefreshSuccess => "TOKEN_REFRESH_SUCCESS",
             EventType::TokenRefreshFailed => "TOKEN_REFRESH_FAILED",
+            EventType::ClockDriftWarning => "CLOCK_DRIFT_WARNING",
             EventType::InstrumentMasterDownloaded => "INSTRUMENT_MASTER_DOWNLOADED",
             EventType::HistoricalDataSyncStarted => "HISTORICAL_DATA_SYNC_STARTED",
             EventType::HistoricalDataSyncCompleted => "HISTORICAL_DATA_SYNC_COMPLETED",
@@ -459,10 +512,12 @@ impl EventType {
             EventType::TickReceived => "TICK_RECEIVED",
             EventType::BarReady => "BAR_READY",
             EventType::DataGapDetected => "DATA_GAP_DETECTED",
+            EventType::RecoveryStarted => "RECOVERY_STARTED",
             EventType::RecoveryCompleted => "RECOVERY_COMPLETED",
             EventType::RecoveryFailed => "RECOVERY_FAILED",
             EventType::DailyDirectionDetermined => "DAILY_DIRECTION_DETERMINED",
             EventType::SignalGenerated => "SIGNAL_GENERATED",
+            EventType::NoTradeSignal => "NO_TRADE_SIGNAL",
             EventType::VixDataReceived => "VIX_DATA_RECEIVED",
             EventType::VixSpike => "VIX_SPIKE",
             EventType::VixNormalResumed => "VIX_NORMAL_RESUMED",
@@ -476,6 +531,8 @@ impl EventType {
             EventType::OrderRejected => "ORDER_REJECTED",
             EventType::OrderFailed => "ORDER_FAILED",
             EventType::OrderRetrying => "ORDER_RETRYING",
+            EventType::OrderCancelled => "ORDER_CANCELLED",
+            EventType::DuplicateFill => "DUPLICATE_FILL",
             EventType::PositionOpened => "POSITION_OPENED",
             EventType::PositionUpdated => "POSITION_UPDATED",
             EventType::ExitSignalGenerated => "EXIT_SIGNAL_GENERATED",
@@ -483,6 +540,7 @@ impl EventType {
             EventType::TrailingStopActivated => "TRAILING_STOP_ACTIVATED",
             EventType::TrailingStopUpdated => "TRAILING_STOP_UPDATED",
             EventType::TargetReached => "TARGET_REACHED",
+            EventType::ExitLevelAdjusted => "EXIT_LEVEL_ADJUSTED",
             EventType::EodMandatoryExit => "EOD_MANDATORY_EXIT",
             EventType::PositionClosed => "POSITION_CLOSED",
             EventType::PositionsClosed => "POSITIONS_CLOSED",
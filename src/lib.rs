@@ -11,6 +11,8 @@ pub mod risk;
 pub mod config;
 pub mod utils;
 pub mod time;
+pub mod analytics;
+pub mod cli;
 
 pub use types::*;
 pub use error::{Result, TradingError};
@@ -2,38 +2,78 @@
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{error, info, warn};
+use tracing::{error, info, warn, Instrument};
 use tracing_subscriber;
 use chrono::Timelike;
 
 use rustro::{
-    broker::{AngelOneClient, AngelWebSocket, InstrumentCache, PaperTradingBroker, TokenExtractor, TokenManager},
+    analytics::PerformanceAnalyzer,
+    broker::{
+        bars_to_ticks, live_ltp, with_csv_fallback, AngelOneClient, MarketDataSource, AngelWebSocket, InstrumentCache, OrderBroker, OrderUpdateSocket, OutageWindow, PaperTradingBroker,
+        ReplayPace, TokenExtractor, TokenManager, TokenMonitor, TokenStatus,
+    },
+    cli::{handle_control_command, parse_args, send_control_command, Command, ControlCommand, CONTROL_FILE, USAGE},
     config::load_config,
-    data::{ConcurrentBarStore, HistoricalDataSync, MultiBarAggregator, Timeframe},
+    data::{
+        ConcurrentBarStore, ConcurrentTickBuffer, HistoricalDataSync, MultiAssetHistoricalSync,
+        incremental_range, new_closed_bars, MultiBarAggregator, TickRecorder, TickReplay, Timeframe, UnderlyingAsset,
+    },
     error::{Result, TradingError},
     events::{Event, EventBus, EventPayload, EventType},
-    orders::{OrderManager, OrderValidator},
-    positions::PositionManager,
+    orders::{apply_feed_update, new_entry_order, place_entry, EntryFill, OrderManager, OrderValidator},
+    positions::{prune_snapshots, save_exit_record, save_snapshot, CloseAllReport, PositionManager, SNAPSHOT_DIR},
     risk::RiskManager,
-    strategy::{adx_strategy::EntrySignal, AdxStrategy, BiasDirection, DailyBias, DailyBiasCalculator, HourlyCrossoverMonitor},
-    time::{get_market_timings, holidays::is_trading_day as is_trading_day_with_holidays},
-    trading::PremarketSelector,
-    utils::{calculate_days_to_expiry, generate_idempotency_key, is_in_entry_window},
-    Config, Direction, OrderType, OptionType, Position, PositionStatus, Side,
+    strategy::{
+        adx_strategy::EntrySignal, oi_buildup_confirms, round_to_strike, AdxStrategy, BiasDirection, DailyBias,
+        DailyBiasCalculator, HourlyCrossoverMonitor, OiTracker, BIAS_SUMMARY_FILE,
+    },
+    time::{get_market_timings, holidays::is_trading_day as is_trading_day_with_holidays, session_transition},
+    trading::{select_liquid_strike, strike_candidates, PremarketSelector},
+    utils::{
+        calculate_days_to_expiry, calculate_trading_days_to_expiry, generate_idempotency_key,
+        next_weekly_expiry, prune_dated_files, round_to_tick, signal_idempotency_key, RateLimiter,
+    },
+    Config, Direction, OrderType, TradingMode, OptionType, Position, PositionStatus, SessionState, Side, Tick,
 };
 
+/// Ticks retained per token in the live tick buffer
+const TICK_BUFFER_CAPACITY: usize = 256;
+
+/// WebSocket reconnect attempts per detected disconnect
+const WS_RECONNECT_ATTEMPTS: u32 = 5;
+
+/// NSE index token for India VIX
+const INDIA_VIX_TOKEN: &str = "99926017";
+
+/// Hourly window fetched for a token with no stored bars yet
+const HOURLY_INITIAL_WINDOW_HOURS: i64 = 2;
+
+/// Upper bound of the random pause between per-token candle requests
+const CANDLE_FETCH_JITTER_MS: u64 = 250;
+
+/// Token checks while idling between cycles or outside market hours
+const TOKEN_CHECK_INTERVAL_SEC: u64 = 60;
+
+/// Control-file polls while idling, so `rustro flatten` acts within seconds
+const CONTROL_POLL_SEC: u64 = 5;
+
 /// Application state
 pub struct TradingApp {
     config: Arc<Config>,
     event_bus: Arc<EventBus>,
     token_manager: Arc<TokenManager>,
+    token_monitor: TokenMonitor,
     broker_client: Arc<AngelOneClient>,
+    /// Candles and REST LTP: Angel One, with the CSV fallback when configured
+    market_data: Arc<dyn MarketDataSource>,
     paper_broker: Option<Arc<PaperTradingBroker>>,
     websocket: Option<Arc<AngelWebSocket>>,
+    order_updates: Option<Arc<OrderUpdateSocket>>,
     bar_aggregator: Arc<MultiBarAggregator>,
+    tick_buffer: Arc<ConcurrentTickBuffer>,
     instrument_cache: Arc<InstrumentCache>,
     token_extractor: Arc<TokenExtractor>,
-    _order_validator: Arc<OrderValidator>,
+    order_validator: Arc<OrderValidator>,
     strategy: Arc<AdxStrategy>,
     order_manager: Arc<OrderManager>,
     position_manager: Arc<PositionManager>,
@@ -43,38 +83,49 @@ pub struct TradingApp {
     daily_bias_calculator: Arc<DailyBiasCalculator>,
     premarket_selector: Arc<PremarketSelector>,
     hourly_crossover: Arc<HourlyCrossoverMonitor>,
+    oi_tracker: Arc<OiTracker>,
     
     // Bar stores
     daily_bars: Arc<ConcurrentBarStore>,
     hourly_bars: Arc<ConcurrentBarStore>,
+    m15_bars: Arc<ConcurrentBarStore>,
+    vix_bars: Arc<ConcurrentBarStore>,
     
     // Historical data sync
     historical_sync: Arc<HistoricalDataSync>,
+    /// Paces per-token candle requests (config.rate_limit_historical)
+    candle_rate_limiter: Arc<RateLimiter>,
+    /// Per-asset daily/hourly stores, paced by `candle_rate_limiter`
+    asset_sync: Arc<MultiAssetHistoricalSync>,
     
     // State
     session_uuid: String,
     nifty_token: Arc<RwLock<Option<String>>>,
     daily_biases: Arc<RwLock<Vec<DailyBias>>>,
     daily_analysis_done: Arc<RwLock<bool>>,
+    warmup_active: Arc<RwLock<bool>>,
     last_hourly_check: Arc<RwLock<Option<chrono::DateTime<chrono::Utc>>>>,
+    /// IST date the end-of-day sequence last completed for
+    eod_done_for: Arc<RwLock<Option<chrono::NaiveDate>>>,
+    /// IST date the paper replay last started for
+    paper_replay_started_for: Arc<RwLock<Option<chrono::NaiveDate>>>,
+    /// Last session state published (MarketSessionDetermined)
+    session_state: Arc<RwLock<Option<SessionState>>>,
+    /// Outcome of the last token check; gates new entries
+    token_status: Arc<RwLock<TokenStatus>>,
     shutdown: Arc<RwLock<bool>>,
 }
 
 impl TradingApp {
     pub async fn new(config_path: &str) -> Result<Self> {
-        // Initialize logging
-        tracing_subscriber::fmt()
-            .with_env_filter("rustro=info")
-            .init();
-        
         info!("🚀 Starting Rustro Trading Bot...");
         
         // Load configuration
         let config = Arc::new(load_config(config_path)?);
         info!("✅ Configuration loaded");
         
-        // Create data directory
-        tokio::fs::create_dir_all("data").await.ok();
+        // Create data directories
+        tokio::fs::create_dir_all("data/bars").await.ok();
         
         // Create event bus
         let event_bus = Arc::new(EventBus::new("data/events.jsonl".to_string()));
@@ -89,6 +140,7 @@ impl TradingApp {
         
         // Create token manager
         let token_manager = Arc::new(TokenManager::new("data/tokens.json".to_string()));
+        let token_monitor = TokenMonitor::new(Arc::clone(&token_manager), Arc::clone(&event_bus), &config);
         
         // Create broker client
         let broker_client = Arc::new(AngelOneClient::new(
@@ -98,19 +150,34 @@ impl TradingApp {
             config.angel_one_mpin.clone(),
             config.angel_one_totp_secret.clone(),
             config.angel_one_api_key.clone(),
-        ));
+        ).with_product_type(config.product_type));
+        let market_data = with_csv_fallback(
+            Arc::clone(&broker_client) as Arc<dyn MarketDataSource>,
+            config.fallback_candle_dir.as_deref(),
+        );
         
         // Create paper trading broker if enabled
-        let paper_broker = if config.enable_paper_trading {
-            info!("📝 Paper trading mode ENABLED");
-            Some(Arc::new(PaperTradingBroker::new(true, 5.0))) // Auto-fill with 5bps slippage
-        } else {
-            info!("💰 Live trading mode");
-            None
+        let mode = config.trading_mode();
+        let paper_broker = match mode {
+            TradingMode::Paper => {
+                info!("📝 Paper trading mode ENABLED");
+                Some(Arc::new(
+                    PaperTradingBroker::new(true, 5.0) // Auto-fill with 5bps slippage
+                        .with_starting_cash(config.paper_starting_cash)
+                ))
+            }
+            TradingMode::Analysis => {
+                info!("🔍 Analysis mode - signals are logged, no orders are placed");
+                None
+            }
+            TradingMode::Live => {
+                info!("💰 Live trading mode");
+                None
+            }
         };
         
         // Create WebSocket client (optional - can use REST fallback)
-        let websocket = if !config.enable_paper_trading {
+        let websocket = if mode != TradingMode::Paper {
             info!("📡 WebSocket enabled for real-time data");
             Some(Arc::new(AngelWebSocket::new(Arc::clone(&token_manager))))
         } else {
@@ -118,22 +185,31 @@ impl TradingApp {
             None
         };
         
+        // Order-status feed (live fills/rejects without waiting for the order book poll)
+        let order_updates = (mode == TradingMode::Live)
+            .then(|| Arc::new(OrderUpdateSocket::new(Arc::clone(&token_manager))));
+        
         // Create bar aggregator
-        let bar_aggregator = Arc::new(MultiBarAggregator::new(Arc::clone(&event_bus)));
+        let bar_aggregator = Arc::new(
+            MultiBarAggregator::new(Arc::clone(&event_bus)).with_bar_ready_grace(config.bar_ready_grace_sec),
+        );
+        let tick_buffer = Arc::new(ConcurrentTickBuffer::new(TICK_BUFFER_CAPACITY));
         
         // Create instrument cache
         let instrument_cache = Arc::new(InstrumentCache::new(Arc::clone(&broker_client)));
         
         // Create token extractor
-        let token_extractor = Arc::new(TokenExtractor::new(Vec::new())); // Will be updated after instrument download
+        let token_extractor = Arc::new(
+            TokenExtractor::from_config(Vec::new(), &config), // Will be updated after instrument download
+        );
         
         // Create order validator
-        let _order_validator = Arc::new(OrderValidator::new(Arc::clone(&config)));
+        let order_validator = Arc::new(OrderValidator::new(Arc::clone(&config)));
         
         // Create managers
         let strategy = Arc::new(AdxStrategy::new(Arc::clone(&config)));
         let order_manager = Arc::new(OrderManager::new(
-            Arc::clone(&broker_client),
+            Arc::clone(&broker_client) as Arc<dyn OrderBroker>,
             Arc::clone(&event_bus),
             Arc::clone(&config),
         ));
@@ -145,18 +221,21 @@ impl TradingApp {
             Arc::clone(&event_bus),
             Arc::clone(&config),
             Arc::clone(&position_manager),
-        ));
+        ).with_paper_broker(paper_broker.clone()));
         
         // Create hybrid strategy components
         let daily_bias_calculator = Arc::new(DailyBiasCalculator::new(
             config.daily_adx_period,
             config.daily_adx_threshold,
-        ));
+        )
+            .with_method(config.daily_bias_method)
+            .with_ema_periods(config.daily_ema_fast, config.daily_ema_slow));
         let premarket_selector = Arc::new(PremarketSelector::new(Arc::clone(&token_extractor)));
         let hourly_crossover = Arc::new(HourlyCrossoverMonitor::new(
             config.hourly_adx_period,
             config.hourly_adx_threshold,
         ));
+        let oi_tracker = Arc::new(OiTracker::new(config.oi_change_window + 1));
         
         // Create bar stores
         let daily_bars = Arc::new(ConcurrentBarStore::new(
@@ -173,9 +252,25 @@ impl TradingApp {
             500, // Keep 500 hours in memory
         ));
         
+        let m15_bars = Arc::new(ConcurrentBarStore::new(
+            "NIFTY".to_string(),
+            "15m".to_string(),
+            PathBuf::from("data/bars_nifty_15m.jsonl"),
+            500,
+        ));
+        
+        let vix_bars = Arc::new(ConcurrentBarStore::new(
+            "INDIA VIX".to_string(),
+            "1d".to_string(),
+            PathBuf::from("data/bars_india_vix_daily.jsonl"),
+            100,
+        ));
+        
         // Load existing bars from disk
         daily_bars.load_from_disk(100).await.ok();
         hourly_bars.load_from_disk(500).await.ok();
+        m15_bars.load_from_disk(500).await.ok();
+        vix_bars.load_from_disk(100).await.ok();
         
         // Create historical data sync
         let historical_sync = Arc::new(HistoricalDataSync::new(
@@ -186,19 +281,39 @@ impl TradingApp {
             Arc::clone(&config),
         ));
         
+        // One limiter paces every historical candle request the app makes
+        let candle_rate_limiter = Arc::new(RateLimiter::new(config.rate_limit_historical.max(1)));
+        
+        // Per-asset bar stores for the non-NIFTY hourly fetch
+        let mut asset_sync = MultiAssetHistoricalSync::new(
+            Arc::clone(&broker_client),
+            Arc::clone(&instrument_cache),
+            Arc::clone(&config),
+        ).with_rate_limiter(Arc::clone(&candle_rate_limiter));
+        for asset in UnderlyingAsset::all() {
+            asset_sync.register_asset_stores(asset);
+            if let Some(store) = asset_sync.hourly_store(asset.as_str()) {
+                store.load_from_disk(500).await.ok();
+            }
+        }
+        
         let session_uuid = uuid::Uuid::new_v4().to_string();
         
         Ok(TradingApp {
             config,
             event_bus,
             token_manager,
+            token_monitor,
             broker_client,
+            market_data,
             paper_broker,
             websocket,
+            order_updates,
             bar_aggregator,
+            tick_buffer,
             instrument_cache,
             token_extractor,
-            _order_validator,
+            order_validator,
             strategy,
             order_manager,
             position_manager,
@@ -206,14 +321,24 @@ impl TradingApp {
             daily_bias_calculator,
             premarket_selector,
             hourly_crossover,
+            oi_tracker,
             daily_bars,
             hourly_bars,
+            m15_bars,
+            vix_bars,
             historical_sync,
+            asset_sync: Arc::new(asset_sync),
+            candle_rate_limiter,
             session_uuid,
             nifty_token: Arc::new(RwLock::new(None)),
             daily_biases: Arc::new(RwLock::new(Vec::new())),
             daily_analysis_done: Arc::new(RwLock::new(false)),
+            warmup_active: Arc::new(RwLock::new(false)),
             last_hourly_check: Arc::new(RwLock::new(None)),
+            eod_done_for: Arc::new(RwLock::new(None)),
+            paper_replay_started_for: Arc::new(RwLock::new(None)),
+            token_status: Arc::new(RwLock::new(TokenStatus::Healthy)),
+            session_state: Arc::new(RwLock::new(None)),
             shutdown: Arc::new(RwLock::new(false)),
         })
     }
@@ -275,23 +400,18 @@ impl TradingApp {
                                         }
                                     }
                                     
-                                    // Publish SignalGenerated event
+                                    // Publish SignalGenerated event (NoTrade signals carry no option)
+                                    let Ok(option_type) = OptionType::try_from(signal.direction) else {
+                                        continue;
+                                    };
                                     let _ = event_bus.publish(Event::new(
                                         EventType::SignalGenerated,
                                         EventPayload::SignalGenerated {
                                             symbol: signal.underlying.clone(),
                                             underlying: signal.underlying.clone(),
-                                            direction: match signal.direction {
-                                                BiasDirection::CE => Direction::CE,
-                                                BiasDirection::PE => Direction::PE,
-                                                BiasDirection::NoTrade => Direction::NoTrade,
-                                            },
+                                            direction: signal.direction.into(),
                                             strike: 0, // Will be filled by premarket selector
-                                            option_type: match signal.direction {
-                                                BiasDirection::CE => OptionType::CE,
-                                                BiasDirection::PE => OptionType::PE,
-                                                BiasDirection::NoTrade => OptionType::CE,
-                                            },
+                                            option_type,
                                             side: Side::Buy,
                                             reason: format!("Hourly crossover aligned with daily bias"),
                                             underlying_ltp: signal.close_price,
@@ -324,6 +444,9 @@ impl TradingApp {
         // Initialize session (authenticate)
         self.initialize_session().await?;
         
+        // Start periodic portfolio snapshots
+        self.start_snapshot_task();
+        
         // Main trading loop
         loop {
             // Check shutdown flag
@@ -338,10 +461,14 @@ impl TradingApp {
             let now = chrono::Utc::now();
             let today = now.date_naive();
             
+            self.publish_session_transition(now).await;
+            self.handle_control_command().await;
+            self.check_tokens().await;
+            
             // Check if today is a trading day (includes NSE holidays)
             if !is_trading_day_with_holidays(today) {
                 info!("📅 Today is not a trading day (weekend or holiday) - waiting");
-                tokio::time::sleep(tokio::time::Duration::from_secs(3600)).await;
+                self.idle(3600).await;
                 continue;
             }
             
@@ -353,7 +480,7 @@ impl TradingApp {
                 info!("⏰ Market opens at {} IST - waiting {} minutes", 
                       market_open.format("%H:%M:%S"),
                       wait_secs / 60);
-                tokio::time::sleep(tokio::time::Duration::from_secs(wait_secs.min(300))).await;
+                self.idle(wait_secs.min(300)).await;
                 continue;
             }
             
@@ -361,14 +488,29 @@ impl TradingApp {
                 info!("🌙 Market closed for the day");
                 // Reset for next day
                 self.end_of_day_sequence().await?;
-                tokio::time::sleep(tokio::time::Duration::from_secs(3600)).await;
+                self.idle(3600).await;
                 continue;
             }
             
+            // Paper replay starts with the session so its ticks meet the trading cycle
+            let today_ist = now.with_timezone(&chrono_tz::Asia::Kolkata).date_naive();
+            if self.paper_replay_active() && *self.paper_replay_started_for.read().await != Some(today_ist) {
+                *self.paper_replay_started_for.write().await = Some(today_ist);
+                self.start_paper_replay().await;
+            }
+            
             // Market is OPEN - run trading cycle
             if let Err(e) = self.run_trading_cycle().await {
                 error!("❌ Trading cycle error: {} ({})", e, e.error_code());
                 
+                // Maintenance window: pause, keep positions and the session
+                if let TradingError::BrokerMaintenance(_) = e {
+                    let pause = e.retry_after().unwrap_or(std::time::Duration::from_secs(60));
+                    warn!("🛠️  Broker maintenance - pausing {}s", pause.as_secs());
+                    tokio::time::sleep(pause).await;
+                    continue;
+                }
+                
                 if e.is_fatal() {
                     error!("💀 Fatal error - initiating shutdown");
                     break;
@@ -376,12 +518,15 @@ impl TradingApp {
                 
                 if e.requires_exit() {
                     warn!("⚠️  Risk event requires position exit");
-                    let _ = self.position_manager.close_all_positions(e.to_string()).await;
+                    if let Err(cancel_err) = self.order_manager.cancel_all_active("RISK_EXIT").await {
+                        error!("Failed to cancel working orders: {}", cancel_err);
+                    }
+                    let _ = self.flatten_all("RISK_EXIT", e.to_string()).await;
                 }
             }
             
-            // Sleep before next cycle (1 minute intervals)
-            tokio::time::sleep(tokio::time::Duration::from_secs(60)).await;
+            // Wait for the next cycle (1 minute intervals)
+            self.idle(60).await;
         }
         
         // Shutdown sequence
@@ -390,22 +535,101 @@ impl TradingApp {
         Ok(())
     }
     
+    /// Publish `MarketSessionDetermined` when the session state changes
+    async fn publish_session_transition(&self, now: chrono::DateTime<chrono::Utc>) {
+        let state = {
+            let mut last = self.session_state.write().await;
+            let Some(state) = session_transition(*last, now) else {
+                return;
+            };
+            *last = Some(state);
+            state
+        };
+        
+        info!("🕘 Market session: {}", state.as_str());
+        let (market_open, market_close) = get_market_timings(now);
+        let ist = |time: chrono::DateTime<chrono::Utc>| {
+            time.with_timezone(&chrono_tz::Asia::Kolkata).format("%H:%M:%S").to_string()
+        };
+        let _ = self.event_bus.publish(Event::new(
+            EventType::MarketSessionDetermined,
+            EventPayload::MarketSessionDetermined {
+                session_state: state,
+                open_time: ist(market_open),
+                close_time: ist(market_close),
+            },
+        )).await;
+    }
+    
+    /// Start periodic portfolio snapshot task
+    fn start_snapshot_task(&self) {
+        let position_manager = Arc::clone(&self.position_manager);
+        let shutdown = Arc::clone(&self.shutdown);
+        let interval_sec = self.config.snapshot_interval_sec.max(1);
+        let retention = chrono::Duration::days(self.config.snapshot_retention_days as i64);
+        
+        tokio::spawn(async move {
+            let dir = std::path::Path::new(SNAPSHOT_DIR);
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_sec));
+            
+            loop {
+                interval.tick().await;
+                
+                if *shutdown.read().await {
+                    break;
+                }
+                
+                let snapshot = position_manager.portfolio_snapshot().await;
+                if let Err(e) = save_snapshot(dir, &snapshot).await {
+                    warn!("Failed to save portfolio snapshot: {}", e);
+                }
+                
+                if let Err(e) = prune_snapshots(dir, retention, chrono::Utc::now()).await {
+                    warn!("Failed to prune portfolio snapshots: {}", e);
+                }
+            }
+        });
+        
+        info!("✅ Portfolio snapshots every {}s → {}", interval_sec, SNAPSHOT_DIR);
+    }
+    
     /// Start tick processing loop
     async fn start_tick_processing(&self) {
-        if let Some(ws) = &self.websocket {
-            let rx = ws.get_tick_receiver();
+        // Live feed, else paper bar replay
+        let rx = match (&self.websocket, &self.paper_broker) {
+            (Some(ws), _) => Some(ws.get_tick_receiver()),
+            (None, Some(paper_broker)) => Some(paper_broker.get_tick_receiver()),
+            (None, None) => None,
+        };
+        if let Some(rx) = rx {
             let aggregator = Arc::clone(&self.bar_aggregator);
+            let tick_buffer = Arc::clone(&self.tick_buffer);
+            let recorder = self.config.record_ticks.then(|| TickRecorder::new("data"));
             
             tokio::spawn(async move {
                 let mut rx = rx.write().await;
                 
                 while let Some(tick) = rx.recv().await {
+                    // Latest-price cache for position updates
+                    tick_buffer.push(tick.clone()).await;
+                    
+                    if let Some(recorder) = &recorder {
+                        if let Err(e) = recorder.record(&tick).await {
+                            warn!("Failed to record tick: {}", e);
+                        }
+                    }
+                    
                     // Process tick through bar aggregators
                     if let Err(e) = aggregator.process_tick(tick).await {
                         error!("Error processing tick: {}", e);
                     }
                 }
                 
+                if let Some(recorder) = &recorder {
+                    if let Err(e) = recorder.flush().await {
+                        warn!("Failed to flush recorded ticks: {}", e);
+                    }
+                }
                 warn!("Tick processing loop ended");
             });
             
@@ -413,10 +637,179 @@ impl TradingApp {
         }
     }
     
+    /// Paper mode with `paper_replay_file` or `paper_replay_tick_files` set
+    fn paper_replay_active(&self) -> bool {
+        self.paper_broker.is_some()
+            && (self.config.paper_replay_file.is_some() || !self.config.paper_replay_tick_files.is_empty())
+    }
+    
+    /// Paper mode: replay `paper_replay_file` (NIFTY bars as ticks) merged with
+    /// the recorded `paper_replay_tick_files` (e.g. option ticks) in the
+    /// background, through the tick handler
+    async fn start_paper_replay(&self) {
+        let Some(paper_broker) = &self.paper_broker else {
+            return;
+        };
+        
+        let mut ticks = Vec::new();
+        if let Some(file) = &self.config.paper_replay_file {
+            let nifty_token = self.nifty_token.read().await.clone().unwrap_or_default();
+            match PaperTradingBroker::load_replay_bars(file).await {
+                Ok(bars) => ticks.extend(bars_to_ticks(&bars, &nifty_token, "NIFTY")),
+                Err(e) => warn!("⚠️  Paper replay file {} unreadable: {}", file, e),
+            }
+        }
+        for file in &self.config.paper_replay_tick_files {
+            match TickReplay::load(file).await {
+                Ok(recorded) => ticks.extend(recorded),
+                Err(e) => warn!("⚠️  Recorded tick file {} unreadable: {}", file, e),
+            }
+        }
+        if ticks.is_empty() {
+            return;
+        }
+        
+        let paper_broker = Arc::clone(paper_broker);
+        let pace = ReplayPace::from_speed(self.config.paper_replay_speed);
+        info!("⏪ [PAPER] Replaying {} ticks ({:?})", ticks.len(), pace);
+        
+        tokio::spawn(async move {
+            if let Err(e) = paper_broker.replay_ticks(ticks, pace).await {
+                warn!("⚠️  Paper replay stopped: {}", e);
+            }
+        });
+    }
+    
+    /// Connect the order-status feed and apply its updates to tracked orders
+    async fn start_order_update_processing(&self) {
+        let Some(feed) = &self.order_updates else {
+            return;
+        };
+        
+        if let Err(e) = feed.connect().await {
+            warn!("⚠️  Order-update feed connection failed: {} - relying on order book polling", e);
+            return;
+        }
+        
+        let rx = feed.get_update_receiver();
+        let order_manager = Arc::clone(&self.order_manager);
+        let position_manager = Arc::clone(&self.position_manager);
+        
+        tokio::spawn(async move {
+            let mut rx = rx.write().await;
+            
+            while let Some(update) = rx.recv().await {
+                match apply_feed_update(&order_manager, &position_manager, &update).await {
+                    Ok(Some(order_id)) => info!("📬 Order update for {}: {:?}", order_id, update),
+                    Ok(None) => {}
+                    Err(e) => error!("Error applying order update: {}", e),
+                }
+            }
+            
+            warn!("Order-update processing loop ended");
+        });
+        
+        info!("✅ Order-update processing loop started");
+    }
+    
+    /// Reconnect the WebSocket when the feed drops and backfill missed bars
+    fn start_reconnect_watchdog(&self) {
+        let Some(ws) = self.websocket.clone() else {
+            return;
+        };
+        let historical_sync = Arc::clone(&self.historical_sync);
+        let bar_aggregator = Arc::clone(&self.bar_aggregator);
+        let event_bus = Arc::clone(&self.event_bus);
+        let nifty_token = Arc::clone(&self.nifty_token);
+        let shutdown = Arc::clone(&self.shutdown);
+        let gap_threshold = self.config.data_gap_threshold_sec;
+        let interval_sec = self.config.data_gap_check_interval_sec.max(1);
+        
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(interval_sec));
+            
+            loop {
+                interval.tick().await;
+                
+                if *shutdown.read().await {
+                    break;
+                }
+                if ws.is_connected().await {
+                    continue;
+                }
+                
+                let outage = match ws.reconnect(WS_RECONNECT_ATTEMPTS).await {
+                    Ok(outage) => outage,
+                    Err(e) => {
+                        error!("❌ WebSocket reconnect failed: {}", e);
+                        continue;
+                    }
+                };
+                
+                // Historical sync only backs the NIFTY stores
+                let Some(token) = nifty_token.read().await.clone() else {
+                    continue;
+                };
+                if !ws.subscribed().await.iter().any(|(t, _)| *t == token) {
+                    continue;
+                }
+                
+                recover_outage(&historical_sync, &event_bus, &token, Timeframe::OneHour, outage).await;
+                
+                for (symbol, timeframe) in bar_aggregator.check_all_gaps(gap_threshold).await {
+                    warn!("⚠️  Data gap persists after backfill: {} {}", symbol, timeframe.as_str());
+                }
+            }
+        });
+    }
+    
+    /// Base the daily loss limit on today's funds: the paper ledger in paper
+    /// mode, broker RMS otherwise (config default if the fetch fails)
+    async fn refresh_start_capital(&self) {
+        let fetched = match &self.paper_broker {
+            Some(paper_broker) => Ok(paper_broker.equity().await),
+            None => self.broker_client.get_available_funds().await,
+        };
+        self.risk_manager.init_daily_start_capital(fetched).await;
+    }
+    
+    /// Compare the local clock against the broker and warn on excessive drift
+    async fn check_clock_drift(&self) -> Result<()> {
+        let drift = match self.broker_client.measure_clock_drift().await {
+            Ok(drift) => drift,
+            Err(e) => {
+                warn!("⚠️ Could not measure clock drift: {}", e);
+                return Ok(());
+            }
+        };
+        
+        let threshold_sec = self.config.max_clock_drift_sec;
+        if drift.num_milliseconds().unsigned_abs() > threshold_sec * 1000 {
+            warn!(
+                "⚠️ Local clock drifts {} ms from broker (max {}s) - TOTP and bar timing may fail",
+                drift.num_milliseconds(), threshold_sec
+            );
+            self.event_bus.publish(Event::new(
+                EventType::ClockDriftWarning,
+                EventPayload::ClockDriftWarning {
+                    drift_ms: drift.num_milliseconds(),
+                    threshold_sec,
+                },
+            )).await?;
+        } else {
+            info!("🕒 Clock drift vs broker: {} ms", drift.num_milliseconds());
+        }
+        
+        Ok(())
+    }
+    
     /// Initialize session (authentication, data loading)
     async fn initialize_session(&self) -> Result<()> {
         info!("🔐 Initializing session...");
         
+        // TOTP and token expiry depend on the local clock; check it before login
+        self.check_clock_drift().await?;
+        
         // Try to load existing tokens
         match self.token_manager.load_from_file().await {
             Ok(_) => {
@@ -435,6 +828,8 @@ impl TradingApp {
             }
         }
         
+        self.refresh_start_capital().await;
+        
         self.event_bus.publish(Event::new(
             EventType::BrokerClientReady,
             EventPayload::BrokerClientReady {
@@ -442,16 +837,30 @@ impl TradingApp {
             },
         )).await?;
         
-        // Download instrument master
+        // Download instrument master (reuse today's copy if already on disk)
+        if let Err(e) = self.instrument_cache.load_from_file().await {
+            info!("No cached instrument master ({})", e);
+        }
+        
         if self.instrument_cache.needs_refresh().await {
             info!("📥 Downloading instrument master...");
-            self.instrument_cache.refresh().await?;
+            let diff = self.instrument_cache.refresh().await?;
+            
+            // A delisted contract we still hold can no longer be priced or exited normally
+            for position in self.position_manager.get_open_positions().await {
+                if diff.removed.iter().any(|i| i.symbol == position.symbol) {
+                    error!("❌ Open position {} references delisted {}", position.position_id, position.symbol);
+                }
+            }
             
             self.event_bus.publish(Event::new(
                 EventType::InstrumentMasterDownloaded,
                 EventPayload::InstrumentMasterDownloaded {
                     instrument_count: self.instrument_cache.size().await,
                     file_path: "memory".to_string(),
+                    added: diff.added.len(),
+                    removed: diff.removed.len(),
+                    new_expiries: diff.new_expiries,
                 },
             )).await?;
         }
@@ -464,28 +873,37 @@ impl TradingApp {
         }
         info!("✅ NIFTY token: {}", nifty_token);
         
-        // Setup bar aggregators
+        // Setup bar aggregators (ticks are routed by token)
         self.bar_aggregator.add_aggregator(
-            "NIFTY".to_string(),
+            nifty_token.clone(),
             Timeframe::OneHour,
             Arc::clone(&self.hourly_bars),
         ).await;
         
         self.bar_aggregator.add_aggregator(
-            "NIFTY".to_string(),
+            nifty_token.clone(),
             Timeframe::OneDay,
             Arc::clone(&self.daily_bars),
         ).await;
         
+        if self.config.require_15m_confirmation {
+            self.bar_aggregator.add_aggregator(
+                nifty_token.clone(),
+                Timeframe::FifteenMinute,
+                Arc::clone(&self.m15_bars),
+            ).await;
+        }
+        
         // Connect WebSocket if available
         if let Some(ws) = &self.websocket {
             match ws.connect().await {
                 Ok(_) => {
-                    // Subscribe to NIFTY
-                    ws.subscribe(vec![nifty_token.clone()], "NFO").await?;
+                    // Subscribe to NIFTY (index spot token lives on NSE)
+                    ws.subscribe(vec![nifty_token.clone()], "NSE").await?;
                     
                     // Start tick processing loop
                     self.start_tick_processing().await;
+                    self.start_reconnect_watchdog();
                     
                     info!("✅ WebSocket connected and subscribed");
                 }
@@ -495,6 +913,12 @@ impl TradingApp {
             }
         }
         
+        // Replayed ticks go through the same handler as the live feed
+        if self.paper_replay_active() {
+            self.start_tick_processing().await;
+        }
+        self.start_order_update_processing().await;
+        
         // Save NIFTY token to JSON for hourly data
         if let Some(nifty_token) = self.nifty_token.read().await.as_ref() {
             let hourly_tokens = rustro::data::hourly_tokens::HourlyTokensManager::new(
@@ -619,6 +1043,11 @@ impl TradingApp {
         let now = chrono::Utc::now();
         let now_ist = now.with_timezone(&chrono_tz::Asia::Kolkata);
         
+        // Step 0: Nothing more to do once flattened for an expiring session
+        if let TokenStatus::Flattened { .. } = *self.token_status.read().await {
+            return Ok(());
+        }
+        
         // Step 1: Fetch latest data
         self.fetch_and_update_bars().await?;
         
@@ -643,8 +1072,15 @@ impl TradingApp {
             return Ok(());
         }
         
+        // Indicators need extra bars beyond their period to stabilize;
+        // no analysis or entries until then (positions are still managed)
+        let warmed_up = self.check_warmup().await?;
+        
+        // Sample OI for the build-up entry filter
+        self.track_open_interest().await;
+        
         // Step 3: Daily analysis (runs once at 9:30 AM)
-        if now_ist.hour() >= 9 && now_ist.minute() >= 30 {
+        if warmed_up && now_ist.hour() >= 9 && now_ist.minute() >= 30 {
             let daily_done = self.daily_analysis_done.read().await;
             if !*daily_done {
                 drop(daily_done);
@@ -653,7 +1089,7 @@ impl TradingApp {
         }
         
         // Step 4: Hourly analysis (runs every hour after bar completes)
-        if now_ist.minute() >= 15 {
+        if warmed_up && now_ist.minute() >= 15 {
             let last_check = self.last_hourly_check.read().await;
             let should_run = match *last_check {
                 None => true,
@@ -669,17 +1105,64 @@ impl TradingApp {
             }
         }
         
-        // Step 5: Update open positions
+        // Step 5: Escalate unfilled entry limits, reconcile duplicate fills,
+        // cancel timed-out partial fills, then update open positions
+        self.order_manager.escalate_unfilled_limits(now).await?;
+        if self.config.trading_mode() == TradingMode::Live && self.order_manager.needs_order_book(now).await {
+            match self.broker_client.get_order_book().await {
+                Ok(order_book) => {
+                    self.order_manager.apply_broker_fills(&order_book).await?;
+                    self.order_manager.reconcile_fills(&order_book).await?;
+                }
+                Err(e) => warn!("⚠️  Order book fetch failed: {}", e),
+            }
+        }
+        for order in self.order_manager.cancel_stale_partial_fills(now).await? {
+            // Live positions are keyed by order ID
+            if order.fill_quantity > 0 {
+                self.position_manager.settle_quantity(&order.order_id, order.fill_quantity).await?;
+            }
+        }
         self.update_positions().await?;
+        self.scale_in_winners().await?;
+        
+        // Expiry day: optional earlier force-exit
+        self.expiry_exit_positions().await?;
         
         // Step 6: Check EOD exit (3:20 PM)
-        if now_ist.hour() == 15 && now_ist.minute() >= 20 {
+        if self.position_manager.is_eod_exit_due() {
             self.eod_exit_positions().await?;
         }
         
         Ok(())
     }
     
+    /// Check indicator warmup; emits NoTradeModeActive when warmup starts
+    async fn check_warmup(&self) -> Result<bool> {
+        let daily_count = self.daily_bars.total_count().await;
+        let hourly_count = self.hourly_bars.total_count().await;
+        let warmed_up = self.strategy.is_warmed_up(daily_count, hourly_count);
+        
+        let mut warmup_active = self.warmup_active.write().await;
+        if !warmed_up && !*warmup_active {
+            let (daily_required, hourly_required) = self.strategy.warmup_bars_required();
+            info!("⏳ Indicator warmup: {}/{} daily, {}/{} hourly bars - no trading",
+                  daily_count, daily_required, hourly_count, hourly_required);
+            
+            self.event_bus.publish(Event::new(
+                EventType::NoTradeModeActive,
+                EventPayload::NoTradeModeActive {
+                    reason: "warmup".to_string(),
+                },
+            )).await?;
+        } else if warmed_up && *warmup_active {
+            info!("✅ Indicator warmup complete");
+        }
+        *warmup_active = !warmed_up;
+        
+        Ok(warmed_up)
+    }
+    
     /// Fetch latest bars from broker
     async fn fetch_and_update_bars(&self) -> Result<()> {
         // Load tokens from JSON for hourly data
@@ -693,19 +1176,40 @@ impl TradingApp {
                 format!("Failed to load hourly tokens: {}", e)
             )))?;
         
-        // Fetch hourly bars for each token
+        if let Err(e) = self.refresh_vix().await {
+            warn!("⚠️  India VIX refresh failed: {}", e);
+        }
+        
+        // Fetch closed hourly bars since each token's last stored one
         for (underlying, token) in tokens_map {
-            let to_date = chrono::Utc::now();
-            let from_date = to_date - chrono::Duration::hours(2); // Last 2 hours
+            let store = if underlying == "NIFTY" {
+                Arc::clone(&self.hourly_bars)
+            } else if let Some(store) = self.asset_sync.hourly_store(&underlying) {
+                store
+            } else {
+                warn!("⚠️  No hourly bar store for {} - skipping", underlying);
+                continue;
+            };
+            let last_stored = store.get_last().await.map(|bar| bar.timestamp);
             
-            match self.broker_client.get_candles(&token, "ONE_HOUR", from_date, to_date).await {
+            let now = chrono::Utc::now();
+            let initial = chrono::Duration::hours(HOURLY_INITIAL_WINDOW_HOURS);
+            let Some((from_date, to_date)) = incremental_range(last_stored, Timeframe::OneHour, now, initial) else {
+                continue;
+            };
+            
+            // Rate limit plus jitter so many tokens don't fire in lockstep
+            self.candle_rate_limiter.acquire().await;
+            let jitter_ms = fastrand::u64(0..=CANDLE_FETCH_JITTER_MS);
+            tokio::time::sleep(tokio::time::Duration::from_millis(jitter_ms)).await;
+            
+            match self.market_data.get_candles(&token, "ONE_HOUR", from_date, to_date).await {
                 Ok(bars) => {
+                    let bars = new_closed_bars(bars, last_stored, Timeframe::OneHour, now);
                     let bars_count = bars.len();
                     if !bars.is_empty() {
                         for bar in bars {
-                            if underlying == "NIFTY" {
-                                self.hourly_bars.append(bar).await?;
-                            }
+                            store.append(bar).await?;
                         }
                         info!("📊 Updated {} hourly bars for {}", bars_count, underlying);
                     }
@@ -719,6 +1223,28 @@ impl TradingApp {
         Ok(())
     }
     
+    /// Append completed India VIX daily bars and feed the latest close to risk
+    /// The last candle may still be forming: it sets the live VIX but is
+    /// only stored once a newer day arrives
+    async fn refresh_vix(&self) -> Result<()> {
+        let to_date = chrono::Utc::now();
+        let from_date = to_date - chrono::Duration::days(7);
+        let bars = self.market_data.get_candles(INDIA_VIX_TOKEN, "ONE_DAY", from_date, to_date).await?;
+        
+        let last_stored = self.vix_bars.get_last().await.map(|b| b.timestamp);
+        for bar in bars.iter().take(bars.len().saturating_sub(1)) {
+            if last_stored.is_none_or(|ts| bar.timestamp > ts) {
+                self.vix_bars.append(bar.clone()).await?;
+            }
+        }
+        
+        if let Some(latest) = bars.last() {
+            self.risk_manager.update_vix(latest.close).await?;
+        }
+        
+        Ok(())
+    }
+    
     /// Run daily direction analysis
     async fn run_daily_analysis(&self) -> Result<()> {
         info!("📊 Running daily bias calculation for all F&O underlyings...");
@@ -733,7 +1259,7 @@ impl TradingApp {
         
         // TODO: Load daily_bias_tokens.json and fetch bars for all underlyings
         // For now, just do NIFTY as example
-        let daily_bars_vec = self.daily_bars.get_recent(30).await?;
+        let daily_bars_vec = self.daily_bars.get_recent_complete(30).await?;
         
         if daily_bars_vec.len() < self.config.daily_adx_period {
             warn!("⚠️  Insufficient daily bars for analysis: have {}, need {}", 
@@ -744,14 +1270,31 @@ impl TradingApp {
             ));
         }
         
+        // Set the strategy's own daily direction (required by hourly analysis);
+        // an underlying that fails is skipped for the day, not the whole analysis
+        let strategy_direction = match self.strategy.analyze_daily(&daily_bars_vec).await {
+            Ok(direction) => Some(direction),
+            Err(e) => {
+                error!("❌ Daily direction for NIFTY failed: {} - skipping NIFTY today", e);
+                None
+            }
+        };
+        
         // Calculate bias for NIFTY
-        if let Some(nifty_token) = self.nifty_token.read().await.as_ref() {
+        if let (Some(strategy_direction), Some(nifty_token)) = (strategy_direction, self.nifty_token.read().await.as_ref()) {
             if let Some(bias) = self.daily_bias_calculator.calculate_bias(
                 "NIFTY",
                 nifty_token,
                 &daily_bars_vec,
             ) {
-                info!("✅ NIFTY daily bias: {} (ADX: {:.2})", bias.bias.as_str(), bias.adx);
+                info!("✅ NIFTY daily bias: {} (ADX: {:.2}, confidence: {:.2}{})",
+                      bias.bias.as_str(), bias.adx, bias.confidence,
+                      bias.no_trade_reason.as_deref().map(|r| format!(", {}", r)).unwrap_or_default());
+                
+                if bias.bias.as_str() != strategy_direction.as_str() {
+                    warn!("⚠️  Daily bias ({}) differs from strategy direction ({})",
+                          bias.bias.as_str(), strategy_direction.as_str());
+                }
                 
                 // Store bias in memory
                 let mut biases = self.daily_biases.write().await;
@@ -767,17 +1310,18 @@ impl TradingApp {
                 
                 // Also save to latest file for easy access
                 tokio::fs::write("data/daily_bias_latest.json", &bias_json).await?;
+
+                // Cross-underlying picture for dashboards
+                if let Err(e) = DailyBiasCalculator::summarize(&biases).save(BIAS_SUMMARY_FILE).await {
+                    warn!("⚠️  Failed to save bias summary: {}", e);
+                }
                 
                 // Publish event
                 self.event_bus.publish(Event::new(
                     EventType::DailyDirectionDetermined,
                     EventPayload::DailyDirectionDetermined {
                         symbol: "NIFTY".to_string(),
-                        direction: match bias.bias {
-                            BiasDirection::CE => Direction::CE,
-                            BiasDirection::PE => Direction::PE,
-                            BiasDirection::NoTrade => Direction::NoTrade,
-                        },
+                        direction: bias.bias.into(),
                         daily_adx: bias.adx,
                         daily_plus_di: bias.plus_di,
                         daily_minus_di: bias.minus_di,
@@ -797,7 +1341,7 @@ impl TradingApp {
     async fn run_hourly_analysis(&self) -> Result<()> {
         info!("🔍 Running hourly analysis...");
         
-        let hourly_bars_vec = self.hourly_bars.get_recent(30).await?;
+        let hourly_bars_vec = self.hourly_bars.get_recent_complete(30).await?;
         
         if hourly_bars_vec.len() < self.config.hourly_adx_period {
             warn!("⚠️  Insufficient hourly bars for analysis");
@@ -814,17 +1358,34 @@ impl TradingApp {
             return Ok(());
         }
         
+        // Optional 15m confirmation; `evaluate_entry` rejects entries without it
+        if self.config.require_15m_confirmation {
+            let m15_bars_vec = self.m15_bars.get_recent_complete(30).await?;
+            match self.strategy.analyze_15m(&m15_bars_vec).await {
+                Ok(true) => info!("✅ 15m confirms daily direction"),
+                Ok(false) => info!("❌ 15m not aligned with daily"),
+                Err(e) => warn!("⚠️  15m confirmation unavailable: {}", e),
+            }
+        }
+        
         // Check if we're in entry window
-        let now = chrono::Utc::now();
-        if !is_in_entry_window(now, &self.config.entry_window_start, &self.config.entry_window_end) {
+        if !self.risk_manager.is_entry_window_open("NIFTY") {
             info!("⏰ Outside entry window");
             let mut last_check = self.last_hourly_check.write().await;
             *last_check = Some(chrono::Utc::now());
             return Ok(());
         }
         
+        // No entries on stale indicators (dead feed)
+        if let Err(e) = self.risk_manager.check_bar_freshness("NIFTY", Timeframe::OneHour, &self.hourly_bars).await {
+            warn!("⚠️  {}", e);
+            let mut last_check = self.last_hourly_check.write().await;
+            *last_check = Some(chrono::Utc::now());
+            return Ok(());
+        }
+        
         // Pre-entry risk check
-        if let Err(e) = self.risk_manager.pre_entry_risk_check().await {
+        if let Err(e) = self.risk_manager.pre_entry_risk_check("NIFTY").await {
             warn!("⚠️  Risk check failed: {}", e);
             let mut last_check = self.last_hourly_check.write().await;
             *last_check = Some(chrono::Utc::now());
@@ -840,7 +1401,8 @@ impl TradingApp {
         // Evaluate entry
         if let Some(signal) = self.strategy.evaluate_entry(&hourly_bars_vec, underlying_ltp, vix).await? {
             info!("🎯 Entry signal generated!");
-            self.execute_entry(signal).await?;
+            let signal_bar_time = hourly_bars_vec.last().map(|b| b.timestamp).unwrap_or_else(chrono::Utc::now);
+            self.execute_entry(signal, signal_bar_time).await?;
         }
         
         let mut last_check = self.last_hourly_check.write().await;
@@ -849,23 +1411,152 @@ impl TradingApp {
         Ok(())
     }
     
-    /// Execute entry based on signal
-    async fn execute_entry(&self, signal: EntrySignal) -> Result<()> {
-        info!("📈 Executing entry: {:?} @ {}", signal.option_type, signal.strike);
+    /// Lot size of the traded contract from the instrument master, else the
+    /// configured size for `underlying` (NSE revises lots between configs)
+    async fn lot_size(&self, symbol: &str, underlying: &str) -> i32 {
+        match self.instrument_cache.get_by_symbol(symbol).await {
+            Some(instrument) if instrument.lotsize > 0 => instrument.lotsize,
+            _ => self.config.get_lot_size(underlying),
+        }
+    }
+    
+    /// Add `scale_in_lots` to positions past `scale_in_pnl_pct` while the daily
+    /// direction and hourly alignment still agree with them
+    async fn scale_in_winners(&self) -> Result<()> {
+        let candidates = self.position_manager.scale_in_candidates().await;
+        if candidates.is_empty() {
+            return Ok(());
+        }
         
-        // Calculate position size
-        let vix = self.risk_manager.get_current_vix().await.unwrap_or(20.0);
-        let dte = calculate_days_to_expiry(chrono::Utc::now());
-        let quantity = self.risk_manager.calculate_position_size(1_000_000.0, vix, dte);
+        let hourly_bars_vec = self.hourly_bars.get_recent_complete(30).await?;
+        let daily_direction = self.strategy.get_daily_direction().await;
+        
+        for position in candidates {
+            let direction = Direction::from(position.option_type);
+            if daily_direction != Some(direction)
+                || self.strategy.check_technical_exit(direction, &hourly_bars_vec).await
+            {
+                info!("Scale-in skipped for {}: alignment no longer holds", position.position_id);
+                continue;
+            }
+            
+            let price = round_to_tick(self.position_price(&position).await, self.config.tick_size);
+            let lot_size = self.lot_size(&position.symbol, &position.underlying).await;
+            let quantity = self.config.scale_in_lots as i32 * lot_size;
+            let total = self.risk_manager.apply_notional_cap(lot_size, position.quantity + quantity, price);
+            let add_quantity = total - position.quantity;
+            if add_quantity <= 0 {
+                info!("⚠️  Notional cap leaves no room to scale in {}", position.position_id);
+                continue;
+            }
+            
+            match self.place_scale_in(&position, add_quantity, price).await {
+                Ok((fill_quantity, fill_price)) => {
+                    self.position_manager.scale_in(&position.position_id, fill_quantity, fill_price).await?;
+                }
+                Err(e) => warn!("⚠️  Scale-in for {} failed: {}", position.position_id, e),
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Place the scale-in order; returns the filled quantity and fill price
+    async fn place_scale_in(&self, position: &Position, quantity: i32, price: f64) -> Result<(i32, f64)> {
+        if let Some(paper_broker) = &self.paper_broker {
+            let order_id = paper_broker.place_order(
+                position.symbol.clone(),
+                position.side,
+                quantity,
+                OrderType::Limit,
+                Some(price),
+            ).await?;
+            return Ok((quantity, paper_broker.get_fill_price(&order_id).await.unwrap_or(price)));
+        }
+        
+        if self.config.use_cover_orders {
+            // The broker-side stop only covers the original quantity
+            return Err(TradingError::InvalidStrategyState("scale-in not supported with cover orders".to_string()));
+        }
         
-        // Generate idempotency key
+        let token = self.instrument_cache.get_by_symbol(&position.symbol).await
+            .map(|i| i.token)
+            .ok_or_else(|| TradingError::InstrumentNotFound(position.symbol.clone()))?;
         let idempotency_key = generate_idempotency_key(&[
             &self.session_uuid,
-            "NIFTY",
-            signal.option_type.as_str(),
-            &signal.strike.to_string(),
-            &chrono::Utc::now().timestamp_millis().to_string(),
+            &position.position_id,
+            "SCALE_IN",
         ]);
+        let order_id = self.order_manager.place_order(
+            position.symbol.clone(),
+            token,
+            position.side,
+            quantity,
+            price,
+            idempotency_key,
+        ).await?;
+        
+        // Book only what filled; the unfilled remainder is cancelled
+        let wait = std::time::Duration::from_secs(self.config.entry_fill_wait_sec);
+        let order = self.order_manager.fill_or_cancel(&order_id, wait).await?;
+        Ok((order.fill_quantity, order.fill_price.unwrap_or(price)))
+    }
+    
+    /// Execute entry based on signal
+    async fn execute_entry(&self, signal: EntrySignal, signal_bar_time: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        info!("📈 Executing entry: {:?} @ {}", signal.option_type, signal.strike);
+        
+        let vix = self.risk_manager.get_current_vix().await.unwrap_or(20.0);
+        let _ = self.event_bus.publish(Event::new(
+            EventType::SignalGenerated,
+            EventPayload::SignalGenerated {
+                symbol: "NIFTY".to_string(),
+                underlying: "NIFTY".to_string(),
+                direction: signal.direction,
+                strike: signal.strike,
+                option_type: signal.option_type,
+                side: signal.side,
+                reason: signal.reason.clone(),
+                underlying_ltp: signal.underlying_ltp,
+                option_ltp: 0.0,
+                vix,
+            },
+        )).await;
+        
+        let mode = self.config.trading_mode();
+        let now = chrono::Utc::now();
+        if mode == TradingMode::Live {
+            if let Err(e) = self.order_validator.check_session(now, false) {
+                warn!("⚠️  Entry refused: {}", e);
+                return Ok(());
+            }
+        }
+        let token_status = self.token_status.read().await.clone();
+        if !token_status.allows_entries() {
+            warn!("⚠️  Entry refused: session tokens not refreshed ({:?})", token_status);
+            return Ok(());
+        }
+        
+        // Calculate position size
+        let expiry = next_weekly_expiry(now);
+        let dte = if self.config.use_trading_days_for_dte {
+            calculate_trading_days_to_expiry(now, expiry)
+        } else {
+            calculate_days_to_expiry(now, expiry)
+        };
+        let capital = match &self.paper_broker {
+            Some(paper_broker) => paper_broker.equity().await,
+            None => self.risk_manager.get_daily_start_capital().await,
+        };
+        // Thin ATM: move to a liquid neighbouring strike
+        let mut signal = signal;
+        match self.liquid_entry_strike(&signal).await {
+            Some(strike) => signal.strike = strike,
+            None => {
+                warn!("⚠️  No strike near {} meets min volume/OI {} - skipping entry", signal.strike, self.config.min_strike_volume);
+                return Ok(());
+            }
+        }
         
         // Get actual token and symbol from instrument cache
         let (token, symbol) = self.instrument_cache
@@ -874,91 +1565,182 @@ impl TradingApp {
         
         info!("📍 Using instrument: {} (token: {})", symbol, token);
         
-        // Placeholder option price
-        let option_price = 125.0;
+        let lot_size = self.lot_size(&symbol, "NIFTY").await;
+        let quantity = self.risk_manager.calculate_position_size(lot_size, capital, vix, dte);
+        
+        // Expiry day: no late entries, and a tighter stop on those that go through
+        let instrument_expiry = self.instrument_cache.get_by_symbol(&symbol).await
+            .map(|instrument| instrument.expiry)
+            .unwrap_or_default();
+        if self.risk_manager.is_expiry_entry_blocked(&instrument_expiry) {
+            warn!("⚠️  {} expires today - past expiry_no_entry_time, skipping entry", symbol);
+            let _ = self.event_bus.publish(Event::new(
+                EventType::NoTradeSignal,
+                EventPayload::NoTradeSignal {
+                    symbol: symbol.clone(),
+                    reason: "expiry_day_cutoff".to_string(),
+                },
+            )).await;
+            return Ok(());
+        }
+        let stop_loss_pct = self.risk_manager.entry_stop_pct(&instrument_expiry);
+        
+        if let Some(min_pct) = self.config.min_oi_buildup_pct {
+            let change = self.oi_tracker.oi_change_pct(&token, self.config.oi_change_window).await;
+            if !oi_buildup_confirms(change, min_pct) {
+                warn!("⚠️  {} OI change {:?}% below {:.1}% build-up - skipping entry", symbol, change, min_pct);
+                return Ok(());
+            }
+        }
         
-        let order_id: String;
-        let filled_price: f64;
-
-        if self.config.enable_paper_trading {
-            if let Some(paper_broker) = &self.paper_broker {
-                // Use paper trading broker
-                order_id = paper_broker.place_order(
-                    symbol.clone(),
-                    signal.side,
-                    quantity,
-                    OrderType::Limit, // Assuming Limit for paper trades
-                    Some(option_price),
-                ).await?;
-                filled_price = paper_broker.get_fill_price(&order_id).await.unwrap_or(option_price);
-                info!("📝 [PAPER] Order executed: {} @ {:.2}", order_id, filled_price);
-            } else {
-                return Err(TradingError::ConfigError("Paper trading enabled but broker not initialized".to_string()));
+        // Wide quotes mean bad fills
+        let bid_ask = self.option_bid_ask(&token).await;
+        if let Some((bid, ask)) = bid_ask {
+            if let Err(e) = self.order_validator.check_spread(bid, ask) {
+                warn!("⚠️  {}: {} - skipping entry", symbol, e);
+                let _ = self.event_bus.publish(Event::new(
+                    EventType::NoTradeSignal,
+                    EventPayload::NoTradeSignal {
+                        symbol: symbol.clone(),
+                        reason: "wide_spread".to_string(),
+                    },
+                )).await;
+                return Ok(());
             }
-        } else {
-            // Use live order manager
-            order_id = self.order_manager.place_order(
+        }
+        
+        // Limit from the live quote; placeholder price without one
+        let option_price = round_to_tick(
+            self.entry_limit_price(bid_ask).unwrap_or(125.0),
+            self.config.tick_size,
+        );
+        
+        let quantity = self.risk_manager.apply_notional_cap(lot_size, quantity, option_price);
+        if quantity == 0 {
+            warn!("⚠️  Notional cap leaves no tradeable lot - skipping entry");
+            return Ok(());
+        }
+        
+        // Analysis: every check above ran; stop short of the order
+        if !mode.places_orders() {
+            info!(
+                "🔍 [ANALYSIS] Would {:?} {} x{} @ {:.2} (confidence {:.2}) - {}",
+                signal.side, symbol, quantity, option_price, signal.confidence, signal.reason
+            );
+            return Ok(());
+        }
+        
+        if let Err(e) = self.risk_manager.check_paper_cash(option_price, quantity).await {
+            warn!("⚠️  {} - skipping entry", e);
+            return Ok(());
+        }
+        
+        // Stable per signal bar so re-evaluating the same signal is deduplicated
+        let idempotency_key = signal_idempotency_key(
+            &self.session_uuid,
+            "NIFTY",
+            signal.option_type.as_str(),
+            signal.strike,
+            signal_bar_time,
+        );
+        
+        // Every log for this trade, through fill and close, carries its span
+        let span = self.position_manager.new_trade_span(&idempotency_key);
+        async {
+            let paper_broker = match mode {
+                TradingMode::Paper => Some(self.paper_broker.as_deref().ok_or_else(|| {
+                    TradingError::ConfigError("Paper trading enabled but broker not initialized".to_string())
+                })?),
+                _ => None,
+            };
+            
+            let mut order = new_entry_order(
                 symbol.clone(),
                 token.to_string(),
                 signal.side,
                 quantity,
                 option_price,
                 idempotency_key.clone(),
-            ).await?;
-            // In a live scenario, you would wait for a fill event.
-            // For now, we assume it's filled at the requested price.
-            filled_price = option_price;
-            info!("✅ Live order placed: {}", order_id);
-        }
-
-        // Create and open the position with the correct fill price
-        let position = Position {
-            position_id: order_id.clone(),
-            symbol,
-            underlying: "NIFTY".to_string(),
-            strike: signal.strike,
-            option_type: signal.option_type,
-            side: signal.side,
-            quantity,
-            entry_price: filled_price, // Use the actual filled price
-            entry_time: chrono::Utc::now(),
-            entry_time_ms: chrono::Utc::now().timestamp_millis(),
-            underlying_entry: signal.underlying_ltp,
-            stop_loss: filled_price * (1.0 - self.config.option_stop_loss_pct),
-            target: None,
-            trailing_stop: None,
-            trailing_active: false,
-            current_price: filled_price,
-            pnl: 0.0,
-            pnl_pct: 0.0,
-            status: PositionStatus::Open,
-            entry_reason: signal.reason,
-            idempotency_key,
-        };
+            );
+            if paper_broker.is_none() && self.config.use_cover_orders {
+                // Cover order keeps a broker-side stop even if the bot goes down
+                let stop_price = round_to_tick(
+                    option_price * (1.0 - stop_loss_pct),
+                    self.config.tick_size,
+                );
+                info!("🛡️  Sending as cover order (stop @ {:.2})", stop_price);
+                order = order.with_stop_price(stop_price);
+            }
+            
+            // A signal bar already traded (re-run before a new bar closed, or
+            // after an earlier error) must not open a second position
+            let wait = std::time::Duration::from_secs(self.config.entry_fill_wait_sec);
+            let Some(fill) = place_entry(
+                &self.order_manager,
+                &self.position_manager,
+                paper_broker,
+                order,
+                span.clone(),
+                wait,
+            ).await? else {
+                warn!("⚠️  Signal bar {} already entered - skipping", signal_bar_time);
+                return Ok(());
+            };
+            let EntryFill { order_id, fill_price: filled_price, fill_quantity: filled_quantity } = fill;
+            
+            // Create and open the position with the correct fill price
+            let position = Position {
+                position_id: order_id.clone(),
+                symbol,
+                underlying: "NIFTY".to_string(),
+                strike: signal.strike,
+                option_type: signal.option_type,
+                side: signal.side,
+                quantity: filled_quantity,
+                entry_price: filled_price, // Use the actual filled price
+                entry_time: chrono::Utc::now(),
+                entry_time_ms: chrono::Utc::now().timestamp_millis(),
+                underlying_entry: signal.underlying_ltp,
+                stop_loss: filled_price * (1.0 - stop_loss_pct),
+                target: None,
+                trailing_stop: None,
+                trailing_active: false,
+                current_price: filled_price,
+                pnl: 0.0,
+                pnl_pct: 0.0,
+                status: PositionStatus::Open,
+                entry_reason: signal.reason,
+                idempotency_key,
+                exit_time: None,
+                legs: Vec::new(),
+            };
 
-        self.position_manager.open_position(position.clone()).await?;
-        
-        // Save position to JSON
-        let position_file = format!("data/position_{}_{}.json", 
-                                   position.symbol, 
-                                   chrono::Utc::now().format("%Y%m%d_%H%M%S"));
-        let position_json = serde_json::to_string_pretty(&position)?;
-        tokio::fs::write(&position_file, &position_json).await?;
-        info!("💾 Saved position to: {}", position_file);
-        
-        // Append to daily positions log
-        let daily_positions_file = format!("data/positions_{}.jsonl", 
-                                          chrono::Utc::now().format("%Y%m%d"));
-        let position_json_line = serde_json::to_string(&position)?;
-        use tokio::io::AsyncWriteExt;
-        let mut file = tokio::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&daily_positions_file)
-            .await?;
-        file.write_all(format!("{}\n", position_json_line).as_bytes()).await?;
-        
-        Ok(())
+            self.position_manager.open_position(position.clone()).await?;
+        
+            // Save position to JSON
+            let position_file = format!("data/position_{}_{}.json", 
+                                       position.symbol, 
+                                       chrono::Utc::now().format("%Y%m%d_%H%M%S"));
+            let position_json = serde_json::to_string_pretty(&position)?;
+            tokio::fs::write(&position_file, &position_json).await?;
+            info!("💾 Saved position to: {}", position_file);
+        
+            // Append to daily positions log
+            let daily_positions_file = format!("data/positions_{}.jsonl", 
+                                              chrono::Utc::now().format("%Y%m%d"));
+            let position_json_line = serde_json::to_string(&position)?;
+            use tokio::io::AsyncWriteExt;
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&daily_positions_file)
+                .await?;
+            file.write_all(format!("{}\n", position_json_line).as_bytes()).await?;
+        
+            Ok(())
+        }
+        .instrument(span.clone())
+        .await
     }
     
     /// Update open positions with current prices
@@ -966,51 +1748,340 @@ impl TradingApp {
         let positions = self.position_manager.get_open_positions().await;
         
         for position in positions {
-            // Fetch current price (placeholder - would fetch from broker)
-            let current_price = position.entry_price * 1.02; // Placeholder: 2% up
+            let span = self.position_manager.trade_span(&position.position_id).await;
+            self.update_position(position).instrument(span).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Mark one position to market and exit it if a rule fires
+    async fn update_position(&self, position: Position) -> Result<()> {
+        let current_price = self.position_price(&position).await;
+        
+        if let Some(paper_broker) = &self.paper_broker {
+            paper_broker.mark_price(&position.symbol, current_price).await;
+        }
+        
+        // Update position
+        if let Some(exit_reason) = self.position_manager.update_position(
+            &position.position_id,
+            current_price,
+        ).await? {
+            // Exit signal generated
+            info!("🚪 Exit signal for {}: {}", position.position_id, exit_reason);
             
-            // Update position
-            if let Some(exit_reason) = self.position_manager.update_position(
+            // Live: the position stays open until the exit fills; the rule
+            // fires again next cycle if it didn't
+            let exit_price = match self.execute_exit(&position, current_price, &exit_reason).await {
+                Ok(exit_price) => exit_price,
+                Err(e) => {
+                    error!("❌ Exit for {} not filled: {}", position.position_id, e);
+                    return Ok(());
+                }
+            };
+            
+            // Close position
+            let trade = self.position_manager.close_position(
                 &position.position_id,
-                current_price,
-            ).await? {
-                // Exit signal generated
-                info!("🚪 Exit signal for {}: {}", position.position_id, exit_reason);
-                
-                // Close position
-                self.position_manager.close_position(
-                    &position.position_id,
-                    current_price,
-                    exit_reason.clone(),
-                ).await?;
-                
-                // Save closed position to JSON
-                if let Some(closed_position) = self.position_manager.get_position(&position.position_id).await {
-                    let exit_file = format!("data/exit_{}_{}.json", 
-                                          closed_position.symbol,
-                                          chrono::Utc::now().format("%Y%m%d_%H%M%S"));
-                    let exit_json = serde_json::to_string_pretty(&closed_position)?;
-                    tokio::fs::write(&exit_file, &exit_json).await?;
-                    info!("💾 Saved exit to: {}", exit_file);
-                    
-                    // Append to daily exits log
-                    let daily_exits_file = format!("data/exits_{}.jsonl", 
-                                                  chrono::Utc::now().format("%Y%m%d"));
-                    let exit_json_line = serde_json::to_string(&closed_position)?;
-                    use tokio::io::AsyncWriteExt;
-                    let mut file = tokio::fs::OpenOptions::new()
-                        .create(true)
-                        .append(true)
-                        .open(&daily_exits_file)
-                        .await?;
-                    file.write_all(format!("{}\n", exit_json_line).as_bytes()).await?;
+                exit_price,
+                exit_reason.clone(),
+            ).await?;
+            
+            // Save the exit from the returned trade (the position is gone from the book)
+            save_exit_record(std::path::Path::new("data"), &trade).await?;
+            
+            // A losing streak at the limit halts entries for the day
+            self.risk_manager.check_consecutive_losses(trade.pnl_net > 0.0).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Current price for a position: fresh tick, else REST LTP, else placeholder
+    async fn position_price(&self, position: &Position) -> f64 {
+        let placeholder = position.entry_price * 1.02; // Placeholder: 2% up
+        
+        // Paper replay: the replayed ticks are the market
+        if let (true, Some(paper_broker)) = (self.paper_replay_active(), &self.paper_broker) {
+            if let Some(mark) = paper_broker.mark(&position.symbol).await {
+                return mark;
+            }
+        }
+        
+        let Some(token) = self.instrument_cache.get_by_symbol(&position.symbol).await.map(|i| i.token) else {
+            return placeholder;
+        };
+        
+        let max_age = chrono::Duration::seconds(self.config.tick_staleness_sec as i64);
+        match live_ltp(
+            &self.tick_buffer,
+            self.market_data.as_ref(),
+            &self.event_bus,
+            &token,
+            &position.symbol,
+            max_age,
+        ).await {
+            Ok(ltp) => ltp,
+            Err(e) => {
+                warn!("⚠️  REST LTP failed for {}: {} - using placeholder", position.symbol, e);
+                placeholder
+            }
+        }
+    }
+    
+    /// Latest tick for a token unless older than `tick_staleness_sec`
+    async fn fresh_tick(&self, token: &str) -> Option<Tick> {
+        let max_age = chrono::Duration::seconds(self.config.tick_staleness_sec as i64);
+        self.tick_buffer.fresh(token, max_age).await
+    }
+    
+    /// Option bid/ask: a fresh two-sided tick, else the REST full quote's
+    /// depth (the WebSocket streams only the underlying, in LTP mode)
+    async fn option_bid_ask(&self, token: &str) -> Option<(f64, f64)> {
+        if let Some(tick) = self.fresh_tick(token).await.filter(|t| t.bid > 0.0 && t.ask > 0.0) {
+            return Some((tick.bid, tick.ask));
+        }
+        match self.broker_client.get_quote(token).await {
+            Ok(quote) => Some((quote.bid, quote.ask)),
+            Err(e) => {
+                warn!("⚠️  Quote for {} failed: {}", token, e);
+                None
+            }
+        }
+    }
+    
+    /// Entry LIMIT price from the live bid/ask per `entry_price_mode`
+    fn entry_limit_price(&self, bid_ask: Option<(f64, f64)>) -> Option<f64> {
+        let (bid, ask) = bid_ask?;
+        self.config.entry_price_mode.limit_price(
+            bid,
+            ask,
+            self.config.tick_size,
+            self.config.entry_price_ticks,
+        )
+    }
+    
+    /// Signal strike, or the nearest neighbour with enough volume and OI when
+    /// `min_strike_volume` is set; `None` if no candidate is liquid
+    async fn liquid_entry_strike(&self, signal: &EntrySignal) -> Option<i32> {
+        if self.config.min_strike_volume <= 0 {
+            return Some(signal.strike);
+        }
+        
+        let candidates = strike_candidates(signal.strike, self.config.strike_increment, signal.underlying_ltp, 1);
+        let mut quotes = std::collections::HashMap::new();
+        for &strike in &candidates {
+            let Ok((token, symbol)) = self.instrument_cache
+                .find_option_token("NIFTY", strike, signal.option_type, None)
+                .await
+            else {
+                continue;
+            };
+            match self.broker_client.get_quote(&token).await {
+                Ok(quote) => {
+                    info!("   {} volume {} OI {}", symbol, quote.volume, quote.open_interest);
+                    quotes.insert(strike, quote);
+                }
+                Err(e) => warn!("⚠️  Quote for {} failed: {}", symbol, e),
+            }
+        }
+        
+        select_liquid_strike(&candidates, self.config.min_strike_volume, |strike| quotes.get(&strike).copied())
+    }
+    
+    /// Record OI for the ATM CE/PE (and liquid-strike neighbours) and open
+    /// positions; no-op while the OI build-up filter is off
+    async fn track_open_interest(&self) {
+        if self.config.min_oi_buildup_pct.is_none() {
+            return;
+        }
+        
+        let mut strikes = Vec::new();
+        if let Some(bar) = self.hourly_bars.get_recent_complete(1).await.ok().and_then(|bars| bars.last().cloned()) {
+            let atm = round_to_strike(bar.close, self.config.strike_increment);
+            let steps = if self.config.min_strike_volume > 0 { 1 } else { 0 };
+            for strike in strike_candidates(atm, self.config.strike_increment, bar.close, steps) {
+                strikes.push((strike, OptionType::CE));
+                strikes.push((strike, OptionType::PE));
+            }
+        }
+        for position in self.position_manager.get_open_positions().await {
+            strikes.push((position.strike, position.option_type));
+        }
+        
+        let mut tokens = Vec::new();
+        for (strike, option_type) in strikes {
+            if let Ok((token, _)) = self.instrument_cache.find_option_token("NIFTY", strike, option_type, None).await {
+                tokens.push(token);
+            }
+        }
+        tokens.sort();
+        tokens.dedup();
+        
+        let now = chrono::Utc::now();
+        for token in tokens {
+            match self.broker_client.get_quote(&token).await {
+                Ok(quote) => self.oi_tracker.record(&token, now, quote.open_interest).await,
+                Err(e) => warn!("⚠️  OI quote for {} failed: {}", token, e),
+            }
+        }
+    }
+    
+    /// Book the closing fill in the paper ledger so cash reflects the exit
+    async fn settle_paper_exit(&self, position: &Position, price: f64) {
+        let Some(paper_broker) = &self.paper_broker else {
+            return;
+        };
+        
+        let exit_side = match position.side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        if let Err(e) = paper_broker.place_order(
+            position.symbol.clone(),
+            exit_side,
+            position.quantity,
+            OrderType::Limit,
+            Some(round_to_tick(price, self.config.tick_size)),
+        ).await {
+            error!("📝 [PAPER] Failed to book exit for {}: {}", position.symbol, e);
+        }
+    }
+    
+    /// Exit a position: paper books the fill at `price`; live sends the
+    /// closing order and waits for its fill (same wait as entries).
+    /// Returns the exit price; a partial fill shrinks the position to the
+    /// unfilled remainder and errors so the caller keeps it open
+    async fn execute_exit(&self, position: &Position, price: f64, exit_reason: &str) -> Result<f64> {
+        if self.paper_broker.is_some() {
+            self.settle_paper_exit(position, price).await;
+            return Ok(price);
+        }
+        
+        let instrument = self.instrument_cache.get_by_symbol(&position.symbol).await
+            .ok_or_else(|| TradingError::InstrumentNotFound(position.symbol.clone()))?;
+        self.order_validator.check_session(chrono::Utc::now(), true)?;
+        
+        let order = self.order_manager.place_exit_order(position, &instrument.token, price, exit_reason).await?;
+        let wait = std::time::Duration::from_secs(self.config.entry_fill_wait_sec);
+        let order = self.order_manager.fill_or_cancel(&order.order_id, wait).await?;
+        
+        if order.fill_quantity < position.quantity {
+            let remaining = position.quantity - order.fill_quantity;
+            self.position_manager.settle_quantity(&position.position_id, remaining).await?;
+            return Err(TradingError::OrderPlacementFailed(format!(
+                "Exit for {} filled {}/{} - {} left open",
+                position.position_id, order.fill_quantity, position.quantity, remaining
+            )));
+        }
+        
+        Ok(order.fill_price.unwrap_or(price))
+    }
+    
+    /// Exit one position at its last price (live order or paper fill)
+    async fn exit_at_last_price(&self, position: Position, exit_reason: &str) -> Result<f64> {
+        self.execute_exit(&position, position.current_price, exit_reason).await
+    }
+    
+    /// Exit and close every open position (live order or paper fill first),
+    /// retrying failures; anything still open afterwards needs a human
+    async fn flatten_all(&self, exit_reason: &str, reason: String) -> Result<CloseAllReport> {
+        let position_ids = self.position_manager.get_open_positions().await
+            .into_iter()
+            .map(|position| position.position_id)
+            .collect();
+        self.flatten(position_ids, exit_reason, reason).await
+    }
+    
+    /// `flatten_all` for just `position_ids`
+    async fn flatten(&self, position_ids: Vec<String>, exit_reason: &str, reason: String) -> Result<CloseAllReport> {
+        let report = self.position_manager
+            .close_positions_with(position_ids, reason, |position| self.exit_at_last_price(position, exit_reason))
+            .await?;
+        
+        for position_id in &report.still_open {
+            error!("🚨 Position {} still open after exit retries - close it manually", position_id);
+        }
+        
+        Ok(report)
+    }
+    
+    /// Run an operator command left in `CONTROL_FILE` (e.g. by `rustro flatten`)
+    /// A flatten closes everything but, unlike Ctrl-C, leaves the bot running.
+    async fn handle_control_command(&self) {
+        handle_control_command(
+            CONTROL_FILE,
+            &self.order_manager,
+            &self.position_manager,
+            |position| self.exit_at_last_price(position, "MANUAL_FLATTEN"),
+        ).await;
+    }
+    
+    /// Expiry-day force-exit from `expiry_exit_time`: closes the positions
+    /// expiring today; later expiries are left to their normal exits
+    async fn expiry_exit_positions(&self) -> Result<()> {
+        let mut due = Vec::new();
+        for position in self.position_manager.get_open_positions().await {
+            if let Some(instrument) = self.instrument_cache.get_by_symbol(&position.symbol).await {
+                if self.risk_manager.is_expiry_exit_due(&instrument.expiry) {
+                    due.push(position.position_id);
                 }
             }
         }
+        if due.is_empty() {
+            return Ok(());
+        }
+        
+        info!("📅 Expiry day: Closing {} expiring positions", due.len());
+        
+        self.flatten(due, "EXPIRY", "EXPIRY_DAY_EXIT".to_string()).await?;
         
         Ok(())
     }
     
+    /// Refresh tokens ahead of expiry; if that fails within
+    /// `token_grace_to_flatten_sec`, square off while exits can still be sent.
+    /// The outcome is kept in `token_status` to gate new entries.
+    async fn check_tokens(&self) {
+        let status = self.token_monitor.check(
+            || self.broker_client.refresh_token(),
+            || async {
+                if let Err(e) = self.order_manager.cancel_all_active("TOKEN_EXPIRY").await {
+                    error!("Failed to cancel working orders: {}", e);
+                }
+                let report = self.flatten_all("TOKEN_EXPIRY", "TOKEN_EXPIRY".to_string()).await?;
+                let closed = report.closed.into_iter().map(|trade| trade.position_id).collect();
+                Ok((closed, report.still_open))
+            },
+        ).await;
+        
+        match status {
+            Ok(status) => *self.token_status.write().await = status,
+            Err(e) => error!("❌ Token check failed: {}", e),
+        }
+    }
+    
+    /// Sleep `secs`, polling the control file every `CONTROL_POLL_SEC` and
+    /// checking tokens every `TOKEN_CHECK_INTERVAL_SEC` so a flatten request
+    /// or an expiring session is not left waiting for the next cycle
+    async fn idle(&self, secs: u64) {
+        let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(secs);
+        let mut control = tokio::time::interval(tokio::time::Duration::from_secs(CONTROL_POLL_SEC));
+        let mut tokens = tokio::time::interval(tokio::time::Duration::from_secs(TOKEN_CHECK_INTERVAL_SEC));
+        // The first ticks are immediate; the loop already ran both checks
+        control.tick().await;
+        tokens.tick().await;
+        
+        while !*self.shutdown.read().await {
+            tokio::select! {
+                _ = tokio::time::sleep_until(deadline) => break,
+                _ = control.tick() => self.handle_control_command().await,
+                _ = tokens.tick() => self.check_tokens().await,
+            }
+        }
+    }
+    
     /// EOD mandatory exit
     async fn eod_exit_positions(&self) -> Result<()> {
         let positions = self.position_manager.get_open_positions().await;
@@ -1021,13 +2092,18 @@ impl TradingApp {
         
         info!("🌆 EOD: Closing {} open positions", positions.len());
         
-        self.position_manager.close_all_positions("EOD_MANDATORY_EXIT".to_string()).await?;
+        self.flatten_all("EOD", "EOD_MANDATORY_EXIT".to_string()).await?;
         
         Ok(())
     }
     
     /// End of day cleanup
     async fn end_of_day_sequence(&self) -> Result<()> {
+        // The main loop calls this every hour after the close; run it once a day
+        let today_ist = chrono::Utc::now().with_timezone(&chrono_tz::Asia::Kolkata).date_naive();
+        if *self.eod_done_for.read().await == Some(today_ist) {
+            return Ok(());
+        }
         info!("🌙 Running end of day sequence...");
         
         // Save trades
@@ -1037,6 +2113,38 @@ impl TradingApp {
             let filename = format!("data/trades_{}.json", chrono::Utc::now().format("%Y%m%d"));
             tokio::fs::write(filename, trades_json).await?;
             info!("💾 Saved {} trades", trades.len());
+            
+            let regimes = PerformanceAnalyzer::vix_regime_breakdown(
+                &trades,
+                self.config.vix_regime_low_max,
+                self.config.vix_regime_high_min,
+            );
+            for stats in regimes {
+                info!(
+                    "   VIX {:?}: {} trades, {:.1}% win, net ₹{:.2}",
+                    stats.regime, stats.trades, stats.win_rate, stats.net_pnl
+                );
+            }
+        }
+        
+        // Daily performance report and summary CSV (an empty book, e.g. after
+        // a restart, must not overwrite the day's report)
+        if trades.is_empty() {
+            info!("No trades today - performance report skipped");
+        } else {
+            let report = PerformanceAnalyzer::generate_report_from_trades(&trades, today_ist);
+            if let Err(e) = PerformanceAnalyzer::save_report(&report).await {
+                warn!("⚠️  Failed to save performance report: {}", e);
+            }
+            PerformanceAnalyzer::print_summary(&report.metrics);
+        }
+        
+        // Rotate event log and prune old per-day files
+        if let Err(e) = self.event_bus.rotate_log(today_ist).await {
+            warn!("⚠️  Event log rotation failed: {}", e);
+        }
+        if let Err(e) = prune_dated_files(std::path::Path::new("data"), self.config.data_retention_days, today_ist).await {
+            warn!("⚠️  Data retention cleanup failed: {}", e);
         }
         
         // Sync historical data during off-hours
@@ -1069,7 +2177,10 @@ impl TradingApp {
         
         self.position_manager.reset_daily_pnl().await;
         self.risk_manager.reset_daily().await;
+        self.refresh_start_capital().await;
         self.strategy.reset().await;
+        self.oi_tracker.clear().await;
+        *self.eod_done_for.write().await = Some(today_ist);
         
         info!("✅ EOD sequence completed");
         Ok(())
@@ -1105,11 +2216,20 @@ impl TradingApp {
         
         let start_time = chrono::Utc::now();
         
+        // Cancel working orders first so nothing fills after we exit
+        match self.order_manager.cancel_all_active("SHUTDOWN").await {
+            Ok(cancelled) if !cancelled.is_empty() => {
+                warn!("⚠️  Cancelled {} working orders", cancelled.len());
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to cancel working orders: {}", e),
+        }
+        
         // Close all open positions
         let open_positions = self.position_manager.get_open_positions().await;
         if !open_positions.is_empty() {
             warn!("⚠️  Closing {} open positions", open_positions.len());
-            let _ = self.position_manager.close_all_positions("Shutdown".to_string()).await;
+            let _ = self.flatten_all("SHUTDOWN", "Shutdown".to_string()).await;
         }
         
         // Save daily trades
@@ -1139,14 +2259,163 @@ impl TradingApp {
     }
 }
 
+/// Backfill one timeframe over a feed outage, reporting progress on the event bus
+async fn recover_outage(
+    historical_sync: &HistoricalDataSync,
+    event_bus: &EventBus,
+    token: &str,
+    timeframe: Timeframe,
+    outage: OutageWindow,
+) {
+    info!("🩹 Backfilling {} {} from {} to {}", token, timeframe.as_str(), outage.start, outage.end);
+    
+    let _ = event_bus.publish(Event::new(
+        EventType::RecoveryStarted,
+        EventPayload::RecoveryStarted {
+            symbol: token.to_string(),
+            timeframe: timeframe.as_str().to_string(),
+            gap_start: outage.start,
+            gap_end: outage.end,
+        },
+    )).await;
+    
+    match historical_sync.backfill_outage(token, timeframe, outage.start, outage.end).await {
+        Ok(bars_recovered) => {
+            let _ = event_bus.publish(Event::new(
+                EventType::RecoveryCompleted,
+                EventPayload::RecoveryCompleted {
+                    symbol: token.to_string(),
+                    timeframe: timeframe.as_str().to_string(),
+                    bars_recovered,
+                },
+            )).await;
+        }
+        Err(e) => {
+            error!("❌ Backfill failed for {}: {}", token, e);
+            let _ = event_bus.publish(Event::new(
+                EventType::RecoveryFailed,
+                EventPayload::RecoveryFailed {
+                    symbol: token.to_string(),
+                    reason: e.to_string(),
+                },
+            )).await;
+        }
+    }
+}
+
+/// Authenticate (reusing saved tokens when still valid) and load the instrument master
+async fn connect_broker(config: &Config) -> Result<(Arc<AngelOneClient>, Arc<InstrumentCache>)> {
+    tokio::fs::create_dir_all("data").await.ok();
+    
+    let token_manager = Arc::new(TokenManager::new("data/tokens.json".to_string()));
+    let broker_client = Arc::new(AngelOneClient::new(
+        Arc::clone(&token_manager),
+        config.angel_one_client_code.clone(),
+        config.angel_one_password.clone(),
+        config.angel_one_mpin.clone(),
+        config.angel_one_totp_secret.clone(),
+        config.angel_one_api_key.clone(),
+    ).with_product_type(config.product_type));
+    
+    let tokens_valid = token_manager.load_from_file().await.is_ok() && token_manager.is_valid().await;
+    if !tokens_valid {
+        info!("🔑 Logging in to Angel One...");
+        broker_client.login().await?;
+    }
+    
+    let instrument_cache = Arc::new(InstrumentCache::new(Arc::clone(&broker_client)));
+    if let Err(e) = instrument_cache.load_from_file().await {
+        info!("No cached instrument master ({})", e);
+    }
+    if instrument_cache.needs_refresh().await {
+        info!("📥 Downloading instrument master...");
+        instrument_cache.refresh().await?;
+    }
+    info!("✅ {} instruments available", instrument_cache.size().await);
+    
+    Ok((broker_client, instrument_cache))
+}
+
+/// `backfill` subcommand: download history for one or all assets without trading
+async fn run_backfill(config_path: &str, asset: Option<UnderlyingAsset>, days: u32) -> Result<()> {
+    let config = Arc::new(load_config(config_path)?);
+    let (broker_client, instrument_cache) = connect_broker(&config).await?;
+    
+    let mut syncer = MultiAssetHistoricalSync::new(broker_client, instrument_cache, Arc::clone(&config))
+        .with_history_days(days);
+    
+    let assets = asset.map(|a| vec![a]).unwrap_or_else(UnderlyingAsset::all);
+    for asset in &assets {
+        syncer.register_asset_stores(*asset);
+    }
+    
+    info!("🔄 Backfilling {} day(s) for {:?}", days, assets.iter().map(|a| a.as_str()).collect::<Vec<_>>());
+    
+    match asset {
+        Some(asset) => {
+            let report = syncer.sync_single_asset(asset).await?;
+            info!(
+                "✅ {}: {} underlying bars, {} options, {} futures",
+                report.asset, report.underlying_bars, report.options_synced, report.futures_synced
+            );
+        }
+        None => {
+            let report = syncer.sync_all_assets().await?;
+            info!(
+                "✅ Backfill complete in {}s: {} instruments, {} bars ({:.1}% success)",
+                report.duration_sec, report.total_instruments, report.total_bars_downloaded, report.success_rate
+            );
+        }
+    }
+    
+    Ok(())
+}
+
+/// `export-tokens` subcommand: write the index token mapping to `output`
+async fn export_tokens(config_path: &str, output: &str) -> Result<()> {
+    let config = load_config(config_path)?;
+    let (_broker_client, instrument_cache) = connect_broker(&config).await?;
+    
+    let extractor = TokenExtractor::from_config(instrument_cache.get_all_instruments().await, &config);
+    extractor
+        .export_tokens_to_file(output)
+        .await
+        .map_err(|e| TradingError::Other(format!("Token export failed: {}", e)))?;
+    
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Initialize logging
+    tracing_subscriber::fmt()
+        .with_env_filter("rustro=info")
+        .init();
+    
     let config_path = std::env::var("CONFIG_PATH")
         .unwrap_or_else(|_| "config.toml".to_string());
     
-    let app = TradingApp::new(&config_path).await?;
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
     
-    app.run().await?;
+    match command {
+        Command::Run => {
+            let app = TradingApp::new(&config_path).await?;
+            app.run().await?;
+        }
+        Command::Backfill { asset, days } => run_backfill(&config_path, asset, days).await?,
+        Command::ExportTokens { output } => export_tokens(&config_path, &output).await?,
+        Command::Flatten => {
+            send_control_command(CONTROL_FILE, ControlCommand::Flatten).await?;
+            info!("🛑 Flatten requested via {} - the running bot closes all positions next cycle", CONTROL_FILE);
+        }
+        Command::Help => println!("{}", USAGE),
+    }
     
     Ok(())
 }
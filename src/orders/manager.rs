@@ -1,16 +1,18 @@
 /// Order management with retry logic and idempotency
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn, Instrument};
 
-use crate::broker::AngelOneClient;
+use crate::broker::{OrderBroker, OrderUpdate, PaperTradingBroker};
 use crate::error::{Result, TradingError};
 use crate::events::{Event, EventBus, EventPayload, EventType};
-use crate::types::{Config, Order, OrderStatus, OrderType, Side};
+use crate::positions::PositionManager;
+use crate::types::{BrokerOrder, Config, Order, OrderPurpose, OrderStatus, OrderType, Position, Side};
+use crate::utils::round_to_tick;
 
 pub struct OrderManager {
-    broker: Arc<AngelOneClient>,
+    broker: Arc<dyn OrderBroker>,
     event_bus: Arc<EventBus>,
     config: Arc<Config>,
     
@@ -19,11 +21,66 @@ pub struct OrderManager {
     
     /// Idempotency tracker
     processed_intents: Arc<RwLock<HashMap<String, String>>>,
+    
+    /// Duplicate broker orders already squared off
+    squared_off_duplicates: Arc<RwLock<HashSet<String>>>,
+}
+
+/// Outcome of `OrderManager::submit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Submission {
+    /// Sent to the broker as this order
+    Placed(String),
+    /// The intent was already placed as this order; nothing was sent
+    Duplicate(String),
+}
+
+impl Submission {
+    /// The order the intent is tracked as
+    pub fn order_id(&self) -> &str {
+        match self {
+            Submission::Placed(order_id) | Submission::Duplicate(order_id) => order_id,
+        }
+    }
+}
+
+/// Extra fills found for one order intent
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateFill {
+    pub order_id: String,
+    pub idempotency_key: String,
+    pub kept_broker_order_id: String,
+    pub duplicate_broker_order_ids: Vec<String>,
+    pub extra_quantity: i32,
+    /// Whether every extra lot was squared off at the broker
+    pub squared_off: bool,
+}
+
+/// Order book poll interval while waiting for a fill
+const FILL_POLL_INTERVAL_MS: u64 = 500;
+
+/// How long after its last update a finished order is still checked for
+/// late duplicate fills (a retried placement can fill after the first)
+const DUPLICATE_FILL_WINDOW_MIN: i64 = 5;
+
+/// Broker `ordertag` for an intent (SmartAPI allows up to 20 characters)
+pub fn order_tag(idempotency_key: &str) -> String {
+    idempotency_key.chars().take(20).collect()
+}
+
+/// Order type for an exit: targets rest as LIMIT, every other exit
+/// (stop, trailing, EOD, risk) uses the configured type
+pub fn exit_order_type(exit_reason: &str, configured: OrderType) -> OrderType {
+    if exit_reason == "TARGET" {
+        OrderType::Limit
+    } else {
+        configured
+    }
 }
 
 impl OrderManager {
     pub fn new(
-        broker: Arc<AngelOneClient>,
+        broker: Arc<dyn OrderBroker>,
         event_bus: Arc<EventBus>,
         config: Arc<Config>,
     ) -> Self {
@@ -33,10 +90,11 @@ impl OrderManager {
             config,
             orders: Arc::new(RwLock::new(HashMap::new())),
             processed_intents: Arc::new(RwLock::new(HashMap::new())),
+            squared_off_duplicates: Arc::new(RwLock::new(HashSet::new())),
         }
     }
     
-    /// Place an order with retry logic
+    /// Place an order with retry logic; an intent placed before is an error
     pub async fn place_order(
         &self,
         symbol: String,
@@ -46,38 +104,36 @@ impl OrderManager {
         initial_price: f64,
         idempotency_key: String,
     ) -> Result<String> {
+        match self.submit(new_entry_order(symbol, token, side, quantity, initial_price, idempotency_key)).await? {
+            Submission::Placed(order_id) => Ok(order_id),
+            Submission::Duplicate(order_id) => Err(TradingError::DuplicateEvent(format!(
+                "intent already placed as {}",
+                order_id
+            ))),
+        }
+    }
+    
+    /// Place an entry built by `new_entry_order` with retry logic; one with a
+    /// `stop_price` goes out as a cover order. An idempotency key that was
+    /// already placed is reported as `Submission::Duplicate` and not resent.
+    pub async fn submit(&self, mut order: Order) -> Result<Submission> {
+        let order_id = order.order_id.clone();
+        let symbol = order.symbol.clone();
+        let token = order.token.clone();
+        let side = order.side;
+        let quantity = order.quantity;
+        let initial_price = order.limit_price.unwrap_or_default();
+        let idempotency_key = order.idempotency_key.clone();
+        
         // Check idempotency
         {
             let processed = self.processed_intents.read().await;
             if let Some(existing_order_id) = processed.get(&idempotency_key) {
                 info!("Order already processed: {}", existing_order_id);
-                return Ok(existing_order_id.clone());
+                return Ok(Submission::Duplicate(existing_order_id.clone()));
             }
         }
         
-        // Create order intent
-        let order_id = uuid::Uuid::new_v4().to_string();
-        
-        let mut order = Order {
-            order_id: order_id.clone(),
-            broker_order_id: None,
-            position_id: String::new(), // Will be set by position manager
-            symbol: symbol.clone(),
-            side,
-            order_type: OrderType::Limit,
-            quantity,
-            limit_price: Some(initial_price),
-            fill_price: None,
-            fill_quantity: 0,
-            fill_time: None,
-            status: OrderStatus::Pending,
-            attempts: 0,
-            retry_count: 0,
-            idempotency_key: idempotency_key.clone(),
-            created_at: chrono::Utc::now(),
-            updated_at: chrono::Utc::now(),
-        };
-        
         // Store order
         {
             let mut orders = self.orders.write().await;
@@ -97,7 +153,7 @@ impl OrderManager {
         )).await?;
         
         // Attempt order placement with retries
-        let mut current_price = initial_price;
+        let mut current_price = round_to_tick(initial_price, self.config.tick_size);
         let max_retries = self.config.order_max_retries as usize;
         
         for attempt in 0..=max_retries {
@@ -135,7 +191,10 @@ impl OrderManager {
                 // Adjust price for retry
                 if attempt <= self.config.order_retry_steps_pct.len() {
                     let adjustment_pct = self.config.order_retry_steps_pct[attempt - 1];
-                    current_price = initial_price * (1.0 + adjustment_pct / 100.0);
+                    current_price = round_to_tick(
+                        initial_price * (1.0 + adjustment_pct / 100.0),
+                        self.config.tick_size,
+                    );
                     info!(
                         "Retry {} for order {}: adjusted price to {:.2} (+{:.2}%)",
                         attempt,
@@ -146,15 +205,26 @@ impl OrderManager {
                 }
             }
             
-            // Place order with broker
-            match self.broker.place_order(
-                &symbol,
-                &token,
-                side,
-                quantity,
-                OrderType::Limit,
-                Some(current_price),
-            ).await {
+            // Place order with broker (a cover stop keeps its distance from a retry price)
+            let placed = match order.stop_price {
+                Some(stop_price) => self.broker.place_cover_order(
+                    &symbol,
+                    &token,
+                    side,
+                    quantity,
+                    current_price,
+                    round_to_tick(stop_price + current_price - initial_price, self.config.tick_size),
+                ).await,
+                None => self.broker.place_tagged_order(
+                    &symbol,
+                    &token,
+                    side,
+                    quantity,
+                    Some(current_price),
+                    &order_tag(&idempotency_key),
+                ).await,
+            };
+            match placed {
                 Ok(broker_order_id) => {
                     // Success!
                     order.broker_order_id = Some(broker_order_id.clone());
@@ -186,7 +256,7 @@ impl OrderManager {
                     )).await?;
                     
                     info!("Order placed successfully: {}", order_id);
-                    return Ok(order_id);
+                    return Ok(Submission::Placed(order_id));
                 }
                 Err(e) => {
                     error!(
@@ -196,7 +266,11 @@ impl OrderManager {
                         e.error_code()
                     );
                     
-                    if attempt == max_retries {
+                    // Maintenance outlasts any retry schedule, and the limit would be
+                    // stale by the time it ends: give up now, unwrapped, so the main
+                    // loop can pause
+                    let maintenance = matches!(e, TradingError::BrokerMaintenance(_));
+                    if attempt == max_retries || maintenance {
                         // Final failure
                         order.status = OrderStatus::Failed;
                         order.updated_at = chrono::Utc::now();
@@ -213,17 +287,27 @@ impl OrderManager {
                             EventPayload::OrderFailed {
                                 order_id: order_id.clone(),
                                 reason: e.to_string(),
-                                retry_count: max_retries as u32,
+                                retry_count: attempt as u32,
                             },
                         )).await?;
                         
+                        if maintenance {
+                            return Err(e);
+                        }
                         return Err(TradingError::OrderPlacementFailed(format!(
                             "Order failed after {} attempts: {}",
-                            max_retries + 1,
+                            attempt + 1,
                             e
                         )));
                     }
                     
+                    // Rate limit: short backoff before the next attempt
+                    if let TradingError::RateLimitExceeded(_) = e {
+                        let wait = e.retry_after().unwrap_or_default();
+                        warn!("Broker asked to back off ({}) - waiting {}s", e.error_code(), wait.as_secs());
+                        tokio::time::sleep(wait).await;
+                    }
+                    
                     // Continue to next retry
                 }
             }
@@ -234,6 +318,137 @@ impl OrderManager {
         ))
     }
     
+    /// Place the closing order for a position (single attempt; callers retry
+    /// on their next update cycle)
+    pub async fn place_exit_order(
+        &self,
+        position: &Position,
+        token: &str,
+        price: f64,
+        exit_reason: &str,
+    ) -> Result<Order> {
+        // A cover position exits through its own order so the broker stop
+        // leg goes with it; a separate closing order would leave it live
+        let cover_entry = self.get_order(&position.position_id).await
+            .filter(|entry| entry.stop_price.is_some());
+        let order_type = if cover_entry.is_some() {
+            OrderType::Market
+        } else {
+            exit_order_type(exit_reason, self.config.exit_order_type)
+        };
+        let limit_price = match order_type {
+            OrderType::Limit => Some(round_to_tick(price, self.config.tick_size)),
+            OrderType::Market => None,
+        };
+        let side = match position.side {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        };
+        let now = chrono::Utc::now();
+        
+        let mut order = Order {
+            order_id: uuid::Uuid::new_v4().to_string(),
+            broker_order_id: None,
+            position_id: position.position_id.clone(),
+            symbol: position.symbol.clone(),
+            token: token.to_string(),
+            side,
+            order_type,
+            quantity: position.quantity,
+            limit_price,
+            fill_price: None,
+            fill_quantity: 0,
+            fill_time: None,
+            status: OrderStatus::Pending,
+            attempts: 1,
+            retry_count: 0,
+            idempotency_key: format!("exit-{}", position.position_id),
+            created_at: now,
+            updated_at: now,
+            purpose: OrderPurpose::Exit,
+            stop_price: cover_entry.as_ref().and_then(|entry| entry.stop_price),
+        };
+        
+        {
+            let mut orders = self.orders.write().await;
+            orders.insert(order.order_id.clone(), order.clone());
+        }
+        
+        self.event_bus.publish(Event::new(
+            EventType::OrderIntentCreated,
+            EventPayload::OrderIntentCreated {
+                order_id: order.order_id.clone(),
+                symbol: order.symbol.clone(),
+                side,
+                quantity: order.quantity,
+                intent_reason: format!("Exit: {}", exit_reason),
+            },
+        )).await?;
+        
+        let cover_broker_id = cover_entry.and_then(|entry| entry.broker_order_id);
+        let result = match &cover_broker_id {
+            Some(entry_broker_id) => self.broker.cancel_cover_order(entry_broker_id).await
+                .map(|_| entry_broker_id.clone()),
+            None => self.broker.place_tagged_order(
+                &order.symbol,
+                token,
+                side,
+                order.quantity,
+                limit_price,
+                &order_tag(&order.idempotency_key),
+            ).await,
+        };
+        
+        order.updated_at = chrono::Utc::now();
+        match result {
+            Ok(broker_order_id) => {
+                order.broker_order_id = Some(broker_order_id.clone());
+                order.status = OrderStatus::Submitted;
+                self.orders.write().await.insert(order.order_id.clone(), order.clone());
+                
+                self.event_bus.publish(Event::new(
+                    EventType::OrderPlaced,
+                    EventPayload::OrderPlaced {
+                        order_id: order.order_id.clone(),
+                        broker_order_id,
+                        symbol: order.symbol.clone(),
+                        quantity: order.quantity,
+                        price: limit_price.unwrap_or(price),
+                    },
+                )).await?;
+                
+                info!(
+                    "{} exit order placed for {} ({})",
+                    order_type.as_str(),
+                    position.position_id,
+                    exit_reason
+                );
+                Ok(order)
+            }
+            Err(e) => {
+                order.status = OrderStatus::Failed;
+                self.orders.write().await.insert(order.order_id.clone(), order.clone());
+                
+                self.event_bus.publish(Event::new(
+                    EventType::OrderFailed,
+                    EventPayload::OrderFailed {
+                        order_id: order.order_id.clone(),
+                        reason: e.to_string(),
+                        retry_count: 0,
+                    },
+                )).await?;
+                
+                if let TradingError::BrokerMaintenance(_) = e {
+                    return Err(e);
+                }
+                Err(TradingError::OrderPlacementFailed(format!(
+                    "Exit order for {} failed: {}",
+                    position.position_id, e
+                )))
+            }
+        }
+    }
+    
     /// Mark order as executed
     pub async fn mark_executed(
         &self,
@@ -247,15 +462,19 @@ impl OrderManager {
             order.fill_price = Some(fill_price);
             order.fill_quantity = fill_quantity;
             order.fill_time = Some(chrono::Utc::now());
+            // A cancelled remainder stays cancelled when its last fill shows up late
             order.status = if fill_quantity >= order.quantity {
                 OrderStatus::Filled
+            } else if order.status == OrderStatus::Cancelled {
+                OrderStatus::Cancelled
             } else {
                 OrderStatus::PartiallyFilled
             };
             order.updated_at = chrono::Utc::now();
             
-            // Emit event
-            self.event_bus.publish(Event::new(
+            // Emit event (keyed on the fill so reconciliation can't double-emit it)
+            let fill_key = format!("fill-{}-{}", order_id, fill_quantity);
+            self.event_bus.publish_idempotent(Event::new(
                 EventType::OrderExecuted,
                 EventPayload::OrderExecuted {
                     order_id: order_id.to_string(),
@@ -264,7 +483,7 @@ impl OrderManager {
                     fill_quantity,
                     fill_time: order.fill_time.unwrap(),
                 },
-            )).await?;
+            ), &fill_key).await?;
             
             info!("Order executed: {} @ {:.2}", order_id, fill_price);
             Ok(())
@@ -273,12 +492,476 @@ impl OrderManager {
         }
     }
     
+    /// Cancel the resting remainder of partially filled orders older than
+    /// `partial_fill_timeout_sec`; the order settles at its filled quantity
+    pub async fn cancel_stale_partial_fills(&self, now: chrono::DateTime<chrono::Utc>) -> Result<Vec<Order>> {
+        let timeout = chrono::Duration::seconds(self.config.partial_fill_timeout_sec as i64);
+        
+        let stale: Vec<Order> = {
+            let orders = self.orders.read().await;
+            orders.values()
+                .filter(|o| o.status == OrderStatus::PartiallyFilled)
+                .filter(|o| now - o.fill_time.unwrap_or(o.updated_at) >= timeout)
+                .cloned()
+                .collect()
+        };
+        
+        let mut cancelled = Vec::new();
+        
+        for order in stale {
+            let Some(order) = self.cancel_remaining(order, "PARTIAL_FILL_TIMEOUT", now).await? else {
+                continue;
+            };
+            
+            warn!(
+                "Partial fill timeout: {} filled {}/{} - cancelled remaining {}",
+                order.order_id,
+                order.fill_quantity,
+                order.quantity,
+                order.quantity - order.fill_quantity
+            );
+            
+            cancelled.push(order);
+        }
+        
+        Ok(cancelled)
+    }
+    
+    /// Escalate entry limits with no fill after `entry_limit_timeout_sec` to market orders
+    /// Only orders the broker has confirmed as completely unfilled are escalated;
+    /// partial fills are left to `cancel_stale_partial_fills`
+    pub async fn escalate_unfilled_limits(&self, now: chrono::DateTime<chrono::Utc>) -> Result<Vec<Order>> {
+        let timeout = chrono::Duration::seconds(self.config.entry_limit_timeout_sec as i64);
+        
+        let due: Vec<Order> = {
+            let orders = self.orders.read().await;
+            orders.values()
+                .filter(|o| is_escalation_due(o, now, timeout))
+                .cloned()
+                .collect()
+        };
+        
+        let mut escalated = Vec::new();
+        
+        for mut order in due {
+            if let Some(broker_order_id) = &order.broker_order_id {
+                if let Err(e) = self.broker.cancel_order(broker_order_id).await {
+                    // The limit may have filled in the meantime; wait for fill status
+                    warn!("Failed to cancel unfilled limit {}: {} - not escalating", order.order_id, e);
+                    continue;
+                }
+            }
+            
+            convert_to_market(&mut order, now);
+            
+            let broker_order_id = match self.broker.place_tagged_order(
+                &order.symbol,
+                &order.token,
+                order.side,
+                order.quantity,
+                None,
+                &order_tag(&order.idempotency_key),
+            ).await {
+                Ok(id) => id,
+                Err(e) => {
+                    error!("Market escalation failed for {}: {}", order.order_id, e);
+                    order.status = OrderStatus::Failed;
+                    self.orders.write().await.insert(order.order_id.clone(), order.clone());
+                    
+                    self.event_bus.publish(Event::new(
+                        EventType::OrderFailed,
+                        EventPayload::OrderFailed {
+                            order_id: order.order_id.clone(),
+                            reason: format!("Market escalation failed: {}", e),
+                            retry_count: order.retry_count,
+                        },
+                    )).await?;
+                    continue;
+                }
+            };
+            
+            order.broker_order_id = Some(broker_order_id.clone());
+            order.status = OrderStatus::Submitted;
+            self.orders.write().await.insert(order.order_id.clone(), order.clone());
+            
+            self.event_bus.publish(Event::new(
+                EventType::OrderPlaced,
+                EventPayload::OrderPlaced {
+                    order_id: order.order_id.clone(),
+                    broker_order_id,
+                    symbol: order.symbol.clone(),
+                    quantity: order.quantity,
+                    price: 0.0,
+                },
+            )).await?;
+            
+            info!("Entry limit {} unfilled after {}s - escalated to market", order.order_id, timeout.num_seconds());
+            escalated.push(order);
+        }
+        
+        Ok(escalated)
+    }
+    
+    /// Cancel every working order (pending, submitted or partially filled)
+    /// Used on shutdown and forced flattening so nothing fills after the bot stops
+    pub async fn cancel_all_active(&self, reason: &str) -> Result<Vec<Order>> {
+        let active = self.get_active_orders().await;
+        let now = chrono::Utc::now();
+        let mut cancelled = Vec::new();
+        
+        for order in active {
+            if let Some(order) = self.cancel_remaining(order, reason, now).await? {
+                info!("Cancelled working order {} ({})", order.order_id, reason);
+                cancelled.push(order);
+            }
+        }
+        
+        Ok(cancelled)
+    }
+    
+    /// Cancel the unfilled remainder of an order at the broker and record it
+    /// Returns `None` if the broker rejected the cancel (caller may retry later)
+    async fn cancel_remaining(
+        &self,
+        mut order: Order,
+        reason: &str,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<Order>> {
+        if let Some(broker_order_id) = &order.broker_order_id {
+            let cancelled = if order.stop_price.is_some() {
+                self.broker.cancel_cover_order(broker_order_id).await
+            } else {
+                self.broker.cancel_order(broker_order_id).await
+            };
+            if let Err(e) = cancelled {
+                warn!("Failed to cancel {}: {} - will retry", order.order_id, e);
+                return Ok(None);
+            }
+        }
+        
+        let shortfall = order.quantity - order.fill_quantity;
+        order.status = OrderStatus::Cancelled;
+        order.updated_at = now;
+        
+        {
+            let mut orders = self.orders.write().await;
+            orders.insert(order.order_id.clone(), order.clone());
+        }
+        
+        self.event_bus.publish(Event::new(
+            EventType::OrderCancelled,
+            EventPayload::OrderCancelled {
+                order_id: order.order_id.clone(),
+                filled_quantity: order.fill_quantity,
+                cancelled_quantity: shortfall,
+                reason: reason.to_string(),
+            },
+        )).await?;
+        
+        Ok(Some(order))
+    }
+    
+    /// Detect intents filled by more than one broker order (e.g. a retry that
+    /// went through after an ambiguous network error) and square off the extras
+    pub async fn reconcile_fills(&self, order_book: &[BrokerOrder]) -> Result<Vec<DuplicateFill>> {
+        let orders: Vec<Order> = {
+            let orders = self.orders.read().await;
+            orders.values().cloned().collect()
+        };
+        
+        let mut duplicates = Vec::new();
+        
+        for order in orders {
+            let tag = order_tag(&order.idempotency_key);
+            let fills: Vec<&BrokerOrder> = order_book.iter()
+                .filter(|b| b.order_tag.as_deref() == Some(tag.as_str()) && b.filled_quantity > 0)
+                .collect();
+            
+            if fills.len() < 2 {
+                continue;
+            }
+            
+            // Keep the fill we know about; everything else is unintended
+            let kept = fills.iter()
+                .find(|b| order.broker_order_id.as_deref() == Some(b.broker_order_id.as_str()))
+                .unwrap_or(&fills[0]);
+            let extras: Vec<&BrokerOrder> = {
+                let handled = self.squared_off_duplicates.read().await;
+                fills.iter()
+                    .filter(|b| b.broker_order_id != kept.broker_order_id)
+                    .filter(|b| !handled.contains(&b.broker_order_id))
+                    .copied()
+                    .collect()
+            };
+            
+            if extras.is_empty() {
+                continue;
+            }
+            
+            let duplicate_broker_order_ids: Vec<String> = extras.iter()
+                .map(|b| b.broker_order_id.clone())
+                .collect();
+            let extra_quantity: i32 = extras.iter().map(|b| b.filled_quantity).sum();
+            
+            warn!(
+                "Duplicate fill for {}: {} extra broker order(s), {} qty",
+                order.order_id,
+                extras.len(),
+                extra_quantity
+            );
+            
+            self.event_bus.publish_idempotent(Event::new(
+                EventType::DuplicateFill,
+                EventPayload::DuplicateFill {
+                    order_id: order.order_id.clone(),
+                    idempotency_key: order.idempotency_key.clone(),
+                    duplicate_broker_order_ids: duplicate_broker_order_ids.clone(),
+                    extra_quantity,
+                },
+            ), &format!("dupfill-{}", duplicate_broker_order_ids.join("-"))).await?;
+            
+            let mut squared_off = true;
+            for extra in &extras {
+                let exit_side = match extra.side {
+                    Side::Buy => Side::Sell,
+                    Side::Sell => Side::Buy,
+                };
+                match self.broker.place_order(
+                    &extra.symbol,
+                    &extra.token,
+                    exit_side,
+                    extra.filled_quantity,
+                    OrderType::Market,
+                    None,
+                ).await {
+                    Ok(exit_id) => {
+                        info!("Squared off duplicate fill {} via {}", extra.broker_order_id, exit_id);
+                        self.squared_off_duplicates.write().await.insert(extra.broker_order_id.clone());
+                    }
+                    Err(e) => {
+                        // Left unmarked so the next reconciliation retries
+                        error!("Failed to square off duplicate fill {}: {}", extra.broker_order_id, e);
+                        squared_off = false;
+                    }
+                }
+            }
+            
+            duplicates.push(DuplicateFill {
+                order_id: order.order_id.clone(),
+                idempotency_key: order.idempotency_key.clone(),
+                kept_broker_order_id: kept.broker_order_id.clone(),
+                duplicate_broker_order_ids,
+                extra_quantity,
+                squared_off,
+            });
+        }
+        
+        Ok(duplicates)
+    }
+    
+    /// Record fills reported in the broker order book for tracked orders
+    /// Returns the number of orders whose filled quantity advanced
+    pub async fn apply_broker_fills(&self, order_book: &[BrokerOrder]) -> Result<usize> {
+        let advanced: Vec<(String, f64, i32)> = {
+            let orders = self.orders.read().await;
+            orders.values()
+                .filter_map(|o| {
+                    let broker_order_id = o.broker_order_id.as_deref()?;
+                    order_book.iter()
+                        .find(|b| fills_order(b, o, broker_order_id) && b.filled_quantity > o.fill_quantity)
+                        .map(|b| (o.order_id.clone(), b.average_price, b.filled_quantity))
+                })
+                .collect()
+        };
+        
+        for (order_id, fill_price, fill_quantity) in &advanced {
+            self.mark_executed(order_id, *fill_price, *fill_quantity).await?;
+        }
+        
+        Ok(advanced.len())
+    }
+    
+    /// Position a broker order belongs to: entries share their position's
+    /// id, exits carry it. `None` for orders this manager didn't place
+    pub async fn position_id_for_broker(&self, broker_order_id: &str) -> Option<String> {
+        let orders = self.orders.read().await;
+        let order = orders.values().find(|o| o.broker_order_id.as_deref() == Some(broker_order_id))?;
+        Some(match order.purpose {
+            OrderPurpose::Entry => order.order_id.clone(),
+            OrderPurpose::Exit => order.position_id.clone(),
+        })
+    }
+    
+    /// Apply one order-feed update to the tracked order it refers to
+    /// Returns the local order id, or `None` for orders this manager didn't place
+    pub async fn apply_order_update(&self, update: &OrderUpdate) -> Result<Option<String>> {
+        let order = {
+            let orders = self.orders.read().await;
+            orders.values()
+                .find(|o| o.broker_order_id.as_deref() == Some(update.broker_order_id()) && !is_cover_exit(o))
+                .cloned()
+        };
+        let Some(mut order) = order else {
+            return Ok(None);
+        };
+
+        match update {
+            OrderUpdate::Fill { fill_price, filled_quantity, .. } => {
+                if *filled_quantity > order.fill_quantity {
+                    self.mark_executed(&order.order_id, *fill_price, *filled_quantity).await?;
+                }
+            }
+            OrderUpdate::Rejected { reason, .. } => {
+                if order.status == OrderStatus::Rejected {
+                    return Ok(Some(order.order_id));
+                }
+                order.status = OrderStatus::Rejected;
+                order.updated_at = chrono::Utc::now();
+                self.orders.write().await.insert(order.order_id.clone(), order.clone());
+
+                self.event_bus.publish(Event::new(
+                    EventType::OrderRejected,
+                    EventPayload::OrderRejected {
+                        order_id: order.order_id.clone(),
+                        reason: "BROKER_REJECTED".to_string(),
+                        broker_message: reason.clone(),
+                    },
+                )).await?;
+
+                warn!("Order {} rejected by broker: {}", order.order_id, reason);
+            }
+            OrderUpdate::Cancelled { .. } => {
+                if order.status == OrderStatus::Cancelled {
+                    return Ok(Some(order.order_id));
+                }
+                order.status = OrderStatus::Cancelled;
+                order.updated_at = chrono::Utc::now();
+                self.orders.write().await.insert(order.order_id.clone(), order.clone());
+
+                info!("Order {} cancelled at broker", order.order_id);
+            }
+        }
+
+        Ok(Some(order.order_id))
+    }
+
+    /// Wait until `order_id` is completely filled, polling the broker order book
+    /// Entry limits left unfilled past `entry_limit_timeout_sec` are escalated
+    /// to market while waiting. A partial fill when `timeout` elapses is
+    /// returned as is; fails if the order ends without a fill or nothing
+    /// fills in time
+    pub async fn await_fill(&self, order_id: &str, timeout: std::time::Duration) -> Result<Order> {
+        let deadline = tokio::time::Instant::now() + timeout;
+        
+        loop {
+            let order = self.get_order(order_id).await
+                .ok_or_else(|| TradingError::OrderNotFound(order_id.to_string()))?;
+            let filled = order.fill_quantity > 0 && order.fill_price.is_some();
+            
+            if filled && order.fill_quantity >= order.quantity {
+                return Ok(order);
+            }
+            if matches!(order.status, OrderStatus::Failed | OrderStatus::Rejected | OrderStatus::Cancelled) {
+                if filled {
+                    return Ok(order);
+                }
+                return Err(TradingError::OrderPlacementFailed(format!(
+                    "Order {} ended {:?} without a fill",
+                    order_id, order.status
+                )));
+            }
+            if tokio::time::Instant::now() >= deadline {
+                if filled {
+                    return Ok(order);
+                }
+                return Err(TradingError::OrderPlacementFailed(format!(
+                    "No fill for {} within {}s",
+                    order_id,
+                    timeout.as_secs()
+                )));
+            }
+            
+            self.escalate_unfilled_limits(chrono::Utc::now()).await?;
+            self.refresh_fills(order_id).await?;
+            tokio::time::sleep(std::time::Duration::from_millis(FILL_POLL_INTERVAL_MS)).await;
+        }
+    }
+    
+    /// Wait up to `timeout` for `order_id` to fill, then cancel whatever is
+    /// still unfilled. A rejected cancel usually means the order just filled,
+    /// so fills are re-read from the order book before settling.
+    /// Returns the order at its final filled quantity; fails if nothing filled
+    pub async fn fill_or_cancel(&self, order_id: &str, timeout: std::time::Duration) -> Result<Order> {
+        let waited = self.await_fill(order_id, timeout).await;
+        if let Ok(order) = &waited {
+            if order.fill_quantity >= order.quantity {
+                return Ok(order.clone());
+            }
+        }
+        
+        if self.cancel_order(order_id, "FILL_TIMEOUT").await?.is_none() {
+            warn!("Cancel of {} rejected - re-checking fills", order_id);
+        }
+        self.refresh_fills(order_id).await?;
+        
+        let order = self.get_order(order_id).await
+            .ok_or_else(|| TradingError::OrderNotFound(order_id.to_string()))?;
+        if order.fill_quantity > 0 && order.fill_price.is_some() {
+            if order.fill_quantity < order.quantity {
+                warn!("Order {} filled {}/{} - remainder cancelled", order_id, order.fill_quantity, order.quantity);
+            }
+            return Ok(order);
+        }
+        waited
+    }
+    
+    /// Apply the broker order book's fills (best effort: a failed fetch is
+    /// retried on the next poll)
+    async fn refresh_fills(&self, order_id: &str) -> Result<()> {
+        match self.broker.get_order_book().await {
+            Ok(order_book) => {
+                self.apply_broker_fills(&order_book).await?;
+            }
+            Err(e) => debug!("Order book fetch failed while awaiting {}: {}", order_id, e),
+        }
+        Ok(())
+    }
+    
+    /// Cancel one working order; returns `None` if the broker rejected the cancel
+    pub async fn cancel_order(&self, order_id: &str, reason: &str) -> Result<Option<Order>> {
+        let order = self.get_order(order_id).await
+            .ok_or_else(|| TradingError::OrderNotFound(order_id.to_string()))?;
+        self.cancel_remaining(order, reason, chrono::Utc::now()).await
+    }
+    
+    /// Order already placed for `idempotency_key`, if any
+    pub async fn intent_order(&self, idempotency_key: &str) -> Option<String> {
+        self.processed_intents.read().await.get(idempotency_key).cloned()
+    }
+    
+    /// Record an order placed outside this manager (the paper broker) so its
+    /// intent is deduplicated like a live one
+    pub async fn record_intent(&self, idempotency_key: &str, order_id: &str) {
+        self.processed_intents.write().await.insert(idempotency_key.to_string(), order_id.to_string());
+    }
+    
     /// Get order by ID
     pub async fn get_order(&self, order_id: &str) -> Option<Order> {
         let orders = self.orders.read().await;
         orders.get(order_id).cloned()
     }
     
+    /// Whether the broker order book has anything to tell us: some order is
+    /// still working, or finished within `DUPLICATE_FILL_WINDOW_MIN`
+    pub async fn needs_order_book(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        let cutoff = now - chrono::Duration::minutes(DUPLICATE_FILL_WINDOW_MIN);
+        let orders = self.orders.read().await;
+        orders.values().any(|o| {
+            matches!(o.status, OrderStatus::Pending | OrderStatus::Submitted | OrderStatus::PartiallyFilled)
+                || o.updated_at >= cutoff
+        })
+    }
+    
     /// Get all active orders
     pub async fn get_active_orders(&self) -> Vec<Order> {
         let orders = self.orders.read().await;
@@ -296,3 +979,871 @@ impl OrderManager {
     }
 }
 
+/// New LIMIT entry intent at `price`, for `OrderManager::submit`
+pub fn new_entry_order(
+    symbol: String,
+    token: String,
+    side: Side,
+    quantity: i32,
+    price: f64,
+    idempotency_key: String,
+) -> Order {
+    let now = chrono::Utc::now();
+    Order {
+        order_id: uuid::Uuid::new_v4().to_string(),
+        broker_order_id: None,
+        position_id: String::new(), // Will be set by position manager
+        symbol,
+        token,
+        side,
+        order_type: OrderType::Limit,
+        quantity,
+        limit_price: Some(price),
+        fill_price: None,
+        fill_quantity: 0,
+        fill_time: None,
+        status: OrderStatus::Pending,
+        attempts: 0,
+        retry_count: 0,
+        idempotency_key,
+        created_at: now,
+        updated_at: now,
+        purpose: OrderPurpose::Entry,
+        stop_price: None,
+    }
+}
+
+/// An unfilled entry limit that has rested at the broker for at least `timeout`
+/// (exits keep their own limit/market choice, see `exit_order_type`; cover
+/// orders would lose their stop)
+fn is_escalation_due(order: &Order, now: chrono::DateTime<chrono::Utc>, timeout: chrono::Duration) -> bool {
+    order.purpose == OrderPurpose::Entry
+        && order.stop_price.is_none()
+        && order.order_type == OrderType::Limit
+        && order.status == OrderStatus::Submitted
+        && order.fill_quantity == 0
+        && now - order.updated_at >= timeout
+}
+
+/// Apply one order-feed update inside the trade span of its position
+pub async fn apply_feed_update(
+    order_manager: &OrderManager,
+    position_manager: &PositionManager,
+    update: &OrderUpdate,
+) -> Result<Option<String>> {
+    let span = match order_manager.position_id_for_broker(update.broker_order_id()).await {
+        Some(position_id) => position_manager.trade_span(&position_id).await,
+        None => tracing::Span::none(),
+    };
+    order_manager.apply_order_update(update).instrument(span).await
+}
+
+/// Entry fill to open a position at
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryFill {
+    pub order_id: String,
+    pub fill_price: f64,
+    pub fill_quantity: i32,
+}
+
+/// Send an entry built by `new_entry_order` and wait for its fill: through
+/// `paper_broker` when given, else live through `order_manager`, cancelling
+/// whatever hasn't filled after `fill_wait`. `span` is registered under the
+/// order id. Returns None when the idempotency key was placed before (the
+/// same signal bar evaluated again), so no second position is opened.
+pub async fn place_entry(
+    order_manager: &OrderManager,
+    position_manager: &PositionManager,
+    paper_broker: Option<&PaperTradingBroker>,
+    order: Order,
+    span: tracing::Span,
+    fill_wait: std::time::Duration,
+) -> Result<Option<EntryFill>> {
+    let limit_price = order.limit_price.unwrap_or_default();
+    
+    if let Some(paper_broker) = paper_broker {
+        if let Some(existing) = order_manager.intent_order(&order.idempotency_key).await {
+            info!("Entry already placed as {} - skipping", existing);
+            return Ok(None);
+        }
+        let order_id = paper_broker.place_order(
+            order.symbol.clone(),
+            order.side,
+            order.quantity,
+            OrderType::Limit,
+            order.limit_price,
+        ).await?;
+        order_manager.record_intent(&order.idempotency_key, &order_id).await;
+        position_manager.register_trade_span(&order_id, span).await;
+        let fill_price = paper_broker.get_fill_price(&order_id).await.unwrap_or(limit_price);
+        info!("📝 [PAPER] Order executed: {} @ {:.2}", order_id, fill_price);
+        return Ok(Some(EntryFill { order_id, fill_price, fill_quantity: order.quantity }));
+    }
+    
+    let quantity = order.quantity;
+    let order_id = match order_manager.submit(order).await? {
+        Submission::Placed(order_id) => order_id,
+        Submission::Duplicate(existing) => {
+            info!("Entry already placed as {} - skipping", existing);
+            return Ok(None);
+        }
+    };
+    info!("✅ Live order placed: {} - awaiting fill", order_id);
+    // Feed fills for the order arrive in its span
+    position_manager.register_trade_span(&order_id, span).await;
+    
+    // Stops are set off the real fill; whatever hasn't filled in time is
+    // cancelled and the position opens at the filled quantity
+    match order_manager.fill_or_cancel(&order_id, fill_wait).await {
+        Ok(filled) => {
+            let fill_price = filled.fill_price.unwrap_or(limit_price);
+            info!(
+                "✅ Live order filled: {} {}/{} @ {:.2} (limit {:.2})",
+                order_id, filled.fill_quantity, quantity, fill_price, limit_price
+            );
+            Ok(Some(EntryFill { order_id, fill_price, fill_quantity: filled.fill_quantity }))
+        }
+        Err(e) => {
+            warn!("⚠️  {} - entry cancelled", e);
+            position_manager.forget_trade_span(&order_id).await;
+            Err(e)
+        }
+    }
+}
+
+/// Exit of a cover position, sent by exiting the entry order itself
+fn is_cover_exit(order: &Order) -> bool {
+    order.purpose == OrderPurpose::Exit && order.stop_price.is_some()
+}
+
+/// Order-book row that reports fills for `order` (placed as `broker_order_id`);
+/// a cover exit fills through the entry's stop leg
+fn fills_order(row: &BrokerOrder, order: &Order, broker_order_id: &str) -> bool {
+    row.side == order.side
+        && (row.broker_order_id == broker_order_id
+            || (is_cover_exit(order) && row.parent_order_id.as_deref() == Some(broker_order_id)))
+}
+
+/// Turn a resting limit into a market order for the same quantity
+fn convert_to_market(order: &mut Order, now: chrono::DateTime<chrono::Utc>) {
+    order.order_type = OrderType::Market;
+    order.limit_price = None;
+    order.broker_order_id = None;
+    order.status = OrderStatus::Pending;
+    order.attempts += 1;
+    order.updated_at = now;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::broker::angel_one::test_client;
+    use crate::config::loader::test_config;
+    use futures_util::future::BoxFuture;
+    use std::sync::Mutex;
+    
+    /// Accepts every order (ids B1, B2, ...), fills nothing unless told to,
+    /// and records what was sent
+    #[derive(Default)]
+    struct MockBroker {
+        /// (broker order id, limit price; None = market)
+        placed: Mutex<Vec<(String, Option<f64>)>>,
+        cancelled: Mutex<Vec<String>>,
+        /// Cover order stops sent, and cover orders cancelled/exited
+        cover_stops: Mutex<Vec<f64>>,
+        cover_cancelled: Mutex<Vec<String>>,
+        order_book: Mutex<Vec<BrokerOrder>>,
+        /// Reject placements as a maintenance window, counting the attempts
+        maintenance: Mutex<Option<usize>>,
+        /// Reject cancels (as when the order has just filled)
+        reject_cancels: Mutex<bool>,
+        /// Fill market orders at once at this price
+        market_fill: Mutex<Option<f64>>,
+    }
+    
+    fn book_fill(broker_order_id: &str, filled_quantity: i32, average_price: f64) -> BrokerOrder {
+        BrokerOrder {
+            broker_order_id: broker_order_id.to_string(),
+            symbol: "NIFTY25JAN19500CE".to_string(),
+            token: "12345".to_string(),
+            side: Side::Buy,
+            filled_quantity,
+            average_price,
+            status: "open".to_string(),
+            order_tag: None,
+            parent_order_id: None,
+        }
+    }
+    
+    impl MockBroker {
+        fn accept(&self, price: Option<f64>) -> Result<String> {
+            if let Some(attempts) = self.maintenance.lock().unwrap().as_mut() {
+                *attempts += 1;
+                return Err(TradingError::BrokerMaintenance("System under maintenance".to_string()));
+            }
+            let mut placed = self.placed.lock().unwrap();
+            let id = format!("B{}", placed.len() + 1);
+            placed.push((id.clone(), price));
+            Ok(id)
+        }
+    }
+    
+    impl OrderBroker for MockBroker {
+        fn place_order<'a>(
+            &'a self,
+            _symbol: &'a str,
+            _token: &'a str,
+            _side: Side,
+            _quantity: i32,
+            _order_type: OrderType,
+            price: Option<f64>,
+        ) -> BoxFuture<'a, Result<String>> {
+            Box::pin(async move { self.accept(price) })
+        }
+        
+        fn place_tagged_order<'a>(
+            &'a self,
+            _symbol: &'a str,
+            _token: &'a str,
+            _side: Side,
+            quantity: i32,
+            price: Option<f64>,
+            _order_tag: &'a str,
+        ) -> BoxFuture<'a, Result<String>> {
+            Box::pin(async move {
+                let id = self.accept(price)?;
+                if let (None, Some(fill)) = (price, *self.market_fill.lock().unwrap()) {
+                    self.order_book.lock().unwrap().push(book_fill(&id, quantity, fill));
+                }
+                Ok(id)
+            })
+        }
+        
+        fn place_cover_order<'a>(
+            &'a self,
+            _symbol: &'a str,
+            _token: &'a str,
+            _side: Side,
+            _quantity: i32,
+            price: f64,
+            stop_price: f64,
+        ) -> BoxFuture<'a, Result<String>> {
+            Box::pin(async move {
+                let id = self.accept(Some(price))?;
+                self.cover_stops.lock().unwrap().push(stop_price);
+                Ok(id)
+            })
+        }
+        
+        fn cancel_cover_order<'a>(&'a self, broker_order_id: &'a str) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async move {
+                self.cover_cancelled.lock().unwrap().push(broker_order_id.to_string());
+                Ok(())
+            })
+        }
+        
+        fn cancel_order<'a>(&'a self, broker_order_id: &'a str) -> BoxFuture<'a, Result<()>> {
+            Box::pin(async move {
+                if *self.reject_cancels.lock().unwrap() {
+                    return Err(TradingError::BrokerApiError {
+                        code: "AB4008".to_string(),
+                        message: "Order already complete".to_string(),
+                    });
+                }
+                self.cancelled.lock().unwrap().push(broker_order_id.to_string());
+                Ok(())
+            })
+        }
+        
+        fn get_order_book(&self) -> BoxFuture<'_, Result<Vec<BrokerOrder>>> {
+            Box::pin(async move { Ok(self.order_book.lock().unwrap().clone()) })
+        }
+    }
+    
+    fn mock_order_manager(broker: Arc<MockBroker>) -> OrderManager {
+        let event_log = std::env::temp_dir().join(format!("orders_{}.jsonl", uuid::Uuid::new_v4()));
+        let event_bus = Arc::new(EventBus::new(event_log.display().to_string()));
+        OrderManager::new(broker, event_bus, Arc::new(test_config()))
+    }
+    
+    fn create_order_manager(event_log: &str) -> OrderManager {
+        let config = Arc::new(test_config());
+        let broker = Arc::new(test_client());
+        let event_bus = Arc::new(EventBus::new(event_log.to_string()));
+        OrderManager::new(broker, event_bus, config)
+    }
+    
+    fn open_position() -> Position {
+        Position {
+            symbol: "NIFTY25JAN19500CE".to_string(),
+            strike: 19500,
+            quantity: 50,
+            current_price: 78.0,
+            ..crate::types::test_position("P1")
+        }
+    }
+    
+    fn working_order(order_id: &str) -> Order {
+        let placed_at = chrono::Utc::now();
+        Order {
+            order_id: order_id.to_string(),
+            broker_order_id: None,
+            position_id: String::new(),
+            symbol: "NIFTY25JAN19500CE".to_string(),
+            token: "12345".to_string(),
+            side: Side::Buy,
+            order_type: OrderType::Limit,
+            quantity: 100,
+            limit_price: Some(125.0),
+            fill_price: None,
+            fill_quantity: 0,
+            fill_time: None,
+            status: OrderStatus::Submitted,
+            attempts: 0,
+            retry_count: 0,
+            idempotency_key: format!("key-{}", order_id),
+            created_at: placed_at,
+            updated_at: placed_at,
+            purpose: OrderPurpose::Entry,
+            stop_price: None,
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_partial_fill_remainder_cancelled_after_timeout() {
+        let manager = create_order_manager("test_partial_fill_events.jsonl");
+        let timeout = manager.config.partial_fill_timeout_sec as i64;
+        
+        let order = working_order("ORD1");
+        manager.orders.write().await.insert(order.order_id.clone(), order);
+        
+        manager.mark_executed("ORD1", 125.0, 50).await.unwrap();
+        let filled_at = manager.get_order("ORD1").await.unwrap().fill_time.unwrap();
+        
+        // Still within the timeout
+        let early = filled_at + chrono::Duration::seconds(timeout - 1);
+        assert!(manager.cancel_stale_partial_fills(early).await.unwrap().is_empty());
+        
+        // Timed out: remainder cancelled, order settles at filled quantity
+        let late = filled_at + chrono::Duration::seconds(timeout);
+        let cancelled = manager.cancel_stale_partial_fills(late).await.unwrap();
+        assert_eq!(cancelled.len(), 1);
+        
+        let order = manager.get_order("ORD1").await.unwrap();
+        assert_eq!(order.status, OrderStatus::Cancelled);
+        assert_eq!(order.fill_quantity, 50);
+        
+        let _ = std::fs::remove_file("test_partial_fill_events.jsonl");
+    }
+    
+    #[tokio::test]
+    async fn test_cancel_all_active_orders() {
+        let manager = create_order_manager("test_cancel_all_events.jsonl");
+        
+        {
+            let mut orders = manager.orders.write().await;
+            orders.insert("ORD1".to_string(), working_order("ORD1"));
+            orders.insert("ORD2".to_string(), working_order("ORD2"));
+        }
+        manager.mark_executed("ORD2", 125.0, 50).await.unwrap();
+        assert_eq!(manager.get_active_orders().await.len(), 2);
+        
+        let cancelled = manager.cancel_all_active("SHUTDOWN").await.unwrap();
+        assert_eq!(cancelled.len(), 2);
+        assert!(manager.get_active_orders().await.is_empty());
+        
+        let partial = manager.get_order("ORD2").await.unwrap();
+        assert_eq!(partial.status, OrderStatus::Cancelled);
+        assert_eq!(partial.fill_quantity, 50);
+        
+        let _ = std::fs::remove_file("test_cancel_all_events.jsonl");
+    }
+    
+    #[tokio::test]
+    async fn test_entry_waits_for_broker_fill_price() {
+        let manager = Arc::new(create_order_manager("test_await_fill_events.jsonl"));
+        let mut order = working_order("ORD1");
+        order.broker_order_id = Some("B1".to_string());
+        manager.orders.write().await.insert(order.order_id.clone(), order);
+        
+        // Fill lands while the entry is waiting, above the 125.0 limit
+        let filler = Arc::clone(&manager);
+        tokio::spawn(async move {
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            let book = vec![BrokerOrder {
+                broker_order_id: "B1".to_string(),
+                symbol: "NIFTY25JAN19500CE".to_string(),
+                token: "12345".to_string(),
+                side: Side::Buy,
+                filled_quantity: 100,
+                average_price: 126.5,
+                status: "complete".to_string(),
+                order_tag: None,
+                parent_order_id: None,
+            }];
+            filler.apply_broker_fills(&book).await.unwrap();
+        });
+        
+        let filled = manager.await_fill("ORD1", std::time::Duration::from_secs(5)).await.unwrap();
+        assert_eq!(filled.limit_price, Some(125.0));
+        assert_eq!(filled.fill_price, Some(126.5));
+        assert_eq!(filled.status, OrderStatus::Filled);
+        
+        // No fill at all times out
+        manager.orders.write().await.insert("ORD2".to_string(), working_order("ORD2"));
+        assert!(manager.await_fill("ORD2", std::time::Duration::from_millis(10)).await.is_err());
+        
+        let _ = std::fs::remove_file("test_await_fill_events.jsonl");
+    }
+    
+    #[tokio::test]
+    async fn test_entry_settles_at_filled_quantity() {
+        let broker = Arc::new(MockBroker::default());
+        let manager = mock_order_manager(Arc::clone(&broker));
+        let place = |key: &'static str| manager.place_order(
+            "NIFTY25JAN19500CE".to_string(),
+            "12345".to_string(),
+            Side::Buy,
+            100,
+            125.0,
+            key.to_string(),
+        );
+        let wait = std::time::Duration::from_millis(10);
+        
+        // 50 of 100 filled above the limit: the rest is cancelled
+        let partial_id = place("entry-partial").await.unwrap();
+        broker.order_book.lock().unwrap().push(book_fill("B1", 50, 126.5));
+        let order = manager.fill_or_cancel(&partial_id, wait).await.unwrap();
+        assert_eq!((order.fill_quantity, order.fill_price), (50, Some(126.5)));
+        assert_eq!(order.status, OrderStatus::Cancelled);
+        assert_eq!(*broker.cancelled.lock().unwrap(), vec!["B1".to_string()]);
+        
+        // Timed out before the fill was seen; the cancel bounces because it
+        // filled meanwhile, and the fill is picked up instead of dropped
+        let late_id = place("entry-late").await.unwrap();
+        *broker.reject_cancels.lock().unwrap() = true;
+        broker.order_book.lock().unwrap().push(book_fill("B2", 100, 124.0));
+        let order = manager.fill_or_cancel(&late_id, std::time::Duration::ZERO).await.unwrap();
+        assert_eq!((order.fill_quantity, order.fill_price), (100, Some(124.0)));
+        assert_eq!(order.status, OrderStatus::Filled);
+        
+        // Nothing filled and cancelled: no position to open
+        *broker.reject_cancels.lock().unwrap() = false;
+        let unfilled_id = place("entry-unfilled").await.unwrap();
+        assert!(manager.fill_or_cancel(&unfilled_id, wait).await.is_err());
+        assert_eq!(manager.get_order(&unfilled_id).await.unwrap().status, OrderStatus::Cancelled);
+    }
+    
+    /// What `execute_entry` does with one signal bar: place, then open
+    async fn enter_bar(
+        manager: &OrderManager,
+        positions: &PositionManager,
+        paper_broker: Option<&PaperTradingBroker>,
+        key: &str,
+    ) -> Option<EntryFill> {
+        let order = new_entry_order(
+            "NIFTY25JAN23500CE".to_string(),
+            "12345".to_string(),
+            Side::Buy,
+            75,
+            100.0,
+            key.to_string(),
+        );
+        let wait = std::time::Duration::from_millis(10);
+        let fill = place_entry(manager, positions, paper_broker, order, tracing::Span::none(), wait)
+            .await
+            .unwrap()?;
+        positions.open_position(Position {
+            quantity: fill.fill_quantity,
+            entry_price: fill.fill_price,
+            current_price: fill.fill_price,
+            ..crate::types::test_position(&fill.order_id)
+        }).await.unwrap();
+        Some(fill)
+    }
+    
+    #[tokio::test]
+    async fn test_same_signal_bar_enters_once() {
+        let dir = std::env::temp_dir().join(format!("entry_dedup_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let positions = PositionManager::new(Arc::clone(&event_bus), Arc::new(test_config()));
+        
+        // Live: the repeat is reported as a duplicate, nothing is resent
+        let broker = Arc::new(MockBroker::default());
+        broker.order_book.lock().unwrap().push(book_fill("B1", 75, 100.0));
+        let manager = mock_order_manager(Arc::clone(&broker));
+        let first = enter_bar(&manager, &positions, None, "bar-live").await.unwrap();
+        assert!(enter_bar(&manager, &positions, None, "bar-live").await.is_none());
+        assert_eq!(broker.placed.lock().unwrap().len(), 1);
+        assert_eq!(positions.get_open_positions().await.len(), 1);
+        
+        // Even once the position is closed, the bar does not re-open it
+        positions.close_position(&first.order_id, 110.0, "TARGET".to_string()).await.unwrap();
+        assert!(enter_bar(&manager, &positions, None, "bar-live").await.is_none());
+        assert!(positions.get_open_positions().await.is_empty());
+        
+        // Paper entries are deduplicated the same way
+        let paper_broker = PaperTradingBroker::new(true, 0.0);
+        assert!(enter_bar(&manager, &positions, Some(&paper_broker), "bar-paper").await.is_some());
+        assert!(enter_bar(&manager, &positions, Some(&paper_broker), "bar-paper").await.is_none());
+        assert_eq!(positions.get_open_positions().await.len(), 1);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_unfilled_entry_limit_escalates_within_fill_wait() {
+        let mut config = test_config();
+        config.entry_limit_timeout_sec = 1;
+        config.entry_fill_wait_sec = 3;
+        let broker = Arc::new(MockBroker::default());
+        *broker.market_fill.lock().unwrap() = Some(101.5);
+        let dir = std::env::temp_dir().join(format!("entry_escalation_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let config = Arc::new(config);
+        let manager = OrderManager::new(broker.clone(), Arc::clone(&event_bus), Arc::clone(&config));
+        let positions = PositionManager::new(event_bus, Arc::clone(&config));
+        
+        // The limit never fills; before the wait ends it goes to market
+        let order = new_entry_order(
+            "NIFTY25JAN23500CE".to_string(),
+            "12345".to_string(),
+            Side::Buy,
+            75,
+            100.0,
+            "bar-unfilled".to_string(),
+        );
+        let wait = std::time::Duration::from_secs(config.entry_fill_wait_sec);
+        let fill = place_entry(&manager, &positions, None, order, tracing::Span::none(), wait)
+            .await
+            .unwrap()
+            .unwrap();
+        
+        assert_eq!((fill.fill_quantity, fill.fill_price), (75, 101.5));
+        assert_eq!(*broker.cancelled.lock().unwrap(), vec!["B1".to_string()]);
+        assert_eq!(
+            *broker.placed.lock().unwrap(),
+            vec![("B1".to_string(), Some(100.0)), ("B2".to_string(), None)]
+        );
+        let order = manager.get_order(&fill.order_id).await.unwrap();
+        assert_eq!((order.order_type, order.status), (OrderType::Market, OrderStatus::Filled));
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_cover_order_tracked_by_order_manager() {
+        let broker = Arc::new(MockBroker::default());
+        let manager = mock_order_manager(Arc::clone(&broker));
+        let cover = || new_entry_order(
+            "NIFTY25JAN19500CE".to_string(),
+            "12345".to_string(),
+            Side::Buy,
+            75,
+            125.0,
+            "cover-1".to_string(),
+        ).with_stop_price(100.0);
+        
+        let Submission::Placed(order_id) = manager.submit(cover()).await.unwrap() else {
+            panic!("first submission must be placed");
+        };
+        assert_eq!(*broker.cover_stops.lock().unwrap(), vec![100.0]);
+        assert_eq!(manager.get_order(&order_id).await.unwrap().broker_order_id.as_deref(), Some("B1"));
+        
+        // Idempotent: the same intent is not sent twice
+        assert_eq!(manager.submit(cover()).await.unwrap(), Submission::Duplicate(order_id.clone()));
+        assert_eq!(broker.placed.lock().unwrap().len(), 1);
+        
+        // Never escalated to a plain market order (that would drop the stop)
+        let late = chrono::Utc::now() + chrono::Duration::seconds(manager.config.entry_limit_timeout_sec as i64 + 1);
+        assert!(manager.escalate_unfilled_limits(late).await.unwrap().is_empty());
+        
+        // Positions open at the broker's fill, not the limit
+        broker.order_book.lock().unwrap().push(book_fill("B1", 75, 124.35));
+        let filled = manager.fill_or_cancel(&order_id, std::time::Duration::from_secs(1)).await.unwrap();
+        assert_eq!((filled.fill_quantity, filled.fill_price), (75, Some(124.35)));
+        
+        // Working cover orders are visible to cancel_all_active and cancelled as covers
+        let second = manager.submit(new_entry_order(
+            "NIFTY25JAN19500CE".to_string(),
+            "12345".to_string(),
+            Side::Buy,
+            75,
+            125.0,
+            "cover-2".to_string(),
+        ).with_stop_price(100.0)).await.unwrap().order_id().to_string();
+        assert_eq!(manager.get_active_orders().await.len(), 1);
+        manager.cancel_all_active("FLATTEN").await.unwrap();
+        assert_eq!(*broker.cover_cancelled.lock().unwrap(), vec!["B2".to_string()]);
+        assert!(broker.cancelled.lock().unwrap().is_empty());
+        assert_eq!(manager.get_order(&second).await.unwrap().status, OrderStatus::Cancelled);
+    }
+    
+    /// Records the id of every span entered while installed
+    #[derive(Clone, Default)]
+    struct EnteredSpans(Arc<Mutex<Vec<tracing::span::Id>>>);
+    
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for EnteredSpans {
+        fn on_enter(&self, id: &tracing::span::Id, _ctx: tracing_subscriber::layer::Context<'_, S>) {
+            self.0.lock().unwrap().push(id.clone());
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_feed_updates_run_in_the_position_span() {
+        use tracing_subscriber::layer::SubscriberExt;
+        
+        let entered = EnteredSpans::default();
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry().with(entered.clone()));
+        
+        let broker = Arc::new(MockBroker::default());
+        let manager = mock_order_manager(Arc::clone(&broker));
+        let event_log = std::env::temp_dir().join(format!("feed_{}.jsonl", uuid::Uuid::new_v4()));
+        let positions = PositionManager::new(
+            Arc::new(EventBus::new(event_log.display().to_string())),
+            Arc::new(test_config()),
+        );
+        
+        let order_id = manager.submit(new_entry_order(
+            "NIFTY25JAN19500CE".to_string(),
+            "12345".to_string(),
+            Side::Buy,
+            50,
+            100.0,
+            "feed-1".to_string(),
+        )).await.unwrap().order_id().to_string();
+        let span = positions.new_trade_span("feed-1");
+        positions.register_trade_span(&order_id, span.clone()).await;
+        
+        // The feed reports the broker id; spans are keyed by the local order id
+        let update = OrderUpdate::Fill { broker_order_id: "B1".to_string(), fill_price: 99.5, filled_quantity: 50 };
+        assert_eq!(apply_feed_update(&manager, &positions, &update).await.unwrap(), Some(order_id.clone()));
+        assert_eq!(manager.get_order(&order_id).await.unwrap().fill_quantity, 50);
+        assert!(entered.0.lock().unwrap().contains(&span.id().unwrap()));
+        
+        // Exits run in their position's span too
+        let exit = manager.place_exit_order(
+            &Position { position_id: order_id.clone(), ..open_position() },
+            "12345",
+            110.0,
+            "TARGET",
+        ).await.unwrap();
+        let exit_broker_id = exit.broker_order_id.unwrap();
+        assert_eq!(manager.position_id_for_broker(&exit_broker_id).await, Some(order_id));
+        assert_eq!(manager.position_id_for_broker("UNKNOWN").await, None);
+    }
+    
+    #[tokio::test]
+    async fn test_exits_settle_only_on_a_confirmed_fill() {
+        let broker = Arc::new(MockBroker::default());
+        let manager = mock_order_manager(Arc::clone(&broker));
+        
+        // An unfilled exit is cancelled and reported, so the position stays open
+        let target_exit = manager.place_exit_order(&open_position(), "12345", 130.0, "TARGET").await.unwrap();
+        assert!(manager.fill_or_cancel(&target_exit.order_id, std::time::Duration::ZERO).await.is_err());
+        assert_eq!(broker.cancelled.lock().unwrap().len(), 1);
+        
+        // A cover position exits its own order rather than sending a new one
+        let entry_id = manager.submit(new_entry_order(
+            "NIFTY25JAN19500CE".to_string(),
+            "12345".to_string(),
+            Side::Buy,
+            50,
+            100.0,
+            "cover-exit".to_string(),
+        ).with_stop_price(80.0)).await.unwrap().order_id().to_string();
+        broker.order_book.lock().unwrap().push(book_fill("B2", 50, 100.0));
+        manager.fill_or_cancel(&entry_id, std::time::Duration::from_secs(1)).await.unwrap();
+        
+        let position = Position { position_id: entry_id, ..open_position() };
+        let placed_before = broker.placed.lock().unwrap().len();
+        let exit = manager.place_exit_order(&position, "12345", 78.0, "STOP_LOSS").await.unwrap();
+        assert_eq!(broker.placed.lock().unwrap().len(), placed_before);
+        assert_eq!(*broker.cover_cancelled.lock().unwrap(), vec!["B2".to_string()]);
+        
+        // ...and fills through the entry's stop leg
+        broker.order_book.lock().unwrap().push(BrokerOrder {
+            broker_order_id: "B2-SL".to_string(),
+            side: Side::Sell,
+            parent_order_id: Some("B2".to_string()),
+            ..book_fill("B2-SL", 50, 79.5)
+        });
+        let filled = manager.fill_or_cancel(&exit.order_id, std::time::Duration::from_secs(1)).await.unwrap();
+        assert_eq!((filled.fill_quantity, filled.fill_price), (50, Some(79.5)));
+    }
+    
+    #[tokio::test]
+    async fn test_unfilled_limit_escalates_to_market() {
+        let broker = Arc::new(MockBroker::default());
+        let manager = mock_order_manager(Arc::clone(&broker));
+        let timeout = chrono::Duration::seconds(manager.config.entry_limit_timeout_sec as i64);
+        
+        // Entry limit and a TARGET exit limit, both left unfilled by the broker
+        let entry_id = manager.place_order(
+            "NIFTY25JAN19500CE".to_string(),
+            "12345".to_string(),
+            Side::Buy,
+            75,
+            125.0,
+            "entry-1".to_string(),
+        ).await.unwrap();
+        let mut position = open_position();
+        position.position_id = "P2".to_string();
+        let exit = manager.place_exit_order(&position, "12345", 130.0, "TARGET").await.unwrap();
+        assert_eq!(exit.order_type, OrderType::Limit);
+        let placed_at = manager.get_order(&entry_id).await.unwrap().updated_at;
+        
+        // Still resting within the timeout: nothing to escalate
+        let early = placed_at + timeout - chrono::Duration::seconds(1);
+        assert!(manager.escalate_unfilled_limits(early).await.unwrap().is_empty());
+        
+        // Timed out: the entry limit (B1) is cancelled and re-sent at market
+        let late = exit.updated_at.max(placed_at) + timeout;
+        let escalated = manager.escalate_unfilled_limits(late).await.unwrap();
+        assert_eq!(escalated.len(), 1);
+        assert_eq!(escalated[0].order_id, entry_id);
+        assert_eq!(*broker.cancelled.lock().unwrap(), vec!["B1".to_string()]);
+        assert_eq!(broker.placed.lock().unwrap().last().unwrap(), &("B3".to_string(), None));
+        
+        let order = manager.get_order(&entry_id).await.unwrap();
+        assert_eq!(order.order_type, OrderType::Market);
+        assert_eq!(order.broker_order_id.as_deref(), Some("B3"));
+        assert_eq!(order.quantity, 75);
+        
+        // The exit limit keeps resting at its price
+        let exit = manager.get_order(&exit.order_id).await.unwrap();
+        assert_eq!((exit.order_type, exit.limit_price), (OrderType::Limit, Some(130.0)));
+        
+        // A partial fill is never escalated
+        let mut partial = working_order("ORD3");
+        partial.fill_quantity = 50;
+        partial.status = OrderStatus::PartiallyFilled;
+        assert!(!is_escalation_due(&partial, late + timeout, timeout));
+    }
+    
+    #[tokio::test]
+    async fn test_maintenance_fails_order_without_retrying() {
+        let broker = Arc::new(MockBroker::default());
+        *broker.maintenance.lock().unwrap() = Some(0);
+        let manager = mock_order_manager(Arc::clone(&broker));
+        assert!(manager.config.order_max_retries > 0);
+        
+        let started = std::time::Instant::now();
+        let err = manager.place_order(
+            "NIFTY25JAN19500CE".to_string(),
+            "12345".to_string(),
+            Side::Buy,
+            75,
+            125.0,
+            "entry-maintenance".to_string(),
+        ).await.unwrap_err();
+        
+        // Surfaced as is (the main loop pauses on it), after a single attempt
+        assert!(matches!(err, TradingError::BrokerMaintenance(_)));
+        assert_eq!(*broker.maintenance.lock().unwrap(), Some(1));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+        assert!(manager.get_active_orders().await.is_empty());
+        
+        let mut position = open_position();
+        position.position_id = "P3".to_string();
+        let err = manager.place_exit_order(&position, "12345", 78.0, "STOP_LOSS").await.unwrap_err();
+        assert!(matches!(err, TradingError::BrokerMaintenance(_)));
+    }
+    
+    #[tokio::test]
+    async fn test_order_book_needed_only_around_working_orders() {
+        let manager = create_order_manager("test_needs_order_book_events.jsonl");
+        let now = chrono::Utc::now();
+        assert!(!manager.needs_order_book(now).await);
+        
+        manager.orders.write().await.insert("ORD1".to_string(), working_order("ORD1"));
+        assert!(manager.needs_order_book(now).await);
+        
+        // A fill is still checked for late duplicates, then left alone
+        let mut filled = working_order("ORD1");
+        filled.status = OrderStatus::Filled;
+        filled.updated_at = now - chrono::Duration::minutes(2);
+        manager.orders.write().await.insert("ORD1".to_string(), filled.clone());
+        assert!(manager.needs_order_book(now).await);
+        
+        filled.updated_at = now - chrono::Duration::minutes(DUPLICATE_FILL_WINDOW_MIN + 1);
+        manager.orders.write().await.insert("ORD1".to_string(), filled);
+        assert!(!manager.needs_order_book(now).await);
+        
+        let _ = std::fs::remove_file("test_needs_order_book_events.jsonl");
+    }
+    
+    #[tokio::test]
+    async fn test_duplicate_fill_detected_for_one_intent() {
+        let manager = create_order_manager("test_duplicate_fill_events.jsonl");
+        
+        let mut order = working_order("ORD1");
+        order.broker_order_id = Some("B2".to_string());
+        order.status = OrderStatus::Filled;
+        order.fill_quantity = 100;
+        manager.orders.write().await.insert(order.order_id.clone(), order.clone());
+        
+        let book_entry = |id: &str, tag: &str, filled: i32| BrokerOrder {
+            broker_order_id: id.to_string(),
+            symbol: order.symbol.clone(),
+            token: order.token.clone(),
+            side: Side::Buy,
+            filled_quantity: filled,
+            average_price: 125.0,
+            status: "complete".to_string(),
+            order_tag: Some(tag.to_string()),
+            parent_order_id: None,
+        };
+        let tag = order_tag(&order.idempotency_key);
+        
+        // First attempt timed out client-side but filled; the retry filled too
+        let book = vec![
+            book_entry("B1", &tag, 100),
+            book_entry("B2", &tag, 100),
+            book_entry("B3", "other-intent", 100),
+            book_entry("B4", &tag, 0),
+        ];
+        
+        let duplicates = manager.reconcile_fills(&book).await.unwrap();
+        assert_eq!(duplicates.len(), 1);
+        
+        let duplicate = &duplicates[0];
+        assert_eq!(duplicate.order_id, "ORD1");
+        assert_eq!(duplicate.kept_broker_order_id, "B2");
+        assert_eq!(duplicate.duplicate_broker_order_ids, vec!["B1".to_string()]);
+        assert_eq!(duplicate.extra_quantity, 100);
+        // No broker session in tests: square-off is retried next time
+        assert!(!duplicate.squared_off);
+        
+        let logged = std::fs::read_to_string("test_duplicate_fill_events.jsonl").unwrap();
+        assert!(logged.contains("DuplicateFill"));
+        
+        // Cleanup
+        let _ = std::fs::remove_file("test_duplicate_fill_events.jsonl");
+    }
+    
+    #[tokio::test]
+    async fn test_stop_loss_exit_uses_market_order() {
+        let manager = create_order_manager("test_exit_order_events.jsonl");
+        assert_eq!(manager.config.exit_order_type, OrderType::Market);
+        let position = open_position();
+        
+        // No broker session in tests, so placement fails after the intent is recorded
+        assert!(manager.place_exit_order(&position, "12345", 78.0, "STOP_LOSS").await.is_err());
+        
+        let orders: Vec<Order> = manager.orders.read().await.values().cloned().collect();
+        assert_eq!(orders.len(), 1);
+        assert_eq!(orders[0].order_type, OrderType::Market);
+        assert_eq!(orders[0].limit_price, None);
+        assert_eq!(orders[0].side, Side::Sell);
+        assert_eq!(orders[0].quantity, 50);
+        
+        // Targets can still rest as limits
+        assert_eq!(exit_order_type("TARGET", OrderType::Market), OrderType::Limit);
+        assert_eq!(exit_order_type("EOD", OrderType::Market), OrderType::Market);
+        
+        let _ = std::fs::remove_file("test_exit_order_events.jsonl");
+    }
+}
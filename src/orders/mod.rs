@@ -1,6 +1,9 @@
 pub mod manager;
 pub mod validator;
 
-pub use manager::OrderManager;
+pub use manager::{
+    apply_feed_update, exit_order_type, new_entry_order, order_tag, place_entry, DuplicateFill, EntryFill, OrderManager,
+    Submission,
+};
 pub use validator::OrderValidator;
 
@@ -1,6 +1,18 @@
 /// Pre-order validation - All 9 checks from spec
+use chrono::{DateTime, Utc};
+
 use crate::error::{Result, TradingError};
-use crate::types::{Config, Instrument, Side};
+use crate::time::current_session_state;
+use crate::types::{Config, Instrument, SessionState, Side};
+use crate::utils::is_past_eod_exit;
+
+/// Bid/ask spread as a fraction of the mid; None without a usable two-sided quote
+pub fn spread_pct(bid: f64, ask: f64) -> Option<f64> {
+    if bid <= 0.0 || ask < bid {
+        return None;
+    }
+    Some((ask - bid) / ((ask + bid) / 2.0))
+}
 
 pub struct OrderValidator {
     config: std::sync::Arc<Config>,
@@ -40,7 +52,7 @@ impl OrderValidator {
         self.check_symbol_validity(symbol, instrument)?;
         
         // Check 7: Market hours
-        self.check_market_hours()?;
+        self.check_session(chrono::Utc::now(), false)?;
         
         // Check 8: Quantity > 0
         self.check_positive_quantity(quantity)?;
@@ -140,14 +152,42 @@ impl OrderValidator {
         Ok(())
     }
     
-    /// Check 7: Market is open
-    fn check_market_hours(&self) -> Result<()> {
-        use crate::utils::is_market_open;
+    /// Check 7: Session is `Open`; new entries also stop at `eod_exit_time`,
+    /// leaving the square-off window until the close for exits only
+    pub fn check_session(&self, now: DateTime<Utc>, is_exit: bool) -> Result<()> {
+        let state = current_session_state(now);
+        if state != SessionState::Open {
+            return Err(TradingError::MarketClosed(format!(
+                "Session is {}",
+                state.as_str()
+            )));
+        }
         
-        if !is_market_open(chrono::Utc::now()) {
-            return Err(TradingError::MarketClosed(
-                "Market is closed".to_string()
-            ));
+        if !is_exit && is_past_eod_exit(now, &self.config.eod_exit_time) {
+            return Err(TradingError::MarketClosed(format!(
+                "Square-off window (after {}) accepts exits only",
+                self.config.eod_exit_time
+            )));
+        }
+        
+        Ok(())
+    }
+    
+    /// Entry quote is tight enough: `(ask - bid) / mid` within `max_spread_pct`
+    /// (no limit configured, or no usable bid/ask, passes)
+    pub fn check_spread(&self, bid: f64, ask: f64) -> Result<()> {
+        let (Some(max_spread), Some(spread)) = (self.config.max_spread_pct, spread_pct(bid, ask)) else {
+            return Ok(());
+        };
+        
+        if spread > max_spread {
+            return Err(TradingError::NoTradeSignal(format!(
+                "wide_spread: {:.2}% > {:.2}% (bid {:.2} / ask {:.2})",
+                spread * 100.0,
+                max_spread * 100.0,
+                bid,
+                ask
+            )));
         }
         
         Ok(())
@@ -219,6 +259,55 @@ mod tests {
         assert!(validator.check_tick_size(125.53, 0.05).is_err());
     }
     
+    #[test]
+    fn test_orders_gated_on_session_state() {
+        use chrono::TimeZone;
+        use chrono_tz::Asia::Kolkata;
+        
+        let validator = OrderValidator::new(std::sync::Arc::new(crate::config::loader::test_config()));
+        let ist = |h, m| Kolkata.with_ymd_and_hms(2025, 1, 15, h, m, 0).unwrap().with_timezone(&Utc);
+        
+        // Mid-session: allowed
+        assert!(validator.check_session(ist(11, 0), false).is_ok());
+        
+        // After the close: refused for entries and exits alike
+        assert!(matches!(validator.check_session(ist(15, 45), false), Err(TradingError::MarketClosed(_))));
+        assert!(validator.check_session(ist(15, 45), true).is_err());
+        
+        // Pre-open auction is not tradeable
+        assert!(validator.check_session(ist(9, 5), false).is_err());
+        
+        // Square-off window: exits only
+        assert!(validator.check_session(ist(15, 25), false).is_err());
+        assert!(validator.check_session(ist(15, 25), true).is_ok());
+        
+        // Mahashivratri holiday
+        let holiday = Kolkata.with_ymd_and_hms(2025, 2, 26, 11, 0, 0).unwrap().with_timezone(&Utc);
+        assert!(validator.check_session(holiday, false).is_err());
+    }
+    
+    #[test]
+    fn test_spread_filter() {
+        let mut config = crate::config::loader::test_config();
+        config.max_spread_pct = Some(0.02);
+        let validator = OrderValidator::new(std::sync::Arc::new(config));
+        
+        // 120.00 / 120.60: 0.5% of mid
+        assert!(validator.check_spread(120.0, 120.6).is_ok());
+        
+        // 100 / 106: ~5.8% of mid
+        assert!((spread_pct(100.0, 106.0).unwrap() - 6.0 / 103.0).abs() < 1e-12);
+        match validator.check_spread(100.0, 106.0) {
+            Err(TradingError::NoTradeSignal(reason)) => assert!(reason.starts_with("wide_spread")),
+            other => panic!("expected wide_spread, got {:?}", other),
+        }
+        
+        // No two-sided quote, or no limit configured: not filtered
+        assert!(validator.check_spread(0.0, 0.0).is_ok());
+        let unlimited = OrderValidator::new(std::sync::Arc::new(crate::config::loader::test_config()));
+        assert!(unlimited.check_spread(100.0, 106.0).is_ok());
+    }
+    
     fn create_test_config() -> Config {
         // Would create actual config in real test
         unimplemented!()
@@ -0,0 +1,71 @@
+/// Exit artifacts written when a position closes
+/// Each exit is saved as `<dir>/exit_<symbol>_<ts>.json` and appended to the
+/// day's `<dir>/exits_YYYYMMDD.jsonl` log.
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use tracing::info;
+
+use crate::error::Result;
+use crate::types::Trade;
+
+/// Write the exit record for a closed trade; returns the per-exit file path
+pub async fn save_exit_record(dir: &Path, trade: &Trade) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir).await?;
+
+    let exit_file = dir.join(format!(
+        "exit_{}_{}.json",
+        trade.symbol,
+        trade.exit_time.format("%Y%m%d_%H%M%S")
+    ));
+    tokio::fs::write(&exit_file, serde_json::to_string_pretty(trade)?).await?;
+    info!("💾 Saved exit to: {}", exit_file.display());
+
+    let daily_exits_file = dir.join(format!("exits_{}.jsonl", trade.exit_time.format("%Y%m%d")));
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&daily_exits_file)
+        .await?;
+    file.write_all(format!("{}\n", serde_json::to_string(trade)?).as_bytes()).await?;
+    file.flush().await?;
+
+    Ok(exit_file)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::loader::test_config;
+    use crate::events::EventBus;
+    use crate::positions::PositionManager;
+    use crate::types::{test_position, Position};
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_closed_trade_becomes_exit_record() {
+        let dir = std::env::temp_dir().join(format!("exits_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let manager = PositionManager::new(event_bus, Arc::new(test_config()));
+
+        manager.open_position(Position {
+            entry_reason: "TEST".to_string(),
+            ..test_position("P1")
+        }).await.unwrap();
+
+        let trade = manager.close_position("P1", 130.0, "TARGET".to_string()).await.unwrap();
+        // The open book no longer has it; the trade carries the exit
+        assert!(manager.get_position("P1").await.is_none());
+        assert_eq!(trade.exit_reason, "TARGET");
+
+        let path = save_exit_record(&dir, &trade).await.unwrap();
+        let saved: Trade = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.trade_id, trade.trade_id);
+        assert_eq!(saved.exit_price, 130.0);
+
+        let daily = dir.join(format!("exits_{}.jsonl", trade.exit_time.format("%Y%m%d")));
+        assert_eq!(std::fs::read_to_string(daily).unwrap().lines().count(), 1);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,208 @@
+/// Exit rules engine
+/// Each rule checks one exit condition; the engine returns the triggered
+/// exit with the highest `ExitPriority` (Mandatory > Risk > Profit > Technical)
+use chrono::{DateTime, Utc};
+
+use crate::types::{Config, ExitPriority, Position};
+use crate::utils::is_past_eod_exit;
+
+/// Inputs available to exit rules for one position
+#[derive(Debug, Clone)]
+pub struct ExitContext<'a> {
+    pub position: &'a Position,
+    pub current_price: f64,
+    pub now: DateTime<Utc>,
+    /// Latest India VIX, if known
+    pub vix: Option<f64>,
+    /// Result of the strategy's technical exit check (e.g. DI alignment lost)
+    pub technical_exit: bool,
+}
+
+/// A triggered exit
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExitDecision {
+    pub priority: ExitPriority,
+    pub reason: String,
+}
+
+/// Single exit condition
+pub trait ExitRule: Send + Sync {
+    fn priority(&self) -> ExitPriority;
+
+    /// Exit reason if the rule fires
+    fn evaluate(&self, ctx: &ExitContext) -> Option<String>;
+}
+
+/// Hard stop loss
+pub struct StopLossRule;
+
+impl ExitRule for StopLossRule {
+    fn priority(&self) -> ExitPriority {
+        ExitPriority::Risk
+    }
+
+    fn evaluate(&self, ctx: &ExitContext) -> Option<String> {
+        (ctx.current_price <= ctx.position.stop_loss).then(|| "STOP_LOSS".to_string())
+    }
+}
+
+/// Trailing stop (only once activated)
+pub struct TrailingStopRule;
+
+impl ExitRule for TrailingStopRule {
+    fn priority(&self) -> ExitPriority {
+        ExitPriority::Risk
+    }
+
+    fn evaluate(&self, ctx: &ExitContext) -> Option<String> {
+        let trail_stop = ctx.position.trailing_stop?;
+        (ctx.position.trailing_active && ctx.current_price <= trail_stop)
+            .then(|| "TRAILING_STOP".to_string())
+    }
+}
+
+/// Profit target
+pub struct TargetRule;
+
+impl ExitRule for TargetRule {
+    fn priority(&self) -> ExitPriority {
+        ExitPriority::Profit
+    }
+
+    fn evaluate(&self, ctx: &ExitContext) -> Option<String> {
+        let target = ctx.position.target?;
+        (ctx.current_price >= target).then(|| "TARGET".to_string())
+    }
+}
+
+/// Strategy technical exit
+pub struct TechnicalRule;
+
+impl ExitRule for TechnicalRule {
+    fn priority(&self) -> ExitPriority {
+        ExitPriority::Technical
+    }
+
+    fn evaluate(&self, ctx: &ExitContext) -> Option<String> {
+        ctx.technical_exit.then(|| "TECHNICAL".to_string())
+    }
+}
+
+/// EOD mandatory exit
+pub struct TimeExitRule {
+    pub eod_exit_time: String,
+}
+
+impl ExitRule for TimeExitRule {
+    fn priority(&self) -> ExitPriority {
+        ExitPriority::Mandatory
+    }
+
+    fn evaluate(&self, ctx: &ExitContext) -> Option<String> {
+        is_past_eod_exit(ctx.now, &self.eod_exit_time).then(|| "EOD".to_string())
+    }
+}
+
+/// Exit on VIX spike
+pub struct VixExitRule {
+    pub spike_threshold: f64,
+}
+
+impl ExitRule for VixExitRule {
+    fn priority(&self) -> ExitPriority {
+        ExitPriority::Risk
+    }
+
+    fn evaluate(&self, ctx: &ExitContext) -> Option<String> {
+        let vix = ctx.vix?;
+        (vix >= self.spike_threshold).then(|| "VIX_SPIKE".to_string())
+    }
+}
+
+/// Evaluates registered exit rules
+#[derive(Default)]
+pub struct ExitEngine {
+    rules: Vec<Box<dyn ExitRule>>,
+}
+
+impl ExitEngine {
+    pub fn new() -> Self {
+        ExitEngine { rules: Vec::new() }
+    }
+
+    /// Standard rule set: stop loss, trailing, target, technical, EOD and VIX
+    pub fn from_config(config: &Config) -> Self {
+        let mut engine = ExitEngine::new()
+            .with_rule(TimeExitRule { eod_exit_time: config.eod_exit_time.clone() })
+            .with_rule(StopLossRule)
+            .with_rule(VixExitRule { spike_threshold: config.vix_spike_threshold })
+            .with_rule(TargetRule)
+            .with_rule(TechnicalRule);
+
+        if config.use_trailing_stop {
+            engine.register(TrailingStopRule);
+        }
+
+        engine
+    }
+
+    /// Add a rule (builder style)
+    pub fn with_rule(mut self, rule: impl ExitRule + 'static) -> Self {
+        self.register(rule);
+        self
+    }
+
+    /// Add a rule
+    pub fn register(&mut self, rule: impl ExitRule + 'static) {
+        self.rules.push(Box::new(rule));
+    }
+
+    /// Highest-priority triggered exit; ties go to the earliest registered rule
+    pub fn evaluate(&self, ctx: &ExitContext) -> Option<ExitDecision> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                rule.evaluate(ctx).map(|reason| ExitDecision {
+                    priority: rule.priority(),
+                    reason,
+                })
+            })
+            .min_by_key(|decision| decision.priority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::test_position;
+
+    #[test]
+    fn test_higher_priority_exit_wins() {
+        // Technical registered first so ordering alone can't pick the winner
+        let engine = ExitEngine::new()
+            .with_rule(TechnicalRule)
+            .with_rule(TargetRule)
+            .with_rule(StopLossRule);
+
+        let position = test_position("P1");
+        let ctx = ExitContext {
+            position: &position,
+            current_price: 135.0,
+            now: Utc::now(),
+            vix: None,
+            technical_exit: true,
+        };
+
+        let decision = engine.evaluate(&ctx).unwrap();
+        assert_eq!(decision.reason, "TARGET");
+        assert_eq!(decision.priority, ExitPriority::Profit);
+
+        // Only the technical rule fires below target
+        let ctx = ExitContext { current_price: 110.0, ..ctx };
+        assert_eq!(engine.evaluate(&ctx).unwrap().reason, "TECHNICAL");
+
+        // Nothing fires
+        let ctx = ExitContext { technical_exit: false, ..ctx };
+        assert!(engine.evaluate(&ctx).is_none());
+    }
+}
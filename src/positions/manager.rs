@@ -1,12 +1,47 @@
 /// Position tracking with stop loss and trailing stop
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::sync::Arc;
+use chrono::{DateTime, NaiveDate, Utc};
 use tokio::sync::RwLock;
-use tracing::{debug, info, warn};
+use tracing::{debug, field, info, info_span, warn, Instrument, Span};
 
+use crate::data::Timeframe;
 use crate::error::{Result, TradingError};
 use crate::events::{Event, EventBus, EventPayload, EventType};
-use crate::types::{Config, Position, PositionStatus, Trade};
+use crate::positions::exit_rules::{ExitContext, ExitEngine};
+use crate::positions::snapshot::PortfolioSnapshot;
+use crate::risk::greeks::{position_greeks, Greeks};
+use crate::time::{Clock, SystemClock};
+use crate::types::{Config, Position, PositionStatus, Side, Trade};
+use crate::utils::{calculate_days_to_expiry, is_past_eod_exit};
+
+/// Outcome of closing every open position
+#[derive(Debug, Default)]
+pub struct CloseAllReport {
+    pub closed: Vec<Trade>,
+    /// Position IDs that could not be closed after the last retry
+    pub still_open: Vec<String>,
+}
+
+impl CloseAllReport {
+    pub fn all_closed(&self) -> bool {
+        self.still_open.is_empty()
+    }
+}
+
+/// Close reason for an operator-requested flatten
+const MANUAL_FLATTEN_REASON: &str = "manual_flatten";
+
+/// Bars whose completed highs advance the trail under `trail_on_bar_close`
+const TRAIL_BAR_TIMEFRAME: Timeframe = Timeframe::OneHour;
+
+/// Forming trail bar for one position
+#[derive(Debug, Clone, Copy)]
+struct TrailBar {
+    start: DateTime<Utc>,
+    high: f64,
+}
 
 pub struct PositionManager {
     event_bus: Arc<EventBus>,
@@ -15,28 +50,111 @@ pub struct PositionManager {
     /// Active positions
     positions: Arc<RwLock<HashMap<String, Position>>>,
     
+    /// Closed positions in their final state, kept until the daily reset
+    closed_positions: Arc<RwLock<HashMap<String, Position>>>,
+    
     /// Completed trades
     trades: Arc<RwLock<Vec<Trade>>>,
     
     /// Daily PNL tracker
     daily_pnl: Arc<RwLock<f64>>,
+    
+    /// Latest India VIX (fed by `RiskManager::update_vix`)
+    current_vix: Arc<RwLock<Option<f64>>>,
+    
+    /// India VIX when each open position was entered
+    entry_vix: Arc<RwLock<HashMap<String, f64>>>,
+    
+    /// Positions that already scaled in (one add per position)
+    scaled_in: Arc<RwLock<HashSet<String>>>,
+    
+    /// Forming trail bar per position (`trail_on_bar_close`)
+    trail_bars: Arc<RwLock<HashMap<String, TrailBar>>>,
+    
+    /// Tracing span per open position (`trade_spans`)
+    trade_spans: Arc<RwLock<HashMap<String, Span>>>,
+    
+    /// Time source for exits and snapshots
+    clock: Arc<dyn Clock>,
+    
+    /// Exit rules evaluated on each price update
+    exit_engine: ExitEngine,
 }
 
 impl PositionManager {
     pub fn new(event_bus: Arc<EventBus>, config: Arc<Config>) -> Self {
+        let exit_engine = ExitEngine::from_config(&config);
+        
         PositionManager {
             event_bus,
             config,
             positions: Arc::new(RwLock::new(HashMap::new())),
+            closed_positions: Arc::new(RwLock::new(HashMap::new())),
             trades: Arc::new(RwLock::new(Vec::new())),
             daily_pnl: Arc::new(RwLock::new(0.0)),
+            current_vix: Arc::new(RwLock::new(None)),
+            entry_vix: Arc::new(RwLock::new(HashMap::new())),
+            scaled_in: Arc::new(RwLock::new(HashSet::new())),
+            trail_bars: Arc::new(RwLock::new(HashMap::new())),
+            trade_spans: Arc::new(RwLock::new(HashMap::new())),
+            clock: Arc::new(SystemClock),
+            exit_engine,
+        }
+    }
+    
+    /// Replace the default exit rules
+    pub fn with_exit_engine(mut self, exit_engine: ExitEngine) -> Self {
+        self.exit_engine = exit_engine;
+        self
+    }
+    
+    /// Use a custom clock (e.g. `MockClock` in tests)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Check if positions must be flattened for the EOD mandatory exit
+    pub fn is_eod_exit_due(&self) -> bool {
+        is_past_eod_exit(self.clock.now(), &self.config.eod_exit_time)
+    }
+    
+    /// Span for a new trade; `position_id` is recorded once the entry order
+    /// is placed. Disabled spans (`trade_spans = false`) log nothing extra.
+    pub fn new_trade_span(&self, idempotency_key: &str) -> Span {
+        if !self.config.trade_spans {
+            return Span::none();
         }
+        info_span!("trade", idempotency_key = %idempotency_key, position_id = field::Empty)
+    }
+    
+    /// Attach `span` to `position_id` so fills, updates and the close log in it
+    pub async fn register_trade_span(&self, position_id: &str, span: Span) {
+        span.record("position_id", position_id);
+        self.trade_spans.write().await.insert(position_id.to_string(), span);
+    }
+    
+    /// Span registered for `position_id` (`Span::none()` if there is none)
+    pub async fn trade_span(&self, position_id: &str) -> Span {
+        self.trade_spans.read().await.get(position_id).cloned().unwrap_or_else(Span::none)
+    }
+    
+    /// Drop the span of a position that closed or never opened
+    pub async fn forget_trade_span(&self, position_id: &str) {
+        self.trade_spans.write().await.remove(position_id);
     }
     
     /// Open a new position
-    pub async fn open_position(&self, position: Position) -> Result<()> {
+    pub async fn open_position(&self, mut position: Position) -> Result<()> {
         let position_id = position.position_id.clone();
         
+        // Multi-leg: track the combined premium; the legs carry their sides
+        if !position.legs.is_empty() {
+            position.side = Side::Buy;
+            position.entry_price = position.net_entry_price();
+            position.current_price = position.net_price();
+        }
+        
         // Store position
         {
             let mut positions = self.positions.write().await;
@@ -46,6 +164,15 @@ impl PositionManager {
             positions.insert(position_id.clone(), position.clone());
         }
         
+        if !self.trade_spans.read().await.contains_key(&position_id) {
+            let span = self.new_trade_span(&position.idempotency_key);
+            self.register_trade_span(&position_id, span).await;
+        }
+        
+        if let Some(vix) = *self.current_vix.read().await {
+            self.entry_vix.write().await.insert(position_id.clone(), vix);
+        }
+        
         // Emit event
         self.event_bus.publish(Event::new(
             EventType::PositionOpened,
@@ -83,13 +210,20 @@ impl PositionManager {
         position.current_price = current_price;
         
         // Calculate PNL
-        let price_diff = current_price - position.entry_price;
-        position.pnl = price_diff * position.quantity as f64;
-        position.pnl_pct = (price_diff / position.entry_price) * 100.0;
+        position.pnl = position.unrealized_pnl(current_price);
+        position.pnl_pct = position.pnl_pct_at(current_price);
+        
+        // Trail off each price, or only off completed bar highs so an
+        // intrabar wick can't drag the stop up
+        let trail_high = if self.config.trail_on_bar_close {
+            self.completed_trail_bar_high(position_id, current_price).await
+        } else {
+            Some(current_price)
+        };
         
         // Update trailing stop if active
-        if self.config.use_trailing_stop && position.trailing_active {
-            let new_trail = current_price * (1.0 - self.config.trail_gap_pct);
+        if let Some(trail_high) = trail_high.filter(|_| self.config.use_trailing_stop && position.trailing_active) {
+            let new_trail = trail_high * (1.0 - self.config.trail_gap_pct);
             if let Some(current_trail) = position.trailing_stop {
                 if new_trail > current_trail {
                     position.trailing_stop = Some(new_trail);
@@ -100,7 +234,7 @@ impl PositionManager {
                         EventPayload::TrailingStopUpdated {
                             position_id: position_id.to_string(),
                             new_trail_stop: new_trail,
-                            high_price: current_price,
+                            high_price: trail_high,
                         },
                     )).await?;
                     
@@ -138,60 +272,59 @@ impl PositionManager {
             );
         }
         
-        // Check stop loss
-        if current_price <= position.stop_loss {
-            self.event_bus.publish(Event::new(
-                EventType::StopLossTriggered,
-                EventPayload::StopLossTriggered {
-                    position_id: position_id.to_string(),
-                    stop_loss: position.stop_loss,
-                    current_price,
-                },
-            )).await?;
-            
-            warn!(
-                "Stop loss triggered for {}: {:.2} <= {:.2}",
-                position_id,
-                current_price,
-                position.stop_loss
-            );
-            
-            return Ok(Some("STOP_LOSS".to_string()));
-        }
-        
-        // Check trailing stop
-        if let Some(trail_stop) = position.trailing_stop {
-            if position.trailing_active && current_price <= trail_stop {
-                info!(
-                    "Trailing stop triggered for {}: {:.2} <= {:.2}",
-                    position_id,
-                    current_price,
-                    trail_stop
-                );
-                return Ok(Some("TRAILING_STOP".to_string()));
-            }
-        }
+        // Evaluate exit rules (VIX and technical exits are checked by callers
+        // that have those inputs)
+        let ctx = ExitContext {
+            position,
+            current_price,
+            now: self.clock.now(),
+            vix: None,
+            technical_exit: false,
+        };
         
-        // Check target
-        if let Some(target) = position.target {
-            if current_price >= target {
-                self.event_bus.publish(Event::new(
-                    EventType::TargetReached,
-                    EventPayload::TargetReached {
-                        position_id: position_id.to_string(),
-                        target,
+        if let Some(decision) = self.exit_engine.evaluate(&ctx) {
+            match decision.reason.as_str() {
+                "STOP_LOSS" => {
+                    self.event_bus.publish(Event::new(
+                        EventType::StopLossTriggered,
+                        EventPayload::StopLossTriggered {
+                            position_id: position_id.to_string(),
+                            stop_loss: position.stop_loss,
+                            current_price,
+                        },
+                    )).await?;
+                    
+                    warn!(
+                        "Stop loss triggered for {}: {:.2} <= {:.2}",
+                        position_id,
                         current_price,
-                    },
-                )).await?;
-                
-                info!(
-                    "Target reached for {}: {:.2} >= {:.2}",
-                    position_id,
-                    current_price,
-                    target
-                );
-                return Ok(Some("TARGET".to_string()));
+                        position.stop_loss
+                    );
+                }
+                "TARGET" => {
+                    let target = position.target.unwrap_or(current_price);
+                    self.event_bus.publish(Event::new(
+                        EventType::TargetReached,
+                        EventPayload::TargetReached {
+                            position_id: position_id.to_string(),
+                            target,
+                            current_price,
+                        },
+                    )).await?;
+                    
+                    info!(
+                        "Target reached for {}: {:.2} >= {:.2}",
+                        position_id,
+                        current_price,
+                        target
+                    );
+                }
+                reason => {
+                    info!("Exit triggered for {} @ {:.2}: {}", position_id, current_price, reason);
+                }
             }
+            
+            return Ok(Some(decision.reason));
         }
         
         // Emit position update event
@@ -208,12 +341,63 @@ impl PositionManager {
         Ok(None)
     }
     
-    /// Close a position
+    /// Mark the open leg on `symbol` of a multi-leg position, then update the
+    /// position at its combined premium so PNL, trailing and exits follow it
+    pub async fn mark_leg(&self, position_id: &str, symbol: &str, price: f64) -> Result<Option<String>> {
+        let net_price = {
+            let mut positions = self.positions.write().await;
+            let position = positions.get_mut(position_id)
+                .ok_or_else(|| TradingError::PositionNotFound(position_id.to_string()))?;
+            let leg = position.legs.iter_mut()
+                .find(|leg| leg.is_open() && leg.symbol == symbol)
+                .ok_or_else(|| TradingError::PositionNotFound(format!("{} leg {}", position_id, symbol)))?;
+            leg.current_price = price;
+            position.net_price()
+        };
+        
+        self.update_position(position_id, net_price).await
+    }
+    
+    /// Fold `price` into the position's forming trail bar; returns the high of
+    /// the bar that just completed when `price` opens a new one
+    async fn completed_trail_bar_high(&self, position_id: &str, price: f64) -> Option<f64> {
+        let bar_start = TRAIL_BAR_TIMEFRAME.get_bar_boundary(self.clock.now());
+        let mut trail_bars = self.trail_bars.write().await;
+        let bar = trail_bars
+            .entry(position_id.to_string())
+            .or_insert(TrailBar { start: bar_start, high: price });
+        
+        if bar_start > bar.start {
+            let completed = bar.high;
+            *bar = TrailBar { start: bar_start, high: price };
+            Some(completed)
+        } else {
+            bar.high = bar.high.max(price);
+            None
+        }
+    }
+    
+    /// Close a position (logged in its trade span)
     pub async fn close_position(
         &self,
         position_id: &str,
         exit_price: f64,
         exit_reason: String,
+    ) -> Result<Trade> {
+        let span = self.trade_span(position_id).await;
+        let trade = self.close_position_in_span(position_id, exit_price, exit_reason)
+            .instrument(span)
+            .await?;
+        self.forget_trade_span(position_id).await;
+        self.scaled_in.write().await.remove(position_id);
+        Ok(trade)
+    }
+    
+    async fn close_position_in_span(
+        &self,
+        position_id: &str,
+        exit_price: f64,
+        exit_reason: String,
     ) -> Result<Trade> {
         let mut positions = self.positions.write().await;
         
@@ -222,18 +406,26 @@ impl PositionManager {
         
         position.status = PositionStatus::Closed;
         
+        // Multi-leg: `exit_price` is the combined premium; legs close at their marks
+        for leg in position.legs.iter_mut().filter(|leg| leg.is_open()) {
+            leg.exit_price = Some(leg.current_price);
+        }
+        
         // Calculate final PNL
-        let price_diff = exit_price - position.entry_price;
-        let pnl_gross = price_diff * position.quantity as f64;
-        let pnl_gross_pct = (price_diff / position.entry_price) * 100.0;
+        let pnl_gross = position.realized_pnl(exit_price);
+        let pnl_gross_pct = position.pnl_pct_at(exit_price);
         
         // Estimate brokerage (simplified)
         let brokerage = (exit_price * position.quantity as f64 * 0.0003).max(20.0);
         let pnl_net = pnl_gross - brokerage;
         
         // Create trade record
-        let exit_time = chrono::Utc::now();
+        let exit_time = self.clock.now();
         let duration_sec = (exit_time - position.entry_time).num_seconds();
+        position.exit_time = Some(exit_time);
+        let vix_at_entry = self.entry_vix.write().await.remove(position_id).unwrap_or(0.0);
+        self.trail_bars.write().await.remove(position_id);
+        let vix_at_exit = self.current_vix.read().await.unwrap_or(0.0);
         
         let trade = Trade {
             trade_id: uuid::Uuid::new_v4().to_string(),
@@ -257,8 +449,8 @@ impl PositionManager {
             duration_sec,
             high_price: position.current_price.max(position.entry_price),
             low_price: position.current_price.min(position.entry_price),
-            vix_at_entry: 0.0, // Would be tracked separately
-            vix_at_exit: 0.0,
+            vix_at_entry,
+            vix_at_exit,
         };
         
         // Update daily PNL
@@ -273,6 +465,12 @@ impl PositionManager {
             trades.push(trade.clone());
         }
         
+        // Keep the final state queryable
+        position.current_price = exit_price;
+        position.pnl = pnl_gross;
+        position.pnl_pct = pnl_gross_pct;
+        self.retain_closed(position).await;
+        
         // Emit event
         self.event_bus.publish(Event::new(
             EventType::PositionClosed,
@@ -296,6 +494,193 @@ impl PositionManager {
         Ok(trade)
     }
     
+    /// Open positions at or past `scale_in_pnl_pct` that have not scaled in yet
+    pub async fn scale_in_candidates(&self) -> Vec<Position> {
+        let Some(threshold) = self.config.scale_in_pnl_pct else {
+            return Vec::new();
+        };
+        
+        let scaled_in = self.scaled_in.read().await;
+        self.get_open_positions().await
+            .into_iter()
+            .filter(|p| p.pnl_pct >= threshold * 100.0 && !scaled_in.contains(&p.position_id))
+            .collect()
+    }
+    
+    /// Add `add_quantity` filled at `fill_price`, blending the entry price
+    /// The stop is left where it is; each position scales in at most once
+    pub async fn scale_in(&self, position_id: &str, add_quantity: i32, fill_price: f64) -> Result<Position> {
+        if add_quantity <= 0 || fill_price <= 0.0 {
+            return Err(TradingError::InvalidParameter(format!(
+                "Scale-in needs a positive quantity and price (got {} @ {:.2})",
+                add_quantity, fill_price
+            )));
+        }
+        if !self.scaled_in.write().await.insert(position_id.to_string()) {
+            return Err(TradingError::InvalidParameter(format!("{} already scaled in", position_id)));
+        }
+        
+        let position = {
+            let mut positions = self.positions.write().await;
+            let Some(position) = positions.get_mut(position_id) else {
+                self.scaled_in.write().await.remove(position_id);
+                return Err(TradingError::PositionNotFound(position_id.to_string()));
+            };
+            
+            let total = position.quantity + add_quantity;
+            position.entry_price = (position.entry_price * position.quantity as f64
+                + fill_price * add_quantity as f64) / total as f64;
+            position.quantity = total;
+            position.pnl = position.unrealized_pnl(position.current_price);
+            position.pnl_pct = position.pnl_pct_at(position.current_price);
+            position.clone()
+        };
+        
+        self.event_bus.publish(Event::new(
+            EventType::PositionUpdated,
+            EventPayload::PositionUpdated {
+                position_id: position_id.to_string(),
+                current_price: position.current_price,
+                pnl: position.pnl,
+                pnl_pct: position.pnl_pct,
+            },
+        )).await?;
+        
+        info!(
+            "Scaled in {}: +{} @ {:.2} → {} @ avg {:.2}",
+            position_id, add_quantity, fill_price, position.quantity, position.entry_price
+        );
+        Ok(position)
+    }
+    
+    /// Manually move the stop loss; it must stay on the losing side of the
+    /// current price (below for longs, above for shorts)
+    pub async fn set_stop_loss(&self, position_id: &str, new_stop: f64) -> Result<()> {
+        let (previous, current_price) = {
+            let mut positions = self.positions.write().await;
+            let position = positions.get_mut(position_id)
+                .ok_or_else(|| TradingError::PositionNotFound(position_id.to_string()))?;
+            
+            let valid = new_stop > 0.0 && match position.side {
+                Side::Buy => new_stop < position.current_price,
+                Side::Sell => new_stop > position.current_price,
+            };
+            if !valid {
+                return Err(TradingError::InvalidParameter(format!(
+                    "Stop {:.2} is not on the losing side of {:?} {} @ {:.2}",
+                    new_stop, position.side, position_id, position.current_price
+                )));
+            }
+            
+            let previous = position.stop_loss;
+            position.stop_loss = new_stop;
+            (previous, position.current_price)
+        };
+        
+        self.publish_level_adjusted(position_id, "STOP_LOSS", Some(previous), new_stop, current_price).await?;
+        info!("Stop loss for {} moved {:.2} -> {:.2}", position_id, previous, new_stop);
+        Ok(())
+    }
+    
+    /// Manually set the profit target; it must be on the winning side of the
+    /// current price (above for longs, below for shorts)
+    pub async fn set_target(&self, position_id: &str, target: f64) -> Result<()> {
+        let (previous, current_price) = {
+            let mut positions = self.positions.write().await;
+            let position = positions.get_mut(position_id)
+                .ok_or_else(|| TradingError::PositionNotFound(position_id.to_string()))?;
+            
+            let valid = target > 0.0 && match position.side {
+                Side::Buy => target > position.current_price,
+                Side::Sell => target < position.current_price,
+            };
+            if !valid {
+                return Err(TradingError::InvalidParameter(format!(
+                    "Target {:.2} is not on the winning side of {:?} {} @ {:.2}",
+                    target, position.side, position_id, position.current_price
+                )));
+            }
+            
+            let previous = position.target;
+            position.target = Some(target);
+            (previous, position.current_price)
+        };
+        
+        self.publish_level_adjusted(position_id, "TARGET", previous, target, current_price).await?;
+        info!("Target for {} set to {:.2}", position_id, target);
+        Ok(())
+    }
+    
+    async fn publish_level_adjusted(
+        &self,
+        position_id: &str,
+        level: &str,
+        previous: Option<f64>,
+        new_level: f64,
+        current_price: f64,
+    ) -> Result<()> {
+        self.event_bus.publish(Event::new(
+            EventType::ExitLevelAdjusted,
+            EventPayload::ExitLevelAdjusted {
+                position_id: position_id.to_string(),
+                level: level.to_string(),
+                previous,
+                new_level,
+                current_price,
+            },
+        )).await
+    }
+    
+    /// Settle an open position at a reduced quantity (e.g. partial fill cancelled)
+    pub async fn settle_quantity(&self, position_id: &str, quantity: i32) -> Result<()> {
+        let mut positions = self.positions.write().await;
+        let position = positions.get_mut(position_id)
+            .ok_or_else(|| TradingError::PositionNotFound(position_id.to_string()))?;
+        
+        if quantity < position.quantity {
+            warn!(
+                "Position {} settled at {} (requested {})",
+                position_id, quantity, position.quantity
+            );
+            position.quantity = quantity;
+        }
+        
+        Ok(())
+    }
+    
+    /// Record the latest India VIX for stamping trades at open and close
+    pub async fn set_current_vix(&self, vix: f64) {
+        *self.current_vix.write().await = Some(vix);
+    }
+    
+    /// Store a closed position, evicting the earliest exit beyond `max_closed_positions`
+    async fn retain_closed(&self, position: Position) {
+        let limit = self.config.max_closed_positions;
+        if limit == 0 {
+            return;
+        }
+        
+        let mut closed = self.closed_positions.write().await;
+        closed.insert(position.position_id.clone(), position);
+        
+        while closed.len() > limit {
+            let Some(oldest) = closed
+                .values()
+                .min_by_key(|p| p.exit_time)
+                .map(|p| p.position_id.clone())
+            else {
+                break;
+            };
+            closed.remove(&oldest);
+        }
+    }
+    
+    /// Get a closed position (final price and PNL) by ID
+    pub async fn get_closed_position(&self, position_id: &str) -> Option<Position> {
+        let closed = self.closed_positions.read().await;
+        closed.get(position_id).cloned()
+    }
+    
     /// Get position by ID
     pub async fn get_position(&self, position_id: &str) -> Option<Position> {
         let positions = self.positions.read().await;
@@ -311,16 +696,65 @@ impl PositionManager {
             .collect()
     }
     
+    /// Estimate aggregate Greeks across the open legs of open positions, with
+    /// VIX as the implied vol proxy. `spots` is keyed by underlying and
+    /// `expiries` by option symbol; legs missing either are left out.
+    pub async fn aggregate_greeks(
+        &self,
+        spots: &HashMap<String, f64>,
+        expiries: &HashMap<String, NaiveDate>,
+        vix: f64,
+    ) -> Greeks {
+        let positions = self.get_open_positions().await;
+        let now = self.clock.now();
+        
+        let mut total = Greeks::default();
+        for position in &positions {
+            let Some(&spot) = spots.get(&position.underlying) else {
+                warn!("No spot for {} - {} left out of Greeks", position.underlying, position.position_id);
+                continue;
+            };
+            for leg in position.open_legs() {
+                let Some(&expiry) = expiries.get(&leg.symbol) else {
+                    warn!("No expiry for {} - left out of Greeks", leg.symbol);
+                    continue;
+                };
+                total += position_greeks(
+                    leg.option_type,
+                    leg.side,
+                    leg.quantity,
+                    spot,
+                    leg.strike as f64,
+                    calculate_days_to_expiry(now, expiry) as f64,
+                    vix,
+                );
+            }
+        }
+        
+        debug!(
+            "Aggregate Greeks ({} positions): delta={:.2}, theta={:.2}, vega={:.2}",
+            positions.len(),
+            total.delta,
+            total.theta,
+            total.vega
+        );
+        
+        total
+    }
+    
     /// Get daily PNL
     pub async fn get_daily_pnl(&self) -> f64 {
         let pnl = self.daily_pnl.read().await;
         *pnl
     }
     
-    /// Reset daily PNL (at EOD)
+    /// Reset daily PNL and the day's closed positions and trades (at EOD)
     pub async fn reset_daily_pnl(&self) {
         let mut pnl = self.daily_pnl.write().await;
         *pnl = 0.0;
+        self.closed_positions.write().await.clear();
+        self.trades.write().await.clear();
+        self.scaled_in.write().await.clear();
         info!("Daily PNL reset");
     }
     
@@ -330,31 +764,668 @@ impl PositionManager {
         trades.clone()
     }
     
-    /// Close all open positions (emergency)
-    pub async fn close_all_positions(&self, reason: String) -> Result<Vec<Trade>> {
-        let position_ids: Vec<String> = {
-            let positions = self.positions.read().await;
-            positions.keys().cloned().collect()
-        };
+    /// Capture the whole book (all positions, trades and daily PNL)
+    pub async fn portfolio_snapshot(&self) -> PortfolioSnapshot {
+        let positions = self.positions.read().await;
+        let trades = self.trades.read().await;
+        let daily_pnl = self.daily_pnl.read().await;
+        
+        PortfolioSnapshot {
+            timestamp: self.clock.now(),
+            positions: positions.values().cloned().collect(),
+            trades: trades.clone(),
+            daily_pnl: *daily_pnl,
+        }
+    }
+    
+    /// Close all open positions (emergency) at their last price
+    pub async fn close_all_positions(&self, reason: String) -> Result<CloseAllReport> {
+        self.close_all_positions_with(reason, |position| async move { Ok(position.current_price) }).await
+    }
+    
+    /// Operator panic button: close every position through `exit` and publish
+    /// `PositionsClosed`. Unlike the kill switch, trading carries on afterwards.
+    pub async fn manual_flatten<F, Fut>(&self, exit: F) -> Result<CloseAllReport>
+    where
+        F: Fn(Position) -> Fut,
+        Fut: Future<Output = Result<f64>>,
+    {
+        warn!("🛑 Manual flatten requested");
+        let report = self.close_all_positions_with(MANUAL_FLATTEN_REASON.to_string(), exit).await?;
         
-        let mut closed_trades = Vec::new();
+        self.event_bus.publish(Event::new(
+            EventType::PositionsClosed,
+            EventPayload::PositionsClosed {
+                position_ids: report.closed.iter().map(|trade| trade.position_id.clone()).collect(),
+                reason: MANUAL_FLATTEN_REASON.to_string(),
+                still_open: report.still_open.clone(),
+            },
+        )).await?;
         
-        for position_id in position_ids {
-            let position = self.get_position(&position_id).await;
-            if let Some(pos) = position {
-                // Use current price as exit price
-                match self.close_position(&position_id, pos.current_price, reason.clone()).await {
-                    Ok(trade) => closed_trades.push(trade),
+        Ok(report)
+    }
+    
+    /// Close all open positions, calling `exit` first for each (e.g. to send
+    /// the closing order) and closing at the price it returns. Failures are
+    /// retried with `order_retry_backoffs_sec` up to `order_max_retries`; an
+    /// accepted exit is never sent again, only its book close is retried.
+    pub async fn close_all_positions_with<F, Fut>(&self, reason: String, exit: F) -> Result<CloseAllReport>
+    where
+        F: Fn(Position) -> Fut,
+        Fut: Future<Output = Result<f64>>,
+    {
+        let position_ids = self.positions.read().await.keys().cloned().collect();
+        self.close_positions_with(position_ids, reason, exit).await
+    }
+    
+    /// `close_all_positions_with` for just `position_ids`
+    pub async fn close_positions_with<F, Fut>(
+        &self,
+        position_ids: Vec<String>,
+        reason: String,
+        exit: F,
+    ) -> Result<CloseAllReport>
+    where
+        F: Fn(Position) -> Fut,
+        Fut: Future<Output = Result<f64>>,
+    {
+        let mut pending: Vec<(String, Option<f64>)> = position_ids.into_iter().map(|id| (id, None)).collect();
+        let mut report = CloseAllReport::default();
+        let max_retries = self.config.order_max_retries as usize;
+        
+        for attempt in 0..=max_retries {
+            if attempt > 0 {
+                let backoff_sec = self.config.order_retry_backoffs_sec
+                    .get(attempt - 1)
+                    .copied()
+                    .unwrap_or(self.config.retry_cap_sec)
+                    .min(self.config.retry_cap_sec);
+                warn!("Retrying close of {} positions in {}s (attempt {})", pending.len(), backoff_sec, attempt + 1);
+                tokio::time::sleep(std::time::Duration::from_secs(backoff_sec)).await;
+            }
+            
+            let mut failed = Vec::new();
+            for (position_id, exit_price) in pending {
+                // Closed elsewhere in the meantime
+                let Some(position) = self.get_position(&position_id).await else {
+                    continue;
+                };
+                
+                let exit_price = match exit_price {
+                    Some(price) => price,
+                    None => match exit(position).await {
+                        Ok(price) => price,
+                        Err(e) => {
+                            warn!("Exit for position {} failed: {}", position_id, e);
+                            failed.push((position_id, None));
+                            continue;
+                        }
+                    },
+                };
+                
+                match self.close_position(&position_id, exit_price, reason.clone()).await {
+                    Ok(trade) => report.closed.push(trade),
                     Err(e) => {
                         warn!("Failed to close position {}: {}", position_id, e);
+                        failed.push((position_id, Some(exit_price)));
                     }
                 }
             }
+            
+            pending = failed;
+            if pending.is_empty() {
+                break;
+            }
         }
         
-        info!("Closed {} positions - Reason: {}", closed_trades.len(), reason);
+        report.still_open = pending.into_iter().map(|(position_id, _)| position_id).collect();
+        if report.still_open.is_empty() {
+            info!("Closed {} positions - Reason: {}", report.closed.len(), reason);
+        } else {
+            warn!(
+                "Closed {} positions - Reason: {} - still open after retries: {}",
+                report.closed.len(),
+                reason,
+                report.still_open.join(", ")
+            );
+        }
         
-        Ok(closed_trades)
+        Ok(report)
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::loader::test_config;
+    use crate::time::MockClock;
+    use crate::types::test_position;
+    use chrono::TimeZone;
+    use chrono_tz::Asia::Kolkata;
+    
+    #[test]
+    fn test_eod_exit_across_boundary() {
+        let mut config = test_config();
+        config.eod_exit_time = "15:20:00".to_string();
+        
+        let start = Kolkata.with_ymd_and_hms(2025, 1, 15, 15, 19, 0).unwrap().with_timezone(&chrono::Utc);
+        let clock = Arc::new(MockClock::new(start));
+        let event_bus = Arc::new(EventBus::new("test_position_events.jsonl".to_string()));
+        let manager = PositionManager::new(event_bus, Arc::new(config))
+            .with_clock(clock.clone());
+        
+        assert!(!manager.is_eod_exit_due());
+        
+        clock.advance(chrono::Duration::seconds(59));
+        assert!(!manager.is_eod_exit_due());
+        
+        clock.advance(chrono::Duration::seconds(1));
+        assert!(manager.is_eod_exit_due());
+    }
+    
+    #[tokio::test]
+    async fn test_closed_position_keeps_final_state() {
+        let dir = std::env::temp_dir().join(format!("closed_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = test_config();
+        config.max_closed_positions = 1;
+        
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let start = chrono::Utc::now();
+        let clock = Arc::new(MockClock::new(start));
+        let manager = PositionManager::new(event_bus, Arc::new(config))
+            .with_clock(clock.clone());
+        
+        manager.open_position(test_position("P1")).await.unwrap();
+        manager.close_position("P1", 120.0, "TARGET".to_string()).await.unwrap();
+        
+        assert!(manager.get_position("P1").await.is_none());
+        let closed = manager.get_closed_position("P1").await.unwrap();
+        assert_eq!(closed.status, PositionStatus::Closed);
+        assert_eq!(closed.current_price, 120.0);
+        assert_eq!(closed.pnl, 20.0 * 75.0);
+        assert_eq!(closed.exit_time, Some(start));
+        
+        // Bounded: a later close evicts the earliest
+        clock.advance(chrono::Duration::minutes(5));
+        manager.open_position(test_position("P2")).await.unwrap();
+        manager.close_position("P2", 90.0, "STOP_LOSS".to_string()).await.unwrap();
+        assert!(manager.get_closed_position("P1").await.is_none());
+        assert!(manager.get_closed_position("P2").await.is_some());
+        
+        // Cleared with the daily reset
+        assert_eq!(manager.get_daily_trades().await.len(), 2);
+        manager.reset_daily_pnl().await;
+        assert!(manager.get_closed_position("P2").await.is_none());
+        assert!(manager.get_daily_trades().await.is_empty());
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_trades_stamped_with_entry_and_exit_vix() {
+        let dir = std::env::temp_dir().join(format!("vix_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let manager = PositionManager::new(event_bus, Arc::new(test_config()));
+        
+        // Unknown VIX leaves the trade untagged
+        manager.open_position(test_position("P0")).await.unwrap();
+        let trade = manager.close_position("P0", 100.0, "TEST".to_string()).await.unwrap();
+        assert_eq!((trade.vix_at_entry, trade.vix_at_exit), (0.0, 0.0));
+        
+        manager.set_current_vix(13.5).await;
+        manager.open_position(test_position("P1")).await.unwrap();
+        manager.set_current_vix(27.2).await;
+        let trade = manager.close_position("P1", 90.0, "VIX_SPIKE".to_string()).await.unwrap();
+        assert_eq!(trade.vix_at_entry, 13.5);
+        assert_eq!(trade.vix_at_exit, 27.2);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_manual_stop_and_target_adjustments() {
+        let dir = std::env::temp_dir().join(format!("levels_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let manager = PositionManager::new(event_bus, Arc::new(test_config()));
+        
+        manager.open_position(test_position("P1")).await.unwrap();
+        manager.update_position("P1", 115.0).await.unwrap();
+        
+        // Tighten the stop under the market and move the target
+        manager.set_stop_loss("P1", 105.0).await.unwrap();
+        manager.set_target("P1", 140.0).await.unwrap();
+        let position = manager.get_position("P1").await.unwrap();
+        assert_eq!(position.stop_loss, 105.0);
+        assert_eq!(position.target, Some(140.0));
+        
+        // A long's stop at/above the price or target below it is rejected
+        assert!(manager.set_stop_loss("P1", 115.0).await.is_err());
+        assert!(manager.set_stop_loss("P1", 0.0).await.is_err());
+        assert!(manager.set_target("P1", 110.0).await.is_err());
+        assert!(manager.set_stop_loss("MISSING", 90.0).await.is_err());
+        
+        let position = manager.get_position("P1").await.unwrap();
+        assert_eq!(position.stop_loss, 105.0);
+        assert_eq!(position.target, Some(140.0));
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_scale_in_blends_entry_price() {
+        let dir = std::env::temp_dir().join(format!("scale_in_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = test_config();
+        config.scale_in_pnl_pct = Some(0.25);
+        config.use_trailing_stop = false;
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let manager = PositionManager::new(event_bus, Arc::new(config));
+        
+        let mut position = test_position("P1");
+        position.target = None;
+        manager.open_position(position).await.unwrap();
+        
+        // +20%: below the threshold
+        manager.update_position("P1", 120.0).await.unwrap();
+        assert!(manager.scale_in_candidates().await.is_empty());
+        
+        // +25%: scale in one lot at 125
+        manager.update_position("P1", 125.0).await.unwrap();
+        let candidates = manager.scale_in_candidates().await;
+        assert_eq!(candidates.len(), 1);
+        
+        let scaled = manager.scale_in("P1", 75, 125.0).await.unwrap();
+        assert_eq!(scaled.quantity, 150);
+        assert!((scaled.entry_price - 112.5).abs() < 1e-9);
+        assert_eq!(scaled.stop_loss, 80.0);
+        
+        // Only once per position
+        assert!(manager.scale_in_candidates().await.is_empty());
+        assert!(manager.scale_in("P1", 75, 130.0).await.is_err());
+        
+        // The once-only mark goes with the position
+        manager.close_position("P1", 130.0, "TARGET".to_string()).await.unwrap();
+        assert!(manager.scaled_in.read().await.is_empty());
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_bar_close_trailing_ignores_intrabar_wick() {
+        let dir = std::env::temp_dir().join(format!("trail_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let start = Kolkata.with_ymd_and_hms(2025, 1, 15, 10, 20, 0).unwrap().with_timezone(&Utc);
+        
+        // Activate at 103, wick to 115, pull back to 110, close the bar at 114,
+        // then open the next hour at 114
+        let series = [(0, 103.0), (10, 115.0), (20, 110.0), (30, 114.0), (60, 114.0)];
+        
+        let mut exits = Vec::new();
+        for trail_on_bar_close in [false, true] {
+            let mut config = test_config();
+            config.use_trailing_stop = true;
+            config.trail_activate_pnl_pct = 0.02;
+            config.trail_gap_pct = 0.015;
+            config.trail_on_bar_close = trail_on_bar_close;
+            let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+            let clock = Arc::new(MockClock::new(start));
+            let manager = PositionManager::new(event_bus, Arc::new(config)).with_clock(clock.clone());
+            manager.open_position(test_position("P1")).await.unwrap();
+            
+            let mut exit = None;
+            for (minutes, price) in series {
+                clock.set(start + chrono::Duration::minutes(minutes));
+                if let Some(reason) = manager.update_position("P1", price).await.unwrap() {
+                    exit = Some((reason, minutes));
+                    break;
+                }
+            }
+            exits.push((exit, manager.get_position("P1").await.and_then(|p| p.trailing_stop)));
+        }
+        
+        // Tick trailing chases the wick (trail 113.275) and is stopped out on the pullback
+        assert_eq!(exits[0].0, Some(("TRAILING_STOP".to_string(), 20)));
+        
+        // Bar-close trailing holds through the bar, then ratchets to the completed high
+        assert_eq!(exits[1].0, None);
+        let trail = exits[1].1.unwrap();
+        assert!((trail - 115.0 * 0.985).abs() < 1e-9);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_manual_flatten_keeps_trading() {
+        let dir = std::env::temp_dir().join(format!("flatten_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let manager = PositionManager::new(Arc::clone(&event_bus), Arc::new(test_config()));
+        for id in ["P1", "P2"] {
+            manager.open_position(test_position(id)).await.unwrap();
+        }
+        
+        let report = manager.manual_flatten(|position| async move { Ok(position.current_price) }).await.unwrap();
+        assert!(report.all_closed());
+        assert_eq!(report.closed.len(), 2);
+        assert!(report.closed.iter().all(|trade| trade.exit_reason == "manual_flatten"));
+        assert!(manager.get_open_positions().await.is_empty());
+        
+        let events = event_bus.replay_events(DateTime::<Utc>::MIN_UTC).await.unwrap();
+        let closed = events.iter().find_map(|e| match &e.payload {
+            EventPayload::PositionsClosed { position_ids, reason, .. } => Some((position_ids.len(), reason.clone())),
+            _ => None,
+        });
+        assert_eq!(closed, Some((2, "manual_flatten".to_string())));
+        assert!(!events.iter().any(|e| matches!(
+            e.event_type,
+            EventType::KillSwitchActivated | EventType::GracefulShutdownInitiated
+        )));
+        
+        // Not a shutdown: the bot keeps trading
+        manager.open_position(test_position("P3")).await.unwrap();
+        assert!(manager.get_position("P3").await.is_some());
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_close_all_retries_failed_exit() {
+        let dir = std::env::temp_dir().join(format!("close_all_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = test_config();
+        config.order_max_retries = 2;
+        config.order_retry_backoffs_sec = vec![0, 0];
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let manager = PositionManager::new(event_bus, Arc::new(config));
+        for id in ["P1", "P2", "P3"] {
+            manager.open_position(test_position(id)).await.unwrap();
+        }
+        
+        // P2's exit fails once then goes through; P3's never does
+        let attempts = std::sync::Mutex::new(HashMap::<String, usize>::new());
+        let report = manager.close_all_positions_with("EOD".to_string(), |position| {
+            let tries = {
+                let mut attempts = attempts.lock().unwrap();
+                let tries = attempts.entry(position.position_id.clone()).or_default();
+                *tries += 1;
+                *tries
+            };
+            async move {
+                match position.position_id.as_str() {
+                    "P2" if tries == 1 => Err(TradingError::OrderPlacementFailed("exchange busy".to_string())),
+                    "P3" => Err(TradingError::OrderPlacementFailed("no instrument".to_string())),
+                    _ => Ok(position.current_price),
+                }
+            }
+        }).await.unwrap();
+        
+        let mut closed: Vec<&str> = report.closed.iter().map(|t| t.position_id.as_str()).collect();
+        closed.sort();
+        assert_eq!(closed, vec!["P1", "P2"]);
+        assert_eq!(report.still_open, vec!["P3".to_string()]);
+        assert!(!report.all_closed());
+        
+        let attempts = attempts.into_inner().unwrap();
+        assert_eq!((attempts["P1"], attempts["P2"], attempts["P3"]), (1, 2, 3));
+        assert!(manager.get_position("P3").await.is_some());
+        
+        // A subset close leaves the other positions alone
+        manager.open_position(test_position("P4")).await.unwrap();
+        let report = manager.close_positions_with(vec!["P4".to_string()], "EXPIRY_DAY_EXIT".to_string(), |position| async move {
+            Ok(position.current_price)
+        }).await.unwrap();
+        assert_eq!(report.closed.len(), 1);
+        assert!(manager.get_position("P3").await.is_some());
+        assert!(manager.get_position("P4").await.is_none());
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    /// Event message with the fields of its enclosing spans
+    type CapturedEvent = (String, HashMap<String, String>);
+    
+    /// Captures every event logged while installed
+    #[derive(Clone, Default)]
+    struct SpanFieldCapture {
+        events: Arc<std::sync::Mutex<Vec<CapturedEvent>>>,
+    }
+    
+    #[derive(Default)]
+    struct FieldMap(HashMap<String, String>);
+    
+    impl tracing::field::Visit for FieldMap {
+        fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value));
+        }
+        
+        fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+    }
+    
+    impl<S> tracing_subscriber::Layer<S> for SpanFieldCapture
+    where
+        S: tracing::Subscriber + for<'a> tracing_subscriber::registry::LookupSpan<'a>,
+    {
+        fn on_new_span(
+            &self,
+            attrs: &tracing::span::Attributes<'_>,
+            id: &tracing::span::Id,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            let mut fields = FieldMap::default();
+            attrs.record(&mut fields);
+            if let Some(span) = ctx.span(id) {
+                span.extensions_mut().insert(fields);
+            }
+        }
+        
+        fn on_record(
+            &self,
+            id: &tracing::span::Id,
+            values: &tracing::span::Record<'_>,
+            ctx: tracing_subscriber::layer::Context<'_, S>,
+        ) {
+            if let Some(span) = ctx.span(id) {
+                if let Some(fields) = span.extensions_mut().get_mut::<FieldMap>() {
+                    values.record(fields);
+                }
+            }
+        }
+        
+        fn on_event(&self, event: &tracing::Event<'_>, ctx: tracing_subscriber::layer::Context<'_, S>) {
+            let mut message = FieldMap::default();
+            event.record(&mut message);
+            let mut fields = HashMap::new();
+            if let Some(scope) = ctx.event_scope(event) {
+                for span in scope {
+                    if let Some(span_fields) = span.extensions().get::<FieldMap>() {
+                        fields.extend(span_fields.0.clone());
+                    }
+                }
+            }
+            let message = message.0.remove("message").unwrap_or_default();
+            self.events.lock().unwrap().push((message, fields));
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_trade_span_fields_attached() {
+        use tracing_subscriber::layer::SubscriberExt;
+        
+        let capture = SpanFieldCapture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+        
+        let dir = std::env::temp_dir().join(format!("trade_span_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let manager = PositionManager::new(event_bus, Arc::new(test_config()));
+        
+        // Entry: span created from the idempotency key, position_id recorded on placement
+        let span = manager.new_trade_span("key-1");
+        async {
+            info!("placing entry");
+            manager.register_trade_span("P1", Span::current()).await;
+            let mut position = test_position("P1");
+            position.idempotency_key = "key-1".to_string();
+            manager.open_position(position).await.unwrap();
+        }
+        .instrument(span)
+        .await;
+        
+        // Close: picks the registered span up by position_id
+        manager.close_position("P1", 110.0, "TARGET".to_string()).await.unwrap();
+        
+        let events = capture.events.lock().unwrap().clone();
+        let fields = |message: &str| events.iter()
+            .find(|(m, _)| m.starts_with(message))
+            .map(|(_, fields)| fields.clone())
+            .unwrap_or_else(|| panic!("no '{}' event", message));
+        
+        // Before placement only the key is known
+        let placing = fields("placing entry");
+        assert_eq!(placing.get("idempotency_key").map(String::as_str), Some("key-1"));
+        assert!(!placing.contains_key("position_id"));
+        
+        for message in ["Position opened", "Position closed"] {
+            let trade = fields(message);
+            assert_eq!(trade.get("position_id").map(String::as_str), Some("P1"), "{}", message);
+            assert_eq!(trade.get("idempotency_key").map(String::as_str), Some("key-1"), "{}", message);
+        }
+        
+        // Span dropped with the position
+        assert!(manager.trade_span("P1").await.is_none());
+        
+        // Disabled: no span fields
+        let mut config = test_config();
+        config.trade_spans = false;
+        let event_bus = Arc::new(EventBus::new(dir.join("events2.jsonl").display().to_string()));
+        let manager = PositionManager::new(event_bus, Arc::new(config));
+        assert!(manager.new_trade_span("key-2").is_none());
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_greeks_use_each_position_spot_expiry_and_legs() {
+        use crate::types::{Leg, OptionType};
+        
+        let dir = std::env::temp_dir().join(format!("greeks_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let now = Kolkata.with_ymd_and_hms(2025, 1, 20, 10, 0, 0).unwrap().with_timezone(&chrono::Utc);
+        let manager = PositionManager::new(event_bus, Arc::new(test_config()))
+            .with_clock(Arc::new(MockClock::new(now)));
+        
+        let mut bank = test_position("P1");
+        bank.underlying = "BANKNIFTY".to_string();
+        bank.symbol = "BANKNIFTY27FEB2549000CE".to_string();
+        bank.strike = 49000;
+        bank.quantity = 30;
+        manager.open_position(bank).await.unwrap();
+        
+        // Spread: the closed short leg no longer counts
+        let leg = |symbol: &str, strike: i32, side: Side, exit_price: Option<f64>| Leg {
+            symbol: symbol.to_string(),
+            strike,
+            option_type: OptionType::PE,
+            side,
+            quantity: 75,
+            entry_price: 100.0,
+            current_price: 100.0,
+            exit_price,
+        };
+        let mut spread = test_position("P2");
+        spread.legs = vec![
+            leg("NIFTY23JAN2523500PE", 23500, Side::Buy, None),
+            leg("NIFTY23JAN2523300PE", 23300, Side::Sell, Some(60.0)),
+        ];
+        manager.open_position(spread).await.unwrap();
+        
+        let spots = HashMap::from([
+            ("NIFTY".to_string(), 23450.0),
+            ("BANKNIFTY".to_string(), 49120.0),
+        ]);
+        let expiries = HashMap::from([
+            ("BANKNIFTY27FEB2549000CE".to_string(), NaiveDate::from_ymd_opt(2025, 2, 27).unwrap()),
+            ("NIFTY23JAN2523500PE".to_string(), NaiveDate::from_ymd_opt(2025, 1, 23).unwrap()),
+            ("NIFTY23JAN2523300PE".to_string(), NaiveDate::from_ymd_opt(2025, 1, 23).unwrap()),
+        ]);
+        let total = manager.aggregate_greeks(&spots, &expiries, 15.0).await;
+        
+        let mut expected = position_greeks(OptionType::CE, Side::Buy, 30, 49120.0, 49000.0, 38.0, 15.0);
+        expected += position_greeks(OptionType::PE, Side::Buy, 75, 23450.0, 23500.0, 3.0, 15.0);
+        assert!((total.delta - expected.delta).abs() < 1e-9);
+        assert!((total.theta - expected.theta).abs() < 1e-9);
+        assert!((total.vega - expected.vega).abs() < 1e-9);
+        
+        // Unknown spot: that position is left out
+        let nifty_only = HashMap::from([("NIFTY".to_string(), 23450.0)]);
+        let partial = manager.aggregate_greeks(&nifty_only, &expiries, 15.0).await;
+        let leg_only = position_greeks(OptionType::PE, Side::Buy, 75, 23450.0, 23500.0, 3.0, 15.0);
+        assert!((partial.delta - leg_only.delta).abs() < 1e-9);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_multi_leg_pnl_and_exits_follow_the_legs() {
+        use crate::types::{Leg, OptionType};
+        
+        let dir = std::env::temp_dir().join(format!("legs_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = test_config();
+        config.use_trailing_stop = false;
+        let event_bus = Arc::new(EventBus::new(dir.join("events.jsonl").display().to_string()));
+        let now = Kolkata.with_ymd_and_hms(2025, 1, 20, 10, 0, 0).unwrap().with_timezone(&chrono::Utc);
+        let manager = PositionManager::new(event_bus, Arc::new(config))
+            .with_clock(Arc::new(MockClock::new(now)));
+        
+        let leg = |symbol: &str, option_type: OptionType, entry_price: f64| Leg {
+            symbol: symbol.to_string(),
+            strike: 23500,
+            option_type,
+            side: Side::Buy,
+            quantity: 75,
+            entry_price,
+            current_price: entry_price,
+            exit_price: None,
+        };
+        // Long straddle: 230 combined premium, stop 184, target 299
+        manager.open_position(Position {
+            symbol: "NIFTY25JAN23500STRADDLE".to_string(),
+            stop_loss: 184.0,
+            target: Some(299.0),
+            legs: vec![
+                leg("NIFTY25JAN23500CE", OptionType::CE, 120.0),
+                leg("NIFTY25JAN23500PE", OptionType::PE, 110.0),
+            ],
+            ..test_position("S1")
+        }).await.unwrap();
+        assert_eq!(manager.get_position("S1").await.unwrap().entry_price, 230.0);
+        
+        // CE +40, PE -30: net +10 per unit
+        assert_eq!(manager.mark_leg("S1", "NIFTY25JAN23500CE", 160.0).await.unwrap(), None);
+        assert_eq!(manager.mark_leg("S1", "NIFTY25JAN23500PE", 80.0).await.unwrap(), None);
+        let marked = manager.get_position("S1").await.unwrap();
+        assert_eq!(marked.current_price, 240.0);
+        assert_eq!(marked.pnl, 10.0 * 75.0);
+        assert!(manager.mark_leg("S1", "NIFTY25JAN24000CE", 50.0).await.is_err());
+        
+        // Combined premium through the stop even though the CE is up
+        let exit = manager.mark_leg("S1", "NIFTY25JAN23500PE", 20.0).await.unwrap();
+        assert_eq!(exit.as_deref(), Some("STOP_LOSS"));
+        
+        let trade = manager.close_position("S1", 180.0, "STOP_LOSS".to_string()).await.unwrap();
+        assert_eq!(trade.pnl_gross, -50.0 * 75.0);
+        let closed = manager.get_closed_position("S1").await.unwrap();
+        assert!(closed.legs.iter().all(|leg| !leg.is_open()));
+        assert_eq!(closed.legs.iter().map(Leg::pnl).sum::<f64>(), trade.pnl_gross);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
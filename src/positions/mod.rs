@@ -1,4 +1,11 @@
+pub mod exit_record;
+pub mod exit_rules;
 pub mod manager;
+pub mod multi_leg;
+pub mod snapshot;
 
-pub use manager::PositionManager;
-
+pub use exit_record::save_exit_record;
+pub use exit_rules::{ExitContext, ExitDecision, ExitEngine, ExitRule};
+pub use manager::{CloseAllReport, PositionManager};
+pub use multi_leg::MultiLegPosition;
+pub use snapshot::{prune_snapshots, save_snapshot, PortfolioSnapshot, SNAPSHOT_DIR};
@@ -0,0 +1,132 @@
+/// Multi-leg option positions (straddles, strangles, spreads)
+/// Legs are marked and closed individually; PNL is netted across them.
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Result, TradingError};
+use crate::types::{Leg, Position};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MultiLegPosition {
+    pub position_id: String,
+    pub underlying: String,
+    pub legs: Vec<Leg>,
+    pub entry_time: DateTime<Utc>,
+    pub entry_reason: String,
+    pub idempotency_key: String,
+}
+
+impl MultiLegPosition {
+    /// Net PNL across legs: realized on closed legs, mark-to-market on open ones
+    pub fn net_pnl(&self) -> f64 {
+        self.legs.iter().map(Leg::pnl).sum()
+    }
+
+    /// PNL locked in by legs already closed
+    pub fn realized_pnl(&self) -> f64 {
+        self.legs.iter().filter(|leg| !leg.is_open()).map(Leg::pnl).sum()
+    }
+
+    pub fn open_legs(&self) -> impl Iterator<Item = &Leg> {
+        self.legs.iter().filter(|leg| leg.is_open())
+    }
+
+    /// Closed once every leg is
+    pub fn is_closed(&self) -> bool {
+        self.open_legs().next().is_none()
+    }
+
+    /// Update the current price of the open leg on `symbol`
+    pub fn mark(&mut self, symbol: &str, price: f64) {
+        if let Some(leg) = self.legs.iter_mut().find(|leg| leg.is_open() && leg.symbol == symbol) {
+            leg.current_price = price;
+        }
+    }
+
+    /// Close the open leg on `symbol` at `exit_price`, leaving the others open
+    /// Returns that leg's realized PNL
+    pub fn close_leg(&mut self, symbol: &str, exit_price: f64) -> Result<f64> {
+        let leg = self.legs.iter_mut()
+            .find(|leg| leg.is_open() && leg.symbol == symbol)
+            .ok_or_else(|| TradingError::PositionNotFound(format!("{} leg {}", self.position_id, symbol)))?;
+
+        leg.current_price = exit_price;
+        leg.exit_price = Some(exit_price);
+        Ok(leg.pnl())
+    }
+}
+
+/// Compatibility shim: a single-leg `Position` (empty `legs`) becomes one leg
+impl From<&Position> for MultiLegPosition {
+    fn from(position: &Position) -> Self {
+        let legs = if position.legs.is_empty() {
+            vec![position.as_leg()]
+        } else {
+            position.legs.clone()
+        };
+
+        MultiLegPosition {
+            position_id: position.position_id.clone(),
+            underlying: position.underlying.clone(),
+            legs,
+            entry_time: position.entry_time,
+            entry_reason: position.entry_reason.clone(),
+            idempotency_key: position.idempotency_key.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{OptionType, Side};
+
+    fn leg(symbol: &str, option_type: OptionType, entry_price: f64) -> Leg {
+        Leg {
+            symbol: symbol.to_string(),
+            strike: 23500,
+            option_type,
+            side: Side::Buy,
+            quantity: 75,
+            entry_price,
+            current_price: entry_price,
+            exit_price: None,
+        }
+    }
+
+    #[test]
+    fn test_straddle_close_one_leg() {
+        let mut straddle = MultiLegPosition {
+            position_id: "S1".to_string(),
+            underlying: "NIFTY".to_string(),
+            legs: vec![
+                leg("NIFTY25JAN23500CE", OptionType::CE, 120.0),
+                leg("NIFTY25JAN23500PE", OptionType::PE, 110.0),
+            ],
+            entry_time: Utc::now(),
+            entry_reason: "straddle".to_string(),
+            idempotency_key: "S1".to_string(),
+        };
+
+        // Market rallies: CE +40, PE -30
+        straddle.mark("NIFTY25JAN23500CE", 160.0);
+        straddle.mark("NIFTY25JAN23500PE", 80.0);
+        assert_eq!(straddle.net_pnl(), (40.0 - 30.0) * 75.0);
+
+        // Book the CE; the PE keeps floating
+        assert_eq!(straddle.close_leg("NIFTY25JAN23500CE", 160.0).unwrap(), 3000.0);
+        assert_eq!(straddle.realized_pnl(), 3000.0);
+        assert!(!straddle.is_closed());
+        assert_eq!(straddle.open_legs().count(), 1);
+        assert!(straddle.close_leg("NIFTY25JAN23500CE", 170.0).is_err());
+
+        // Closed legs ignore further marks
+        straddle.mark("NIFTY25JAN23500CE", 200.0);
+        straddle.mark("NIFTY25JAN23500PE", 95.0);
+        assert_eq!(straddle.net_pnl(), 3000.0 - 15.0 * 75.0);
+
+        straddle.close_leg("NIFTY25JAN23500PE", 95.0).unwrap();
+        assert!(straddle.is_closed());
+        assert_eq!(straddle.realized_pnl(), straddle.net_pnl());
+    }
+}
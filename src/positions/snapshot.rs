@@ -0,0 +1,117 @@
+/// Periodic portfolio snapshots for crash recovery auditing
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+use crate::error::Result;
+use crate::types::{Position, Trade};
+
+/// Default directory for snapshot files
+pub const SNAPSHOT_DIR: &str = "data/snapshots";
+
+const SNAPSHOT_PREFIX: &str = "positions_";
+const SNAPSHOT_TS_FORMAT: &str = "%Y%m%d_%H%M%S";
+
+/// Point-in-time view of the whole book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PortfolioSnapshot {
+    pub timestamp: DateTime<Utc>,
+    pub positions: Vec<Position>,
+    pub trades: Vec<Trade>,
+    pub daily_pnl: f64,
+}
+
+/// Write a snapshot to `<dir>/positions_<ts>.json`
+pub async fn save_snapshot(dir: &Path, snapshot: &PortfolioSnapshot) -> Result<PathBuf> {
+    tokio::fs::create_dir_all(dir).await?;
+    
+    let path = dir.join(format!(
+        "{}{}.json",
+        SNAPSHOT_PREFIX,
+        snapshot.timestamp.format(SNAPSHOT_TS_FORMAT)
+    ));
+    
+    let json = serde_json::to_string_pretty(snapshot)?;
+    tokio::fs::write(&path, json).await?;
+    
+    debug!("📸 Saved portfolio snapshot: {}", path.display());
+    Ok(path)
+}
+
+/// Delete snapshots older than the retention window
+/// Age is taken from the timestamp in the file name; unrelated files are left alone
+pub async fn prune_snapshots(
+    dir: &Path,
+    retention: chrono::Duration,
+    now: DateTime<Utc>,
+) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    
+    let cutoff = now - retention;
+    let mut removed = 0;
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    
+    while let Some(entry) = entries.next_entry().await? {
+        let file_name = entry.file_name();
+        let Some(taken_at) = file_name.to_str().and_then(parse_snapshot_time) else {
+            continue;
+        };
+        
+        if taken_at < cutoff {
+            tokio::fs::remove_file(entry.path()).await?;
+            removed += 1;
+        }
+    }
+    
+    if removed > 0 {
+        info!("🧹 Pruned {} portfolio snapshots older than {}", removed, cutoff);
+    }
+    
+    Ok(removed)
+}
+
+/// Parse the timestamp out of a `positions_<ts>.json` file name
+fn parse_snapshot_time(file_name: &str) -> Option<DateTime<Utc>> {
+    let ts = file_name
+        .strip_prefix(SNAPSHOT_PREFIX)?
+        .strip_suffix(".json")?;
+    
+    NaiveDateTime::parse_from_str(ts, SNAPSHOT_TS_FORMAT)
+        .ok()
+        .map(|dt| dt.and_utc())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    
+    #[tokio::test]
+    async fn test_prune_removes_only_expired_snapshots() {
+        let dir = std::env::temp_dir().join(format!("snapshots_{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        
+        let now = Utc.with_ymd_and_hms(2024, 11, 20, 12, 0, 0).unwrap();
+        for name in [
+            "positions_20241110_093000.json", // 10 days old
+            "positions_20241119_150000.json", // under a day old
+            "positions_20241120_115500.json", // 5 minutes old
+            "notes.txt",                      // not a snapshot
+        ] {
+            tokio::fs::write(dir.join(name), "{}").await.unwrap();
+        }
+        
+        let removed = prune_snapshots(&dir, chrono::Duration::days(7), now).await.unwrap();
+        assert_eq!(removed, 1);
+        
+        assert!(!dir.join("positions_20241110_093000.json").exists());
+        assert!(dir.join("positions_20241119_150000.json").exists());
+        assert!(dir.join("positions_20241120_115500.json").exists());
+        assert!(dir.join("notes.txt").exists());
+        
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
@@ -0,0 +1,169 @@
+/// Option Greeks estimation (Black-Scholes approximation)
+/// Used for monitoring aggregate book risk, not for pricing
+use serde::{Deserialize, Serialize};
+
+use crate::types::{OptionType, Side};
+
+/// Calendar days per year used to annualize days-to-expiry
+const DAYS_PER_YEAR: f64 = 365.0;
+
+/// Aggregate or per-position Greeks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Greeks {
+    /// Change in option value per 1 point move in the underlying
+    pub delta: f64,
+    /// Change in option value per calendar day
+    pub theta: f64,
+    /// Change in option value per 1 vol point (1%)
+    pub vega: f64,
+}
+
+impl Greeks {
+    /// Scale Greeks by a signed quantity (positive = long, negative = short)
+    pub fn scaled(&self, quantity: f64) -> Self {
+        Greeks {
+            delta: self.delta * quantity,
+            theta: self.theta * quantity,
+            vega: self.vega * quantity,
+        }
+    }
+}
+
+impl std::ops::Add for Greeks {
+    type Output = Greeks;
+
+    fn add(self, other: Greeks) -> Greeks {
+        Greeks {
+            delta: self.delta + other.delta,
+            theta: self.theta + other.theta,
+            vega: self.vega + other.vega,
+        }
+    }
+}
+
+impl std::ops::AddAssign for Greeks {
+    fn add_assign(&mut self, other: Greeks) {
+        *self = *self + other;
+    }
+}
+
+/// Estimate per-unit Greeks for a European option
+///
+/// `implied_vol_pct` is annualized volatility in percent (India VIX is a
+/// reasonable proxy for index options). Risk-free rate is assumed zero.
+pub fn estimate_greeks(
+    option_type: OptionType,
+    spot: f64,
+    strike: f64,
+    days_to_expiry: f64,
+    implied_vol_pct: f64,
+) -> Greeks {
+    if spot <= 0.0 || strike <= 0.0 || implied_vol_pct <= 0.0 {
+        return Greeks::default();
+    }
+
+    let sigma = implied_vol_pct / 100.0;
+    // Floor time at a fraction of a day so expiry-day positions stay finite
+    let t = (days_to_expiry.max(0.0) / DAYS_PER_YEAR).max(1.0 / (DAYS_PER_YEAR * 24.0));
+    let sqrt_t = t.sqrt();
+
+    let d1 = ((spot / strike).ln() + 0.5 * sigma * sigma * t) / (sigma * sqrt_t);
+    let pdf_d1 = norm_pdf(d1);
+
+    let delta = match option_type {
+        OptionType::CE => norm_cdf(d1),
+        OptionType::PE => norm_cdf(d1) - 1.0,
+    };
+
+    // With r = 0, theta is identical for calls and puts
+    let theta_annual = -(spot * pdf_d1 * sigma) / (2.0 * sqrt_t);
+    let vega = spot * pdf_d1 * sqrt_t;
+
+    Greeks {
+        delta,
+        theta: theta_annual / DAYS_PER_YEAR,
+        vega: vega / 100.0,
+    }
+}
+
+/// Estimate position-level Greeks (signed by side and scaled by quantity)
+pub fn position_greeks(
+    option_type: OptionType,
+    side: Side,
+    quantity: i32,
+    spot: f64,
+    strike: f64,
+    days_to_expiry: f64,
+    implied_vol_pct: f64,
+) -> Greeks {
+    let unit = estimate_greeks(option_type, spot, strike, days_to_expiry, implied_vol_pct);
+    let signed_qty = match side {
+        Side::Buy => quantity as f64,
+        Side::Sell => -(quantity as f64),
+    };
+    unit.scaled(signed_qty)
+}
+
+/// Standard normal probability density
+fn norm_pdf(x: f64) -> f64 {
+    (-0.5 * x * x).exp() / (2.0 * std::f64::consts::PI).sqrt()
+}
+
+/// Standard normal cumulative distribution
+fn norm_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Error function (Abramowitz & Stegun 7.1.26, max error ~1.5e-7)
+fn erf(x: f64) -> f64 {
+    let sign = if x < 0.0 { -1.0 } else { 1.0 };
+    let x = x.abs();
+
+    let a1 = 0.254829592;
+    let a2 = -0.284496736;
+    let a3 = 1.421413741;
+    let a4 = -1.453152027;
+    let a5 = 1.061405429;
+    let p = 0.3275911;
+
+    let t = 1.0 / (1.0 + p * x);
+    let y = 1.0 - (((((a5 * t + a4) * t) + a3) * t + a2) * t + a1) * t * (-x * x).exp();
+
+    sign * y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_atm_delta_near_half() {
+        let ce = estimate_greeks(OptionType::CE, 19500.0, 19500.0, 7.0, 15.0);
+        let pe = estimate_greeks(OptionType::PE, 19500.0, 19500.0, 7.0, 15.0);
+
+        assert!((ce.delta - 0.5).abs() < 0.02, "CE delta was {}", ce.delta);
+        assert!((pe.delta + 0.5).abs() < 0.02, "PE delta was {}", pe.delta);
+
+        // Long options lose value with time and gain with vol
+        assert!(ce.theta < 0.0);
+        assert!(ce.vega > 0.0);
+    }
+
+    #[test]
+    fn test_moneyness_ordering() {
+        let itm = estimate_greeks(OptionType::CE, 19500.0, 19000.0, 7.0, 15.0);
+        let otm = estimate_greeks(OptionType::CE, 19500.0, 20000.0, 7.0, 15.0);
+
+        assert!(itm.delta > 0.5);
+        assert!(otm.delta < 0.5);
+    }
+
+    #[test]
+    fn test_position_greeks_signed_by_side() {
+        let long = position_greeks(OptionType::CE, Side::Buy, 50, 19500.0, 19500.0, 7.0, 15.0);
+        let short = position_greeks(OptionType::CE, Side::Sell, 50, 19500.0, 19500.0, 7.0, 15.0);
+
+        assert!((long.delta - 25.0).abs() < 1.0);
+        assert!((long.delta + short.delta).abs() < 1e-9);
+    }
+}
@@ -1,12 +1,20 @@
 /// Risk management: VIX monitoring, loss limits, circuit breakers
 use std::sync::Arc;
+use chrono::Datelike;
+use chrono_tz::Asia::Kolkata;
 use tokio::sync::RwLock;
 use tracing::{info, warn};
 
+use crate::broker::PaperTradingBroker;
+use crate::data::{ConcurrentBarStore, Timeframe};
 use crate::error::{Result, TradingError};
 use crate::events::{Event, EventBus, EventPayload, EventType};
 use crate::positions::PositionManager;
+use crate::time::{Clock, SystemClock};
 use crate::types::Config;
+use crate::utils::{
+    is_expiry_day, is_in_entry_window, is_market_open, is_past_eod_exit, is_past_opening_blackout, session_age,
+};
 
 pub struct RiskManager {
     event_bus: Arc<EventBus>,
@@ -22,6 +30,15 @@ pub struct RiskManager {
     /// Daily loss tracker
     daily_start_capital: Arc<RwLock<f64>>,
     consecutive_losses: Arc<RwLock<usize>>,
+    
+    /// No further entries today (set at `consecutive_loss_limit`)
+    halted_for_day: Arc<RwLock<bool>>,
+    
+    /// Time source for entry window checks
+    clock: Arc<dyn Clock>,
+    
+    /// Paper ledger used instead of broker RMS in paper mode
+    paper_broker: Option<Arc<PaperTradingBroker>>,
 }
 
 impl RiskManager {
@@ -30,30 +47,80 @@ impl RiskManager {
         config: Arc<Config>,
         position_manager: Arc<PositionManager>,
     ) -> Self {
+        let start_capital = config.default_start_capital;
         RiskManager {
             event_bus,
             config,
             position_manager,
             current_vix: Arc::new(RwLock::new(None)),
             circuit_breaker_active: Arc::new(RwLock::new(false)),
-            daily_start_capital: Arc::new(RwLock::new(1_000_000.0)), // Default 10L
+            daily_start_capital: Arc::new(RwLock::new(start_capital)),
             consecutive_losses: Arc::new(RwLock::new(0)),
+            halted_for_day: Arc::new(RwLock::new(false)),
+            clock: Arc::new(SystemClock),
+            paper_broker: None,
+        }
+    }
+    
+    /// Use a custom clock (e.g. `MockClock` in tests)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+    
+    /// Check entries against the paper trading cash ledger
+    pub fn with_paper_broker(mut self, paper_broker: Option<Arc<PaperTradingBroker>>) -> Self {
+        self.paper_broker = paper_broker;
+        self
+    }
+    
+    /// Check if new entries in `underlying` are allowed at the current time
+    /// Both its entry window (see `Config::entry_window`) and the opening
+    /// blackout must allow it
+    pub fn is_entry_window_open(&self, underlying: &str) -> bool {
+        let now = self.clock.now();
+        let (start, end) = self.config.entry_window(underlying, now.with_timezone(&Kolkata).weekday());
+        is_in_entry_window(now, start, end)
+            && is_past_opening_blackout(now, self.config.opening_blackout_minutes)
+    }
+    
+    /// Stop distance for a new entry in an instrument expiring on `expiry`:
+    /// `expiry_stop_pct` on expiry day, otherwise `option_stop_loss_pct`
+    pub fn entry_stop_pct(&self, expiry: &str) -> f64 {
+        match self.config.expiry_stop_pct {
+            Some(pct) if is_expiry_day(self.clock.now(), expiry) => pct,
+            _ => self.config.option_stop_loss_pct,
         }
     }
     
+    /// New entries in an instrument expiring today are blocked after `expiry_no_entry_time`
+    pub fn is_expiry_entry_blocked(&self, expiry: &str) -> bool {
+        let now = self.clock.now();
+        self.config.expiry_no_entry_time.as_deref()
+            .is_some_and(|cutoff| is_expiry_day(now, expiry) && is_past_eod_exit(now, cutoff))
+    }
+    
+    /// Positions in an instrument expiring today are exited from `expiry_exit_time`
+    pub fn is_expiry_exit_due(&self, expiry: &str) -> bool {
+        let now = self.clock.now();
+        self.config.expiry_exit_time.as_deref()
+            .is_some_and(|exit_time| is_expiry_day(now, expiry) && is_past_eod_exit(now, exit_time))
+    }
+    
     /// Update VIX and check circuit breaker
     pub async fn update_vix(&self, vix: f64) -> Result<()> {
         {
             let mut current = self.current_vix.write().await;
             *current = Some(vix);
         }
+        self.position_manager.set_current_vix(vix).await;
         
         // Emit VIX data event
         self.event_bus.publish(Event::new(
             EventType::VixDataReceived,
             EventPayload::VixDataReceived {
                 vix,
-                timestamp: chrono::Utc::now(),
+                timestamp: self.clock.now(),
             },
         )).await?;
         
@@ -196,32 +263,49 @@ impl RiskManager {
         Ok(false)
     }
     
-    /// Check consecutive loss limit
-    pub async fn check_consecutive_losses(&self, trade_result: bool) -> bool {
-        let mut losses = self.consecutive_losses.write().await;
-        
-        if trade_result {
-            // Win - reset counter
-            *losses = 0;
-        } else {
-            // Loss - increment
-            *losses += 1;
-            
-            if *losses >= self.config.consecutive_loss_limit {
-                warn!(
-                    "CONSECUTIVE LOSS LIMIT reached: {} losses",
-                    *losses
-                );
-                return true;
+    /// Check consecutive loss limit; reaching it halts entries for the day
+    pub async fn check_consecutive_losses(&self, trade_result: bool) -> Result<bool> {
+        let losses = {
+            let mut losses = self.consecutive_losses.write().await;
+            if trade_result {
+                // Win - reset counter
+                *losses = 0;
+            } else {
+                // Loss - increment
+                *losses += 1;
             }
+            *losses
+        };
+        
+        if trade_result || losses < self.config.consecutive_loss_limit {
+            return Ok(false);
+        }
+        
+        warn!("CONSECUTIVE LOSS LIMIT reached: {} losses - no more entries today", losses);
+        
+        let newly_halted = !std::mem::replace(&mut *self.halted_for_day.write().await, true);
+        if newly_halted {
+            self.event_bus.publish(Event::new(
+                EventType::NoTradeModeActive,
+                EventPayload::NoTradeModeActive {
+                    reason: "consecutive_losses".to_string(),
+                },
+            )).await?;
         }
         
-        false
+        Ok(true)
+    }
+    
+    /// Whether entries are halted for the rest of the day
+    pub async fn is_halted_for_day(&self) -> bool {
+        *self.halted_for_day.read().await
     }
     
-    /// Calculate position size based on VIX and DTE
+    /// Calculate position size based on VIX and DTE, bounded to
+    /// `min_lots..=max_lots` of the instrument's `lot_size`
     pub fn calculate_position_size(
         &self,
+        lot_size: i32,
         base_capital: f64,
         vix: f64,
         days_to_expiry: i32,
@@ -254,19 +338,97 @@ impl RiskManager {
         let base_size = base_capital * (self.config.base_position_size_pct / 100.0);
         let adjusted_size = base_size * vix_mult * dte_mult;
         
-        // Round down to nearest lot
-        let quantity = (adjusted_size / 50.0).floor() as i32 * 50;
+        // Round down to whole lots, then apply the floor/ceiling
+        let lots = (adjusted_size / lot_size as f64).floor() as i32;
+        let bounded_lots = lots
+            .min(self.config.max_lots as i32)
+            .max(self.config.min_lots as i32);
+        let quantity = bounded_lots * lot_size;
         
         info!(
-            "Position size: VIX={:.1} (mult={:.2}), DTE={} (mult={:.2}) → {} qty",
-            vix, vix_mult, days_to_expiry, dte_mult, quantity
+            "Position size: VIX={:.1} (mult={:.2}), DTE={} (mult={:.2}) → {} lots ({} raw) = {} qty",
+            vix, vix_mult, days_to_expiry, dte_mult, bounded_lots, lots, quantity
+        );
+        
+        quantity
+    }
+    
+    /// Cap quantity so that `option_price * quantity <= max_notional_per_trade`
+    /// Rounded down to `lot_size`; may return 0 if a single lot exceeds the cap
+    pub fn apply_notional_cap(&self, lot_size: i32, quantity: i32, option_price: f64) -> i32 {
+        let max_notional = self.config.max_notional_per_trade;
+        if option_price <= 0.0 || option_price * quantity as f64 <= max_notional {
+            return quantity;
+        }
+        
+        let max_lots = (max_notional / (option_price * lot_size as f64)).floor() as i32;
+        let capped = max_lots.max(0) * lot_size;
+        
+        warn!(
+            "Notional cap hit: {} qty @ {:.2} = {:.2} > {:.2} → capped to {} qty",
+            quantity,
+            option_price,
+            option_price * quantity as f64,
+            max_notional,
+            capped
         );
         
-        quantity.max(50) // At least 1 lot (NIFTY)
+        capped
+    }
+    
+    /// Refuse entries on a dead feed: during market hours the latest bar in
+    /// `store` must be within `max_bar_age_minutes` of session time
+    /// Emits `DataGapDetected` when it is not
+    pub async fn check_bar_freshness(
+        &self,
+        symbol: &str,
+        timeframe: Timeframe,
+        store: &ConcurrentBarStore,
+    ) -> Result<()> {
+        let now = self.clock.now();
+        if !is_market_open(now) {
+            return Ok(());
+        }
+        
+        let Some(last_bar) = store.get_last().await else {
+            return Err(TradingError::MissingData(format!("No {} bars for {}", timeframe.as_str(), symbol)));
+        };
+        
+        let age = session_age(last_bar.timestamp, now);
+        if age.num_minutes() <= self.config.max_bar_age_minutes as i64 {
+            return Ok(());
+        }
+        
+        self.event_bus.publish(Event::new(
+            EventType::DataGapDetected,
+            EventPayload::DataGapDetected {
+                symbol: symbol.to_string(),
+                timeframe: timeframe.as_str().to_string(),
+                gap_start: last_bar.timestamp,
+                gap_end: now,
+                missing_bars: (age.num_minutes() / timeframe.duration_minutes()).max(0) as usize,
+            },
+        )).await?;
+        
+        Err(TradingError::RiskCheckFailed(format!(
+            "Stale {} data for {}: last bar {} is {}m old (max {}m)",
+            timeframe.as_str(),
+            symbol,
+            last_bar.timestamp,
+            age.num_minutes(),
+            self.config.max_bar_age_minutes
+        )))
     }
     
     /// Pre-entry risk check
-    pub async fn pre_entry_risk_check(&self) -> Result<()> {
+    pub async fn pre_entry_risk_check(&self, underlying: &str) -> Result<()> {
+        // Check day halt (consecutive losses)
+        if self.is_halted_for_day().await {
+            return Err(TradingError::RiskCheckFailed(
+                "Trading halted for the day: consecutive loss limit".to_string()
+            ));
+        }
+        
         // Check circuit breaker
         if self.is_circuit_breaker_active().await {
             return Err(TradingError::RiskCheckFailed(
@@ -289,6 +451,35 @@ impl RiskManager {
             ));
         }
         
+        // Check per-underlying limit
+        let same_underlying = open_positions.iter()
+            .filter(|p| p.underlying == underlying)
+            .count();
+        if same_underlying >= self.config.max_positions_per_underlying {
+            let reason = format!(
+                "{} open {} position(s), limit {}",
+                same_underlying, underlying, self.config.max_positions_per_underlying
+            );
+            self.event_bus.publish(Event::new(
+                EventType::RiskCheckFailed,
+                EventPayload::RiskCheckFailed {
+                    check_type: "PER_UNDERLYING_LIMIT".to_string(),
+                    reason: reason.clone(),
+                },
+            )).await?;
+            return Err(TradingError::PositionLimitExceeded(reason));
+        }
+        
+        // Paper mode: no broker RMS, check the simulated cash balance
+        if let Some(paper_broker) = &self.paper_broker {
+            let cash = paper_broker.available_cash().await;
+            if cash <= 0.0 {
+                return Err(TradingError::InsufficientMargin(
+                    format!("Paper cash exhausted: {:.2}", cash)
+                ));
+            }
+        }
+        
         // Emit risk check passed event
         self.event_bus.publish(Event::new(
             EventType::RiskCheckPassed,
@@ -300,6 +491,22 @@ impl RiskManager {
         Ok(())
     }
     
+    /// Paper mode: the entry's premium plus estimated charges must fit in the
+    /// simulated cash balance
+    pub async fn check_paper_cash(&self, entry_price: f64, quantity: i32) -> Result<()> {
+        let Some(paper_broker) = &self.paper_broker else {
+            return Ok(());
+        };
+        let required = paper_broker.buy_cost(entry_price, quantity);
+        let cash = paper_broker.available_cash().await;
+        if cash < required {
+            return Err(TradingError::InsufficientMargin(
+                format!("Paper cash {:.2} < required {:.2}", cash, required)
+            ));
+        }
+        Ok(())
+    }
+    
     /// Get current VIX
     pub async fn get_current_vix(&self) -> Option<f64> {
         let vix = self.current_vix.read().await;
@@ -313,6 +520,28 @@ impl RiskManager {
         info!("Daily start capital set to: {:.2}", capital);
     }
     
+    /// Daily start capital (base for the loss limit)
+    pub async fn get_daily_start_capital(&self) -> f64 {
+        *self.daily_start_capital.read().await
+    }
+    
+    /// Set start capital from fetched funds; falls back to
+    /// `default_start_capital` when the fetch failed or returned nothing usable
+    pub async fn init_daily_start_capital(&self, fetched: Result<f64>) {
+        let capital = match fetched {
+            Ok(funds) if funds > 0.0 => funds,
+            Ok(funds) => {
+                warn!("Fetched funds {:.2} unusable - using default start capital", funds);
+                self.config.default_start_capital
+            }
+            Err(e) => {
+                warn!("Funds fetch failed ({}) - using default start capital", e);
+                self.config.default_start_capital
+            }
+        };
+        self.set_daily_start_capital(capital).await;
+    }
+    
     /// Reset daily counters
     pub async fn reset_daily(&self) {
         {
@@ -323,7 +552,340 @@ impl RiskManager {
             let mut breaker = self.circuit_breaker_active.write().await;
             *breaker = false;
         }
+        {
+            let mut halted = self.halted_for_day.write().await;
+            *halted = false;
+        }
         info!("Risk manager daily reset complete");
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::loader::test_config;
+    
+    fn create_risk_manager(config: Config) -> RiskManager {
+        let config = Arc::new(config);
+        let event_log = std::env::temp_dir().join(format!("risk_{}.jsonl", uuid::Uuid::new_v4()));
+        let event_bus = Arc::new(EventBus::new(event_log.display().to_string()));
+        let position_manager = Arc::new(PositionManager::new(Arc::clone(&event_bus), Arc::clone(&config)));
+        RiskManager::new(event_bus, config, position_manager)
+    }
+    
+    #[test]
+    fn test_entry_window_follows_clock() {
+        use crate::time::MockClock;
+        use chrono::TimeZone;
+        use chrono_tz::Asia::Kolkata;
+        
+        let mut config = test_config();
+        config.entry_window_start = "10:00:00".to_string();
+        config.entry_window_end = "15:00:00".to_string();
+        
+        let start = Kolkata.with_ymd_and_hms(2025, 1, 15, 9, 59, 0).unwrap().with_timezone(&chrono::Utc);
+        let clock = Arc::new(MockClock::new(start));
+        let risk = create_risk_manager(config).with_clock(clock.clone());
+        
+        assert!(!risk.is_entry_window_open("NIFTY"));
+        
+        clock.advance(chrono::Duration::minutes(1));
+        assert!(risk.is_entry_window_open("NIFTY"));
+        
+        clock.advance(chrono::Duration::hours(5));
+        assert!(!risk.is_entry_window_open("NIFTY"));
+    }
+    
+    #[test]
+    fn test_opening_blackout_blocks_early_entries() {
+        use crate::time::MockClock;
+        use chrono::TimeZone;
+        use chrono_tz::Asia::Kolkata;
+        
+        let mut config = test_config();
+        config.entry_window_start = "09:15:00".to_string();
+        config.entry_window_end = "15:00:00".to_string();
+        config.opening_blackout_minutes = 15;
+        
+        let start = Kolkata.with_ymd_and_hms(2025, 1, 15, 9, 16, 0).unwrap().with_timezone(&chrono::Utc);
+        let clock = Arc::new(MockClock::new(start));
+        let risk = create_risk_manager(config).with_clock(clock.clone());
+        
+        // Inside the entry window but within the blackout
+        assert!(!risk.is_entry_window_open("NIFTY"));
+        
+        clock.advance(chrono::Duration::minutes(15));
+        assert!(risk.is_entry_window_open("NIFTY"));
+    }
+    
+    #[test]
+    fn test_entry_window_overrides() {
+        use crate::time::MockClock;
+        use crate::types::EntryWindowOverride;
+        use chrono::TimeZone;
+        
+        let mut config = test_config();
+        config.entry_window_start = "10:00:00".to_string();
+        config.entry_window_end = "15:00:00".to_string();
+        config.opening_blackout_minutes = 0;
+        let window = |underlying: &str, start: &str, end: &str| EntryWindowOverride {
+            underlying: Some(underlying.to_string()),
+            weekday: None,
+            start: start.to_string(),
+            end: end.to_string(),
+        };
+        let thursday: EntryWindowOverride = toml::from_str("weekday = \"Thu\"\nstart = \"10:00:00\"\nend = \"13:00:00\"").unwrap();
+        assert_eq!(thursday.weekday, Some(chrono::Weekday::Thu));
+        config.entry_window_overrides = vec![
+            window("BANKNIFTY", "11:00:00", "14:00:00"),
+            thursday,
+        ];
+        
+        // Wednesday 10:30: BANKNIFTY waits for its own window, NIFTY uses the global one
+        let wednesday = Kolkata.with_ymd_and_hms(2025, 1, 15, 10, 30, 0).unwrap().with_timezone(&chrono::Utc);
+        let clock = Arc::new(MockClock::new(wednesday));
+        let risk = create_risk_manager(config).with_clock(clock.clone());
+        assert!(risk.is_entry_window_open("NIFTY"));
+        assert!(!risk.is_entry_window_open("BANKNIFTY"));
+        
+        // Wednesday 14:30: the Thursday window does not apply
+        clock.advance(chrono::Duration::hours(4));
+        assert!(risk.is_entry_window_open("NIFTY"));
+        assert!(!risk.is_entry_window_open("BANKNIFTY"));
+        
+        // Thursday 14:30: NIFTY closes early; BANKNIFTY's own window is more specific
+        clock.advance(chrono::Duration::days(1));
+        assert!(!risk.is_entry_window_open("NIFTY"));
+        assert!(!risk.is_entry_window_open("BANKNIFTY"));
+        clock.advance(chrono::Duration::hours(-2));
+        assert!(risk.is_entry_window_open("NIFTY"));
+        assert!(risk.is_entry_window_open("BANKNIFTY"));
+    }
+    
+    #[test]
+    fn test_notional_cap_reduces_size() {
+        let mut config = test_config();
+        config.max_notional_per_trade = 500_000.0;
+        config.max_lots = 1000; // keep the lot ceiling out of the way
+        let risk = create_risk_manager(config);
+        
+        // Low VIX, far expiry → largest multipliers
+        let quantity = risk.calculate_position_size(50, 1_000_000.0, 12.0, 5);
+        let capped = risk.apply_notional_cap(50, quantity, 125.0);
+        
+        assert!(capped < quantity);
+        assert_eq!(capped, 4000); // 500000 / 125 = 4000, a multiple of 50
+        assert!(capped as f64 * 125.0 <= 500_000.0);
+    }
+    
+    #[test]
+    fn test_lot_floor_binds_on_small_account() {
+        let mut config = test_config();
+        config.min_lots = 2;
+        let risk = create_risk_manager(config);
+        
+        // High VIX, expiry day, tiny capital → less than one lot before the floor
+        assert_eq!(risk.calculate_position_size(50, 500.0, 35.0, 1), 100);
+        assert_eq!(risk.calculate_position_size(15, 100.0, 35.0, 1), 30);
+    }
+    
+    #[test]
+    fn test_lot_ceiling_binds_in_low_vix() {
+        let mut config = test_config();
+        config.max_lots = 5;
+        let risk = create_risk_manager(config);
+        
+        assert_eq!(risk.calculate_position_size(50, 1_000_000.0, 10.0, 5), 250);
+        assert_eq!(risk.calculate_position_size(15, 1_000_000.0, 10.0, 5), 75);
+        // The instrument's lot size, not the configured one, sets the unit
+        assert_eq!(risk.calculate_position_size(75, 1_000_000.0, 10.0, 5), 375);
+    }
+    
+    #[test]
+    fn test_notional_cap_rounds_to_lot() {
+        let mut config = test_config();
+        config.max_notional_per_trade = 10_000.0;
+        let risk = create_risk_manager(config);
+        
+        // 10000 / 130 = 76.9 → one lot of 50
+        assert_eq!(risk.apply_notional_cap(50, 1000, 130.0), 50);
+        // Below the cap → untouched
+        assert_eq!(risk.apply_notional_cap(50, 50, 130.0), 50);
+    }
+    
+    #[tokio::test]
+    async fn test_stale_bars_block_entries() {
+        use crate::time::MockClock;
+        use crate::types::Bar;
+        use chrono::TimeZone;
+        use chrono_tz::Asia::Kolkata;
+        
+        let dir = std::env::temp_dir().join(format!("fresh_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let mut config = test_config();
+        config.max_bar_age_minutes = 150;
+        
+        let ist = |d: u32, h: u32, m: u32| Kolkata.with_ymd_and_hms(2025, 1, d, h, m, 0).unwrap().with_timezone(&chrono::Utc);
+        let clock = Arc::new(MockClock::new(ist(15, 11, 0)));
+        let risk = create_risk_manager(config).with_clock(clock.clone());
+        
+        let store = ConcurrentBarStore::new("NIFTY".to_string(), "1h".to_string(), dir.join("bars.jsonl"), 10);
+        let bar = |timestamp: chrono::DateTime<chrono::Utc>| Bar {
+            timestamp,
+            timestamp_ms: timestamp.timestamp_millis(),
+            open: 23500.0,
+            high: 23550.0,
+            low: 23450.0,
+            close: 23520.0,
+            volume: 0,
+            bar_complete: true,
+        };
+        
+        // Yesterday's last bar: only session time since 09:15 counts
+        store.append(bar(ist(14, 14, 15))).await.unwrap();
+        assert!(risk.check_bar_freshness("NIFTY", Timeframe::OneHour, &store).await.is_ok());
+        
+        // 12:00 with no bar today: 165 session minutes
+        clock.advance(chrono::Duration::hours(1));
+        assert!(risk.check_bar_freshness("NIFTY", Timeframe::OneHour, &store).await.is_err());
+        
+        // A fresh bar clears it
+        store.append(bar(ist(15, 10, 15))).await.unwrap();
+        assert!(risk.check_bar_freshness("NIFTY", Timeframe::OneHour, &store).await.is_ok());
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    #[tokio::test]
+    async fn test_paper_entry_must_fit_cash() {
+        let paper_broker = Arc::new(PaperTradingBroker::new(true, 0.0).with_starting_cash(10_000.0));
+        let risk = create_risk_manager(test_config()).with_paper_broker(Some(paper_broker));
+        
+        // 75 x 100 = 7,500 plus charges fits; 75 x 150 = 11,250 does not
+        assert!(risk.pre_entry_risk_check("NIFTY").await.is_ok());
+        assert!(risk.check_paper_cash(100.0, 75).await.is_ok());
+        assert!(matches!(
+            risk.check_paper_cash(150.0, 75).await,
+            Err(TradingError::InsufficientMargin(_))
+        ));
+        
+        // Without a paper broker the broker RMS decides
+        assert!(create_risk_manager(test_config()).check_paper_cash(150.0, 75).await.is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_per_underlying_position_limit() {
+        use crate::types::{test_position, Position};
+        
+        let mut config = test_config();
+        config.max_positions = 3;
+        config.max_positions_per_underlying = 1;
+        let risk = create_risk_manager(config);
+        
+        let position = |id: &str, underlying: &str| Position {
+            symbol: format!("{}25JAN19500CE", underlying),
+            underlying: underlying.to_string(),
+            idempotency_key: id.to_string(),
+            ..test_position(id)
+        };
+        
+        assert!(risk.pre_entry_risk_check("NIFTY").await.is_ok());
+        risk.position_manager.open_position(position("P1", "NIFTY")).await.unwrap();
+        
+        // Second NIFTY entry blocked, BANKNIFTY still allowed
+        assert!(matches!(
+            risk.pre_entry_risk_check("NIFTY").await,
+            Err(TradingError::PositionLimitExceeded(_))
+        ));
+        assert!(risk.pre_entry_risk_check("BANKNIFTY").await.is_ok());
+    }
+    
+    #[tokio::test]
+    async fn test_loss_limit_uses_fetched_capital() {
+        use crate::types::{test_position, Position};
+        
+        let mut config = test_config();
+        config.daily_loss_limit_pct = 2.0;
+        config.default_start_capital = 1_000_000.0;
+        let risk = create_risk_manager(config);
+        
+        // Lose ~50k: 5% of the default capital, 1% of the fetched 5M
+        risk.position_manager.open_position(Position {
+            quantity: 5000,
+            ..test_position("P1")
+        }).await.unwrap();
+        risk.position_manager.close_position("P1", 90.0, "SL".to_string()).await.unwrap();
+        
+        risk.init_daily_start_capital(Ok(5_000_000.0)).await;
+        assert_eq!(risk.get_daily_start_capital().await, 5_000_000.0);
+        assert!(!risk.check_daily_loss_limit().await.unwrap());
+        
+        // Failed fetch falls back to the configured default
+        risk.init_daily_start_capital(Err(TradingError::MissingData("RMS down".to_string()))).await;
+        assert_eq!(risk.get_daily_start_capital().await, 1_000_000.0);
+        assert!(risk.check_daily_loss_limit().await.unwrap());
+    }
+    
+    #[tokio::test]
+    async fn test_consecutive_losses_halt_entries_for_day() {
+        let mut config = test_config();
+        config.consecutive_loss_limit = 3;
+        let risk = create_risk_manager(config);
+        
+        // A win in between resets the streak
+        assert!(!risk.check_consecutive_losses(false).await.unwrap());
+        assert!(!risk.check_consecutive_losses(false).await.unwrap());
+        assert!(!risk.check_consecutive_losses(true).await.unwrap());
+        assert!(risk.pre_entry_risk_check("NIFTY").await.is_ok());
+        
+        for _ in 0..2 {
+            assert!(!risk.check_consecutive_losses(false).await.unwrap());
+        }
+        assert!(risk.check_consecutive_losses(false).await.unwrap());
+        assert!(risk.is_halted_for_day().await);
+        
+        // Halt holds for the rest of the day, even after a later win
+        assert!(matches!(
+            risk.pre_entry_risk_check("NIFTY").await,
+            Err(TradingError::RiskCheckFailed(_))
+        ));
+        risk.check_consecutive_losses(true).await.unwrap();
+        assert!(risk.pre_entry_risk_check("BANKNIFTY").await.is_err());
+        
+        // Next day
+        risk.reset_daily().await;
+        assert!(risk.pre_entry_risk_check("NIFTY").await.is_ok());
+    }
+    
+    #[test]
+    fn test_expiry_day_blocks_late_entry_and_tightens_stop() {
+        use crate::time::MockClock;
+        use chrono::TimeZone;
+        use chrono_tz::Asia::Kolkata;
+        
+        let mut config = test_config();
+        config.option_stop_loss_pct = 0.20;
+        config.expiry_stop_pct = Some(0.10);
+        config.expiry_no_entry_time = Some("13:30:00".to_string());
+        config.expiry_exit_time = Some("14:45:00".to_string());
+        
+        let start = Kolkata.with_ymd_and_hms(2025, 1, 30, 13, 0, 0).unwrap().with_timezone(&chrono::Utc);
+        let clock = Arc::new(MockClock::new(start));
+        let risk = create_risk_manager(config).with_clock(clock.clone());
+        
+        // Expiry day before the cutoff: entry allowed with the tighter stop
+        assert!(!risk.is_expiry_entry_blocked("30JAN2025"));
+        assert_eq!(risk.entry_stop_pct("30JAN2025"), 0.10);
+        
+        // Late on expiry day: blocked; next week's contract is unaffected
+        clock.advance(chrono::Duration::minutes(45));
+        assert!(risk.is_expiry_entry_blocked("30JAN2025"));
+        assert!(!risk.is_expiry_entry_blocked("06FEB2025"));
+        assert_eq!(risk.entry_stop_pct("06FEB2025"), 0.20);
+        
+        assert!(!risk.is_expiry_exit_due("30JAN2025"));
+        clock.advance(chrono::Duration::hours(1));
+        assert!(risk.is_expiry_exit_due("30JAN2025"));
+        assert!(!risk.is_expiry_exit_due("06FEB2025"));
+    }
+}
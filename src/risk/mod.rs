@@ -1,4 +1,5 @@
 pub mod manager;
+pub mod greeks;
 
 pub use manager::RiskManager;
-
+pub use greeks::{estimate_greeks, position_greeks, Greeks};
@@ -19,12 +19,28 @@ pub struct EntrySignal {
     pub confidence: f64,
 }
 
+/// Trend state with hysteresis: turns on at `enter`, stays on until ADX
+/// drops below `exit`
+pub fn trend_active(adx: f64, was_active: bool, enter: f64, exit: f64) -> bool {
+    if was_active {
+        adx >= exit
+    } else {
+        adx >= enter
+    }
+}
+
 /// ADX Strategy state
 pub struct AdxStrategy {
     config: Arc<Config>,
     daily_direction: Arc<RwLock<Option<Direction>>>,
     last_daily_analysis: Arc<RwLock<Option<chrono::DateTime<chrono::Utc>>>>,
     last_hourly_analysis: Arc<RwLock<Option<chrono::DateTime<chrono::Utc>>>>,
+    /// Result of the latest 15m alignment check
+    m15_aligned: Arc<RwLock<Option<bool>>>,
+    /// Hysteresis trend state per timeframe
+    daily_trend_on: Arc<RwLock<bool>>,
+    hourly_trend_on: Arc<RwLock<bool>>,
+    m15_trend_on: Arc<RwLock<bool>>,
 }
 
 impl AdxStrategy {
@@ -34,6 +50,10 @@ impl AdxStrategy {
             daily_direction: Arc::new(RwLock::new(None)),
             last_daily_analysis: Arc::new(RwLock::new(None)),
             last_hourly_analysis: Arc::new(RwLock::new(None)),
+            m15_aligned: Arc::new(RwLock::new(None)),
+            daily_trend_on: Arc::new(RwLock::new(false)),
+            hourly_trend_on: Arc::new(RwLock::new(false)),
+            m15_trend_on: Arc::new(RwLock::new(false)),
         }
     }
     
@@ -52,11 +72,15 @@ impl AdxStrategy {
             daily_adx, daily_plus_di, daily_minus_di
         );
         
+        let trend_on = self
+            .update_trend(&self.daily_trend_on, daily_adx)
+            .await;
+        
         // Determine direction based on ADX and DI crossover
-        let direction = if daily_adx < self.config.daily_adx_threshold {
+        let direction = if !trend_on {
             // Weak trend - no trade
-            info!("Daily ADX ({:.2}) below threshold ({:.2}) - NO TRADE", 
-                  daily_adx, self.config.daily_adx_threshold);
+            info!("Daily ADX ({:.2}) not trending (enter {:.2} / exit {:.2}) - NO TRADE", 
+                  daily_adx, self.config.adx_enter_threshold, self.config.adx_exit_threshold);
             Direction::NoTrade
         } else if daily_plus_di > daily_minus_di {
             // Strong uptrend - trade Call options
@@ -109,31 +133,9 @@ impl AdxStrategy {
             return Ok(false);
         }
         
-        // Calculate hourly ADX
-        let (hourly_adx, hourly_plus_di, hourly_minus_di) = calculate_adx(
-            hourly_bars,
-            self.config.hourly_adx_period,
-        ).ok_or_else(|| TradingError::MissingData("Insufficient bars for hourly ADX".to_string()))?;
-        
-        debug!(
-            "Hourly ADX: {:.2}, +DI: {:.2}, -DI: {:.2}",
-            hourly_adx, hourly_plus_di, hourly_minus_di
-        );
-        
-        // Check alignment
-        let aligned = match daily_direction {
-            Direction::CE => {
-                // For CE direction, hourly must show uptrend
-                hourly_adx >= self.config.hourly_adx_threshold
-                    && hourly_plus_di > hourly_minus_di
-            }
-            Direction::PE => {
-                // For PE direction, hourly must show downtrend
-                hourly_adx >= self.config.hourly_adx_threshold
-                    && hourly_minus_di > hourly_plus_di
-            }
-            Direction::NoTrade => false,
-        };
+        let (aligned, hourly_adx) = self
+            .adx_aligned(daily_direction, hourly_bars, "hourly", &self.hourly_trend_on)
+            .await?;
         
         if aligned {
             info!(
@@ -157,6 +159,79 @@ impl AdxStrategy {
         Ok(aligned)
     }
     
+    /// Analyze 15m bars and record whether they agree with the daily direction
+    pub async fn analyze_15m(&self, m15_bars: &[Bar]) -> Result<bool> {
+        debug!("Running 15m confirmation check");
+        
+        let daily_direction = self.get_daily_direction().await.ok_or_else(|| {
+            TradingError::InvalidStrategyState("Daily direction not set".to_string())
+        })?;
+        
+        let aligned = if daily_direction == Direction::NoTrade {
+            false
+        } else {
+            let result = self
+                .adx_aligned(daily_direction, m15_bars, "15m", &self.m15_trend_on)
+                .await;
+            if result.is_err() {
+                // Don't leave a stale confirmation behind
+                *self.m15_aligned.write().await = None;
+            }
+            result?.0
+        };
+        
+        if !aligned {
+            debug!("15m confirmation NOT met for {} direction", daily_direction.as_str());
+        }
+        
+        *self.m15_aligned.write().await = Some(aligned);
+        
+        Ok(aligned)
+    }
+    
+    /// Advance a timeframe's hysteresis state with the latest ADX
+    async fn update_trend(&self, state: &RwLock<bool>, adx: f64) -> bool {
+        let mut trend_on = state.write().await;
+        *trend_on = trend_active(
+            adx,
+            *trend_on,
+            self.config.adx_enter_threshold,
+            self.config.adx_exit_threshold,
+        );
+        *trend_on
+    }
+    
+    /// ADX alignment of `bars` with `direction` (hourly ADX settings, trend
+    /// strength via `trend_state` hysteresis); returns (aligned, adx)
+    async fn adx_aligned(
+        &self,
+        direction: Direction,
+        bars: &[Bar],
+        label: &str,
+        trend_state: &RwLock<bool>,
+    ) -> Result<(bool, f64)> {
+        let (adx, plus_di, minus_di) = calculate_adx(bars, self.config.hourly_adx_period)
+            .ok_or_else(|| TradingError::MissingData(format!("Insufficient bars for {} ADX", label)))?;
+        
+        debug!(
+            "{} ADX: {:.2}, +DI: {:.2}, -DI: {:.2}",
+            label, adx, plus_di, minus_di
+        );
+        
+        let trend_on = self
+            .update_trend(trend_state, adx)
+            .await;
+        let aligned = match direction {
+            // For CE direction, bars must show an uptrend
+            Direction::CE => trend_on && plus_di > minus_di,
+            // For PE direction, bars must show a downtrend
+            Direction::PE => trend_on && minus_di > plus_di,
+            Direction::NoTrade => false,
+        };
+        
+        Ok((aligned, adx))
+    }
+    
     /// Evaluate entry filters and generate signal
     pub async fn evaluate_entry(
         &self,
@@ -176,6 +251,12 @@ impl AdxStrategy {
         
         let daily_direction = daily_direction.unwrap();
         
+        // Optional 15m confirmation (set by `analyze_15m`)
+        if self.config.require_15m_confirmation && *self.m15_aligned.read().await != Some(true) {
+            debug!("15m confirmation missing - no entry");
+            return Ok(None);
+        }
+        
         // Filter 1: RSI check
         let rsi = calculate_rsi(hourly_bars, self.config.rsi_period)
             .ok_or_else(|| TradingError::MissingData("Insufficient bars for RSI".to_string()))?;
@@ -226,8 +307,9 @@ impl AdxStrategy {
         };
         
         let reason = format!(
-            "Daily: {}, Hourly aligned, RSI: {:.1}, EMA: {:.1}, VIX: {:.1}",
+            "Daily: {}, Hourly aligned{}, RSI: {:.1}, EMA: {:.1}, VIX: {:.1}",
             daily_direction.as_str(),
+            if self.config.require_15m_confirmation { ", 15m confirmed" } else { "" },
             rsi,
             ema,
             vix
@@ -248,6 +330,21 @@ impl AdxStrategy {
         Ok(Some(signal))
     }
     
+    /// Bars needed before trading as (daily, hourly): indicator period plus
+    /// `warmup_extra_bars` so ADX/RSI smoothing has stabilized
+    pub fn warmup_bars_required(&self) -> (usize, usize) {
+        let extra = self.config.warmup_extra_bars;
+        let hourly_period = self.config.hourly_adx_period.max(self.config.rsi_period);
+        
+        (self.config.daily_adx_period + extra, hourly_period + extra)
+    }
+    
+    /// Check if enough daily and hourly bars are available to trade
+    pub fn is_warmed_up(&self, daily_bars: usize, hourly_bars: usize) -> bool {
+        let (daily_required, hourly_required) = self.warmup_bars_required();
+        daily_bars >= daily_required && hourly_bars >= hourly_required
+    }
+    
     /// Check if exit conditions are met (technical)
     pub async fn check_technical_exit(
         &self,
@@ -291,6 +388,13 @@ impl AdxStrategy {
         let mut last_hourly = self.last_hourly_analysis.write().await;
         *last_hourly = None;
         
+        let mut m15 = self.m15_aligned.write().await;
+        *m15 = None;
+        
+        for state in [&self.daily_trend_on, &self.hourly_trend_on, &self.m15_trend_on] {
+            *state.write().await = false;
+        }
+        
         info!("Strategy state reset");
     }
 }
@@ -301,8 +405,7 @@ mod tests {
     use chrono::Utc;
     
     fn create_test_config() -> Config {
-        // Would load from TOML in real code
-        unimplemented!("Create test config")
+        crate::config::loader::test_config()
     }
     
     fn create_trending_bars(count: usize, uptrend: bool) -> Vec<Bar> {
@@ -323,4 +426,114 @@ mod tests {
             })
             .collect()
     }
+    
+    #[tokio::test]
+    async fn test_hourly_analysis_after_daily() {
+        let strategy = AdxStrategy::new(Arc::new(create_test_config()));
+        let bars = create_trending_bars(30, true);
+        
+        // Without daily analysis the strategy has no direction
+        assert!(strategy.analyze_hourly(&bars).await.is_err());
+        
+        let direction = strategy.analyze_daily(&bars).await.unwrap();
+        assert_eq!(direction, Direction::CE);
+        assert_eq!(strategy.get_daily_direction().await, Some(Direction::CE));
+        
+        assert!(strategy.analyze_hourly(&bars).await.unwrap());
+    }
+    
+    /// Uptrend with pullbacks (+30/-20) so RSI stays below overbought
+    fn create_pullback_bars(count: usize) -> Vec<Bar> {
+        let mut close = 19000.0;
+        (0..count)
+            .map(|i| {
+                close += if i % 2 == 0 { 30.0 } else { -20.0 };
+                Bar {
+                    timestamp: Utc::now(),
+                    timestamp_ms: Utc::now().timestamp_millis(),
+                    open: close - 5.0,
+                    high: close + 10.0,
+                    low: close - 10.0,
+                    close,
+                    volume: 1000000,
+                    bar_complete: true,
+                }
+            })
+            .collect()
+    }
+    
+    #[tokio::test]
+    async fn test_15m_disagreement_blocks_entry() {
+        let mut config = create_test_config();
+        config.require_15m_confirmation = false;
+        let hourly = create_pullback_bars(40);
+        
+        // Baseline: daily + hourly alone produce a signal
+        let strategy = AdxStrategy::new(Arc::new(config.clone()));
+        strategy.analyze_daily(&create_trending_bars(30, true)).await.unwrap();
+        assert!(strategy.evaluate_entry(&hourly, 19500.0, 12.0).await.unwrap().is_some());
+        
+        config.require_15m_confirmation = true;
+        let strategy = AdxStrategy::new(Arc::new(config));
+        strategy.analyze_daily(&create_trending_bars(30, true)).await.unwrap();
+        
+        // 15m in a downtrend disagrees with the daily CE bias
+        assert!(!strategy.analyze_15m(&create_trending_bars(30, false)).await.unwrap());
+        assert!(strategy.evaluate_entry(&hourly, 19500.0, 12.0).await.unwrap().is_none());
+        
+        // Once 15m agrees the entry goes through
+        assert!(strategy.analyze_15m(&create_trending_bars(30, true)).await.unwrap());
+        assert!(strategy.evaluate_entry(&hourly, 19500.0, 12.0).await.unwrap().is_some());
+    }
+    
+    #[test]
+    fn test_adx_hysteresis_holds_between_thresholds() {
+        let config = create_test_config();
+        let enter = config.adx_enter_threshold;
+        let exit = config.adx_exit_threshold;
+        assert!(exit < enter);
+        let mid = (enter + exit) / 2.0;
+        
+        let mut on = false;
+        let mut states = Vec::new();
+        for adx in [exit - 1.0, mid, enter, mid, exit, exit - 0.1, mid] {
+            on = trend_active(adx, on, enter, exit);
+            states.push(on);
+        }
+        
+        // Off until enter; stays on between the thresholds and at exit;
+        // off below exit and stays off until enter again
+        assert_eq!(states, vec![false, false, true, true, true, false, false]);
+    }
+    
+    #[tokio::test]
+    async fn test_daily_trend_enters_at_enter_threshold() {
+        let bars = create_trending_bars(50, true);
+        let mut config = create_test_config();
+        let (adx, _, _) = calculate_adx(&bars, config.daily_adx_period).unwrap();
+        
+        config.adx_enter_threshold = adx - 0.1;
+        config.adx_exit_threshold = adx - 5.0;
+        let strategy = AdxStrategy::new(Arc::new(config.clone()));
+        assert_eq!(strategy.analyze_daily(&bars).await.unwrap(), Direction::CE);
+        
+        config.adx_enter_threshold = adx + 0.1;
+        let strategy = AdxStrategy::new(Arc::new(config));
+        assert_eq!(strategy.analyze_daily(&bars).await.unwrap(), Direction::NoTrade);
+    }
+    
+    #[test]
+    fn test_warmup_boundary() {
+        let config = create_test_config();
+        let strategy = AdxStrategy::new(Arc::new(config.clone()));
+        let (daily_required, hourly_required) = strategy.warmup_bars_required();
+        
+        assert_eq!(daily_required, config.daily_adx_period + config.warmup_extra_bars);
+        assert!(hourly_required >= config.hourly_adx_period + config.warmup_extra_bars);
+        
+        // One bar short on either timeframe keeps the bot in warmup
+        assert!(!strategy.is_warmed_up(daily_required - 1, hourly_required));
+        assert!(!strategy.is_warmed_up(daily_required, hourly_required - 1));
+        assert!(strategy.is_warmed_up(daily_required, hourly_required));
+    }
 }
@@ -6,8 +6,15 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use tracing::{info, warn};
 
-use crate::strategy::indicators::calculate_adx;
-use crate::types::Bar;
+use crate::strategy::indicators::{calculate_adx, calculate_ema};
+use crate::error::TradingError;
+use crate::types::{Bar, DailyBiasMethod, Direction, OptionType};
+
+/// Minimum +DI/-DI gap (in DI points) for a directional bias
+const MIN_DI_SEPARATION: f64 = 2.0;
+
+/// Latest cross-underlying bias summary, for dashboards
+pub const BIAS_SUMMARY_FILE: &str = "data/bias_summary_latest.json";
 
 /// Daily bias direction
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -27,6 +34,48 @@ impl BiasDirection {
     }
 }
 
+impl From<BiasDirection> for Direction {
+    fn from(bias: BiasDirection) -> Self {
+        match bias {
+            BiasDirection::CE => Direction::CE,
+            BiasDirection::PE => Direction::PE,
+            BiasDirection::NoTrade => Direction::NoTrade,
+        }
+    }
+}
+
+impl From<Direction> for BiasDirection {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::CE => BiasDirection::CE,
+            Direction::PE => BiasDirection::PE,
+            Direction::NoTrade => BiasDirection::NoTrade,
+        }
+    }
+}
+
+/// Option bought for a bias; a NoTrade bias has none
+impl TryFrom<BiasDirection> for OptionType {
+    type Error = TradingError;
+
+    fn try_from(bias: BiasDirection) -> Result<Self, Self::Error> {
+        match bias {
+            BiasDirection::CE => Ok(OptionType::CE),
+            BiasDirection::PE => Ok(OptionType::PE),
+            BiasDirection::NoTrade => Err(TradingError::NoTradeSignal("NoTrade bias has no option type".to_string())),
+        }
+    }
+}
+
+impl From<OptionType> for BiasDirection {
+    fn from(option_type: OptionType) -> Self {
+        match option_type {
+            OptionType::CE => BiasDirection::CE,
+            OptionType::PE => BiasDirection::PE,
+        }
+    }
+}
+
 /// Token info for daily bias calculation
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DailyBiasToken {
@@ -47,12 +96,21 @@ pub struct DailyBias {
     pub minus_di: f64,
     pub close_price: f64,
     pub timestamp: DateTime<Utc>,
+    /// 0.0-1.0 blend of ADX strength and DI separation
+    #[serde(default)]
+    pub confidence: f64,
+    /// Why the bias is NoTrade (None for CE/PE)
+    #[serde(default)]
+    pub no_trade_reason: Option<String>,
 }
 
 /// Daily bias calculator
 pub struct DailyBiasCalculator {
     adx_period: usize,
     adx_threshold: f64,
+    method: DailyBiasMethod,
+    ema_fast: usize,
+    ema_slow: usize,
 }
 
 impl DailyBiasCalculator {
@@ -60,6 +118,30 @@ impl DailyBiasCalculator {
         Self {
             adx_period,
             adx_threshold,
+            method: DailyBiasMethod::Adx,
+            ema_fast: 9,
+            ema_slow: 21,
+        }
+    }
+
+    /// Select the bias indicator
+    pub fn with_method(mut self, method: DailyBiasMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// EMA periods used by `DailyBiasMethod::EmaCross`
+    pub fn with_ema_periods(mut self, fast: usize, slow: usize) -> Self {
+        self.ema_fast = fast;
+        self.ema_slow = slow;
+        self
+    }
+
+    /// Bars required by the configured method
+    fn min_bars(&self) -> usize {
+        match self.method {
+            DailyBiasMethod::Adx => self.adx_period + 1,
+            DailyBiasMethod::EmaCross => self.ema_slow.max(self.ema_fast),
         }
     }
 
@@ -70,24 +152,32 @@ impl DailyBiasCalculator {
         spot_token: &str,
         daily_bars: &[Bar],
     ) -> Option<DailyBias> {
-        if daily_bars.len() < self.adx_period + 1 {
+        let min_bars = self.min_bars();
+        if daily_bars.len() < min_bars {
             warn!("{}: Not enough bars ({} < {})", 
-                  underlying, daily_bars.len(), self.adx_period + 1);
+                  underlying, daily_bars.len(), min_bars);
             return None;
         }
 
-        // Calculate ADX/DMI (returns tuple: (adx, +DI, -DI))
-        let (latest_adx, latest_plus_di, latest_minus_di) = calculate_adx(daily_bars, self.adx_period)?;
         let latest_close = daily_bars.last()?.close;
         let timestamp = daily_bars.last()?.timestamp;
 
-        // Determine bias
-        let bias = if latest_adx < self.adx_threshold {
-            BiasDirection::NoTrade
-        } else if latest_plus_di > latest_minus_di {
-            BiasDirection::CE
-        } else {
-            BiasDirection::PE
+        let (latest_adx, latest_plus_di, latest_minus_di, bias, no_trade_reason, confidence) = match self.method {
+            DailyBiasMethod::Adx => {
+                // Calculate ADX/DMI (returns tuple: (adx, +DI, -DI))
+                let (adx, plus_di, minus_di) = calculate_adx(daily_bars, self.adx_period)?;
+                let (bias, reason) = self.classify(adx, plus_di, minus_di);
+                (adx, plus_di, minus_di, bias, reason, self.confidence(adx, plus_di, minus_di))
+            }
+            DailyBiasMethod::EmaCross => {
+                let fast = calculate_ema(daily_bars, self.ema_fast)?;
+                let slow = calculate_ema(daily_bars, self.ema_slow)?;
+                let (bias, reason) = Self::classify_ema(fast, slow, latest_close);
+                // ADX/DMI still reported when there is enough history
+                let (adx, plus_di, minus_di) =
+                    calculate_adx(daily_bars, self.adx_period).unwrap_or((0.0, 0.0, 0.0));
+                (adx, plus_di, minus_di, bias, reason, Self::ema_confidence(fast, slow))
+            }
         };
 
         Some(DailyBias {
@@ -99,9 +189,64 @@ impl DailyBiasCalculator {
             minus_di: latest_minus_di,
             close_price: latest_close,
             timestamp,
+            confidence,
+            no_trade_reason,
         })
     }
 
+    /// Determine bias direction, with a reason when it is NoTrade
+    fn classify(&self, adx: f64, plus_di: f64, minus_di: f64) -> (BiasDirection, Option<String>) {
+        if adx < self.adx_threshold {
+            let reason = format!("WEAK_ADX: {:.1} < {:.1}", adx, self.adx_threshold);
+            (BiasDirection::NoTrade, Some(reason))
+        } else if (plus_di - minus_di).abs() < MIN_DI_SEPARATION {
+            let reason = format!("AMBIGUOUS_DI: +DI {:.1} vs -DI {:.1}", plus_di, minus_di);
+            (BiasDirection::NoTrade, Some(reason))
+        } else if plus_di > minus_di {
+            (BiasDirection::CE, None)
+        } else {
+            (BiasDirection::PE, None)
+        }
+    }
+
+    /// EMA-cross direction: CE when fast > slow and price > slow, PE mirrored
+    fn classify_ema(fast: f64, slow: f64, close: f64) -> (BiasDirection, Option<String>) {
+        if fast > slow && close > slow {
+            (BiasDirection::CE, None)
+        } else if fast < slow && close < slow {
+            (BiasDirection::PE, None)
+        } else {
+            let reason = format!("EMA_MIXED: fast {:.2} slow {:.2} close {:.2}", fast, slow, close);
+            (BiasDirection::NoTrade, Some(reason))
+        }
+    }
+
+    /// EMA-cross confidence: fast/slow gap as a fraction of slow, full at 2%
+    fn ema_confidence(fast: f64, slow: f64) -> f64 {
+        if slow <= 0.0 {
+            return 0.0;
+        }
+        ((fast - slow).abs() / slow / 0.02).clamp(0.0, 1.0)
+    }
+
+    /// Confidence in the trend: ADX relative to twice the threshold, and DI gap
+    /// relative to the DI sum, each capped at 1.0 and weighted equally
+    fn confidence(&self, adx: f64, plus_di: f64, minus_di: f64) -> f64 {
+        let adx_score = if self.adx_threshold > 0.0 {
+            (adx / (2.0 * self.adx_threshold)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let di_sum = plus_di + minus_di;
+        let di_score = if di_sum > 0.0 {
+            ((plus_di - minus_di).abs() / di_sum).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        0.5 * adx_score + 0.5 * di_score
+    }
+
     /// Calculate bias for all underlyings
     pub fn calculate_all_bias(
         &self,
@@ -139,34 +284,121 @@ impl DailyBiasCalculator {
             .collect()
     }
 
-    /// Get summary statistics
-    pub fn get_summary(biases: &[DailyBias]) -> BiasSummary {
+    /// One-glance market picture across underlyings: bias counts, average
+    /// ADX and the strongest trend (highest ADX among CE/PE biases)
+    pub fn summarize(biases: &[DailyBias]) -> BiasSummary {
         let ce_count = biases.iter().filter(|b| b.bias == BiasDirection::CE).count();
         let pe_count = biases.iter().filter(|b| b.bias == BiasDirection::PE).count();
         let no_trade_count = biases.iter().filter(|b| b.bias == BiasDirection::NoTrade).count();
 
+        let avg_adx = if biases.is_empty() {
+            0.0
+        } else {
+            biases.iter().map(|b| b.adx).sum::<f64>() / biases.len() as f64
+        };
+        let strongest_underlying = biases.iter()
+            .filter(|b| b.bias != BiasDirection::NoTrade)
+            .max_by(|a, b| a.adx.total_cmp(&b.adx))
+            .map(|b| b.underlying.clone());
+
         BiasSummary {
             total: biases.len(),
             ce_count,
             pe_count,
             no_trade_count,
+            avg_adx,
+            strongest_underlying,
         }
     }
 }
 
 /// Summary of bias calculations
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BiasSummary {
     pub total: usize,
     pub ce_count: usize,
     pub pe_count: usize,
     pub no_trade_count: usize,
+    #[serde(default)]
+    pub avg_adx: f64,
+    /// Highest-ADX underlying with a CE/PE bias (None if all are NoTrade)
+    #[serde(default)]
+    pub strongest_underlying: Option<String>,
+}
+
+impl BiasSummary {
+    /// Write as pretty JSON (e.g. to `BIAS_SUMMARY_FILE`)
+    pub async fn save(&self, path: &str) -> crate::error::Result<()> {
+        let json = serde_json::to_string_pretty(self)?;
+        tokio::fs::write(path, json).await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_bias_direction_conversions() {
+        for (bias, direction) in [
+            (BiasDirection::CE, Direction::CE),
+            (BiasDirection::PE, Direction::PE),
+            (BiasDirection::NoTrade, Direction::NoTrade),
+        ] {
+            assert_eq!(Direction::from(bias), direction);
+            assert_eq!(BiasDirection::from(direction), bias);
+            assert_eq!(bias.as_str(), direction.as_str());
+        }
+
+        for (bias, option_type) in [(BiasDirection::CE, OptionType::CE), (BiasDirection::PE, OptionType::PE)] {
+            assert_eq!(OptionType::try_from(bias).unwrap(), option_type);
+            assert_eq!(BiasDirection::from(option_type), bias);
+            assert_eq!(Direction::from(option_type), Direction::from(bias));
+        }
+        assert!(matches!(
+            OptionType::try_from(BiasDirection::NoTrade),
+            Err(TradingError::NoTradeSignal(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_bias_summary_across_underlyings() {
+        let bias = |underlying: &str, bias: BiasDirection, adx: f64| DailyBias {
+            underlying: underlying.to_string(),
+            spot_token: String::new(),
+            bias,
+            adx,
+            plus_di: 0.0,
+            minus_di: 0.0,
+            close_price: 0.0,
+            timestamp: Utc::now(),
+            confidence: 0.0,
+            no_trade_reason: None,
+        };
+        let biases = vec![
+            bias("NIFTY", BiasDirection::CE, 28.0),
+            bias("BANKNIFTY", BiasDirection::PE, 34.0),
+            bias("FINNIFTY", BiasDirection::CE, 26.0),
+            // Highest ADX, but not a tradeable bias
+            bias("RELIANCE", BiasDirection::NoTrade, 40.0),
+        ];
+
+        let summary = DailyBiasCalculator::summarize(&biases);
+        assert_eq!((summary.total, summary.ce_count, summary.pe_count, summary.no_trade_count), (4, 2, 1, 1));
+        assert!((summary.avg_adx - 32.0).abs() < 1e-9);
+        assert_eq!(summary.strongest_underlying.as_deref(), Some("BANKNIFTY"));
+
+        let empty = DailyBiasCalculator::summarize(&[]);
+        assert_eq!((empty.total, empty.avg_adx, empty.strongest_underlying), (0, 0.0, None));
+
+        let path = std::env::temp_dir().join(format!("bias_summary_{}.json", uuid::Uuid::new_v4()));
+        summary.save(&path.display().to_string()).await.unwrap();
+        let saved: BiasSummary = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved, summary);
+        let _ = std::fs::remove_file(&path);
+    }
+
     fn create_test_bars() -> Vec<Bar> {
         // Create sample bars with uptrend
         vec![
@@ -205,5 +437,76 @@ mod tests {
             assert!(bias.is_some());
         }
     }
-}
 
+    #[test]
+    fn test_no_trade_reasons() {
+        let calculator = DailyBiasCalculator::new(14, 25.0);
+
+        let (bias, reason) = calculator.classify(18.0, 30.0, 10.0);
+        assert_eq!(bias, BiasDirection::NoTrade);
+        assert!(reason.unwrap().starts_with("WEAK_ADX"));
+
+        let (bias, reason) = calculator.classify(32.0, 21.0, 20.0);
+        assert_eq!(bias, BiasDirection::NoTrade);
+        assert!(reason.unwrap().starts_with("AMBIGUOUS_DI"));
+
+        let (bias, reason) = calculator.classify(32.0, 30.0, 12.0);
+        assert_eq!(bias, BiasDirection::CE);
+        assert!(reason.is_none());
+    }
+
+    #[test]
+    fn test_confidence_grows_with_trend_strength() {
+        let calculator = DailyBiasCalculator::new(14, 25.0);
+
+        let weak = calculator.confidence(20.0, 22.0, 20.0);
+        let strong = calculator.confidence(45.0, 35.0, 10.0);
+
+        assert!(weak < strong);
+        assert!((0.0..=1.0).contains(&weak));
+        assert!((0.0..=1.0).contains(&strong));
+        assert_eq!(calculator.confidence(100.0, 50.0, 0.0), 1.0);
+    }
+
+    fn trending_bars(count: usize, step: f64) -> Vec<Bar> {
+        (0..count)
+            .map(|i| {
+                let close = 1000.0 + step * i as f64;
+                Bar {
+                    timestamp: Utc::now(),
+                    timestamp_ms: 0,
+                    open: close - step,
+                    high: close.max(close - step) + 2.0,
+                    low: close.min(close - step) - 2.0,
+                    close,
+                    volume: 1000,
+                    bar_complete: true,
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_adx_and_ema_cross_agree_on_trends() {
+        let adx = DailyBiasCalculator::new(14, 20.0);
+        let ema = DailyBiasCalculator::new(14, 20.0)
+            .with_method(DailyBiasMethod::EmaCross)
+            .with_ema_periods(9, 21);
+
+        let up = trending_bars(60, 10.0);
+        assert_eq!(adx.calculate_bias("T", "1", &up).unwrap().bias, BiasDirection::CE);
+        let ema_up = ema.calculate_bias("T", "1", &up).unwrap();
+        assert_eq!(ema_up.bias, BiasDirection::CE);
+        assert!(ema_up.no_trade_reason.is_none());
+        assert!(ema_up.confidence > 0.0);
+
+        let down = trending_bars(60, -10.0);
+        assert_eq!(adx.calculate_bias("T", "1", &down).unwrap().bias, BiasDirection::PE);
+        assert_eq!(ema.calculate_bias("T", "1", &down).unwrap().bias, BiasDirection::PE);
+
+        // Price back below a rising slow EMA is mixed
+        let (bias, reason) = DailyBiasCalculator::classify_ema(1010.0, 1000.0, 990.0);
+        assert_eq!(bias, BiasDirection::NoTrade);
+        assert!(reason.unwrap().starts_with("EMA_MIXED"));
+    }
+}
@@ -34,12 +34,61 @@ struct CrossoverState {
     last_adx: f64,
 }
 
+/// Crossover already turned into a signal
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ActedCrossover {
+    direction: BiasDirection,
+    bar_time: DateTime<Utc>,
+}
+
+/// Remembers the last acted crossover per underlying so a persisting
+/// alignment is only signalled once
+#[derive(Debug, Default)]
+struct SignalDedup {
+    acted: HashMap<String, ActedCrossover>,
+}
+
+impl SignalDedup {
+    /// Forget the acted crossover once the DI leader no longer matches it
+    fn observe_leader(&mut self, underlying: &str, leader: Option<BiasDirection>) {
+        if let Some(acted) = self.acted.get(underlying) {
+            if leader != Some(acted.direction) {
+                self.acted.remove(underlying);
+            }
+        }
+    }
+
+    /// Record a signal; false if this crossover was already acted on
+    fn try_record(&mut self, underlying: &str, direction: BiasDirection, bar_time: DateTime<Utc>) -> bool {
+        if let Some(acted) = self.acted.get(underlying) {
+            if acted.direction == direction && bar_time >= acted.bar_time {
+                return false;
+            }
+        }
+
+        self.acted.insert(underlying.to_string(), ActedCrossover { direction, bar_time });
+        true
+    }
+}
+
+/// Direction of the currently leading DI
+fn di_leader(plus_di: f64, minus_di: f64) -> Option<BiasDirection> {
+    if plus_di > minus_di {
+        Some(BiasDirection::CE)
+    } else if minus_di > plus_di {
+        Some(BiasDirection::PE)
+    } else {
+        None
+    }
+}
+
 /// Hourly crossover monitor
 pub struct HourlyCrossoverMonitor {
     adx_period: usize,
     adx_threshold: f64,
     hourly_stores: Arc<RwLock<HashMap<String, Arc<ConcurrentBarStore>>>>,
     crossover_states: Arc<RwLock<HashMap<String, CrossoverState>>>,
+    signal_dedup: Arc<RwLock<SignalDedup>>,
 }
 
 impl HourlyCrossoverMonitor {
@@ -49,6 +98,7 @@ impl HourlyCrossoverMonitor {
             adx_threshold,
             hourly_stores: Arc::new(RwLock::new(HashMap::new())),
             crossover_states: Arc::new(RwLock::new(HashMap::new())),
+            signal_dedup: Arc::new(RwLock::new(SignalDedup::default())),
         }
     }
 
@@ -93,6 +143,10 @@ impl HourlyCrossoverMonitor {
                     "Failed to calculate ADX".to_string()
                 ))?;
 
+        // A flipped or flat DI resets the last acted crossover
+        self.signal_dedup.write().await
+            .observe_leader(underlying, di_leader(current_plus_di, current_minus_di));
+
         // Check ADX threshold
         if current_adx < self.adx_threshold {
             return Ok(None);
@@ -129,6 +183,14 @@ impl HourlyCrossoverMonitor {
             if aligned {
                 let latest_bar = hourly_bars.last().unwrap();
                 
+                if !self.signal_dedup.write().await
+                    .try_record(underlying, direction, latest_bar.timestamp)
+                {
+                    info!("⏭️  {} {} crossover already acted on - skipping", 
+                          underlying, direction.as_str());
+                    return Ok(None);
+                }
+                
                 info!("🎯 CROSSOVER DETECTED: {} {} @ {}", 
                       underlying, direction.as_str(), latest_bar.timestamp);
                 info!("   ADX: {:.2}, +DI: {:.2}, -DI: {:.2}, Close: {:.2}",
@@ -236,6 +298,7 @@ impl HourlyCrossoverMonitor {
     pub async fn clear_states(&self) {
         let mut states = self.crossover_states.write().await;
         states.clear();
+        self.signal_dedup.write().await.acted.clear();
         info!("🧹 Cleared crossover states");
     }
 }
@@ -286,5 +349,88 @@ mod tests {
         // Not aligned: PE vs CE
         assert!(!monitor.is_aligned_with_daily(BiasDirection::PE, BiasDirection::CE));
     }
-}
 
+    #[test]
+    fn test_persisting_alignment_signals_once() {
+        let mut dedup = SignalDedup::default();
+        let bar1 = Utc::now();
+        let bar2 = bar1 + chrono::Duration::hours(1);
+
+        // First hourly bar: new CE crossover is acted on
+        dedup.observe_leader("NIFTY", di_leader(26.0, 24.0));
+        assert!(dedup.try_record("NIFTY", BiasDirection::CE, bar1));
+
+        // Next bar: +DI still leads, same alignment is not re-emitted
+        dedup.observe_leader("NIFTY", di_leader(28.0, 22.0));
+        assert!(!dedup.try_record("NIFTY", BiasDirection::CE, bar2));
+
+        // Condition resets (-DI takes over), then a fresh CE crossover fires
+        let bar3 = bar2 + chrono::Duration::hours(1);
+        let bar4 = bar3 + chrono::Duration::hours(1);
+        dedup.observe_leader("NIFTY", di_leader(22.0, 27.0));
+        dedup.observe_leader("NIFTY", di_leader(27.0, 23.0));
+        assert!(dedup.try_record("NIFTY", BiasDirection::CE, bar4));
+
+        // Other underlyings are tracked independently
+        assert!(dedup.try_record("BANKNIFTY", BiasDirection::CE, bar4));
+    }
+
+    #[tokio::test]
+    async fn test_check_crossover_emits_persisting_crossover_once() {
+        use crate::types::Bar;
+
+        let dir = std::env::temp_dir().join(format!("hourly_crossover_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let store = Arc::new(ConcurrentBarStore::new(
+            "NIFTY".to_string(),
+            "1h".to_string(),
+            dir.join("bars.jsonl"),
+            100,
+        ));
+        let start = Utc::now() - chrono::Duration::days(5);
+        let mut count = 0;
+        let mut close = 20000.0;
+        let mut push = |step: f64| {
+            close += step;
+            let timestamp = start + chrono::Duration::hours(count);
+            count += 1;
+            Bar {
+                timestamp,
+                timestamp_ms: timestamp.timestamp_millis(),
+                open: close - step / 2.0,
+                high: close + 20.0,
+                low: close - 20.0,
+                close,
+                volume: 1000,
+                bar_complete: true,
+            }
+        };
+
+        // Downtrend: -DI leads on the first check
+        for _ in 0..30 {
+            store.append(push(-40.0)).await.unwrap();
+        }
+        let monitor = HourlyCrossoverMonitor::new(14, 0.0);
+        monitor.register_underlying("NIFTY".to_string(), "26000".to_string(), Arc::clone(&store)).await;
+        let check = || monitor.check_crossover("NIFTY", "26000", BiasDirection::CE);
+        assert!(check().await.unwrap().is_none());
+
+        // Rally until +DI crosses above -DI on a completed bar
+        let mut signal = None;
+        for _ in 0..20 {
+            store.append(push(80.0)).await.unwrap();
+            signal = check().await.unwrap();
+            if signal.is_some() {
+                break;
+            }
+        }
+        let signal = signal.expect("bullish crossover");
+        assert_eq!(signal.direction, BiasDirection::CE);
+
+        // The next completed bar keeps the same crossover: nothing new to act on
+        store.append(push(80.0)).await.unwrap();
+        assert!(check().await.unwrap().is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
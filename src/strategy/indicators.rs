@@ -1,8 +1,70 @@
 /// Technical indicators implementation
 use crate::types::Bar;
 
+/// Keltner Channel (EMA ± multiple of ATR)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeltnerChannel {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// Bollinger Bands (SMA ± multiple of standard deviation)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerBands {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+/// Price/RSI divergence at the latest swing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Divergence {
+    /// Price lower low, RSI higher low: selling is drying up
+    Bullish,
+    /// Price higher high, RSI lower high: buying is exhausting
+    Bearish,
+}
+
+/// Averaging applied to TR and directional movement in ADX
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Smoothing {
+    /// Wilder's running average (alpha = 1/period), the standard ADX
+    #[default]
+    Wilder,
+    /// Exponential average (alpha = 2/(period+1)), seeded with the first SMA
+    Ema,
+    /// Simple average of the last `period` values
+    Sma,
+}
+
+/// ADX parameters for `calculate_adx_with`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AdxConfig {
+    pub period: usize,
+    pub smoothing: Smoothing,
+}
+
+impl AdxConfig {
+    /// Wilder-smoothed ADX over `period`
+    pub fn new(period: usize) -> Self {
+        AdxConfig { period, smoothing: Smoothing::Wilder }
+    }
+    
+    pub fn with_smoothing(mut self, smoothing: Smoothing) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+}
+
 /// Calculate ADX (Average Directional Index) with +DI and -DI
 pub fn calculate_adx(bars: &[Bar], period: usize) -> Option<(f64, f64, f64)> {
+    calculate_adx_with(bars, AdxConfig::new(period))
+}
+
+/// Calculate ADX, +DI and -DI with the given period and smoothing
+pub fn calculate_adx_with(bars: &[Bar], config: AdxConfig) -> Option<(f64, f64, f64)> {
+    let AdxConfig { period, smoothing } = config;
     if bars.len() < period + 1 {
         return None;
     }
@@ -45,10 +107,10 @@ pub fn calculate_adx(bars: &[Bar], period: usize) -> Option<(f64, f64, f64)> {
         minus_dm.push(minus_dm_val);
     }
     
-    // Smooth TR and DM using Wilder's smoothing
-    let smoothed_tr = wilder_smooth(&tr_values, period)?;
-    let smoothed_plus_dm = wilder_smooth(&plus_dm, period)?;
-    let smoothed_minus_dm = wilder_smooth(&minus_dm, period)?;
+    // Smooth TR and DM (Wilder's smoothing by default)
+    let smoothed_tr = smooth(&tr_values, period, smoothing)?;
+    let smoothed_plus_dm = smooth(&plus_dm, period, smoothing)?;
+    let smoothed_minus_dm = smooth(&minus_dm, period, smoothing)?;
     
     // Calculate +DI and -DI
     let plus_di = (smoothed_plus_dm / smoothed_tr) * 100.0;
@@ -72,6 +134,31 @@ pub fn calculate_adx(bars: &[Bar], period: usize) -> Option<(f64, f64, f64)> {
     Some((adx, plus_di, minus_di))
 }
 
+/// Final smoothed value of `values` under `smoothing`
+fn smooth(values: &[f64], period: usize, smoothing: Smoothing) -> Option<f64> {
+    match smoothing {
+        Smoothing::Wilder => wilder_smooth(values, period),
+        Smoothing::Ema => ema_smooth(values, period),
+        Smoothing::Sma => {
+            if period == 0 || values.len() < period {
+                return None;
+            }
+            Some(values[values.len() - period..].iter().sum::<f64>() / period as f64)
+        }
+    }
+}
+
+/// Exponential smoothing seeded with the first `period` average
+fn ema_smooth(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+    
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let seed = values.iter().take(period).sum::<f64>() / period as f64;
+    Some(values[period..].iter().fold(seed, |ema, v| alpha * v + (1.0 - alpha) * ema))
+}
+
 /// Wilder's smoothing (EMA-like with 1/period factor)
 fn wilder_smooth(values: &[f64], period: usize) -> Option<f64> {
     if values.len() < period {
@@ -127,6 +214,42 @@ pub fn calculate_rsi(bars: &[Bar], period: usize) -> Option<f64> {
     Some(rsi)
 }
 
+/// Detect RSI divergence over the last `lookback` bars: the latest swing
+/// high (low) is compared with the highest (lowest) earlier swing in the
+/// window. A higher price high on a lower RSI is Bearish, a lower price low on
+/// a higher RSI is Bullish; if both, the more recent swing wins.
+/// Swings are bars strictly above (below) the previous bar and at least the next.
+pub fn detect_rsi_divergence(bars: &[Bar], period: usize, lookback: usize) -> Option<Divergence> {
+    if lookback < 3 || bars.len() < lookback + period {
+        return None;
+    }
+    let start = bars.len() - lookback;
+    let rsi_at = |i: usize| calculate_rsi(&bars[..=i], period);
+    
+    let swing_highs: Vec<usize> = (start + 1..bars.len() - 1)
+        .filter(|&i| bars[i].high > bars[i - 1].high && bars[i].high >= bars[i + 1].high)
+        .collect();
+    let swing_lows: Vec<usize> = (start + 1..bars.len() - 1)
+        .filter(|&i| bars[i].low < bars[i - 1].low && bars[i].low <= bars[i + 1].low)
+        .collect();
+    
+    let bearish = swing_highs.split_last().and_then(|(&last, earlier)| {
+        let peak = *earlier.iter().max_by(|&&a, &&b| bars[a].high.total_cmp(&bars[b].high))?;
+        (bars[last].high > bars[peak].high && rsi_at(last)? < rsi_at(peak)?).then_some(last)
+    });
+    let bullish = swing_lows.split_last().and_then(|(&last, earlier)| {
+        let trough = *earlier.iter().min_by(|&&a, &&b| bars[a].low.total_cmp(&bars[b].low))?;
+        (bars[last].low < bars[trough].low && rsi_at(last)? > rsi_at(trough)?).then_some(last)
+    });
+    
+    match (bearish, bullish) {
+        (Some(high), Some(low)) if low > high => Some(Divergence::Bullish),
+        (Some(_), _) => Some(Divergence::Bearish),
+        (None, Some(_)) => Some(Divergence::Bullish),
+        (None, None) => None,
+    }
+}
+
 /// Calculate EMA (Exponential Moving Average)
 pub fn calculate_ema(bars: &[Bar], period: usize) -> Option<f64> {
     if bars.len() < period {
@@ -212,6 +335,53 @@ pub fn calculate_atr(bars: &[Bar], period: usize) -> Option<f64> {
     wilder_smooth(&tr_values, period)
 }
 
+/// Calculate Keltner Channel (middle = EMA, bands = EMA ± mult * ATR)
+pub fn calculate_keltner(
+    bars: &[Bar],
+    ema_period: usize,
+    atr_period: usize,
+    mult: f64,
+) -> Option<KeltnerChannel> {
+    let middle = calculate_ema(bars, ema_period)?;
+    let atr = calculate_atr(bars, atr_period)?;
+    
+    Some(KeltnerChannel {
+        upper: middle + mult * atr,
+        middle,
+        lower: middle - mult * atr,
+    })
+}
+
+/// Calculate Bollinger Bands (middle = SMA, bands = SMA ± mult * std dev)
+pub fn calculate_bollinger(bars: &[Bar], period: usize, mult: f64) -> Option<BollingerBands> {
+    let middle = calculate_sma(bars, period)?;
+    
+    let variance: f64 = bars.iter()
+        .rev()
+        .take(period)
+        .map(|b| (b.close - middle).powi(2))
+        .sum::<f64>() / period as f64;
+    let std_dev = variance.sqrt();
+    
+    Some(BollingerBands {
+        upper: middle + mult * std_dev,
+        middle,
+        lower: middle - mult * std_dev,
+    })
+}
+
+/// Volatility squeeze: Bollinger Bands (20, 2.0) inside Keltner Channel (20, 20, 1.5)
+pub fn is_squeeze(bars: &[Bar]) -> bool {
+    let (Some(bb), Some(kc)) = (
+        calculate_bollinger(bars, 20, 2.0),
+        calculate_keltner(bars, 20, 20, 1.5),
+    ) else {
+        return false;
+    };
+    
+    bb.upper < kc.upper && bb.lower > kc.lower
+}
+
 /// Helper: Calculate percentage change
 pub fn percentage_change(from: f64, to: f64) -> f64 {
     if from == 0.0 {
@@ -262,11 +432,106 @@ mod tests {
         assert!(ema.is_some());
     }
     
+    #[test]
+    fn test_adx_smoothing_variants() {
+        // TR [3,3,4,2,3,3], +DM [2,0,2,0,2,0], -DM [0,1,0,0,0,1]
+        let bars: Vec<Bar> = [(10.0, 8.0, 9.0), (12.0, 9.0, 11.0), (11.0, 8.0, 9.0), (13.0, 9.0, 12.0),
+            (12.0, 10.0, 11.0), (14.0, 11.0, 13.0), (13.0, 10.0, 10.0)]
+            .iter()
+            .map(|&(high, low, close)| Bar { high, low, close, open: close, ..create_test_bars(1)[0].clone() })
+            .collect();
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+        
+        // SMA of the last 3: TR 8/3, +DM 2/3, -DM 1/3 → +DI 25, -DI 12.5
+        let (adx, plus_di, minus_di) = calculate_adx_with(&bars, AdxConfig::new(3).with_smoothing(Smoothing::Sma)).unwrap();
+        assert!(close(plus_di, 25.0) && close(minus_di, 12.5));
+        assert!(close(adx, 100.0 / 3.0));
+        
+        // Wilder (the default): +DI 6800/239, -DI 3500/239
+        let wilder = calculate_adx(&bars, 3).unwrap();
+        assert_eq!(wilder, calculate_adx_with(&bars, AdxConfig::new(3)).unwrap());
+        assert!(close(wilder.1, 6800.0 / 239.0) && close(wilder.2, 3500.0 / 239.0));
+        assert!(close(wilder.0, 3300.0 / 103.0));
+        
+        // EMA (alpha 0.5): +DI 160/7, -DI 130/7
+        let ema = calculate_adx_with(&bars, AdxConfig::new(3).with_smoothing(Smoothing::Ema)).unwrap();
+        assert!(close(ema.1, 160.0 / 7.0) && close(ema.2, 130.0 / 7.0));
+        assert!(close(ema.0, 300.0 / 29.0));
+        
+        assert!(calculate_adx_with(&bars[..3], AdxConfig::new(3)).is_none());
+    }
+    
     #[test]
     fn test_round_to_strike() {
         assert_eq!(round_to_strike(19345.0, 50), 19300);
         assert_eq!(round_to_strike(19375.0, 50), 19350);
         assert_eq!(round_to_strike(19399.99, 50), 19350);
     }
+    
+    fn bars_from_closes(closes: &[f64], half_range: f64) -> Vec<Bar> {
+        closes.iter()
+            .map(|&close| Bar {
+                timestamp: Utc::now(),
+                timestamp_ms: Utc::now().timestamp_millis(),
+                open: close,
+                high: close + half_range,
+                low: close - half_range,
+                close,
+                volume: 1000000,
+                bar_complete: true,
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn test_keltner_channel() {
+        // Flat closes with a 2-point range: EMA = 100, ATR = 2
+        let bars = bars_from_closes(&[100.0; 30], 1.0);
+        let kc = calculate_keltner(&bars, 20, 14, 2.0).unwrap();
+        
+        assert!((kc.middle - 100.0).abs() < 1e-9);
+        assert!((kc.upper - 104.0).abs() < 1e-9);
+        assert!((kc.lower - 96.0).abs() < 1e-9);
+        
+        assert!(calculate_keltner(&bars[..10], 20, 14, 2.0).is_none());
+    }
+    
+    #[test]
+    fn test_squeeze_detection() {
+        // Quiet market: no close-to-close dispersion, bars still have range
+        let quiet = bars_from_closes(&[100.0; 30], 1.0);
+        assert!(is_squeeze(&quiet));
+        
+        // Strong trend with tight bars: Bollinger widens beyond Keltner
+        let closes: Vec<f64> = (0..30).map(|i| 100.0 + i as f64 * 10.0).collect();
+        let trending = bars_from_closes(&closes, 0.5);
+        assert!(!is_squeeze(&trending));
+        
+        // Not enough data
+        assert!(!is_squeeze(&quiet[..10]));
+    }
+    
+    #[test]
+    fn test_rsi_divergence() {
+        // Rally to 130 on straight gains, then a choppy push to 131: higher high, RSI 87.5 < 100
+        let topping = bars_from_closes(
+            &[100.0, 100.0, 100.0, 100.0, 110.0, 120.0, 130.0, 125.0, 118.0, 115.0, 119.0, 117.0, 124.0, 131.0, 127.0],
+            0.5,
+        );
+        assert_eq!(detect_rsi_divergence(&topping, 3, 12), Some(Divergence::Bearish));
+        
+        // Mirror image: lower low at 99 with RSI 12.5 above the 0 at the 100 low
+        let bottoming = bars_from_closes(
+            &[130.0, 130.0, 130.0, 130.0, 120.0, 110.0, 100.0, 105.0, 112.0, 115.0, 111.0, 113.0, 106.0, 99.0, 103.0],
+            0.5,
+        );
+        assert_eq!(detect_rsi_divergence(&bottoming, 3, 12), Some(Divergence::Bullish));
+        
+        // Steady trend confirms rather than diverges
+        let closes: Vec<f64> = (0..20).map(|i| 100.0 + i as f64 * 2.0).collect();
+        assert_eq!(detect_rsi_divergence(&bars_from_closes(&closes, 0.5), 3, 12), None);
+        
+        // Not enough bars for RSI across the window
+        assert_eq!(detect_rsi_divergence(&topping, 3, 14), None);
+    }
 }
-
@@ -2,9 +2,11 @@ pub mod indicators;
 pub mod adx_strategy;
 pub mod daily_bias;
 pub mod hourly_crossover;
+pub mod oi_tracker;
 
 pub use indicators::*;
 pub use adx_strategy::AdxStrategy;
-pub use daily_bias::{DailyBiasCalculator, DailyBias, BiasDirection, DailyBiasToken, BiasSummary};
+pub use daily_bias::{DailyBiasCalculator, DailyBias, BiasDirection, DailyBiasToken, BiasSummary, BIAS_SUMMARY_FILE};
 pub use hourly_crossover::{HourlyCrossoverMonitor, CrossoverSignal};
+pub use oi_tracker::{oi_buildup_confirms, OiSample, OiTracker};
 
@@ -0,0 +1,92 @@
+/// Open-interest history per option token
+/// OI build-up alongside price confirms that new positions are backing the move.
+use std::collections::{HashMap, VecDeque};
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OiSample {
+    pub time: DateTime<Utc>,
+    pub open_interest: i64,
+}
+
+pub struct OiTracker {
+    samples: RwLock<HashMap<String, VecDeque<OiSample>>>,
+    /// Samples kept per token (oldest dropped first)
+    capacity: usize,
+}
+
+impl OiTracker {
+    pub fn new(capacity: usize) -> Self {
+        OiTracker {
+            samples: RwLock::new(HashMap::new()),
+            capacity: capacity.max(2),
+        }
+    }
+
+    /// Record the latest OI for `token`
+    pub async fn record(&self, token: &str, time: DateTime<Utc>, open_interest: i64) {
+        let mut samples = self.samples.write().await;
+        let series = samples.entry(token.to_string()).or_default();
+        series.push_back(OiSample { time, open_interest });
+        while series.len() > self.capacity {
+            series.pop_front();
+        }
+    }
+
+    /// Percent OI change from `window` samples back to the latest; None until
+    /// the series is that long or when the base OI is zero
+    pub async fn oi_change_pct(&self, token: &str, window: usize) -> Option<f64> {
+        let samples = self.samples.read().await;
+        let series = samples.get(token)?;
+        if window == 0 || series.len() <= window {
+            return None;
+        }
+
+        let latest = series[series.len() - 1].open_interest;
+        let base = series[series.len() - 1 - window].open_interest;
+        if base <= 0 {
+            return None;
+        }
+        Some((latest - base) as f64 / base as f64 * 100.0)
+    }
+
+    /// Drop all history (daily reset)
+    pub async fn clear(&self) {
+        self.samples.write().await.clear();
+    }
+}
+
+/// Entry filter: the traded strike's OI grew by at least `min_pct`
+/// (an unknown change does not confirm)
+pub fn oi_buildup_confirms(change_pct: Option<f64>, min_pct: f64) -> bool {
+    change_pct.is_some_and(|change| change >= min_pct)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[tokio::test]
+    async fn test_oi_change_over_stored_series() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 15, 4, 0, 0).unwrap();
+        let tracker = OiTracker::new(4);
+        for (i, oi) in [1000, 1100, 1200, 1500, 1650].into_iter().enumerate() {
+            tracker.record("43250", start + chrono::Duration::minutes(i as i64), oi).await;
+        }
+
+        // Capacity 4 dropped the first sample: series is 1100, 1200, 1500, 1650
+        assert_eq!(tracker.oi_change_pct("43250", 1).await, Some(10.0));
+        assert_eq!(tracker.oi_change_pct("43250", 3).await, Some(50.0));
+        assert_eq!(tracker.oi_change_pct("43250", 4).await, None);
+        assert_eq!(tracker.oi_change_pct("99999", 1).await, None);
+
+        assert!(oi_buildup_confirms(Some(50.0), 20.0));
+        assert!(!oi_buildup_confirms(Some(10.0), 20.0));
+        assert!(!oi_buildup_confirms(None, 0.0));
+
+        tracker.clear().await;
+        assert_eq!(tracker.oi_change_pct("43250", 1).await, None);
+    }
+}
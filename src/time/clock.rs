@@ -0,0 +1,69 @@
+/// Clock abstraction so time-dependent logic can be tested deterministically
+use chrono::{DateTime, Duration, Utc};
+use std::sync::RwLock;
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Wall clock (default in production)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Manually controlled clock for tests and simulations
+#[derive(Debug)]
+pub struct MockClock {
+    now: RwLock<DateTime<Utc>>,
+}
+
+impl MockClock {
+    pub fn new(start: DateTime<Utc>) -> Self {
+        MockClock {
+            now: RwLock::new(start),
+        }
+    }
+    
+    /// Jump to an absolute time
+    pub fn set(&self, time: DateTime<Utc>) {
+        *self.now.write().unwrap() = time;
+    }
+    
+    /// Move time forward
+    pub fn advance(&self, duration: Duration) {
+        let mut now = self.now.write().unwrap();
+        *now += duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.read().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    
+    #[test]
+    fn test_mock_clock_advance() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 15, 4, 0, 0).unwrap();
+        let clock = MockClock::new(start);
+        
+        assert_eq!(clock.now(), start);
+        
+        clock.advance(Duration::minutes(90));
+        assert_eq!(clock.now(), start + Duration::minutes(90));
+        
+        clock.set(start);
+        assert_eq!(clock.now(), start);
+    }
+}
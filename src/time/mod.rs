@@ -1,7 +1,8 @@
 pub mod session;
 pub mod holidays;
+pub mod clock;
 
 // Re-export specific items to avoid ambiguity
-pub use session::{get_market_timings, is_trading_day as is_trading_day_weekday_only};
+pub use session::{get_market_timings, is_trading_day as is_trading_day_weekday_only, current_session_state, session_transition};
 pub use holidays::{is_trading_day, next_trading_day, get_nse_holidays_2025};
-
+pub use clock::{Clock, MockClock, SystemClock};
@@ -2,6 +2,8 @@
 use chrono::{DateTime, Datelike, TimeZone, Utc};
 use chrono_tz::Asia::Kolkata;
 
+use crate::types::SessionState;
+
 /// Check if today is a trading day (simplified - doesn't check holidays)
 pub fn is_trading_day(date: DateTime<Utc>) -> bool {
     let date_ist = date.with_timezone(&Kolkata);
@@ -44,3 +46,76 @@ pub fn get_market_timings(date: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>)
     
     (market_open, market_close)
 }
+
+/// Pre-open call auction starts at 09:00 IST; post-market runs until 16:00 IST
+const PRE_OPEN_START: (u32, u32) = (9, 0);
+const POST_MARKET_END: (u32, u32) = (16, 0);
+
+/// Session state at `now` (NSE holidays and weekends are `Closed`)
+pub fn current_session_state(now: DateTime<Utc>) -> SessionState {
+    let date_ist = now.with_timezone(&Kolkata).date_naive();
+    if !crate::time::holidays::is_trading_day(date_ist) {
+        return SessionState::Closed;
+    }
+    
+    let (market_open, market_close) = get_market_timings(now);
+    let at = |(hour, minute): (u32, u32)| {
+        Kolkata
+            .from_local_datetime(&date_ist.and_hms_opt(hour, minute, 0).unwrap())
+            .unwrap()
+            .with_timezone(&Utc)
+    };
+    
+    if now >= market_open && now < market_close {
+        SessionState::Open
+    } else if now >= at(PRE_OPEN_START) && now < market_open {
+        SessionState::PreOpen
+    } else if now >= market_close && now < at(POST_MARKET_END) {
+        SessionState::PostMarket
+    } else {
+        SessionState::Closed
+    }
+}
+
+/// New session state at `now` if it differs from `previous` (`None` before
+/// the first check, so the initial state is always reported)
+pub fn session_transition(previous: Option<SessionState>, now: DateTime<Utc>) -> Option<SessionState> {
+    let state = current_session_state(now);
+    (previous != Some(state)).then_some(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn ist(day: u32, h: u32, m: u32, sec: u32) -> DateTime<Utc> {
+        Kolkata.with_ymd_and_hms(2025, 1, day, h, m, sec).unwrap().with_timezone(&Utc)
+    }
+    
+    #[test]
+    fn test_session_state_boundaries() {
+        // Wednesday 2025-01-15
+        let cases = [
+            ((8, 59, 59), SessionState::Closed),
+            ((9, 0, 0), SessionState::PreOpen),
+            ((9, 14, 59), SessionState::PreOpen),
+            ((9, 15, 0), SessionState::Open),
+            ((15, 29, 59), SessionState::Open),
+            ((15, 30, 0), SessionState::PostMarket),
+            ((15, 59, 59), SessionState::PostMarket),
+            ((16, 0, 0), SessionState::Closed),
+        ];
+        for ((h, m, sec), expected) in cases {
+            assert_eq!(current_session_state(ist(15, h, m, sec)), expected, "{:02}:{:02}:{:02}", h, m, sec);
+        }
+        
+        // Saturday stays closed during market hours
+        assert_eq!(current_session_state(ist(18, 10, 0, 0)), SessionState::Closed);
+        
+        // Transitions are reported once
+        assert_eq!(session_transition(None, ist(15, 9, 10, 0)), Some(SessionState::PreOpen));
+        assert_eq!(session_transition(Some(SessionState::PreOpen), ist(15, 9, 14, 0)), None);
+        assert_eq!(session_transition(Some(SessionState::PreOpen), ist(15, 9, 15, 0)), Some(SessionState::Open));
+        assert_eq!(session_transition(Some(SessionState::Open), ist(15, 15, 30, 0)), Some(SessionState::PostMarket));
+    }
+}
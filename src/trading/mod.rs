@@ -1,4 +1,4 @@
 pub mod premarket_selector;
 
-pub use premarket_selector::{PremarketSelector, PreSelectedOption, AtmStrike};
+pub use premarket_selector::{select_liquid_strike, strike_candidates, PremarketSelector, PreSelectedOption, AtmStrike};
 
@@ -1,12 +1,15 @@
 /// Pre-market ATM option selector
-/// Selects ATM strike based on previous day close and daily bias
+/// Selects ATM strike based on previous day close and daily bias; at the open
+/// the selection can be re-centered on the opening print
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{info, warn};
 
 use crate::broker::TokenExtractor;
 use crate::strategy::{BiasDirection, DailyBias};
+use crate::types::Quote;
 
 /// ATM strike information
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +25,9 @@ pub struct PreSelectedOption {
     pub spot_token: String,
     pub bias: BiasDirection,
     pub close_price: f64,
+    /// Opening print the ATM was re-centered on (None = previous close)
+    #[serde(default)]
+    pub open_price: Option<f64>,
     pub atm_strike: AtmStrike,
     
     // Option tokens based on bias
@@ -34,6 +40,33 @@ pub struct PreSelectedOption {
     pub expiry: String,
 }
 
+/// Strikes to try for an entry: the ATM, then its neighbours nearest
+/// `reference_price` first, out to `steps` increments either side
+pub fn strike_candidates(atm_strike: i32, strike_increment: i32, reference_price: f64, steps: i32) -> Vec<i32> {
+    let mut candidates: Vec<i32> = (-steps..=steps)
+        .map(|k| atm_strike + k * strike_increment)
+        .collect();
+    candidates.sort_by(|a, b| {
+        let da = (*a as f64 - reference_price).abs();
+        let db = (*b as f64 - reference_price).abs();
+        da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    candidates
+}
+
+/// First candidate whose volume and open interest both reach `min_volume`
+/// `liquidity` returns `None` when a strike's quote is unavailable; such
+/// strikes are skipped
+pub fn select_liquid_strike(
+    candidates: &[i32],
+    min_volume: i64,
+    liquidity: impl Fn(i32) -> Option<Quote>,
+) -> Option<i32> {
+    candidates.iter().copied().find(|&strike| {
+        liquidity(strike).is_some_and(|q| q.volume >= min_volume && q.open_interest >= min_volume)
+    })
+}
+
 /// Pre-market ATM selector
 pub struct PremarketSelector {
     token_extractor: Arc<TokenExtractor>,
@@ -101,10 +134,34 @@ impl PremarketSelector {
         }
     }
 
-    /// Select pre-market option for a bias
+    /// Select pre-market option for a bias (ATM from the previous close)
     pub fn select_premarket_option(
         &self,
         bias: &DailyBias,
+    ) -> Option<PreSelectedOption> {
+        self.select_option_near(bias, bias.close_price)
+    }
+
+    /// Re-select at the open using the 9:15–9:16 opening print (LTP), so a gap
+    /// away from the previous close moves the strike to the actual ATM
+    pub fn select_option_at_open(
+        &self,
+        bias: &DailyBias,
+        open_price: f64,
+    ) -> Option<PreSelectedOption> {
+        let mut option = self.select_option_near(bias, open_price)?;
+        option.open_price = Some(open_price);
+
+        info!("   {} re-centered on open {:.2} (prev close {:.2}) → strike {}",
+              bias.underlying, open_price, bias.close_price, option.atm_strike.strike);
+        Some(option)
+    }
+
+    /// Select the option nearest the ATM for `reference_price`
+    fn select_option_near(
+        &self,
+        bias: &DailyBias,
+        reference_price: f64,
     ) -> Option<PreSelectedOption> {
         // Skip NoTrade bias
         if bias.bias == BiasDirection::NoTrade {
@@ -112,7 +169,7 @@ impl PremarketSelector {
         }
 
         // Calculate ATM strike
-        let atm_strike = self.select_atm_strike(&bias.underlying, bias.close_price)?;
+        let atm_strike = self.select_atm_strike(&bias.underlying, reference_price)?;
 
         // Extract tokens for this underlying
         let tokens = self.token_extractor.extract_asset_tokens(&bias.underlying);
@@ -122,18 +179,48 @@ impl PremarketSelector {
         
         info!("   Selected expiry: {} for {}", selected_expiry, bias.underlying);
 
-        // Find options at ATM strike with selected expiry
+        // Try the selected expiry first, then later expiries as fallback
+        let mut candidate_expiries = vec![selected_expiry.clone()];
+        candidate_expiries.extend(self.later_expiries(&selected_expiry, &tokens.options));
+
+        let strike_increment = self.get_strike_increment(&bias.underlying);
+        let mut selection = None;
+
+        for (expiry_idx, expiry) in candidate_expiries.iter().enumerate() {
+            if expiry_idx > 0 {
+                warn!("   {} - Falling back to next expiry: {}", bias.underlying, expiry);
+            }
+
+            if let Some(strike) = Self::find_available_strike(
+                &tokens.options,
+                expiry,
+                atm_strike.strike,
+                strike_increment,
+                reference_price,
+            ) {
+                if strike != atm_strike.strike {
+                    warn!("   {} - No options at ATM {} for {}, falling back to strike {}",
+                          bias.underlying, atm_strike.strike, expiry, strike);
+                }
+                selection = Some((strike, expiry.clone()));
+                break;
+            }
+
+            warn!("{}: No options found near strike {} for expiry {}", 
+                  bias.underlying, atm_strike.strike, expiry);
+        }
+
+        let (strike, selected_expiry) = selection?;
+        let atm_strike = AtmStrike {
+            strike,
+            distance_from_price: (strike as f64 - reference_price).abs(),
+        };
+
         let atm_options: Vec<_> = tokens.options
             .iter()
-            .filter(|o| o.strike as i32 == atm_strike.strike && o.expiry == selected_expiry)
+            .filter(|o| o.strike as i32 == strike && o.expiry == selected_expiry)
             .collect();
 
-        if atm_options.is_empty() {
-            warn!("{}: No options found at strike {} for expiry {}", 
-                  bias.underlying, atm_strike.strike, selected_expiry);
-            return None;
-        }
-
         // Get CE and PE tokens
         let ce_option = atm_options.iter().find(|o| o.option_type == "CE");
         let pe_option = atm_options.iter().find(|o| o.option_type == "PE");
@@ -146,6 +233,7 @@ impl PremarketSelector {
             spot_token: bias.spot_token.clone(),
             bias: bias.bias,
             close_price: bias.close_price,
+            open_price: None,
             atm_strike,
             ce_token: ce_option.map(|o| o.token.clone()),
             ce_symbol: ce_option.map(|o| o.symbol.clone()),
@@ -156,6 +244,57 @@ impl PremarketSelector {
         })
     }
     
+    /// Find a strike with listed options for an expiry
+    /// Prefers the exact ATM strike, then the nearest strike one increment away
+    fn find_available_strike(
+        options: &[crate::broker::OptionToken],
+        expiry: &str,
+        atm_strike: i32,
+        strike_increment: i32,
+        close_price: f64,
+    ) -> Option<i32> {
+        let has_strike = |strike: i32| {
+            options.iter().any(|o| o.strike as i32 == strike && o.expiry == expiry)
+        };
+
+        if has_strike(atm_strike) {
+            return Some(atm_strike);
+        }
+
+        // Check neighbours, closest to the underlying price first
+        let mut neighbours = [atm_strike - strike_increment, atm_strike + strike_increment];
+        neighbours.sort_by(|a, b| {
+            let da = (*a as f64 - close_price).abs();
+            let db = (*b as f64 - close_price).abs();
+            da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        neighbours.into_iter().find(|&strike| has_strike(strike))
+    }
+
+    /// Expiries after the given one, nearest first
+    fn later_expiries(
+        &self,
+        expiry: &str,
+        options: &[crate::broker::OptionToken],
+    ) -> Vec<String> {
+        let Some(current) = self.parse_expiry_date(expiry) else {
+            return Vec::new();
+        };
+
+        let mut later: Vec<(chrono::NaiveDate, String)> = options
+            .iter()
+            .map(|o| o.expiry.clone())
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .filter_map(|e| self.parse_expiry_date(&e).map(|d| (d, e)))
+            .filter(|(d, _)| *d > current)
+            .collect();
+        later.sort();
+
+        later.into_iter().map(|(_, e)| e).collect()
+    }
+    
     /// Select nearest expiry based on days to expiry (DTE)
     /// - For indices: Skip if DTE < 2 (avoid expiry day margin)
     /// - For stocks: Skip if DTE < 7 (avoid increasing margin)
@@ -249,6 +388,16 @@ impl PremarketSelector {
     pub fn select_all_premarket_options(
         &self,
         biases: &[DailyBias],
+    ) -> Vec<PreSelectedOption> {
+        self.select_all_options_at_open(biases, &HashMap::new())
+    }
+
+    /// Select options for all biases, re-centering on the opening print in
+    /// `open_prices` (keyed by spot token) where there is one
+    pub fn select_all_options_at_open(
+        &self,
+        biases: &[DailyBias],
+        open_prices: &HashMap<String, f64>,
     ) -> Vec<PreSelectedOption> {
         info!("🎯 Selecting pre-market ATM options...");
         
@@ -261,7 +410,11 @@ impl PremarketSelector {
                 continue;
             }
 
-            if let Some(option) = self.select_premarket_option(bias) {
+            let selected = match open_prices.get(&bias.spot_token) {
+                Some(&open_price) => self.select_option_at_open(bias, open_price),
+                None => self.select_premarket_option(bias),
+            };
+            if let Some(option) = selected {
                 match option.bias {
                     BiasDirection::CE => ce_count += 1,
                     BiasDirection::PE => pe_count += 1,
@@ -315,5 +468,116 @@ mod tests {
         let atm = ((close / increment as f64).round() * increment as f64) as i32;
         assert_eq!(atm, 48900);
     }
-}
 
+    fn option_instrument(token: &str, strike: f64, option_type: &str, expiry: &str) -> crate::types::Instrument {
+        crate::types::Instrument {
+            token: token.to_string(),
+            symbol: format!("NIFTY{}{}{}", expiry, strike as i32, option_type),
+            name: "NIFTY".to_string(),
+            expiry: expiry.to_string(),
+            strike,
+            lotsize: 50,
+            instrument_type: "OPTIDX".to_string(),
+            exch_seg: "NFO".to_string(),
+            tick_size: 0.05,
+        }
+    }
+
+    #[test]
+    fn test_fallback_to_neighbouring_strike() {
+        // ATM (23550) is not listed; 23500 and 23600 are
+        let instruments = vec![
+            option_instrument("1", 23500.0, "CE", "30DEC2099"),
+            option_instrument("2", 23500.0, "PE", "30DEC2099"),
+            option_instrument("3", 23600.0, "CE", "30DEC2099"),
+            option_instrument("4", 23600.0, "PE", "30DEC2099"),
+        ];
+        let selector = PremarketSelector::new(Arc::new(TokenExtractor::new(instruments)));
+
+        let bias = DailyBias {
+            underlying: "NIFTY".to_string(),
+            spot_token: "99926000".to_string(),
+            bias: BiasDirection::CE,
+            adx: 30.0,
+            plus_di: 28.0,
+            minus_di: 15.0,
+            close_price: 23547.50,
+            timestamp: chrono::Utc::now(),
+            confidence: 0.6,
+            no_trade_reason: None,
+        };
+
+        let selected = selector.select_premarket_option(&bias).unwrap();
+        assert_eq!(selected.atm_strike.strike, 23500);
+        assert_eq!(selected.ce_token.as_deref(), Some("1"));
+        assert_eq!(selected.expiry, "30DEC2099");
+    }
+
+    #[test]
+    fn test_gap_open_recenters_strike() {
+        let instruments: Vec<_> = [23500.0, 23550.0, 23700.0, 23750.0]
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &strike)| {
+                vec![
+                    option_instrument(&format!("{}C", i), strike, "CE", "30DEC2099"),
+                    option_instrument(&format!("{}P", i), strike, "PE", "30DEC2099"),
+                ]
+            })
+            .collect();
+        let selector = PremarketSelector::new(Arc::new(TokenExtractor::new(instruments)));
+
+        let bias = DailyBias {
+            underlying: "NIFTY".to_string(),
+            spot_token: "99926000".to_string(),
+            bias: BiasDirection::CE,
+            adx: 30.0,
+            plus_di: 28.0,
+            minus_di: 15.0,
+            close_price: 23547.50,
+            timestamp: chrono::Utc::now(),
+            confidence: 0.6,
+            no_trade_reason: None,
+        };
+
+        // Pre-open preparation: previous close
+        let prepared = selector.select_premarket_option(&bias).unwrap();
+        assert_eq!(prepared.atm_strike.strike, 23550);
+        assert_eq!(prepared.open_price, None);
+
+        // Gap up ~190 points at 9:15
+        let at_open = selector.select_option_at_open(&bias, 23738.0).unwrap();
+        assert_eq!(at_open.atm_strike.strike, 23750);
+        assert_eq!(at_open.ce_token.as_deref(), Some("3C"));
+        assert_eq!(at_open.open_price, Some(23738.0));
+        assert_eq!(at_open.close_price, 23547.50);
+        assert_eq!(at_open.atm_strike.distance_from_price, 12.0);
+
+        // Batch selection re-centers only where an opening print is known
+        let opens = HashMap::from([("99926000".to_string(), 23738.0)]);
+        let batch = selector.select_all_options_at_open(std::slice::from_ref(&bias), &opens);
+        assert_eq!(batch[0].atm_strike.strike, 23750);
+        assert_eq!(selector.select_all_premarket_options(&[bias])[0].atm_strike.strike, 23550);
+    }
+
+    #[test]
+    fn test_illiquid_atm_skipped_for_liquid_neighbour() {
+        // Spot 23530: ATM 23550, then 23500 (closer) before 23600
+        let candidates = strike_candidates(23550, 50, 23530.0, 1);
+        assert_eq!(candidates, vec![23550, 23500, 23600]);
+
+        let quote = |volume, open_interest| Quote { ltp: 120.0, volume, open_interest, bid: 0.0, ask: 0.0 };
+        let book = |strike: i32| match strike {
+            23550 => Some(quote(200, 50_000)),    // thin volume
+            23500 => Some(quote(80_000, 600)),    // thin OI
+            23600 => Some(quote(150_000, 90_000)),
+            _ => None,
+        };
+
+        assert_eq!(select_liquid_strike(&candidates, 1_000, book), Some(23600));
+        // Low bar: the ATM itself qualifies
+        assert_eq!(select_liquid_strike(&candidates, 100, book), Some(23550));
+        // Nothing liquid enough
+        assert_eq!(select_liquid_strike(&candidates, 1_000_000, book), None);
+    }
+}
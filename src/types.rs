@@ -15,8 +15,20 @@ pub struct Bar {
     pub bar_complete: bool,
 }
 
+impl Bar {
+    /// Positive, finite OHLC with `low <= open, close <= high` and non-negative volume
+    pub fn is_valid(&self) -> bool {
+        let prices = [self.open, self.high, self.low, self.close];
+        prices.iter().all(|p| p.is_finite() && *p > 0.0)
+            && self.high >= self.low
+            && self.high >= self.open.max(self.close)
+            && self.low <= self.open.min(self.close)
+            && self.volume >= 0
+    }
+}
+
 /// Live tick data from WebSocket
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tick {
     pub symbol: String,
     pub token: String,
@@ -52,6 +64,158 @@ pub struct Position {
     pub status: PositionStatus,
     pub entry_reason: String,
     pub idempotency_key: String,
+    /// Set when the position is closed. Performance metrics walk positions
+    /// in close order by it (win/loss streaks, drawdown) and measure hold
+    /// time from `entry_time` to it; None while the position is open.
+    #[serde(default)]
+    pub exit_time: Option<DateTime<Utc>>,
+    /// Option legs of a multi-leg position; empty for a single-leg position
+    /// described by the fields above (see `MultiLegPosition`). With legs,
+    /// prices, stop and target are the combined premium (`net_price`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub legs: Vec<Leg>,
+}
+
+/// One option leg of a multi-leg position
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Leg {
+    pub symbol: String,
+    pub strike: i32,
+    pub option_type: OptionType,
+    pub side: Side,
+    pub quantity: i32,
+    pub entry_price: f64,
+    pub current_price: f64,
+    /// Set when this leg is closed
+    #[serde(default)]
+    pub exit_price: Option<f64>,
+}
+
+impl Leg {
+    pub fn is_open(&self) -> bool {
+        self.exit_price.is_none()
+    }
+    
+    /// Realized PNL once closed, else mark-to-market at `current_price`
+    pub fn pnl(&self) -> f64 {
+        (self.price() - self.entry_price) * self.side_sign() * self.quantity as f64
+    }
+    
+    /// Exit price once closed, else `current_price`
+    fn price(&self) -> f64 {
+        self.exit_price.unwrap_or(self.current_price)
+    }
+    
+    fn side_sign(&self) -> f64 {
+        match self.side {
+            Side::Buy => 1.0,
+            Side::Sell => -1.0,
+        }
+    }
+}
+
+impl Position {
+    /// The position's own fields as a single leg
+    pub fn as_leg(&self) -> Leg {
+        Leg {
+            symbol: self.symbol.clone(),
+            strike: self.strike,
+            option_type: self.option_type,
+            side: self.side,
+            quantity: self.quantity,
+            entry_price: self.entry_price,
+            current_price: self.current_price,
+            exit_price: None,
+        }
+    }
+    
+    /// Combined premium per unit of `quantity`: legs signed by side (so a
+    /// credit position is negative), closed ones at their exit price. A
+    /// single-leg position's `current_price`.
+    pub fn net_price(&self) -> f64 {
+        self.net_value(Leg::price)
+    }
+    
+    /// `net_price` at entry
+    pub fn net_entry_price(&self) -> f64 {
+        self.net_value(|leg| leg.entry_price)
+    }
+    
+    fn net_value(&self, price: impl Fn(&Leg) -> f64) -> f64 {
+        if self.legs.is_empty() || self.quantity == 0 {
+            return self.current_price;
+        }
+        let value: f64 = self.legs.iter()
+            .map(|leg| price(leg) * leg.side_sign() * leg.quantity as f64)
+            .sum();
+        value / self.quantity as f64
+    }
+    
+    /// Open legs: the position itself when single-leg
+    pub fn open_legs(&self) -> Vec<Leg> {
+        if self.legs.is_empty() {
+            return vec![self.as_leg()];
+        }
+        self.legs.iter().filter(|leg| leg.is_open()).cloned().collect()
+    }
+    
+    /// +1 for long (bought premium), -1 for short (sold premium)
+    fn side_sign(&self) -> f64 {
+        match self.side {
+            Side::Buy => 1.0,
+            Side::Sell => -1.0,
+        }
+    }
+    
+    /// Mark-to-market PNL at `price`
+    /// `quantity` is in units (lots × lot size), so no extra lot multiplier applies
+    pub fn unrealized_pnl(&self, price: f64) -> f64 {
+        (price - self.entry_price) * self.side_sign() * self.quantity as f64
+    }
+    
+    /// Final PNL when exiting at `exit_price` (before brokerage)
+    pub fn realized_pnl(&self, exit_price: f64) -> f64 {
+        self.unrealized_pnl(exit_price)
+    }
+    
+    /// PNL at `price` as a percentage of entry premium
+    pub fn pnl_pct_at(&self, price: f64) -> f64 {
+        if self.entry_price == 0.0 {
+            return 0.0;
+        }
+        (price - self.entry_price) * self.side_sign() / self.entry_price * 100.0
+    }
+}
+
+/// Open test position: 75 × NIFTY 23500 CE bought at 100, stop 80, target 130
+#[cfg(test)]
+pub(crate) fn test_position(position_id: &str) -> Position {
+    let entry_time = Utc::now();
+    Position {
+        position_id: position_id.to_string(),
+        symbol: "NIFTY25JAN23500CE".to_string(),
+        underlying: "NIFTY".to_string(),
+        strike: 23500,
+        option_type: OptionType::CE,
+        side: Side::Buy,
+        quantity: 75,
+        entry_price: 100.0,
+        entry_time,
+        entry_time_ms: entry_time.timestamp_millis(),
+        underlying_entry: 23500.0,
+        stop_loss: 80.0,
+        target: Some(130.0),
+        trailing_stop: None,
+        trailing_active: false,
+        current_price: 100.0,
+        pnl: 0.0,
+        pnl_pct: 0.0,
+        status: PositionStatus::Open,
+        entry_reason: String::new(),
+        idempotency_key: String::new(),
+        exit_time: None,
+        legs: Vec::new(),
+    }
 }
 
 /// Order data structure
@@ -61,6 +225,7 @@ pub struct Order {
     pub broker_order_id: Option<String>,
     pub position_id: String,
     pub symbol: String,
+    pub token: String,
     pub side: Side,
     pub order_type: OrderType,
     pub quantity: i32,
@@ -74,6 +239,60 @@ pub struct Order {
     pub idempotency_key: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// Opens/adds to a position or closes one; only entries are escalated
+    #[serde(default)]
+    pub purpose: OrderPurpose,
+    /// Broker-side stop: set for cover orders
+    #[serde(default)]
+    pub stop_price: Option<f64>,
+}
+
+impl Order {
+    /// Make this a cover order with a broker-side stop at `stop_price`
+    pub fn with_stop_price(mut self, stop_price: f64) -> Self {
+        self.stop_price = Some(stop_price);
+        self
+    }
+}
+
+/// Why an order was placed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderPurpose {
+    /// New position or scale-in
+    #[default]
+    Entry,
+    /// Closing order for an open position
+    Exit,
+}
+
+/// Full-mode market quote (traded volume, open interest and best bid/ask)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Quote {
+    pub ltp: f64,
+    pub volume: i64,
+    pub open_interest: i64,
+    /// Best bid/ask from the market depth (0 when that side is empty)
+    #[serde(default)]
+    pub bid: f64,
+    #[serde(default)]
+    pub ask: f64,
+}
+
+/// Order as reported by the broker order book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokerOrder {
+    pub broker_order_id: String,
+    pub symbol: String,
+    pub token: String,
+    pub side: Side,
+    pub filled_quantity: i32,
+    pub average_price: f64,
+    pub status: String,
+    /// Tag set at placement, links the broker order to its intent
+    pub order_tag: Option<String>,
+    /// Entry order this leg belongs to (cover order stop/target legs)
+    #[serde(default)]
+    pub parent_order_id: Option<String>,
 }
 
 /// Trade result (completed position)
@@ -152,6 +371,87 @@ impl OrderType {
     }
 }
 
+/// Broker product type for option orders
+/// INTRADAY gets margin benefit but is auto-squared off by the broker near close;
+/// CARRYFORWARD (NRML) allows overnight holding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ProductType {
+    Intraday,
+    Carryforward,
+}
+
+impl ProductType {
+    pub fn as_str(&self) -> &str {
+        match self {
+            ProductType::Intraday => "INTRADAY",
+            ProductType::Carryforward => "CARRYFORWARD",
+        }
+    }
+}
+
+/// Indicator used for the daily CE/PE/NoTrade bias
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DailyBiasMethod {
+    /// +DI/-DI direction gated by ADX strength
+    Adx,
+    /// Fast EMA vs slow EMA, with price on the same side of the slow EMA
+    EmaCross,
+}
+
+/// What the bot does with entry signals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TradingMode {
+    /// Run the data/analysis pipeline and log signals; never place orders
+    Analysis,
+    /// Fill orders against the simulated paper broker
+    Paper,
+    /// Send orders to the broker
+    Live,
+}
+
+impl TradingMode {
+    pub fn places_orders(&self) -> bool {
+        !matches!(self, TradingMode::Analysis)
+    }
+}
+
+/// How the entry LIMIT price is derived from the live quote
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum EntryPriceMode {
+    /// Midpoint of bid/ask (rounded up to tick)
+    Mid,
+    /// Best ask
+    Ask,
+    /// Best ask plus `entry_price_ticks` ticks
+    AskPlusTicks,
+}
+
+impl EntryPriceMode {
+    /// Buy-side limit price, or None if the quote has no usable bid/ask
+    pub fn limit_price(&self, bid: f64, ask: f64, tick_size: f64, ticks: u32) -> Option<f64> {
+        if bid <= 0.0 || ask <= 0.0 || ask < bid || tick_size <= 0.0 {
+            return None;
+        }
+        
+        let raw = match self {
+            EntryPriceMode::Mid => {
+                let mid = (bid + ask) / 2.0;
+                // Round mid up towards the ask so it stays marketable
+                ((mid / tick_size) - 1e-9).ceil() * tick_size
+            }
+            EntryPriceMode::Ask => ask,
+            EntryPriceMode::AskPlusTicks => ask + ticks as f64 * tick_size,
+        };
+        
+        // Snap to the tick grid to avoid float noise
+        Some(crate::utils::round_to_tick(raw, tick_size))
+    }
+}
+
 /// Order status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum OrderStatus {
@@ -199,6 +499,15 @@ impl Direction {
     }
 }
 
+impl From<OptionType> for Direction {
+    fn from(option_type: OptionType) -> Self {
+        match option_type {
+            OptionType::CE => Direction::CE,
+            OptionType::PE => Direction::PE,
+        }
+    }
+}
+
 /// Market session state
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SessionState {
@@ -242,12 +551,30 @@ pub struct Instrument {
     pub tick_size: f64,
 }
 
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_closed_positions() -> usize {
+    200
+}
+
+fn default_oi_change_window() -> usize {
+    15
+}
+
 /// Configuration for the trading bot
+/// Feature flags and optional knobs carry serde defaults so older config
+/// files keep loading; see `Config::merged_with_defaults` for the rest.
 #[derive(Debug, Clone, Deserialize)]
 pub struct Config {
     // Time Windows
     pub entry_window_start: String,
     pub entry_window_end: String,
+    /// Entry windows for specific underlyings and/or weekdays
+    #[serde(default)]
+    pub entry_window_overrides: Vec<EntryWindowOverride>,
+    pub opening_blackout_minutes: u32,
     pub eod_exit_time: String,
     pub market_close_time: String,
     
@@ -258,14 +585,45 @@ pub struct Config {
     pub option_stop_loss_pct: f64,
     pub trail_activate_pnl_pct: f64,
     pub trail_gap_pct: f64,
+    /// Advance the trailing stop only from completed hourly bar highs, not every price
+    #[serde(default)]
+    pub trail_on_bar_close: bool,
+    /// Add `scale_in_lots` once per position at this unrealized gain (fraction); None disables
+    #[serde(default)]
+    pub scale_in_pnl_pct: Option<f64>,
+    /// Stop distance on the traded instrument's expiry day; None keeps `option_stop_loss_pct`
+    #[serde(default)]
+    pub expiry_stop_pct: Option<f64>,
+    /// No new entries after this time (HH:MM:SS IST) on expiry day; None disables
+    #[serde(default)]
+    pub expiry_no_entry_time: Option<String>,
+    /// Force-exit expiring positions from this time on expiry day; None leaves it to `eod_exit_time`
+    #[serde(default)]
+    pub expiry_exit_time: Option<String>,
+    pub scale_in_lots: u32,
     pub max_positions: usize,
+    /// Open positions allowed per underlying (counted on `Position.underlying`)
+    pub max_positions_per_underlying: usize,
+    /// Closed positions kept for `get_closed_position` until the daily reset
+    #[serde(default = "default_max_closed_positions")]
+    pub max_closed_positions: usize,
     pub daily_loss_limit_pct: f64,
+    /// Daily start capital used when broker funds can't be fetched
+    pub default_start_capital: f64,
     pub consecutive_loss_limit: usize,
+    pub max_notional_per_trade: f64,
+    /// Per-trade size bounds, in lots of the traded underlying
+    pub min_lots: u32,
+    pub max_lots: u32,
     
     // VIX Circuit Breaker
     pub vix_threshold: f64,
     pub vix_spike_threshold: f64,
     pub vix_resume_threshold: f64,
+    /// India VIX regimes for trade analytics: Low below `vix_regime_low_max`,
+    /// High at or above `vix_regime_high_min`, Medium between
+    pub vix_regime_low_max: f64,
+    pub vix_regime_high_min: f64,
     
     // Position Sizing
     pub base_position_size_pct: f64,
@@ -277,27 +635,75 @@ pub struct Config {
     pub order_max_retries: u32,
     pub order_retry_backoffs_sec: Vec<u64>,
     pub retry_cap_sec: u64,
+    pub partial_fill_timeout_sec: u64,
+    pub entry_limit_timeout_sec: u64,
+    /// How long a live entry waits for its broker fill before the unfilled
+    /// remainder is cancelled; must exceed `entry_limit_timeout_sec` so an
+    /// unfilled limit is escalated to market within the wait
+    pub entry_fill_wait_sec: u64,
     
     // Token Management
     pub token_expiry_warning_min: i64,
     pub token_grace_to_flatten_sec: u64,
     pub token_check_interval_sec: u64,
+    pub max_clock_drift_sec: u64,
     
     // Data Quality
     pub data_gap_threshold_sec: u64,
     pub data_gap_check_interval_sec: u64,
+    pub tick_staleness_sec: u64,
+    /// Entries are skipped when the latest hourly bar is older than this (session minutes)
+    pub max_bar_age_minutes: u64,
+    /// Append live ticks to data/ticks_<token>_YYYYMMDD.jsonl for replay
+    #[serde(default)]
+    pub record_ticks: bool,
     pub recovery_timeout_sec: u64,
+    /// Candles/LTP from `<dir>/<token>_<interval>.csv` when Angel One
+    /// errors; None disables the fallback
+    #[serde(default)]
+    pub fallback_candle_dir: Option<String>,
     
     // Broker Constraints
     pub freeze_quantity: BrokerLimits,
     pub lot_size: LotSizes,
     pub tick_size: f64,
     pub price_band_pct: f64,
+    pub product_type: ProductType,
+    pub entry_price_mode: EntryPriceMode,
+    pub entry_price_ticks: u32,
+    /// Skip entries whose bid/ask spread exceeds this fraction of the mid; None disables
+    #[serde(default)]
+    pub max_spread_pct: Option<f64>,
+    /// Entry strikes need at least this traded volume and open interest (0 = off)
+    #[serde(default)]
+    pub min_strike_volume: i64,
+    /// Entry strike's OI must have grown this much (%) over `oi_change_window` cycles; None disables
+    #[serde(default)]
+    pub min_oi_buildup_pct: Option<f64>,
+    /// OI samples (one per trading cycle) the build-up is measured over
+    #[serde(default = "default_oi_change_window")]
+    pub oi_change_window: usize,
+    /// Order type for stop/trailing/EOD/risk exits (targets always use LIMIT)
+    pub exit_order_type: OrderType,
     
     // Rate Limiting
     pub rate_limit_orders: u32,
     pub rate_limit_market_data: u32,
     pub rate_limit_historical: u32,
+    pub sync_concurrency: usize,
+    /// Historical sync also downloads option strikes around ATM; off for index-only strategies
+    #[serde(default = "default_true")]
+    pub sync_option_strikes: bool,
+    /// Underlyings synced concurrently by `MultiAssetHistoricalSync`
+    pub asset_sync_concurrency: usize,
+    /// Sync priority by index symbol; unlisted assets follow in default order
+    pub asset_sync_order: Vec<String>,
+    /// Token extraction keeps options in only the nearest N expiries; None keeps all
+    #[serde(default)]
+    pub token_max_expiries: Option<usize>,
+    /// Token extraction drops options expiring more than this many days out; None keeps all
+    #[serde(default)]
+    pub token_max_dte: Option<i64>,
     
     // WebSocket
     pub ws_ping_interval_sec: u64,
@@ -308,12 +714,24 @@ pub struct Config {
     // Strategy
     pub daily_adx_period: usize,
     pub daily_adx_threshold: f64,
+    pub daily_bias_method: DailyBiasMethod,
+    /// EMA periods for `daily_bias_method = "ema_cross"`
+    pub daily_ema_fast: usize,
+    pub daily_ema_slow: usize,
     pub hourly_adx_period: usize,
     pub hourly_adx_threshold: f64,
+    /// Also require 15m ADX alignment with the daily direction (hourly ADX settings)
+    #[serde(default)]
+    pub require_15m_confirmation: bool,
+    /// ADX hysteresis for `AdxStrategy`: a trend turns on at or above
+    /// `adx_enter_threshold` and only turns off below `adx_exit_threshold`
+    pub adx_enter_threshold: f64,
+    pub adx_exit_threshold: f64,
     pub rsi_period: usize,
     pub rsi_oversold: f64,
     pub rsi_overbought: f64,
     pub ema_period: usize,
+    pub warmup_extra_bars: usize,
     
     // Strike Selection
     pub strike_increment: i32,
@@ -321,16 +739,45 @@ pub struct Config {
     pub strike_subscription_count: usize,
     
     // Feature Flags
+    #[serde(default)]
     pub strategy_invalidate_on_recompute: bool,
+    #[serde(default = "default_true")]
     pub use_trailing_stop: bool,
+    #[serde(default)]
     pub use_underlying_soft_check: bool,
+    /// Paper unless a config says otherwise
+    #[serde(default = "default_true")]
     pub enable_paper_trading: bool,
+    /// Overrides `enable_paper_trading` when set
+    #[serde(default)]
+    pub mode: Option<TradingMode>,
+    pub paper_starting_cash: f64,
+    /// Paper mode: replay these stored NIFTY bars (JSONL) as synthetic ticks; None disables
+    #[serde(default)]
+    pub paper_replay_file: Option<String>,
+    /// Paper mode: recorded tick files (`record_ticks` output, e.g. option
+    /// tokens) replayed alongside `paper_replay_file`
+    #[serde(default)]
+    pub paper_replay_tick_files: Vec<String>,
+    /// Replay speed as a multiple of real time (0 = as fast as possible)
+    pub paper_replay_speed: f64,
+    #[serde(default)]
+    pub use_cover_orders: bool,
+    #[serde(default)]
+    pub use_trading_days_for_dte: bool,
     
     // Logging
     pub log_level: String,
     pub log_rotation: String,
     pub log_retention_days: u32,
+    pub data_retention_days: u32,
+    #[serde(default = "default_true")]
     pub audit_trail_enabled: bool,
+    /// Per-trade tracing span keyed by position_id and idempotency_key
+    #[serde(default = "default_true")]
+    pub trade_spans: bool,
+    pub snapshot_interval_sec: u64,
+    pub snapshot_retention_days: u32,
     
     // Broker Credentials
     pub angel_one_client_code: String,
@@ -356,6 +803,35 @@ pub struct DteMultipliers {
     pub day_1: f64,
 }
 
+/// Entry window replacing the global one; unset `underlying`/`weekday` match any
+#[derive(Debug, Clone, Deserialize)]
+pub struct EntryWindowOverride {
+    #[serde(default)]
+    pub underlying: Option<String>,
+    /// IST weekday ("Mon".."Fri")
+    #[serde(default)]
+    pub weekday: Option<chrono::Weekday>,
+    pub start: String,
+    pub end: String,
+}
+
+impl EntryWindowOverride {
+    /// Specificity when this override applies (underlying + weekday > underlying > weekday)
+    fn specificity(&self, underlying: &str, weekday: chrono::Weekday) -> Option<u8> {
+        let underlying_match = match &self.underlying {
+            Some(u) if u.eq_ignore_ascii_case(underlying) => Some(2),
+            Some(_) => None,
+            None => Some(0),
+        }?;
+        let weekday_match = match self.weekday {
+            Some(w) if w == weekday => Some(1),
+            Some(_) => None,
+            None => Some(0),
+        }?;
+        Some(underlying_match + weekday_match)
+    }
+}
+
 #[derive(Debug, Clone, Deserialize)]
 pub struct BrokerLimits {
     pub nifty: i32,
@@ -371,6 +847,26 @@ pub struct LotSizes {
 }
 
 impl Config {
+    /// Effective mode: `mode` if set, else paper/live from `enable_paper_trading`
+    pub fn trading_mode(&self) -> TradingMode {
+        self.mode.unwrap_or(if self.enable_paper_trading {
+            TradingMode::Paper
+        } else {
+            TradingMode::Live
+        })
+    }
+    
+    /// Entry window (start, end) for `underlying` on `weekday`: the most
+    /// specific matching override, else the global window
+    pub fn entry_window(&self, underlying: &str, weekday: chrono::Weekday) -> (&str, &str) {
+        self.entry_window_overrides
+            .iter()
+            .filter_map(|o| o.specificity(underlying, weekday).map(|rank| (rank, o)))
+            .max_by_key(|(rank, _)| *rank)
+            .map(|(_, o)| (o.start.as_str(), o.end.as_str()))
+            .unwrap_or((&self.entry_window_start, &self.entry_window_end))
+    }
+    
     pub fn get_lot_size(&self, underlying: &str) -> i32 {
         match underlying.to_uppercase().as_str() {
             "NIFTY" => self.lot_size.nifty,
@@ -390,3 +886,80 @@ impl Config {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn position(option_type: OptionType, side: Side) -> Position {
+        Position {
+            option_type,
+            side,
+            quantity: 100, // 2 lots of 50
+            entry_price: 120.0,
+            current_price: 120.0,
+            stop_loss: 96.0,
+            target: None,
+            ..test_position("P1")
+        }
+    }
+    
+    #[test]
+    fn test_long_pnl() {
+        for option_type in [OptionType::CE, OptionType::PE] {
+            let p = position(option_type, Side::Buy);
+            assert_eq!(p.unrealized_pnl(130.0), 1000.0);
+            assert_eq!(p.realized_pnl(110.0), -1000.0);
+            assert!((p.pnl_pct_at(132.0) - 10.0).abs() < 1e-9);
+        }
+    }
+    
+    #[test]
+    fn test_short_pnl() {
+        for option_type in [OptionType::CE, OptionType::PE] {
+            let p = position(option_type, Side::Sell);
+            assert_eq!(p.unrealized_pnl(130.0), -1000.0);
+            assert_eq!(p.realized_pnl(110.0), 1000.0);
+            assert!((p.pnl_pct_at(108.0) - 10.0).abs() < 1e-9);
+        }
+    }
+    
+    #[test]
+    fn test_entry_limit_price_modes() {
+        let (bid, ask, tick) = (120.05, 120.70, 0.05);
+        
+        // Mid 120.375 rounds up to 120.40
+        let mid = EntryPriceMode::Mid.limit_price(bid, ask, tick, 2).unwrap();
+        assert!((mid - 120.40).abs() < 1e-9);
+        
+        let at_ask = EntryPriceMode::Ask.limit_price(bid, ask, tick, 2).unwrap();
+        assert!((at_ask - 120.70).abs() < 1e-9);
+        
+        let aggressive = EntryPriceMode::AskPlusTicks.limit_price(bid, ask, tick, 2).unwrap();
+        assert!((aggressive - 120.80).abs() < 1e-9);
+        
+        // LTP-only ticks carry no bid/ask
+        assert!(EntryPriceMode::Mid.limit_price(0.0, 0.0, tick, 2).is_none());
+    }
+    
+    #[test]
+    fn test_analysis_mode_places_no_orders() {
+        let mut config = crate::config::loader::test_config();
+        config.mode = None;
+        config.enable_paper_trading = true;
+        assert_eq!(config.trading_mode(), TradingMode::Paper);
+        config.enable_paper_trading = false;
+        assert_eq!(config.trading_mode(), TradingMode::Live);
+        
+        // Explicit mode wins over the legacy flag
+        config.mode = Some(TradingMode::Analysis);
+        assert_eq!(config.trading_mode(), TradingMode::Analysis);
+        assert!(!config.trading_mode().places_orders());
+        assert!(TradingMode::Paper.places_orders());
+        
+        let parsed: Config = toml::from_str(
+            &include_str!("../config.toml").replace("\nmode = \"paper\"", "\nmode = \"analysis\""),
+        ).unwrap();
+        assert_eq!(parsed.trading_mode(), TradingMode::Analysis);
+    }
+}
+
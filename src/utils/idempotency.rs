@@ -1,27 +1,92 @@
 /// Idempotency key generation
+use std::sync::atomic::{AtomicU64, Ordering};
+use chrono::{DateTime, Utc};
 use sha2::{Digest, Sha256};
 
+/// Per-process counter so unique keys differ even within one nanosecond tick
+static UNIQUE_KEY_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// SHA-256 over the components; each is length-prefixed so
+/// `["ab", "c"]` and `["a", "bc"]` hash differently
 pub fn generate_idempotency_key(components: &[&str]) -> String {
     let mut hasher = Sha256::new();
     for component in components {
+        hasher.update((component.len() as u64).to_le_bytes());
         hasher.update(component.as_bytes());
     }
     let result = hasher.finalize();
     format!("{:x}", result)
 }
 
+/// Stable key for one entry signal: reprocessing the same signal bar in this
+/// process (retry, duplicate evaluation) yields the same key. `session_id`
+/// is new per process, so keys do not carry across a restart.
+pub fn signal_idempotency_key(
+    session_id: &str,
+    underlying: &str,
+    option_type: &str,
+    strike: i32,
+    signal_bar_time: DateTime<Utc>,
+) -> String {
+    generate_idempotency_key(&[
+        session_id,
+        underlying,
+        option_type,
+        &strike.to_string(),
+        &signal_bar_time.timestamp_millis().to_string(),
+    ])
+}
+
+/// Key that is never reused, for intents that must not be deduplicated
+pub fn unique_idempotency_key(components: &[&str]) -> String {
+    let now = Utc::now().timestamp_nanos_opt().unwrap_or_default().to_string();
+    let seq = UNIQUE_KEY_SEQ.fetch_add(1, Ordering::Relaxed).to_string();
+    let mut parts = components.to_vec();
+    parts.push(&now);
+    parts.push(&seq);
+    generate_idempotency_key(&parts)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    
+    use chrono::TimeZone;
+
     #[test]
     fn test_idempotency_key() {
         let key1 = generate_idempotency_key(&["session1", "NIFTY", "CE", "19000"]);
         let key2 = generate_idempotency_key(&["session1", "NIFTY", "CE", "19000"]);
         let key3 = generate_idempotency_key(&["session1", "NIFTY", "PE", "19000"]);
-        
+
         assert_eq!(key1, key2);
         assert_ne!(key1, key3);
     }
-}
 
+    #[test]
+    fn test_signal_key_stable_and_unique_key_fresh() {
+        let bar = Utc.with_ymd_and_hms(2025, 1, 15, 4, 45, 0).unwrap();
+
+        // Same signal bar reprocessed: same key
+        let first = signal_idempotency_key("session1", "NIFTY", "CE", 19500, bar);
+        let again = signal_idempotency_key("session1", "NIFTY", "CE", 19500, bar);
+        assert_eq!(first, again);
+
+        // Any business field changing gives a new key
+        let next_bar = bar + chrono::Duration::hours(1);
+        assert_ne!(first, signal_idempotency_key("session1", "NIFTY", "CE", 19500, next_bar));
+        assert_ne!(first, signal_idempotency_key("session1", "NIFTY", "CE", 19550, bar));
+        assert_ne!(first, signal_idempotency_key("session2", "NIFTY", "CE", 19500, bar));
+
+        // Component boundaries matter
+        assert_ne!(
+            generate_idempotency_key(&["ab", "c"]),
+            generate_idempotency_key(&["a", "bc"])
+        );
+
+        // Unique keys never repeat, even back to back
+        let keys: std::collections::HashSet<String> = (0..100)
+            .map(|_| unique_idempotency_key(&["session1", "NIFTY"]))
+            .collect();
+        assert_eq!(keys.len(), 100);
+    }
+}
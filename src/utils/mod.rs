@@ -1,8 +1,12 @@
 pub mod idempotency;
 pub mod time;
 pub mod rate_limiter;
+pub mod retention;
+pub mod price;
 
-pub use idempotency::generate_idempotency_key;
+pub use idempotency::{generate_idempotency_key, signal_idempotency_key, unique_idempotency_key};
 pub use time::*;
 pub use rate_limiter::RateLimiter;
+pub use retention::{prune_dated_files, rotate_file};
+pub use price::round_to_tick;
 
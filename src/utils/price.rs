@@ -0,0 +1,28 @@
+/// Round a price to the nearest multiple of `tick_size`
+/// Non-positive tick sizes leave the price unchanged.
+pub fn round_to_tick(price: f64, tick_size: f64) -> f64 {
+    if tick_size <= 0.0 {
+        return price;
+    }
+    
+    let rounded = (price / tick_size).round() * tick_size;
+    
+    // Trim float noise (e.g. 124.95000000000002) to the tick's decimal places
+    let decimals = (-tick_size.log10()).ceil().max(0.0) as i32;
+    let factor = 10f64.powi(decimals);
+    (rounded * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_round_to_tick() {
+        assert_eq!(round_to_tick(124.993, 0.05), 125.00);
+        assert_eq!(round_to_tick(124.97, 0.05), 124.95);
+        assert_eq!(round_to_tick(130.02, 0.05), 130.00);
+        assert_eq!(round_to_tick(19512.0, 50.0), 19500.0);
+        assert_eq!(round_to_tick(124.993, 0.0), 124.993);
+    }
+}
@@ -0,0 +1,139 @@
+/// Data retention: prune old per-day files and rotate the event log
+use chrono::NaiveDate;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+use crate::error::Result;
+
+/// Delete per-day files in `dir` older than `retention_days`
+/// Only files with a `_YYYYMMDD` date in their name are considered, so
+/// long-lived stores (bar history, tokens) are never touched. Not recursive.
+pub async fn prune_dated_files(dir: &Path, retention_days: u32, today: NaiveDate) -> Result<usize> {
+    if !dir.exists() {
+        return Ok(0);
+    }
+    
+    let cutoff = today - chrono::Duration::days(retention_days as i64);
+    let mut removed = 0;
+    let mut entries = tokio::fs::read_dir(dir).await?;
+    
+    while let Some(entry) = entries.next_entry().await? {
+        if !entry.file_type().await?.is_file() {
+            continue;
+        }
+        
+        let path = entry.path();
+        let Some(file_date) = path.file_name().and_then(|n| n.to_str()).and_then(file_date) else {
+            continue;
+        };
+        
+        if file_date < cutoff {
+            tokio::fs::remove_file(&path).await?;
+            debug!("Deleted expired data file: {}", path.display());
+            removed += 1;
+        }
+    }
+    
+    if removed > 0 {
+        info!("🧹 Deleted {} data files older than {}", removed, cutoff);
+    }
+    
+    Ok(removed)
+}
+
+/// Rename `<stem>.jsonl` to `<stem>_YYYYMMDD.jsonl`
+/// Writers that open the file per append (like `EventBus`) start a fresh file on next write.
+pub async fn rotate_file(path: &Path, date: NaiveDate) -> Result<Option<PathBuf>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("log");
+    let rotated_name = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}_{}.{}", stem, date.format("%Y%m%d"), ext),
+        None => format!("{}_{}", stem, date.format("%Y%m%d")),
+    };
+    let rotated = path.with_file_name(rotated_name);
+    
+    if rotated.exists() {
+        // Already rotated today (EOD can run more than once): append instead of clobbering
+        use tokio::io::AsyncWriteExt;
+        let content = tokio::fs::read(path).await?;
+        let mut file = tokio::fs::OpenOptions::new().append(true).open(&rotated).await?;
+        file.write_all(&content).await?;
+        tokio::fs::remove_file(path).await?;
+    } else {
+        tokio::fs::rename(path, &rotated).await?;
+    }
+    info!("🔄 Rotated {} → {}", path.display(), rotated.display());
+    
+    Ok(Some(rotated))
+}
+
+/// Extract the first `YYYYMMDD` segment from a file name
+fn file_date(file_name: &str) -> Option<NaiveDate> {
+    let stem = file_name.split('.').next()?;
+    
+    stem.split('_')
+        .filter(|part| part.len() == 8 && part.chars().all(|c| c.is_ascii_digit()))
+        .find_map(|part| NaiveDate::parse_from_str(part, "%Y%m%d").ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_prune_dated_files() {
+        let dir = std::env::temp_dir().join(format!("retention_{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        
+        let today = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        for name in [
+            "events_20250101.jsonl",                  // expired
+            "position_NIFTY_20250210_101500.json",    // expired
+            "exits_20250315.jsonl",                   // within retention
+            "daily_bias_20250331.json",               // today
+            "bars_nifty_daily.jsonl",                 // undated store
+            "tokens.json",                            // undated store
+        ] {
+            tokio::fs::write(dir.join(name), "{}").await.unwrap();
+        }
+        
+        let removed = prune_dated_files(&dir, 30, today).await.unwrap();
+        assert_eq!(removed, 2);
+        
+        assert!(!dir.join("events_20250101.jsonl").exists());
+        assert!(!dir.join("position_NIFTY_20250210_101500.json").exists());
+        assert!(dir.join("exits_20250315.jsonl").exists());
+        assert!(dir.join("daily_bias_20250331.json").exists());
+        assert!(dir.join("bars_nifty_daily.jsonl").exists());
+        assert!(dir.join("tokens.json").exists());
+        
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_rotate_file() {
+        let dir = std::env::temp_dir().join(format!("rotate_{}", uuid::Uuid::new_v4()));
+        tokio::fs::create_dir_all(&dir).await.unwrap();
+        
+        let path = dir.join("events.jsonl");
+        tokio::fs::write(&path, "{}\n").await.unwrap();
+        
+        let date = NaiveDate::from_ymd_opt(2025, 3, 31).unwrap();
+        let rotated = rotate_file(&path, date).await.unwrap().unwrap();
+        
+        assert_eq!(rotated, dir.join("events_20250331.jsonl"));
+        assert!(!path.exists());
+        assert!(rotate_file(&path, date).await.unwrap().is_none());
+        
+        // A second rotation on the same day appends
+        tokio::fs::write(&path, "{\"late\":true}\n").await.unwrap();
+        rotate_file(&path, date).await.unwrap();
+        let content = tokio::fs::read_to_string(&rotated).await.unwrap();
+        assert_eq!(content.lines().count(), 2);
+        
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+}
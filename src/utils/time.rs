@@ -1,7 +1,16 @@
 /// Time utilities for market session management
-use chrono::{DateTime, Datelike, NaiveTime, TimeZone, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, TimeZone, Utc};
 use chrono_tz::Asia::Kolkata;
 
+use crate::time::holidays::is_trading_day;
+
+/// Parse a session time ("HH:MM:SS" or "HH:MM"), falling back to a default
+fn parse_session_time(value: &str, default: NaiveTime) -> NaiveTime {
+    NaiveTime::parse_from_str(value, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(value, "%H:%M"))
+        .unwrap_or(default)
+}
+
 /// Check if current time is within entry window
 pub fn is_in_entry_window(
     now: DateTime<Utc>,
@@ -10,18 +19,35 @@ pub fn is_in_entry_window(
 ) -> bool {
     let now_ist = now.with_timezone(&Kolkata);
     
-    let start_time = NaiveTime::parse_from_str(window_start, "%H:%M:%S")
-        .or_else(|_| NaiveTime::parse_from_str(window_start, "%H:%M"))
-        .unwrap_or_else(|_| NaiveTime::from_hms_opt(10, 0, 0).unwrap());
-    
-    let end_time = NaiveTime::parse_from_str(window_end, "%H:%M:%S")
-        .or_else(|_| NaiveTime::parse_from_str(window_end, "%H:%M"))
-        .unwrap_or_else(|_| NaiveTime::from_hms_opt(15, 0, 0).unwrap());
+    let start_time = parse_session_time(window_start, NaiveTime::from_hms_opt(10, 0, 0).unwrap());
+    let end_time = parse_session_time(window_end, NaiveTime::from_hms_opt(15, 0, 0).unwrap());
     
     let current_time = now_ist.time();
     current_time >= start_time && current_time < end_time
 }
 
+/// Check if the opening blackout (first N minutes after 9:15) has passed
+pub fn is_past_opening_blackout(now: DateTime<Utc>, blackout_minutes: u32) -> bool {
+    let market_open = NaiveTime::from_hms_opt(9, 15, 0).unwrap();
+    let blackout_end = market_open + chrono::Duration::minutes(blackout_minutes as i64);
+    
+    now.with_timezone(&Kolkata).time() >= blackout_end
+}
+
+/// Check if the EOD mandatory exit time has been reached
+pub fn is_past_eod_exit(now: DateTime<Utc>, eod_exit_time: &str) -> bool {
+    let now_ist = now.with_timezone(&Kolkata);
+    let exit_time = parse_session_time(eod_exit_time, NaiveTime::from_hms_opt(15, 20, 0).unwrap());
+    
+    now_ist.time() >= exit_time
+}
+
+/// Check if `expiry` (instrument master format, e.g. "30JAN2025") is today in IST
+pub fn is_expiry_day(now: DateTime<Utc>, expiry: &str) -> bool {
+    NaiveDate::parse_from_str(expiry, "%d%b%Y")
+        .is_ok_and(|date| date == now.with_timezone(&Kolkata).date_naive())
+}
+
 /// Check if market is open
 pub fn is_market_open(now: DateTime<Utc>) -> bool {
     let now_ist = now.with_timezone(&Kolkata);
@@ -33,6 +59,17 @@ pub fn is_market_open(now: DateTime<Utc>) -> bool {
     current_time >= market_open && current_time < market_close
 }
 
+/// Time elapsed since `since`, counted from no earlier than today's 09:15 IST
+/// open so the overnight gap does not make yesterday's last bar look stale
+pub fn session_age(since: DateTime<Utc>, now: DateTime<Utc>) -> chrono::Duration {
+    let today_open = Kolkata
+        .from_local_datetime(&now.with_timezone(&Kolkata).date_naive().and_hms_opt(9, 15, 0).unwrap())
+        .unwrap()
+        .with_timezone(&Utc);
+    
+    now - since.max(today_open)
+}
+
 /// Get next market open time
 pub fn next_market_open(now: DateTime<Utc>) -> DateTime<Utc> {
     let now_ist = now.with_timezone(&Kolkata);
@@ -55,19 +92,44 @@ pub fn next_market_open(now: DateTime<Utc>) -> DateTime<Utc> {
     }
 }
 
-/// Calculate days to expiry (simplified - assumes weekly Thursday expiry)
-pub fn calculate_days_to_expiry(now: DateTime<Utc>) -> i32 {
-    let now_ist = now.with_timezone(&Kolkata);
-    let current_day = now_ist.weekday().num_days_from_monday();
+/// Next weekly expiry (Thursday, moved to the previous trading day on holidays)
+/// Used where the traded contract's actual expiry is not known
+pub fn next_weekly_expiry(now: DateTime<Utc>) -> NaiveDate {
+    let today = now.with_timezone(&Kolkata).date_naive();
+    let current_day = today.weekday().num_days_from_monday();
     
     // Thursday is day 3 (Mon=0, Tue=1, Wed=2, Thu=3, Fri=4)
-    let days_until_thursday = if current_day <= 3 {
-        3 - current_day
-    } else {
-        7 - current_day + 3
-    };
+    let days_until_thursday = (7 + 3 - current_day) % 7;
+    let mut thursday = today + chrono::Duration::days(days_until_thursday as i64);
     
-    days_until_thursday as i32
+    loop {
+        let mut expiry = thursday;
+        while !is_trading_day(expiry) {
+            expiry -= chrono::Duration::days(1);
+        }
+        if expiry >= today {
+            return expiry;
+        }
+        thursday += chrono::Duration::days(7);
+    }
+}
+
+/// Calendar days from today (IST) to `expiry`; 0 on expiry day
+pub fn calculate_days_to_expiry(now: DateTime<Utc>, expiry: NaiveDate) -> i32 {
+    let today = now.with_timezone(&Kolkata).date_naive();
+    (expiry - today).num_days().max(0) as i32
+}
+
+/// Trading days (weekends and NSE holidays excluded) after today up to and
+/// including `expiry`; 0 on expiry day
+pub fn calculate_trading_days_to_expiry(now: DateTime<Utc>, expiry: NaiveDate) -> i32 {
+    let today = now.with_timezone(&Kolkata).date_naive();
+    
+    today.iter_days()
+        .skip(1)
+        .take_while(|date| *date <= expiry)
+        .filter(|date| is_trading_day(*date))
+        .count() as i32
 }
 
 #[cfg(test)]
@@ -96,4 +158,36 @@ mod tests {
         
         assert!(!is_market_open(before_market_utc));
     }
+    
+    #[test]
+    fn test_days_to_expiry_across_weekend() {
+        // Friday 17 Jan 2025 → Thursday 23 Jan 2025
+        let friday = Kolkata.with_ymd_and_hms(2025, 1, 17, 10, 0, 0).unwrap().with_timezone(&Utc);
+        let expiry = NaiveDate::from_ymd_opt(2025, 1, 23).unwrap();
+        
+        assert_eq!(calculate_days_to_expiry(friday, expiry), 6);
+        assert_eq!(calculate_trading_days_to_expiry(friday, expiry), 4);
+        assert_eq!(calculate_trading_days_to_expiry(friday, NaiveDate::from_ymd_opt(2025, 1, 17).unwrap()), 0);
+    }
+    
+    #[test]
+    fn test_trading_days_to_expiry_skips_holiday() {
+        // Friday 11 Apr 2025 → Thursday 17 Apr 2025, Monday 14 Apr is Ambedkar Jayanti
+        let friday = Kolkata.with_ymd_and_hms(2025, 4, 11, 10, 0, 0).unwrap().with_timezone(&Utc);
+        let expiry = NaiveDate::from_ymd_opt(2025, 4, 17).unwrap();
+        
+        assert_eq!(calculate_days_to_expiry(friday, expiry), 6);
+        assert_eq!(calculate_trading_days_to_expiry(friday, expiry), 3);
+    }
+    
+    #[test]
+    fn test_next_weekly_expiry_moves_off_holiday() {
+        // Thursday 2 Oct 2025 is Gandhi Jayanti; expiry moves to Wednesday
+        let monday = Kolkata.with_ymd_and_hms(2025, 9, 29, 10, 0, 0).unwrap().with_timezone(&Utc);
+        assert_eq!(next_weekly_expiry(monday), NaiveDate::from_ymd_opt(2025, 10, 1).unwrap());
+        
+        // On the holiday itself the next week's Thursday applies
+        let holiday = Kolkata.with_ymd_and_hms(2025, 10, 2, 10, 0, 0).unwrap().with_timezone(&Utc);
+        assert_eq!(next_weekly_expiry(holiday), NaiveDate::from_ymd_opt(2025, 10, 9).unwrap());
+    }
 }